@@ -0,0 +1,13 @@
+-- the latest message each user has read in a room. message ids are
+-- ordered so everything after last_read is unread
+CREATE TABLE read_states (
+	uid			UUID NOT NULL REFERENCES users(uid) ON DELETE CASCADE,
+	room_id		UUID NOT NULL REFERENCES rooms(room_id) ON DELETE CASCADE,
+	-- not a reference so the marker stays in place when the message is deleted
+	last_read	UUID NOT NULL,
+	updated		BIGINT NOT NULL,
+	PRIMARY KEY(uid, room_id)
+);
+
+-- used to count unread messages
+CREATE INDEX messages_room_order ON messages (room_id, m_id);
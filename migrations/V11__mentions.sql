@@ -0,0 +1,14 @@
+-- users, roles and @everyone mentioned by a message
+CREATE TABLE mentions (
+	m_id		UUID NOT NULL REFERENCES messages(m_id) ON DELETE CASCADE,
+	-- the order mentions appear in the message
+	position	BIGINT NOT NULL,
+	-- the mention is of @everyone when neither is set
+	uid			UUID NULL REFERENCES users(uid) ON DELETE CASCADE,
+	role_id		UUID NULL REFERENCES roles(role_id) ON DELETE CASCADE,
+	CHECK (uid IS NULL OR role_id IS NULL),
+	PRIMARY KEY(m_id, position)
+);
+
+CREATE INDEX mentions_uid ON mentions (uid);
+CREATE INDEX mentions_role ON mentions (role_id);
@@ -0,0 +1,39 @@
+-- full text search of message content. the text search config is picked
+-- from the message's language when it is sent so words are stemmed for
+-- that language, messages without a known language use 'simple'.
+-- as the config differs between messages searches build the query with
+-- each message's own config. V22 indexes this column so searches only
+-- check the messages that match the query parsed with any config
+ALTER TABLE messages ADD COLUMN search_config TEXT NOT NULL DEFAULT 'simple';
+ALTER TABLE messages ADD COLUMN search TSVECTOR;
+
+UPDATE messages SET search_config = CASE language
+	WHEN 'ar' THEN 'arabic'
+	WHEN 'da' THEN 'danish'
+	WHEN 'de' THEN 'german'
+	WHEN 'el' THEN 'greek'
+	WHEN 'en' THEN 'english'
+	WHEN 'es' THEN 'spanish'
+	WHEN 'fi' THEN 'finnish'
+	WHEN 'fr' THEN 'french'
+	WHEN 'ga' THEN 'irish'
+	WHEN 'hu' THEN 'hungarian'
+	WHEN 'id' THEN 'indonesian'
+	WHEN 'it' THEN 'italian'
+	WHEN 'lt' THEN 'lithuanian'
+	WHEN 'ne' THEN 'nepali'
+	WHEN 'nl' THEN 'dutch'
+	WHEN 'no' THEN 'norwegian'
+	WHEN 'nb' THEN 'norwegian'
+	WHEN 'nn' THEN 'norwegian'
+	WHEN 'pt' THEN 'portuguese'
+	WHEN 'ro' THEN 'romanian'
+	WHEN 'ru' THEN 'russian'
+	WHEN 'sv' THEN 'swedish'
+	WHEN 'ta' THEN 'tamil'
+	WHEN 'tr' THEN 'turkish'
+	ELSE 'simple'
+END;
+
+UPDATE messages SET search = to_tsvector(search_config::regconfig, content);
+ALTER TABLE messages ALTER COLUMN search SET NOT NULL;
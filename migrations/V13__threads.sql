@@ -0,0 +1,8 @@
+-- a message in a thread has the message that started the thread as its
+-- thread_id. threads are in the same room as the message that started
+-- them and are deleted with it
+ALTER TABLE messages ADD COLUMN thread_id UUID NULL REFERENCES messages(m_id) ON DELETE CASCADE;
+
+-- used to page through threads and find the threads active in a room
+CREATE INDEX messages_thread_order ON messages (thread_id, m_id) WHERE thread_id IS NOT NULL;
+CREATE INDEX messages_room_threads ON messages (room_id, thread_id) WHERE thread_id IS NOT NULL;
@@ -0,0 +1,8 @@
+-- the markdown of messages rendered for clients and the plain text used for
+-- notifications, search and reply previews. rendered is null for plain text
+-- messages, plain_text is null for messages that have not been rendered yet
+-- and those sent before rendering are rendered when the server starts
+ALTER TABLE messages ADD COLUMN rendered JSONB NULL;
+ALTER TABLE messages ADD COLUMN plain_text TEXT NULL;
+
+UPDATE messages SET plain_text = content WHERE format = 'Plain';
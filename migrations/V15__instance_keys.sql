@@ -0,0 +1,8 @@
+-- the key our instance signs federation requests with and the cached
+-- public keys of other instances. keys are stored pem encoded
+ALTER TABLE instances ADD COLUMN key_algorithm TEXT NULL;
+ALTER TABLE instances ADD COLUMN public_key TEXT NULL;
+-- only present for our own instance
+ALTER TABLE instances ADD COLUMN private_key TEXT NULL;
+-- when the public key of a remote instance was last fetched
+ALTER TABLE instances ADD COLUMN key_fetched_at BIGINT NULL;
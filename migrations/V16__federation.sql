@@ -0,0 +1,16 @@
+-- activities waiting to be delivered to the inbox of another instance.
+-- deliveries are claimed by pushing next_attempt forward, those that fail
+-- are retried later until they run out of attempts
+CREATE TABLE outbox (
+	delivery_id		BIGSERIAL PRIMARY KEY,
+	domain			TEXT NOT NULL REFERENCES instances(domain) ON DELETE CASCADE,
+	activity		JSONB NOT NULL,
+	attempts		BIGINT NOT NULL DEFAULT 0,
+	next_attempt	BIGINT NOT NULL,
+	created			BIGINT NOT NULL
+);
+CREATE INDEX outbox_next_attempt ON outbox (next_attempt);
+
+-- rooms of remote communities are looked up by the id they have on
+-- the community's instance
+CREATE UNIQUE INDEX rooms_external ON rooms (external_id, domain);
@@ -0,0 +1,4 @@
+-- when a community mirrored from another instance was last fetched,
+-- null for our own communities. the community's instance is authoratative
+-- over it as with rooms
+ALTER TABLE communities ADD COLUMN fetched_at BIGINT NULL;
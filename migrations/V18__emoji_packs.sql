@@ -0,0 +1,41 @@
+-- emoji packs are served to other instances and imported from other
+-- instances or static sites, see crate::federation::emoji
+
+-- existing packs are named after their id
+ALTER TABLE emoji_packs ADD COLUMN name TEXT NULL;
+UPDATE emoji_packs SET name = emoji_pack_external_id;
+ALTER TABLE emoji_packs ALTER COLUMN name SET NOT NULL;
+ALTER TABLE emoji_packs ADD COLUMN description TEXT NULL;
+ALTER TABLE emoji_packs ADD COLUMN author_url TEXT NULL;
+-- the pack is previewed with the shortcode of one of its emoji rather than
+-- a file, null until the pack has emoji
+ALTER TABLE emoji_packs DROP COLUMN preview;
+ALTER TABLE emoji_packs ADD COLUMN highlight_emoji TEXT NULL;
+-- when a pack from another website was last fetched, null for our own packs
+ALTER TABLE emoji_packs ADD COLUMN fetched_at BIGINT NULL;
+
+-- file_id referenced users rather than files
+ALTER TABLE custom_emoji DROP CONSTRAINT custom_emoji_file_id_fkey;
+-- only our own emoji have a file, emoji from other websites link to their media
+ALTER TABLE custom_emoji ALTER COLUMN file_id DROP NOT NULL;
+UPDATE custom_emoji SET file_id = NULL
+	WHERE file_id NOT IN (SELECT file_id FROM files);
+ALTER TABLE custom_emoji ADD CONSTRAINT custom_emoji_file_id_fkey
+	FOREIGN KEY (file_id) REFERENCES files(file_id) ON DELETE CASCADE;
+ALTER TABLE custom_emoji ADD COLUMN url TEXT NULL;
+ALTER TABLE custom_emoji ADD COLUMN shortcode TEXT NULL;
+-- one of the mime types of crate::federation::emoji::EmojiFormat
+ALTER TABLE custom_emoji ADD COLUMN media_type TEXT NULL;
+-- existing emoji get a shortcode from their id and the type of their file,
+-- emoji left without a file or a known type aren't served
+UPDATE custom_emoji SET
+	shortcode = 'emoji' || replace(emoji_id::text, '-', ''),
+	media_type = COALESCE(
+		(SELECT mime FROM files WHERE files.file_id = custom_emoji.file_id),
+		'application/octet-stream'
+	);
+ALTER TABLE custom_emoji ALTER COLUMN shortcode SET NOT NULL;
+ALTER TABLE custom_emoji ALTER COLUMN media_type SET NOT NULL;
+-- alt text for the emoji
+ALTER TABLE custom_emoji ADD COLUMN description TEXT NULL;
+ALTER TABLE custom_emoji ADD UNIQUE (emoji_pack_id, shortcode);
@@ -0,0 +1,4 @@
+-- deleting the message that started a thread leaves the replies in it as
+-- messages in the room instead of deleting everyone's replies with it
+ALTER TABLE messages DROP CONSTRAINT messages_thread_id_fkey;
+ALTER TABLE messages ADD CONSTRAINT messages_thread_id_fkey FOREIGN KEY (thread_id) REFERENCES messages(m_id) ON DELETE SET NULL;
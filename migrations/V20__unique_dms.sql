@@ -0,0 +1,43 @@
+-- two users only ever have one direct message between them. duplicates that
+-- were opened at the same time are merged into the oldest one
+CREATE TEMPORARY TABLE duplicate_dms AS
+SELECT room_id, first_value(room_id) OVER (
+	PARTITION BY LEAST(user_a, user_b), GREATEST(user_a, user_b)
+	ORDER BY created, room_id
+) AS keep
+FROM rooms WHERE is_dm;
+
+-- everything scoped to the duplicates is moved over before they are deleted.
+-- both users are already members of the room that is kept so the memberships
+-- of the duplicates are left to be removed along with them, direct messages
+-- have no permission overrides
+UPDATE messages SET room_id = duplicate_dms.keep
+FROM duplicate_dms
+WHERE messages.room_id = duplicate_dms.room_id AND duplicate_dms.room_id <> duplicate_dms.keep;
+
+-- pinned message ids are unique so moving them can't conflict
+UPDATE pins SET room_id = duplicate_dms.keep
+FROM duplicate_dms
+WHERE pins.room_id = duplicate_dms.room_id AND duplicate_dms.room_id <> duplicate_dms.keep;
+
+UPDATE embedded_emoji SET room_id = duplicate_dms.keep
+FROM duplicate_dms
+WHERE embedded_emoji.room_id = duplicate_dms.room_id AND duplicate_dms.room_id <> duplicate_dms.keep;
+
+-- a user keeps the furthest they have read across the merged rooms
+INSERT INTO read_states (uid, room_id, last_read, updated)
+SELECT DISTINCT ON (read_states.uid)
+	read_states.uid, duplicate_dms.keep, read_states.last_read, read_states.updated
+FROM read_states JOIN duplicate_dms ON read_states.room_id = duplicate_dms.room_id
+WHERE duplicate_dms.room_id <> duplicate_dms.keep
+ORDER BY read_states.uid, read_states.last_read DESC
+ON CONFLICT (uid, room_id) DO UPDATE SET
+	last_read = GREATEST(read_states.last_read, EXCLUDED.last_read),
+	updated = GREATEST(read_states.updated, EXCLUDED.updated);
+
+DELETE FROM rooms USING duplicate_dms
+WHERE rooms.room_id = duplicate_dms.room_id AND duplicate_dms.room_id <> duplicate_dms.keep;
+
+DROP TABLE duplicate_dms;
+
+CREATE UNIQUE INDEX rooms_unique_dm ON rooms (LEAST(user_a, user_b), GREATEST(user_a, user_b)) WHERE is_dm;
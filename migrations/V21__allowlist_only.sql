@@ -0,0 +1,3 @@
+-- set on our own instance from the config when the server starts, so queries
+-- can hide content from instances that aren't allowlisted in allowlist only mode
+ALTER TABLE instances ADD COLUMN allowlist_only BOOLEAN NOT NULL DEFAULT false;
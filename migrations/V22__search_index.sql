@@ -0,0 +1,9 @@
+-- searches first match messages against the query parsed with every text
+-- search config, which can use this index, and then check the matches
+-- against the query parsed with each message's own config
+CREATE INDEX messages_search ON messages USING GIN (search);
+
+CREATE AGGREGATE tsquery_or_agg (tsquery) (
+	SFUNC = tsquery_or,
+	STYPE = tsquery
+);
@@ -0,0 +1,16 @@
+-- the position of the highest role of whoever issued a ban, only members
+-- at least as high are able to lift it. the owner is above every role
+ALTER TABLE community_bans ADD COLUMN banned_position BIGINT NULL;
+
+UPDATE community_bans SET banned_position = CASE
+	WHEN community_bans.banned_by = communities.owner THEN 9223372036854775807
+	ELSE COALESCE((
+		SELECT MAX(roles.position) FROM role_membership
+		JOIN roles ON roles.role_id = role_membership.role_id
+		WHERE role_membership.com_id = community_bans.com_id
+		AND role_membership.uid = community_bans.banned_by
+	), 0)
+END
+FROM communities WHERE communities.com_id = community_bans.com_id;
+
+ALTER TABLE community_bans ALTER COLUMN banned_position SET NOT NULL;
@@ -0,0 +1,50 @@
+-- roles are per community and carry a permission bitset, see 
+-- `crate::db::types::comm::permissions::Permissions` for the bits
+CREATE TABLE roles (
+	role_id		UUID NOT NULL PRIMARY KEY UNIQUE,
+	com_id		UUID NOT NULL REFERENCES communities(com_id) ON DELETE CASCADE,
+	name		TEXT NOT NULL,
+	permissions	BIGINT NOT NULL DEFAULT 0,
+	-- roles are ordered from lowest to highest. users may only manage
+	-- roles and members that are lower than their highest role
+	position	BIGINT NOT NULL DEFAULT 0,
+	-- the default role is held by every member of the community and
+	-- can not be deleted or assigned
+	is_default	BOOLEAN NOT NULL DEFAULT false,
+	created		BIGINT NOT NULL
+);
+
+CREATE UNIQUE INDEX roles_one_default ON roles (com_id) WHERE is_default;
+
+-- role assignments are tied to the membership so they are cleaned
+-- up when a user leaves or is removed from the community
+CREATE TABLE role_membership (
+	com_id		UUID NOT NULL,
+	uid			UUID NOT NULL,
+	role_id		UUID NOT NULL REFERENCES roles(role_id) ON DELETE CASCADE,
+	FOREIGN KEY (com_id, uid) REFERENCES community_membership(com_id, uid) ON DELETE CASCADE,
+	PRIMARY KEY(role_id, uid)
+);
+
+-- per room overrides of a role's permissions, deny is applied before allow
+CREATE TABLE room_permission_overrides (
+	room_id 	UUID NOT NULL REFERENCES rooms(room_id) ON DELETE CASCADE,
+	role_id		UUID NOT NULL REFERENCES roles(role_id) ON DELETE CASCADE,
+	allow		BIGINT NOT NULL DEFAULT 0,
+	deny		BIGINT NOT NULL DEFAULT 0,
+	PRIMARY KEY(room_id, role_id)
+);
+
+CREATE TABLE community_bans (
+	com_id		UUID NOT NULL REFERENCES communities(com_id) ON DELETE CASCADE,
+	uid			UUID NOT NULL REFERENCES users(uid) ON DELETE CASCADE,
+	banned_by	UUID NULL REFERENCES users(uid) ON DELETE SET NULL,
+	reason		TEXT NULL,
+	created		BIGINT NOT NULL,
+	PRIMARY KEY(com_id, uid)
+);
+
+-- give existing communities their default role, 115 is view rooms, send messages,
+-- add reactions, attach files and create invites
+INSERT INTO roles (role_id, com_id, name, permissions, position, is_default, created)
+SELECT gen_random_uuid(), com_id, 'everyone', 115, 0, true, created FROM communities;
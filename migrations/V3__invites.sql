@@ -0,0 +1,11 @@
+ALTER TABLE join_token
+	-- null for unlimited uses
+	ADD COLUMN max_uses		BIGINT NULL,
+	ADD COLUMN uses			BIGINT NOT NULL DEFAULT 0,
+	ADD COLUMN created		BIGINT NOT NULL DEFAULT 0;
+
+-- messages generated by the server such as announcing a user joining
+-- a community, content will contain a plain text fallback and uid
+-- will be the user that caused the event
+ALTER TABLE messages
+	ADD COLUMN system_event	JSONB NULL;
@@ -0,0 +1,15 @@
+-- previous versions of a message, a row is created each time a message
+-- is edited holding the content that was replaced
+CREATE TABLE message_edits (
+	edit_id		UUID NOT NULL PRIMARY KEY UNIQUE,
+	m_id 		UUID NOT NULL REFERENCES messages(m_id) ON DELETE CASCADE,
+	content		TEXT NOT NULL,
+	format		TEXT NOT NULL,
+	language	TEXT NULL,
+	-- when this version was first visible, either the publish time
+	-- or the time of the previous edit
+	published	BIGINT NOT NULL,
+	-- when this version was replaced
+	replaced	BIGINT NOT NULL
+);
+CREATE INDEX message_edits_m_id ON message_edits (m_id, replaced);
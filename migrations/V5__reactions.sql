@@ -0,0 +1,2 @@
+-- reactions are aggregated per message whenever messages are fetched
+CREATE INDEX reactions_m_id ON reactions (m_id, published);
@@ -0,0 +1,8 @@
+ALTER TABLE files
+	-- the mime type of the file
+	ADD COLUMN mime			TEXT NOT NULL DEFAULT 'application/octet-stream',
+	-- size in bytes
+	ADD COLUMN size			BIGINT NOT NULL DEFAULT 0,
+	-- the name of the file when it was uploaded
+	ADD COLUMN file_name	TEXT NULL,
+	ADD COLUMN created		BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,15 @@
+ALTER TABLE files
+	-- dimensions of images and videos in pixels
+	ADD COLUMN width		BIGINT NULL,
+	ADD COLUMN height		BIGINT NULL;
+
+CREATE TABLE message_attachments (
+	m_id 		UUID NOT NULL REFERENCES messages(m_id) ON DELETE CASCADE,
+	file_id		UUID NOT NULL REFERENCES files(file_id) ON DELETE CASCADE,
+	-- order of the attachment within the message
+	position	BIGINT NOT NULL,
+	-- alt text for the attachment
+	description	TEXT NULL,
+	PRIMARY KEY(m_id, file_id)
+);
+CREATE INDEX message_attachments_file_id ON message_attachments (file_id);
@@ -0,0 +1,17 @@
+ALTER TABLE files
+	-- compact placeholder shown while images load
+	ADD COLUMN blurhash		TEXT NULL;
+
+CREATE TABLE file_thumbnails (
+	file_id		UUID NOT NULL REFERENCES files(file_id) ON DELETE CASCADE,
+	-- the longest edge the thumbnail was scaled to fit within
+	size		BIGINT NOT NULL,
+	-- path of the thumbnail within the storage backend
+	path		TEXT NOT NULL,
+	mime		TEXT NOT NULL,
+	width		BIGINT NOT NULL,
+	height		BIGINT NOT NULL,
+	-- size in bytes
+	file_size	BIGINT NOT NULL,
+	PRIMARY KEY(file_id, size)
+);
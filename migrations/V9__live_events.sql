@@ -0,0 +1,8 @@
+-- live server events too large to fit in a postgres notification,
+-- instances are notified of the event_id and fetch the payload
+CREATE TABLE live_events (
+	event_id	BIGSERIAL PRIMARY KEY,
+	payload		TEXT NOT NULL,
+	created		BIGINT NOT NULL
+);
+CREATE INDEX live_events_created ON live_events (created);
@@ -3,7 +3,16 @@ use actix_multipart::form::MultipartFormConfig;
 use actix_web::{rt::spawn, web::Data, App, HttpServer};
 use tokio::try_join;
 
-use crate::{config::Config, live_server::server::ChatServer, routes::get_routes};
+use crate::{
+    config::Config,
+    cryptography::file_tokens::FileTokens,
+    federation::{
+        outbox::start_outbox,
+        signatures::{InstanceSigner, KeyStore},
+    },
+    live_server::{event_bus::EventBus, server::ChatServer},
+    routes::{api::files::file_upload::FORM_OVERHEAD, get_routes},
+};
 
 pub async fn start_application(config: Config) -> std::io::Result<()> {
     //init the conn and instance actor
@@ -12,13 +21,24 @@ pub async fn start_application(config: Config) -> std::io::Result<()> {
         eprintln!("{}", x);
         return Ok(());
     }
-    let _ = conn
-        .get_or_init_main_instance(&config.instance_domain)
+    conn.render_message_backlog();
+    let instance = conn
+        .get_or_init_main_instance(
+            &config.instance_domain,
+            config.signing_algo,
+            config.allowlist_only,
+        )
         .await;
-    // conn.get_instance_actor(config.signing_algo).await;
+    let signer = Data::new(InstanceSigner::new(&config, &instance));
+    let key_store = Data::new(KeyStore::default());
+    let file_tokens = Data::new(FileTokens::from_instance(&instance));
 
     let (chat_server, server_tx) = ChatServer::new();
     let chat_server = spawn(chat_server.run());
+    // lets multiple instances share the database and deliver each other's events
+    let event_bus = EventBus::start(conn.clone(), config.pg_config(), server_tx.clone());
+    let server_tx = server_tx.with_event_bus(event_bus);
+    start_outbox(conn.clone(), config.clone(), signer.clone().into_inner());
 
     let bind = config.bind_address.clone();
     let port = config.port;
@@ -35,14 +55,24 @@ pub async fn start_application(config: Config) -> std::io::Result<()> {
             .app_data(Data::new(conn.clone()))
             .app_data(Data::new(config.to_owned()))
             .app_data(Data::new(server_tx.clone()))
+            .app_data(signer.clone())
+            .app_data(key_store.clone())
+            .app_data(file_tokens.clone())
             .service(get_routes())
             .app_data(
                 MultipartFormConfig::default()
+                    // uploads are held to the user's own limit as they're read, this is
+                    // only an upper bound. we leave some extra space in case the file +
+                    // the metadata are a bit too big
                     .total_limit(
-                        100 * 1024 + // we leave some extra space in case the file + the metadata are a bit too big
-                        config.max_superuser_upload_size.unwrap_or(config.max_standard_upload_size) * 1024 * 1024
-                    ) 
-                    .memory_limit(config.upload_memory_limit * 1024 * 1024)
+                        FORM_OVERHEAD
+                            + config
+                                .max_superuser_upload_size
+                                .unwrap_or(config.max_standard_upload_size)
+                                * 1024
+                                * 1024,
+                    )
+                    .memory_limit(config.upload_memory_limit * 1024 * 1024),
             )
     })
     .bind((bind, port))?
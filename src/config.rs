@@ -1,7 +1,7 @@
 use config::ConfigError;
 use serde::Deserialize;
 
-use crate::{db::pg_conn::PgConn, file_manager::FileManager};
+use crate::{cryptography::keys::SigningAlgorithm, db::pg_conn::PgConn, file_manager::FileManager};
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Config {
@@ -32,21 +32,68 @@ pub struct Config {
     pub pg_dbname: String,
 
     pub storage_options: FileManager,
+
+    /// reach other instances over http rather than https and give out
+    /// http urls for this one, only meant for running instances locally
+    #[serde(default)]
+    pub insecure_federation: bool,
+    /// the algorithm of the key federation requests are signed with,
+    /// only used when the instance's key is first generated
+    #[serde(default)]
+    pub signing_algo: SigningAlgorithm,
+    /// seconds before the profiles of users and communities from other
+    /// instances are fetched again, defaults to a day
+    #[serde(default = "default_remote_profile_ttl")]
+    pub remote_profile_ttl: u64,
+    /// only federate with instances that have been allowlisted
+    /// rather than every instance that isn't blocked
+    #[serde(default)]
+    pub allowlist_only: bool,
+}
+
+fn default_remote_profile_ttl() -> u64 {
+    24 * 60 * 60
 }
 
 impl Config {
+    /// how old in milliseconds a remote profile may be before it is fetched again
+    pub fn remote_profile_ttl_millis(&self) -> i64 {
+        (self.remote_profile_ttl * 1000) as i64
+    }
+    pub fn scheme(&self) -> &'static str {
+        match self.insecure_federation {
+            true => "http",
+            false => "https",
+        }
+    }
+    /// where this instance is reached, eg `https://bayou.town`
+    pub fn base_url(&self) -> String {
+        format!("{}://{}", self.scheme(), self.instance_domain)
+    }
     pub fn create_conn(&self) -> PgConn {
-        let db_config = deadpool_postgres::Config {
+        let pool = self
+            .db_config()
+            .create_pool(None, tokio_postgres::NoTls)
+            .unwrap();
+        PgConn {
+            db: pool,
+            base_url: self.base_url(),
+        }
+    }
+    /// config for connections made outside of the pool, such as
+    /// the one the live server listens for events on
+    pub fn pg_config(&self) -> tokio_postgres::Config {
+        self.db_config().get_pg_config().unwrap()
+    }
+    fn db_config(&self) -> deadpool_postgres::Config {
+        deadpool_postgres::Config {
             user: Some(self.pg_user.clone()),
             password: Some(self.pg_password.clone()),
             host: Some(self.pg_host.clone()),
             dbname: Some(self.pg_dbname.clone()),
 
             ..Default::default()
-        };
-
-        let pool = db_config.create_pool(None, tokio_postgres::NoTls).unwrap();
-        PgConn { db: pool }
+        }
     }
 }
 
@@ -0,0 +1,156 @@
+//! short lived tokens for downloading files without an authorization header,
+//! so file urls can be used directly by elements such as `<img>` and `<video>`.
+//!
+//! a token belongs to the user it was issued to and they must still be able to
+//! view a file to download it. tokens stay the same for a user until the next
+//! [`TOKEN_INTERVAL`] starts so urls using them can be cached by browsers
+
+use std::time::Duration;
+
+use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::db::types::instance::Instance;
+
+/// tokens are valid for between one and two intervals after they are issued
+pub const TOKEN_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileToken {
+    /// passed as the `token` query parameter when downloading a file
+    pub token: String,
+    /// when the token stops being accepted, in milliseconds
+    pub expires: i64,
+}
+
+/// issues and verifies file tokens
+pub struct FileTokens {
+    key: Vec<u8>,
+}
+
+impl FileTokens {
+    pub fn new(secret: &[u8]) -> Self {
+        FileTokens {
+            key: sign(secret, b"bayou file tokens"),
+        }
+    }
+    /// tokens are signed with a key derived from the instance's private key so
+    /// every process sharing the database accepts them. panics if the instance
+    /// has no private key, see [`crate::db::pg_conn::PgConn::get_or_init_main_instance`]
+    pub fn from_instance(instance: &Instance) -> Self {
+        let private_key = instance
+            .key
+            .as_ref()
+            .and_then(|x| x.private_key.as_ref())
+            .expect("instance has no private key");
+        FileTokens::new(private_key.as_bytes())
+    }
+    /// the token of a user for the current interval
+    pub fn issue(&self, uid: &Uuid, now: i64) -> FileToken {
+        let interval = TOKEN_INTERVAL.as_millis() as i64;
+        let expires = (now / interval + 2) * interval;
+        let signature = sign(&self.key, signed_string(uid, expires).as_bytes());
+        FileToken {
+            token: format!(
+                "{}.{expires}.{}",
+                uid.as_simple(),
+                BASE64_URL_SAFE_NO_PAD.encode(signature)
+            ),
+            expires,
+        }
+    }
+    /// get the user a token was issued to, none if it is invalid or has expired
+    pub fn verify(&self, token: &str, now: i64) -> Option<Uuid> {
+        let mut parts = token.split('.');
+        let (Some(uid), Some(expires), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+        let uid = Uuid::try_parse(uid).ok()?;
+        let expires: i64 = expires.parse().ok()?;
+        if expires <= now {
+            return None;
+        }
+        let signature = BASE64_URL_SAFE_NO_PAD.decode(signature).ok()?;
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("hmac accepts any key length");
+        mac.update(signed_string(&uid, expires).as_bytes());
+        mac.verify_slice(&signature).ok()?;
+        Some(uid)
+    }
+}
+
+fn signed_string(uid: &Uuid, expires: i64) -> String {
+    format!("{}:{expires}", uid.as_simple())
+}
+
+fn sign(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 60 * 60 * 1000;
+
+    #[test]
+    fn issued_tokens_verify_until_they_expire() {
+        let tokens = FileTokens::new(b"secret");
+        let uid = Uuid::now_v7();
+        let now = 10 * HOUR + 5;
+        let token = tokens.issue(&uid, now);
+        assert_eq!(token.expires, 12 * HOUR);
+        assert_eq!(tokens.verify(&token.token, now), Some(uid));
+        assert_eq!(tokens.verify(&token.token, token.expires - 1), Some(uid));
+        assert_eq!(tokens.verify(&token.token, token.expires), None);
+    }
+
+    #[test]
+    fn tokens_are_stable_within_an_interval() {
+        let tokens = FileTokens::new(b"secret");
+        let uid = Uuid::now_v7();
+        let first = tokens.issue(&uid, 10 * HOUR);
+        assert_eq!(first.token, tokens.issue(&uid, 11 * HOUR - 1).token);
+        assert_ne!(first.token, tokens.issue(&uid, 11 * HOUR).token);
+        assert_ne!(first.token, tokens.issue(&Uuid::now_v7(), 10 * HOUR).token);
+    }
+
+    #[test]
+    fn tampered_tokens_are_refused() {
+        let tokens = FileTokens::new(b"secret");
+        let uid = Uuid::now_v7();
+        let token = tokens.issue(&uid, 0).token;
+        let parts: Vec<&str> = token.split('.').collect();
+
+        let other_user = format!("{}.{}.{}", Uuid::now_v7().as_simple(), parts[1], parts[2]);
+        assert_eq!(tokens.verify(&other_user, 0), None);
+        let extended = format!("{}.{}.{}", parts[0], i64::MAX, parts[2]);
+        assert_eq!(tokens.verify(&extended, 0), None);
+        let resigned = format!("{}.{}.{}", parts[0], parts[1], "AAAA");
+        assert_eq!(tokens.verify(&resigned, 0), None);
+
+        assert_eq!(FileTokens::new(b"other secret").verify(&token, 0), None);
+    }
+
+    #[test]
+    fn malformed_tokens_are_refused() {
+        let tokens = FileTokens::new(b"secret");
+        let token = tokens.issue(&Uuid::now_v7(), 0).token;
+        for token in [
+            "",
+            "...",
+            "a.b.c",
+            &format!("{token}."),
+            &format!("{token}.x"),
+        ] {
+            assert_eq!(tokens.verify(token, 0), None, "{token:?} should be refused");
+        }
+    }
+}
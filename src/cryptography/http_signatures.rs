@@ -0,0 +1,254 @@
+//! signing http requests, following the cavage http signatures draft used
+//! across the fediverse. the request target, host, date and a sha-256 digest
+//! of the body are always signed
+
+use std::time::SystemTime;
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use sha2::{Digest, Sha256};
+
+use super::keys::{PrivateKey, PublicKey, SigningAlgorithm};
+
+/// the headers every signature must cover, in the order they are signed
+pub const SIGNED_HEADERS: [&str; 4] = ["(request-target)", "host", "date", "digest"];
+
+/// the value of the digest header for a body
+pub fn digest(body: &[u8]) -> String {
+    format!("SHA-256={}", BASE64_STANDARD.encode(Sha256::digest(body)))
+}
+
+/// the parts of a request that are signed
+pub struct SignedParts<'a> {
+    /// lowercase http method
+    pub method: &'a str,
+    /// the path and query of the request
+    pub path: &'a str,
+    pub host: &'a str,
+    pub date: &'a str,
+    pub digest: &'a str,
+}
+
+impl SignedParts<'_> {
+    pub fn signing_string(&self) -> String {
+        format!(
+            "(request-target): {} {}\nhost: {}\ndate: {}\ndigest: {}",
+            self.method, self.path, self.host, self.date, self.digest
+        )
+    }
+}
+
+/// a parsed signature header
+#[derive(Debug, Clone)]
+pub struct SignatureHeader {
+    pub key_id: String,
+    pub algorithm: Option<String>,
+    pub headers: Vec<String>,
+    pub signature: Vec<u8>,
+}
+
+impl SignatureHeader {
+    /// parse a header of the form `keyId="...",algorithm="...",headers="...",signature="..."`
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut key_id = None;
+        let mut algorithm = None;
+        let mut headers = None;
+        let mut signature = None;
+        for field in header.split(',') {
+            let (name, value) = field.trim().split_once('=')?;
+            let value = value.strip_prefix('"')?.strip_suffix('"')?;
+            match name {
+                "keyId" => key_id = Some(value.to_string()),
+                "algorithm" => algorithm = Some(value.to_string()),
+                "headers" => headers = Some(value.split(' ').map(|x| x.to_lowercase()).collect()),
+                "signature" => signature = Some(BASE64_STANDARD.decode(value).ok()?),
+                _ => {}
+            }
+        }
+        Some(SignatureHeader {
+            key_id: key_id?,
+            algorithm,
+            // the date is the only header signed when none are listed
+            headers: headers.unwrap_or_else(|| vec!["date".to_string()]),
+            signature: signature?,
+        })
+    }
+    /// if the signature covers every header it must
+    pub fn covers_required(&self) -> bool {
+        SIGNED_HEADERS
+            .iter()
+            .all(|x| self.headers.iter().any(|y| y == x))
+            && self.headers.len() == SIGNED_HEADERS.len()
+    }
+    /// if the algorithm named in the header is the one the key uses, `hs2019`
+    /// leaves it to the key
+    pub fn algorithm_matches(&self, algorithm: SigningAlgorithm) -> bool {
+        match self.algorithm.as_deref() {
+            None | Some("hs2019") => true,
+            Some(x) => x == algorithm.signature_name(),
+        }
+    }
+    pub fn verify(&self, key: &PublicKey, parts: &SignedParts) -> bool {
+        self.algorithm_matches(key.algorithm())
+            && key.verify(parts.signing_string().as_bytes(), &self.signature)
+    }
+}
+
+/// the date, digest and signature headers for a request
+pub struct RequestSignature {
+    pub date: String,
+    pub digest: String,
+    pub signature: String,
+}
+
+pub fn sign_request(
+    key: &PrivateKey,
+    key_id: &str,
+    method: &str,
+    path: &str,
+    host: &str,
+    body: &[u8],
+) -> RequestSignature {
+    let date = httpdate::fmt_http_date(SystemTime::now());
+    let digest = digest(body);
+    let parts = SignedParts {
+        method,
+        path,
+        host,
+        date: &date,
+        digest: &digest,
+    };
+    let signature = key.sign(parts.signing_string().as_bytes());
+    let signature = format!(
+        r#"keyId="{key_id}",algorithm="{}",headers="{}",signature="{}""#,
+        key.algorithm().signature_name(),
+        SIGNED_HEADERS.join(" "),
+        BASE64_STANDARD.encode(signature)
+    );
+    RequestSignature {
+        date,
+        digest,
+        signature,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_ID: &str = "https://a.example/.well-known/bayou/key";
+
+    fn parts<'a>(signature: &'a RequestSignature, host: &'a str) -> SignedParts<'a> {
+        SignedParts {
+            method: "post",
+            path: "/.well-known/bayou/inbox?a=b",
+            host,
+            date: &signature.date,
+            digest: &signature.digest,
+        }
+    }
+
+    #[test]
+    fn signatures_verify_with_the_signing_key() {
+        for algorithm in [SigningAlgorithm::Ed25519, SigningAlgorithm::Rsa] {
+            let key = PrivateKey::generate(algorithm);
+            let signed = sign_request(
+                &key,
+                KEY_ID,
+                "post",
+                "/.well-known/bayou/inbox?a=b",
+                "b.example",
+                b"{}",
+            );
+            let header = SignatureHeader::parse(&signed.signature).unwrap();
+            assert_eq!(header.key_id, KEY_ID);
+            assert!(header.covers_required());
+            assert!(header.verify(&key.public_key(), &parts(&signed, "b.example")));
+        }
+        // generating rsa keys is slow so only ed25519 is checked against another key
+        let key = PrivateKey::generate(SigningAlgorithm::Ed25519);
+        let signed = sign_request(&key, KEY_ID, "post", "/inbox", "b.example", b"{}");
+        let header = SignatureHeader::parse(&signed.signature).unwrap();
+        let other = PrivateKey::generate(SigningAlgorithm::Ed25519);
+        let parts = SignedParts {
+            path: "/inbox",
+            ..parts(&signed, "b.example")
+        };
+        assert!(header.verify(&key.public_key(), &parts));
+        assert!(!header.verify(&other.public_key(), &parts));
+    }
+
+    #[test]
+    fn changed_requests_do_not_verify() {
+        let key = PrivateKey::generate(SigningAlgorithm::Ed25519);
+        let signed = sign_request(&key, KEY_ID, "post", "/inbox", "b.example", b"{}");
+        let header = SignatureHeader::parse(&signed.signature).unwrap();
+        let public_key = key.public_key();
+
+        assert!(!header.verify(&public_key, &parts(&signed, "c.example")));
+        let other_digest = digest(b"{\"a\":1}");
+        let changed = SignedParts {
+            digest: &other_digest,
+            ..parts(&signed, "b.example")
+        };
+        assert!(!header.verify(&public_key, &changed));
+        let changed = SignedParts {
+            method: "get",
+            path: "/inbox",
+            ..parts(&signed, "b.example")
+        };
+        assert!(!header.verify(&public_key, &changed));
+    }
+
+    #[test]
+    fn the_algorithm_must_match_the_key() {
+        let key = PrivateKey::generate(SigningAlgorithm::Ed25519);
+        let signed = sign_request(&key, KEY_ID, "post", "/inbox", "b.example", b"");
+        let mut header = SignatureHeader::parse(&signed.signature).unwrap();
+        let parts = SignedParts {
+            method: "post",
+            path: "/inbox",
+            host: "b.example",
+            date: &signed.date,
+            digest: &signed.digest,
+        };
+        header.algorithm = Some("hs2019".to_string());
+        assert!(header.verify(&key.public_key(), &parts));
+        header.algorithm = None;
+        assert!(header.verify(&key.public_key(), &parts));
+        header.algorithm = Some("rsa-sha256".to_string());
+        assert!(!header.verify(&key.public_key(), &parts));
+    }
+
+    #[test]
+    fn parses_signature_headers() {
+        let header = SignatureHeader::parse(
+            r#"keyId="https://a.example/key", headers="(request-target) Host Date Digest",signature="AAEC""#,
+        )
+        .unwrap();
+        assert_eq!(header.key_id, "https://a.example/key");
+        assert_eq!(header.algorithm, None);
+        assert_eq!(header.signature, [0, 1, 2]);
+        assert!(header.covers_required());
+
+        // only the date is signed when no headers are listed
+        let header = SignatureHeader::parse(r#"keyId="k",signature="AAEC""#).unwrap();
+        assert_eq!(header.headers, ["date"]);
+        assert!(!header.covers_required());
+        let header = SignatureHeader::parse(
+            r#"keyId="k",headers="(request-target) host date digest content-type",signature="AAEC""#,
+        )
+        .unwrap();
+        assert!(!header.covers_required());
+
+        for malformed in [
+            "",
+            r#"signature="AAEC""#,
+            r#"keyId="k""#,
+            r#"keyId=k,signature="AAEC""#,
+            r#"keyId="k",signature="not base64!""#,
+            r#"keyId="k",signature"#,
+        ] {
+            assert!(SignatureHeader::parse(malformed).is_none(), "{malformed:?}");
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! the keys instances sign federation requests with
+
+use std::str::FromStr;
+
+use ed25519_dalek::{
+    pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey},
+    Signer, Verifier,
+};
+use rand::rngs::OsRng;
+use rsa::{
+    pkcs1v15,
+    pkcs8::LineEnding,
+    signature::{SignatureEncoding, Signer as RsaSigner, Verifier as RsaVerifier},
+    RsaPrivateKey, RsaPublicKey,
+};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+const RSA_BITS: usize = 2048;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningAlgorithm {
+    #[default]
+    Ed25519,
+    /// rsa with pkcs1v15 signatures of sha256 digests
+    Rsa,
+}
+
+impl SigningAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SigningAlgorithm::Ed25519 => "ed25519",
+            SigningAlgorithm::Rsa => "rsa",
+        }
+    }
+    /// the name of the algorithm in the signature header
+    pub fn signature_name(&self) -> &'static str {
+        match self {
+            SigningAlgorithm::Ed25519 => "ed25519",
+            SigningAlgorithm::Rsa => "rsa-sha256",
+        }
+    }
+}
+
+impl FromStr for SigningAlgorithm {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ed25519" => Ok(Self::Ed25519),
+            "rsa" => Ok(Self::Rsa),
+            _ => Err(()),
+        }
+    }
+}
+
+pub enum PrivateKey {
+    Ed25519(ed25519_dalek::SigningKey),
+    Rsa(pkcs1v15::SigningKey<Sha256>),
+}
+
+impl PrivateKey {
+    pub fn generate(algorithm: SigningAlgorithm) -> Self {
+        match algorithm {
+            SigningAlgorithm::Ed25519 => {
+                PrivateKey::Ed25519(ed25519_dalek::SigningKey::generate(&mut OsRng))
+            }
+            SigningAlgorithm::Rsa => {
+                let key = RsaPrivateKey::new(&mut OsRng, RSA_BITS).expect("failed to generate key");
+                PrivateKey::Rsa(pkcs1v15::SigningKey::new(key))
+            }
+        }
+    }
+    /// read a pkcs8 pem encoded key
+    pub fn from_pem(algorithm: SigningAlgorithm, pem: &str) -> Option<Self> {
+        match algorithm {
+            SigningAlgorithm::Ed25519 => ed25519_dalek::SigningKey::from_pkcs8_pem(pem)
+                .ok()
+                .map(PrivateKey::Ed25519),
+            SigningAlgorithm::Rsa => RsaPrivateKey::from_pkcs8_pem(pem)
+                .ok()
+                .map(|x| PrivateKey::Rsa(pkcs1v15::SigningKey::new(x))),
+        }
+    }
+    pub fn to_pem(&self) -> String {
+        let pem = match self {
+            PrivateKey::Ed25519(key) => key.to_pkcs8_pem(LineEnding::LF),
+            PrivateKey::Rsa(key) => key.as_ref().to_pkcs8_pem(LineEnding::LF),
+        };
+        pem.expect("failed to encode key").to_string()
+    }
+    pub fn algorithm(&self) -> SigningAlgorithm {
+        match self {
+            PrivateKey::Ed25519(_) => SigningAlgorithm::Ed25519,
+            PrivateKey::Rsa(_) => SigningAlgorithm::Rsa,
+        }
+    }
+    pub fn public_key(&self) -> PublicKey {
+        match self {
+            PrivateKey::Ed25519(key) => PublicKey::Ed25519(key.verifying_key()),
+            PrivateKey::Rsa(key) => PublicKey::Rsa(key.as_ref().to_public_key()),
+        }
+    }
+    pub fn sign(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            PrivateKey::Ed25519(key) => key.sign(data).to_vec(),
+            PrivateKey::Rsa(key) => RsaSigner::sign(key, data).to_vec(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum PublicKey {
+    Ed25519(ed25519_dalek::VerifyingKey),
+    Rsa(RsaPublicKey),
+}
+
+impl PublicKey {
+    /// read a spki pem encoded key
+    pub fn from_pem(algorithm: SigningAlgorithm, pem: &str) -> Option<Self> {
+        match algorithm {
+            SigningAlgorithm::Ed25519 => ed25519_dalek::VerifyingKey::from_public_key_pem(pem)
+                .ok()
+                .map(PublicKey::Ed25519),
+            SigningAlgorithm::Rsa => RsaPublicKey::from_public_key_pem(pem)
+                .ok()
+                .map(PublicKey::Rsa),
+        }
+    }
+    pub fn to_pem(&self) -> String {
+        let pem = match self {
+            PublicKey::Ed25519(key) => key.to_public_key_pem(LineEnding::LF),
+            PublicKey::Rsa(key) => key.to_public_key_pem(LineEnding::LF),
+        };
+        pem.expect("failed to encode key")
+    }
+    pub fn algorithm(&self) -> SigningAlgorithm {
+        match self {
+            PublicKey::Ed25519(_) => SigningAlgorithm::Ed25519,
+            PublicKey::Rsa(_) => SigningAlgorithm::Rsa,
+        }
+    }
+    pub fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        match self {
+            PublicKey::Ed25519(key) => {
+                let Ok(signature) = ed25519_dalek::Signature::from_slice(signature) else {
+                    return false;
+                };
+                key.verify(data, &signature).is_ok()
+            }
+            PublicKey::Rsa(key) => {
+                let Ok(signature) = pkcs1v15::Signature::try_from(signature) else {
+                    return false;
+                };
+                let key = pkcs1v15::VerifyingKey::<Sha256>::new(key.clone());
+                RsaVerifier::verify(&key, data, &signature).is_ok()
+            }
+        }
+    }
+}
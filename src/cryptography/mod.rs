@@ -1 +1,4 @@
+pub mod file_tokens;
+pub mod http_signatures;
+pub mod keys;
 pub mod passwords;
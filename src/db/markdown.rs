@@ -0,0 +1,920 @@
+//! the markdown dialect of messages, a subset of commonmark with strikethrough,
+//! `||spoilers||`, mentions and `:shortcode:` custom emoji. messages are rendered
+//! to a tree of [`Block`] so clients don't need their own parser, raw html is
+//! kept as text and only web and email links are allowed
+
+use std::ops::Range;
+
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+use super::{mentions::mention_at, types::message::TextFormat};
+
+/// blocks and inline formatting nested deeper than this are flattened into their parent
+pub const MAX_NESTING: usize = 10;
+pub const MAX_SHORTCODE_LENGTH: usize = 64;
+/// replaces the content of spoilers in the plain text of a message
+pub const SPOILER_PLACEHOLDER: &str = "[spoiler]";
+
+const ALLOWED_SCHEMES: [&str; 3] = ["http:", "https:", "mailto:"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    Paragraph(Vec<Inline>),
+    Heading {
+        /// 1 to 6
+        level: u8,
+        content: Vec<Inline>,
+    },
+    Quote(Vec<Block>),
+    CodeBlock {
+        language: Option<String>,
+        code: String,
+    },
+    List {
+        /// the number of the first item of ordered lists
+        start: Option<u64>,
+        items: Vec<Vec<Block>>,
+    },
+    Rule,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Inline {
+    Text(String),
+    Emphasis(Vec<Inline>),
+    Strong(Vec<Inline>),
+    Strikethrough(Vec<Inline>),
+    Spoiler(Vec<Inline>),
+    Code(String),
+    Link {
+        url: String,
+        content: Vec<Inline>,
+    },
+    /// a mention as written, clients should only display it as a mention
+    /// when it is one of the message's resolved mentions
+    Mention {
+        name: String,
+        domain: Option<String>,
+    },
+    Emoji(String),
+    LineBreak,
+}
+
+/// a message as rendered for clients along with its plain text
+#[derive(Debug, Clone)]
+pub struct RenderedMessage {
+    /// none for plain text messages
+    pub blocks: Option<Vec<Block>>,
+    /// the content without formatting and with spoilers hidden, used for
+    /// notifications, search and reply previews
+    pub plain: String,
+}
+
+/// render the content of a message
+pub fn render_message(content: &str, format: TextFormat) -> RenderedMessage {
+    match format {
+        TextFormat::Plain => RenderedMessage {
+            blocks: None,
+            plain: content.to_string(),
+        },
+        TextFormat::Markdown => {
+            let blocks = render_markdown(content);
+            RenderedMessage {
+                plain: plain_blocks(&blocks),
+                blocks: Some(blocks),
+            }
+        }
+    }
+}
+
+pub fn render_markdown(content: &str) -> Vec<Block> {
+    let parser = Parser::new_ext(content, Options::ENABLE_STRIKETHROUGH);
+    let mut builder = Builder {
+        source: content,
+        stack: vec![Frame::new(Container::Root)],
+    };
+    for (event, range) in parser.into_offset_iter() {
+        builder.event(event, range);
+    }
+    let root = builder.stack.pop().expect("the root is never popped");
+    root.into_blocks()
+}
+
+/// every inline of the blocks in the order they appear
+pub fn walk_inlines<'a>(blocks: &'a [Block], visit: &mut impl FnMut(&'a Inline)) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(content) | Block::Heading { content, .. } => {
+                walk_inline_list(content, visit)
+            }
+            Block::Quote(blocks) => walk_inlines(blocks, visit),
+            Block::List { items, .. } => {
+                for item in items {
+                    walk_inlines(item, visit);
+                }
+            }
+            Block::CodeBlock { .. } | Block::Rule => {}
+        }
+    }
+}
+
+fn walk_inline_list<'a>(inlines: &'a [Inline], visit: &mut impl FnMut(&'a Inline)) {
+    for inline in inlines {
+        visit(inline);
+        match inline {
+            Inline::Emphasis(content)
+            | Inline::Strong(content)
+            | Inline::Strikethrough(content)
+            | Inline::Spoiler(content)
+            | Inline::Link { content, .. } => walk_inline_list(content, visit),
+            _ => {}
+        }
+    }
+}
+
+enum Container {
+    Root,
+    Quote,
+    List(Option<u64>),
+    Item,
+    Paragraph,
+    Heading(u8),
+    CodeBlock(Option<String>),
+    Emphasis,
+    Strong,
+    Strikethrough,
+    /// links with a disallowed url keep their content without the link
+    Link(Option<String>),
+    /// a container past [`MAX_NESTING`], its content is moved to its parent
+    Flattened,
+}
+
+impl Container {
+    fn nests(&self) -> bool {
+        !matches!(
+            self,
+            Container::Root
+                | Container::Paragraph
+                | Container::Heading(_)
+                | Container::CodeBlock(_)
+        )
+    }
+}
+
+/// a piece of inline content that is not finished yet
+enum Piece {
+    /// text with the byte offsets of characters that were escaped with a backslash
+    Text(String, Vec<usize>),
+    Inline(Inline),
+    /// `||`
+    SpoilerDelimiter,
+}
+
+struct Frame {
+    container: Container,
+    blocks: Vec<Block>,
+    pieces: Vec<Piece>,
+    items: Vec<Vec<Block>>,
+    code: String,
+}
+
+impl Frame {
+    fn new(container: Container) -> Self {
+        Frame {
+            container,
+            blocks: Vec::new(),
+            pieces: Vec::new(),
+            items: Vec::new(),
+            code: String::new(),
+        }
+    }
+    fn push_text(&mut self, text: &str, escaped: bool) {
+        if let Some(Piece::Text(existing, escapes)) = self.pieces.last_mut() {
+            if escaped {
+                escapes.push(existing.len());
+            }
+            existing.push_str(text);
+            return;
+        }
+        let escapes = if escaped { vec![0] } else { Vec::new() };
+        self.pieces.push(Piece::Text(text.to_string(), escapes));
+    }
+    /// inline content directly in a block container, such as in the items
+    /// of tight lists, becomes a paragraph
+    fn flush_paragraph(&mut self) {
+        if self.pieces.is_empty() {
+            return;
+        }
+        let content = finish_inlines(std::mem::take(&mut self.pieces));
+        self.blocks.push(Block::Paragraph(content));
+    }
+    fn into_blocks(mut self) -> Vec<Block> {
+        self.flush_paragraph();
+        self.blocks
+    }
+}
+
+struct Builder<'a> {
+    source: &'a str,
+    stack: Vec<Frame>,
+}
+
+impl Builder<'_> {
+    fn top(&mut self) -> &mut Frame {
+        self.stack.last_mut().expect("the root is never popped")
+    }
+    fn nesting(&self) -> usize {
+        self.stack.iter().filter(|x| x.container.nests()).count()
+    }
+    fn push_block(&mut self, block: Block) {
+        let top = self.top();
+        top.flush_paragraph();
+        top.blocks.push(block);
+    }
+    fn push_inline(&mut self, inline: Inline) {
+        self.top().pieces.push(Piece::Inline(inline));
+    }
+    fn event(&mut self, event: Event, range: Range<usize>) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => {
+                if let Container::CodeBlock(_) = self.top().container {
+                    self.top().code.push_str(&text);
+                    return;
+                }
+                // the backslash of an escape is left out of the text event
+                let escaped = range.start > 0
+                    && self.source.as_bytes()[range.start - 1] == b'\\'
+                    && !self.source[range.clone()].starts_with('\\');
+                self.top().push_text(&text, escaped);
+            }
+            Event::Code(code) => self.push_inline(Inline::Code(code.to_string())),
+            // raw html is kept as text
+            Event::Html(html) | Event::InlineHtml(html) => self.top().push_text(&html, false),
+            // newlines are kept as written as is expected in chat
+            Event::SoftBreak | Event::HardBreak => self.push_inline(Inline::LineBreak),
+            Event::Rule => self.push_block(Block::Rule),
+            _ => {}
+        }
+    }
+    fn start(&mut self, tag: Tag) {
+        let container = match tag {
+            Tag::Paragraph => Container::Paragraph,
+            Tag::Heading { level, .. } => Container::Heading(level as u8),
+            Tag::BlockQuote(_) => Container::Quote,
+            Tag::CodeBlock(kind) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .filter(|x| !x.is_empty())
+                        .map(|x| x.to_string()),
+                    CodeBlockKind::Indented => None,
+                };
+                Container::CodeBlock(language)
+            }
+            Tag::List(start) => Container::List(start),
+            Tag::Item => Container::Item,
+            Tag::Emphasis => Container::Emphasis,
+            Tag::Strong => Container::Strong,
+            Tag::Strikethrough => Container::Strikethrough,
+            // images are only linked to
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+                Container::Link(allowed_url(&dest_url))
+            }
+            _ => Container::Flattened,
+        };
+        let container = match container.nests() && self.nesting() >= MAX_NESTING {
+            true => Container::Flattened,
+            false => container,
+        };
+        if !matches!(
+            container,
+            Container::Emphasis
+                | Container::Strong
+                | Container::Strikethrough
+                | Container::Link(_)
+                | Container::Flattened
+        ) {
+            self.top().flush_paragraph();
+        }
+        self.stack.push(Frame::new(container));
+    }
+    fn end(&mut self, _tag: TagEnd) {
+        if self.stack.len() <= 1 {
+            return;
+        }
+        let mut frame = self.stack.pop().expect("checked above");
+        match frame.container {
+            Container::Root => unreachable!("the root is never popped"),
+            Container::Paragraph => {
+                let content = finish_inlines(frame.pieces);
+                self.push_block(Block::Paragraph(content));
+            }
+            Container::Heading(level) => {
+                let content = finish_inlines(frame.pieces);
+                self.push_block(Block::Heading { level, content });
+            }
+            Container::CodeBlock(language) => {
+                let mut code = frame.code;
+                if code.ends_with('\n') {
+                    code.pop();
+                }
+                self.push_block(Block::CodeBlock { language, code });
+            }
+            Container::Quote => {
+                let blocks = frame.into_blocks();
+                self.push_block(Block::Quote(blocks));
+            }
+            Container::List(start) => {
+                frame.flush_paragraph();
+                // flattened items leave their content directly in the list
+                if !frame.blocks.is_empty() {
+                    frame.items.push(frame.blocks);
+                }
+                self.push_block(Block::List {
+                    start,
+                    items: frame.items,
+                });
+            }
+            Container::Item => {
+                let blocks = frame.into_blocks();
+                match &mut self.top().container {
+                    Container::List(_) => self.top().items.push(blocks),
+                    _ => {
+                        for block in blocks {
+                            self.push_block(block);
+                        }
+                    }
+                }
+            }
+            Container::Emphasis => self.push_inline(Inline::Emphasis(finish_inlines(frame.pieces))),
+            Container::Strong => self.push_inline(Inline::Strong(finish_inlines(frame.pieces))),
+            Container::Strikethrough => {
+                self.push_inline(Inline::Strikethrough(finish_inlines(frame.pieces)))
+            }
+            Container::Link(Some(url)) => {
+                let content = finish_inlines(frame.pieces);
+                self.push_inline(Inline::Link { url, content });
+            }
+            Container::Link(None) | Container::Flattened => {
+                if !frame.blocks.is_empty() {
+                    frame.flush_paragraph();
+                    for block in frame.blocks {
+                        self.push_block(block);
+                    }
+                }
+                let top = self.top();
+                for piece in frame.pieces {
+                    match piece {
+                        Piece::Text(text, escapes) => {
+                            let offset = match top.pieces.last() {
+                                Some(Piece::Text(existing, _)) => existing.len(),
+                                _ => 0,
+                            };
+                            top.push_text(&text, false);
+                            if let Some(Piece::Text(_, existing)) = top.pieces.last_mut() {
+                                existing.extend(escapes.into_iter().map(|x| x + offset));
+                            }
+                        }
+                        piece => top.pieces.push(piece),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn allowed_url(url: &str) -> Option<String> {
+    let lowercase = url.trim().to_ascii_lowercase();
+    ALLOWED_SCHEMES
+        .iter()
+        .any(|x| lowercase.starts_with(x))
+        .then(|| url.trim().to_string())
+}
+
+/// split text into spoilers, mentions and emoji
+fn finish_inlines(pieces: Vec<Piece>) -> Vec<Inline> {
+    let mut split = Vec::with_capacity(pieces.len());
+    for piece in pieces {
+        match piece {
+            Piece::Text(text, escapes) => split_spoilers(text, escapes, &mut split),
+            piece => split.push(piece),
+        }
+    }
+    // delimiters pair up from the left, a delimiter left over is text
+    let delimiters = split
+        .iter()
+        .filter(|x| matches!(x, Piece::SpoilerDelimiter))
+        .count();
+    let mut unpaired = delimiters % 2 == 1;
+    let mut remaining = delimiters;
+    let mut inlines: Vec<Inline> = Vec::new();
+    let mut spoiler: Option<Vec<Inline>> = None;
+    for piece in split {
+        let target = spoiler.as_mut().unwrap_or(&mut inlines);
+        match piece {
+            Piece::Text(text, escapes) => split_text(&text, &escapes, target),
+            Piece::Inline(inline) => target.push(inline),
+            Piece::SpoilerDelimiter => {
+                remaining -= 1;
+                if unpaired && remaining == 0 {
+                    unpaired = false;
+                    push_text(target, "||");
+                    continue;
+                }
+                match spoiler.take() {
+                    None => spoiler = Some(Vec::new()),
+                    Some(content) if content.is_empty() => push_text(&mut inlines, "||||"),
+                    Some(content) => inlines.push(Inline::Spoiler(content)),
+                }
+            }
+        }
+    }
+    inlines
+}
+
+fn split_spoilers(text: String, escapes: Vec<usize>, split: &mut Vec<Piece>) {
+    let mut start = 0;
+    let mut search = 0;
+    while let Some(found) = text[search..].find("||") {
+        let at = search + found;
+        if escapes.contains(&at) || escapes.contains(&(at + 1)) {
+            search = at + 1;
+            continue;
+        }
+        if at > start {
+            split.push(text_piece(&text, start..at, &escapes));
+        }
+        split.push(Piece::SpoilerDelimiter);
+        start = at + 2;
+        search = start;
+    }
+    if start < text.len() {
+        split.push(text_piece(&text, start..text.len(), &escapes));
+    }
+}
+
+fn text_piece(text: &str, range: Range<usize>, escapes: &[usize]) -> Piece {
+    let escapes = escapes
+        .iter()
+        .filter(|x| range.contains(*x))
+        .map(|x| x - range.start)
+        .collect();
+    Piece::Text(text[range].to_string(), escapes)
+}
+
+fn push_text(inlines: &mut Vec<Inline>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match inlines.last_mut() {
+        Some(Inline::Text(existing)) => existing.push_str(text),
+        _ => inlines.push(Inline::Text(text.to_string())),
+    }
+}
+
+/// find the mentions and emoji in text
+fn split_text(text: &str, escapes: &[usize], inlines: &mut Vec<Inline>) {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let plain: Vec<char> = chars.iter().map(|x| x.1).collect();
+    let escaped = |pos: usize| escapes.contains(&chars[pos].0);
+    let byte = |pos: usize| chars.get(pos).map_or(text.len(), |x| x.0);
+    let mut start = 0;
+    let mut pos = 0;
+    while pos < chars.len() {
+        if escaped(pos) {
+            pos += 1;
+            continue;
+        }
+        let found = match plain[pos] {
+            '@' => mention_at(&plain, pos).map(|(token, end)| {
+                let mention = Inline::Mention {
+                    name: token.name,
+                    domain: token.domain,
+                };
+                (mention, end)
+            }),
+            ':' => emoji_at(&plain, pos).map(|end| {
+                let shortcode = plain[pos + 1..end - 1].iter().collect();
+                (Inline::Emoji(shortcode), end)
+            }),
+            _ => None,
+        };
+        let Some((inline, end)) = found else {
+            pos += 1;
+            continue;
+        };
+        push_text(inlines, &text[byte(start)..byte(pos)]);
+        inlines.push(inline);
+        pos = end;
+        start = end;
+    }
+    push_text(inlines, &text[byte(start)..]);
+}
+
+fn is_shortcode_char(x: char) -> bool {
+    x.is_ascii_alphanumeric() || matches!(x, '_' | '-' | '+')
+}
+
+/// the end of a `:shortcode:` starting at `pos`. shortcodes must not directly
+/// follow or be followed by letters or numbers so times such as `10:30:00`
+/// are left as text
+fn emoji_at(chars: &[char], pos: usize) -> Option<usize> {
+    if pos > 0 && chars[pos - 1].is_alphanumeric() {
+        return None;
+    }
+    let length = chars[pos + 1..]
+        .iter()
+        .take(MAX_SHORTCODE_LENGTH + 1)
+        .take_while(|x| is_shortcode_char(**x))
+        .count();
+    if length == 0 || length > MAX_SHORTCODE_LENGTH {
+        return None;
+    }
+    let end = pos + 1 + length;
+    if chars.get(end) != Some(&':') {
+        return None;
+    }
+    if chars.get(end + 1).is_some_and(|x| x.is_alphanumeric()) {
+        return None;
+    }
+    Some(end + 1)
+}
+
+fn plain_blocks(blocks: &[Block]) -> String {
+    let mut lines: Vec<String> = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        let line = match block {
+            Block::Paragraph(content) | Block::Heading { content, .. } => plain_inlines(content),
+            Block::Quote(blocks) => plain_blocks(blocks),
+            Block::CodeBlock { code, .. } => code.clone(),
+            Block::List { start, items } => items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| match start {
+                    Some(start) => format!("{}. {}", start + index as u64, plain_blocks(item)),
+                    None => format!("- {}", plain_blocks(item)),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Block::Rule => continue,
+        };
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+fn plain_inlines(inlines: &[Inline]) -> String {
+    let mut plain = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Text(text) | Inline::Code(text) => plain.push_str(text),
+            Inline::Emphasis(content)
+            | Inline::Strong(content)
+            | Inline::Strikethrough(content)
+            | Inline::Link { content, .. } => plain.push_str(&plain_inlines(content)),
+            Inline::Spoiler(_) => plain.push_str(SPOILER_PLACEHOLDER),
+            Inline::Mention { name, domain } => {
+                plain.push('@');
+                plain.push_str(name);
+                if let Some(domain) = domain {
+                    plain.push('@');
+                    plain.push_str(domain);
+                }
+            }
+            Inline::Emoji(shortcode) => {
+                plain.push(':');
+                plain.push_str(shortcode);
+                plain.push(':');
+            }
+            Inline::LineBreak => plain.push('\n'),
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> Inline {
+        Inline::Text(text.to_string())
+    }
+
+    fn paragraph(content: Vec<Inline>) -> Block {
+        Block::Paragraph(content)
+    }
+
+    fn plain(content: &str) -> String {
+        render_message(content, TextFormat::Markdown).plain
+    }
+
+    /// how deeply blocks are nested within quotes and lists
+    fn depth(blocks: &[Block]) -> usize {
+        blocks
+            .iter()
+            .map(|block| match block {
+                Block::Quote(blocks) => 1 + depth(blocks),
+                Block::List { items, .. } => 1 + items.iter().map(|x| depth(x)).max().unwrap_or(0),
+                _ => 0,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// how deeply inline formatting is nested
+    fn inline_depth(inlines: &[Inline]) -> usize {
+        inlines
+            .iter()
+            .map(|inline| match inline {
+                Inline::Emphasis(content)
+                | Inline::Strong(content)
+                | Inline::Strikethrough(content)
+                | Inline::Spoiler(content)
+                | Inline::Link { content, .. } => 1 + inline_depth(content),
+                _ => 0,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn paragraphs_keep_their_line_breaks() {
+        assert_eq!(
+            render_markdown("a\nb\n\nc"),
+            [
+                paragraph(vec![text("a"), Inline::LineBreak, text("b")]),
+                paragraph(vec![text("c")])
+            ]
+        );
+        assert_eq!(render_markdown(""), []);
+    }
+
+    #[test]
+    fn renders_inline_formatting() {
+        assert_eq!(
+            render_markdown("*a* **b** ~~c~~ `*d*`"),
+            [paragraph(vec![
+                Inline::Emphasis(vec![text("a")]),
+                text(" "),
+                Inline::Strong(vec![text("b")]),
+                text(" "),
+                Inline::Strikethrough(vec![text("c")]),
+                text(" "),
+                Inline::Code("*d*".to_string()),
+            ])]
+        );
+        assert_eq!(
+            render_markdown("***a***"),
+            [paragraph(vec![Inline::Emphasis(vec![Inline::Strong(
+                vec![text("a")]
+            )])])]
+        );
+    }
+
+    #[test]
+    fn renders_headings_quotes_and_rules() {
+        assert_eq!(
+            render_markdown("# a\n###### b\n\n---\n\n> c\n> > d"),
+            [
+                Block::Heading {
+                    level: 1,
+                    content: vec![text("a")]
+                },
+                Block::Heading {
+                    level: 6,
+                    content: vec![text("b")]
+                },
+                Block::Rule,
+                Block::Quote(vec![
+                    paragraph(vec![text("c")]),
+                    Block::Quote(vec![paragraph(vec![text("d")])])
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_code_blocks() {
+        assert_eq!(
+            render_markdown("```rust ignore\nfn main() {}\n```\n\n    @a *b*"),
+            [
+                Block::CodeBlock {
+                    language: Some("rust".to_string()),
+                    code: "fn main() {}".to_string()
+                },
+                Block::CodeBlock {
+                    language: None,
+                    code: "@a *b*".to_string()
+                },
+            ]
+        );
+        // unclosed fences run to the end of the message
+        assert_eq!(
+            render_markdown("```\n||a|| :b:"),
+            [Block::CodeBlock {
+                language: None,
+                code: "||a|| :b:".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn renders_lists() {
+        assert_eq!(
+            render_markdown("- a\n- b"),
+            [Block::List {
+                start: None,
+                items: vec![
+                    vec![paragraph(vec![text("a")])],
+                    vec![paragraph(vec![text("b")])]
+                ]
+            }]
+        );
+        assert_eq!(
+            render_markdown("3. a\n4. b\n   - c"),
+            [Block::List {
+                start: Some(3),
+                items: vec![
+                    vec![paragraph(vec![text("a")])],
+                    vec![
+                        paragraph(vec![text("b")]),
+                        Block::List {
+                            start: None,
+                            items: vec![vec![paragraph(vec![text("c")])]]
+                        }
+                    ]
+                ]
+            }]
+        );
+    }
+
+    #[test]
+    fn only_web_and_email_links_are_kept() {
+        assert_eq!(
+            render_markdown(
+                "[a](https://example.com) [b](javascript:alert(1)) <mailto:c@example.com> ![d](HTTP://example.com/d.png)"
+            ),
+            [paragraph(vec![
+                Inline::Link {
+                    url: "https://example.com".to_string(),
+                    content: vec![text("a")]
+                },
+                text(" b "),
+                Inline::Link {
+                    url: "mailto:c@example.com".to_string(),
+                    content: vec![text("mailto:c@example.com")]
+                },
+                text(" "),
+                Inline::Link {
+                    url: "HTTP://example.com/d.png".to_string(),
+                    content: vec![text("d")]
+                },
+            ])]
+        );
+        assert_eq!(
+            render_markdown("[a](data:text/html,x)"),
+            [paragraph(vec![text("a")])]
+        );
+    }
+
+    #[test]
+    fn html_is_kept_as_text() {
+        assert_eq!(
+            render_markdown("a <b>c</b>"),
+            [paragraph(vec![text("a <b>c</b>")])]
+        );
+        let blocks = render_markdown("<script>alert(1)</script>");
+        assert_eq!(plain_blocks(&blocks).trim(), "<script>alert(1)</script>");
+    }
+
+    #[test]
+    fn renders_spoilers() {
+        assert_eq!(
+            render_markdown("||a *b*|| c"),
+            [paragraph(vec![
+                Inline::Spoiler(vec![text("a "), Inline::Emphasis(vec![text("b")])]),
+                text(" c"),
+            ])]
+        );
+        // unpaired, empty and escaped delimiters are text
+        assert_eq!(
+            render_markdown("||a|| ||b"),
+            [paragraph(vec![
+                Inline::Spoiler(vec![text("a")]),
+                text(" ||b")
+            ])]
+        );
+        assert_eq!(render_markdown("||||"), [paragraph(vec![text("||||")])]);
+        assert_eq!(render_markdown("\\||a||"), [paragraph(vec![text("||a||")])]);
+    }
+
+    #[test]
+    fn renders_mentions_and_emoji() {
+        assert_eq!(
+            render_markdown("@Alice@Example.com :blob_cat: 10:30:00 \\@bob \\:c: `@d`"),
+            [paragraph(vec![
+                Inline::Mention {
+                    name: "alice".to_string(),
+                    domain: Some("example.com".to_string())
+                },
+                text(" "),
+                Inline::Emoji("blob_cat".to_string()),
+                text(" 10:30:00 @bob :c: "),
+                Inline::Code("@d".to_string()),
+            ])]
+        );
+        let long = format!(":{}:", "a".repeat(MAX_SHORTCODE_LENGTH + 1));
+        assert_eq!(render_markdown(&long), [paragraph(vec![text(&long)])]);
+    }
+
+    #[test]
+    fn walks_nested_inlines() {
+        let blocks = render_markdown("> - **@a [@b](https://example.com)** ||@c||");
+        let mut mentions = Vec::new();
+        walk_inlines(&blocks, &mut |inline| {
+            if let Inline::Mention { name, .. } = inline {
+                mentions.push(name.as_str());
+            }
+        });
+        assert_eq!(mentions, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn deep_nesting_is_flattened() {
+        let quotes = format!("{} a", ">".repeat(100));
+        let blocks = render_markdown(&quotes);
+        assert!(depth(&blocks) <= MAX_NESTING);
+        assert_eq!(plain_blocks(&blocks), "a");
+
+        let list: Vec<String> = (0..50)
+            .map(|x| format!("{}- item{x}", "  ".repeat(x)))
+            .collect();
+        let blocks = render_markdown(&list.join("\n"));
+        assert!(depth(&blocks) <= MAX_NESTING);
+        let plain = plain_blocks(&blocks);
+        assert!((0..50).all(|x| plain.contains(&format!("item{x}"))));
+
+        // *w **w *w ... m ... w* w** w*
+        let delimiter = |x: usize| if x.is_multiple_of(2) { "*" } else { "**" };
+        let opening: String = (0..50).map(|x| format!("{}w ", delimiter(x))).collect();
+        let closing: String = (1..50)
+            .rev()
+            .map(|x| format!("{} w", delimiter(x)))
+            .collect();
+        let emphasis = format!("{opening}m{closing}*");
+        let blocks = render_markdown(&emphasis);
+        let Some(Block::Paragraph(content)) = blocks.first() else {
+            panic!("expected a paragraph");
+        };
+        assert!(inline_depth(content) <= MAX_NESTING);
+        let plain = plain_blocks(&blocks);
+        assert!(!plain.contains('*'));
+        assert_eq!(plain.matches('w').count(), 99);
+    }
+
+    #[test]
+    fn malformed_input_is_kept_as_text() {
+        for content in [
+            "**a",
+            "[a](",
+            "[a]",
+            "`a",
+            "||",
+            "\\",
+            "> ",
+            "- ",
+            "1.",
+            "#",
+            "```",
+            "~~a",
+            "<b",
+            "**[a||*b**c||*](",
+        ] {
+            let rendered = render_message(content, TextFormat::Markdown);
+            assert!(rendered.blocks.is_some());
+            let mut words = content.chars().filter(|x| x.is_alphanumeric());
+            assert!(words.all(|x| rendered.plain.contains(x)), "{content:?}");
+        }
+        assert_eq!(plain("**a"), "**a");
+        assert_eq!(plain("[a]("), "[a](");
+        assert_eq!(plain("`a"), "`a");
+    }
+
+    #[test]
+    fn plain_text_drops_formatting_and_hides_spoilers() {
+        assert_eq!(
+            plain(
+                "# Title\n\n**bold** ||secret|| @a :e: [link](https://example.com)\n\n\
+                 - one\n- two\n\n3. three\n\n> quote\n\n```\ncode\n```\n\n---"
+            ),
+            "Title\nbold [spoiler] @a :e: link\n- one\n- two\n3. three\nquote\ncode"
+        );
+        let rendered = render_message("**a** ||b||", TextFormat::Plain);
+        assert!(rendered.blocks.is_none());
+        assert_eq!(rendered.plain, "**a** ||b||");
+    }
+}
@@ -0,0 +1,200 @@
+//! finding `@user`, `@user@domain`, `@role` and `@everyone` in message content.
+//! mentions are only parsed here, they are resolved against the members
+//! of the room when the message is sent
+
+use super::{
+    markdown::{render_markdown, walk_inlines, Inline},
+    types::message::TextFormat,
+};
+
+/// mentions past this many in a single message are ignored
+pub const MAX_MENTIONS: usize = 50;
+
+/// a mention as written in the message, it may name a user or a role
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MentionToken {
+    /// lowercase as usernames are stored lowercase
+    pub name: String,
+    pub domain: Option<String>,
+}
+
+impl MentionToken {
+    pub fn is_everyone(&self) -> bool {
+        self.domain.is_none() && self.name == "everyone"
+    }
+}
+
+/// get the unique mentions in the order they first appear. mentions within
+/// markdown code spans, code blocks or escaped with a backslash are skipped
+pub fn parse_mentions(content: &str, format: TextFormat) -> Vec<MentionToken> {
+    let mut mentions: Vec<MentionToken> = Vec::new();
+    match format {
+        TextFormat::Plain => {
+            let chars: Vec<char> = content.chars().collect();
+            let mut pos = 0;
+            while pos < chars.len() && mentions.len() < MAX_MENTIONS {
+                let Some((token, end)) = mention_at(&chars, pos) else {
+                    pos += 1;
+                    continue;
+                };
+                if !mentions.contains(&token) {
+                    mentions.push(token);
+                }
+                pos = end;
+            }
+        }
+        TextFormat::Markdown => {
+            let blocks = render_markdown(content);
+            walk_inlines(&blocks, &mut |inline| {
+                let Inline::Mention { name, domain } = inline else {
+                    return;
+                };
+                let token = MentionToken {
+                    name: name.clone(),
+                    domain: domain.clone(),
+                };
+                if mentions.len() < MAX_MENTIONS && !mentions.contains(&token) {
+                    mentions.push(token);
+                }
+            });
+        }
+    }
+    mentions
+}
+
+/// the mention starting at `pos` and where it ends, none if there isn't
+/// an `@` at `pos` that starts a mention
+pub fn mention_at(chars: &[char], pos: usize) -> Option<(MentionToken, usize)> {
+    if chars.get(pos) != Some(&'@') || (pos > 0 && !is_boundary(chars[pos - 1])) {
+        return None;
+    }
+    let (name, end) = take_while(chars, pos + 1, is_name_char);
+    let name = name.trim_end_matches('-');
+    if name.is_empty() {
+        return None;
+    }
+    let mut pos_after = pos + 1 + name.chars().count();
+    let mut domain = None;
+    if end == pos_after && chars.get(end) == Some(&'@') {
+        let (found, _) = take_while(chars, end + 1, is_domain_char);
+        let found = found.trim_end_matches(['.', '-']);
+        if found.contains('.') {
+            pos_after = end + 1 + found.chars().count();
+            domain = Some(found.to_ascii_lowercase());
+        }
+    }
+    let token = MentionToken {
+        name: name.to_ascii_lowercase(),
+        domain,
+    };
+    Some((token, pos_after))
+}
+
+/// mentions must not directly follow text, this keeps email addresses
+/// and urls such as `https://example.com/@user` from being parsed
+pub fn is_boundary(previous: char) -> bool {
+    !(previous.is_alphanumeric() || matches!(previous, '_' | '-' | '.' | '@' | '/'))
+}
+
+fn is_name_char(x: char) -> bool {
+    x.is_ascii_alphanumeric() || x == '_' || x == '-'
+}
+
+fn is_domain_char(x: char) -> bool {
+    x.is_ascii_alphanumeric() || x == '.' || x == '-'
+}
+
+fn take_while(chars: &[char], start: usize, predicate: fn(char) -> bool) -> (String, usize) {
+    let mut end = start;
+    while end < chars.len() && predicate(chars[end]) {
+        end += 1;
+    }
+    (chars[start.min(end)..end].iter().collect(), end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mention(name: &str, domain: Option<&str>) -> MentionToken {
+        MentionToken {
+            name: name.to_string(),
+            domain: domain.map(str::to_string),
+        }
+    }
+
+    fn plain(content: &str) -> Vec<MentionToken> {
+        parse_mentions(content, TextFormat::Plain)
+    }
+
+    fn markdown(content: &str) -> Vec<MentionToken> {
+        parse_mentions(content, TextFormat::Markdown)
+    }
+
+    #[test]
+    fn parses_local_and_remote_mentions() {
+        assert_eq!(
+            plain("hi @Alice and @bob@Example.com!"),
+            [mention("alice", None), mention("bob", Some("example.com"))]
+        );
+        assert_eq!(
+            plain("(@alice), @bob."),
+            [mention("alice", None), mention("bob", None)]
+        );
+        assert!(plain("@everyone look")[0].is_everyone());
+        assert!(!plain("@everyone@example.com")[0].is_everyone());
+    }
+
+    #[test]
+    fn trailing_punctuation_is_not_part_of_a_mention() {
+        assert_eq!(plain("@alice-"), [mention("alice", None)]);
+        assert_eq!(
+            plain("@alice@example.com."),
+            [mention("alice", Some("example.com"))]
+        );
+        // a domain needs a dot, otherwise only the name is taken
+        assert_eq!(plain("@alice@localhost"), [mention("alice", None)]);
+        assert_eq!(plain("@alice@"), [mention("alice", None)]);
+    }
+
+    #[test]
+    fn mentions_must_start_at_a_boundary() {
+        assert!(plain("alice@example.com").is_empty());
+        assert!(plain("https://example.com/@alice").is_empty());
+        assert!(plain("@@alice").is_empty());
+        assert!(plain("@ alice").is_empty());
+        assert!(plain("@-").is_empty());
+        assert!(plain("").is_empty());
+    }
+
+    #[test]
+    fn mentions_are_unique_and_limited() {
+        assert_eq!(
+            plain("@bob @alice @Bob @bob@example.com"),
+            [
+                mention("bob", None),
+                mention("alice", None),
+                mention("bob", Some("example.com"))
+            ]
+        );
+        let content: Vec<String> = (0..MAX_MENTIONS + 10)
+            .map(|x| format!("@user{x}"))
+            .collect();
+        let mentions = plain(&content.join(" "));
+        assert_eq!(mentions.len(), MAX_MENTIONS);
+        assert_eq!(mentions[0], mention("user0", None));
+    }
+
+    #[test]
+    fn markdown_code_and_escapes_are_skipped() {
+        assert_eq!(
+            markdown("**@alice** `@bob` \\@carol\n\n```\n@dave\n```\n\n> @erin"),
+            [mention("alice", None), mention("erin", None)]
+        );
+        // plain text has no code spans or escapes
+        assert_eq!(
+            plain("`@bob` \\@carol"),
+            [mention("bob", None), mention("carol", None)]
+        );
+    }
+}
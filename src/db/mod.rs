@@ -1,4 +1,6 @@
 pub mod curr_time;
+pub mod markdown;
+pub mod mentions;
 pub mod pg_conn;
 mod pg_sesh;
 pub mod types;
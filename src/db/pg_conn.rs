@@ -1,37 +1,83 @@
-use std::ops::DerefMut;
+use std::{collections::HashSet, ops::DerefMut};
 
 use crate::{
+    config::Config,
+    cryptography::keys::{PrivateKey, SigningAlgorithm},
     db::{pg_sesh::Sesh, types::room::Room},
+    federation::{
+        activity::{
+            FederatedCommunity, FederatedHistory, FederatedMessage, FederatedUser, InboxErr,
+            ObjectRef,
+        },
+        emoji::{valid_shortcode, EmojiErr, EmojiFormat, EmojiPackFederation},
+    },
     routes::api::types::{
-        api_community::ApiCommunity, api_message::ApiMessage, api_user::ApiUser,
+        api_community::ApiCommunity, api_message::ApiMessage, api_reaction::ApiReaction,
+        api_read_state::ApiReadState, api_room::ApiRoom, api_user::ApiUser,
+        invite_preview::InvitePreview, join_err::JoinErr, message_window::MessageWindow,
         signup_result::SignupResult, signup_user::SignupUser,
     },
 };
-use deadpool_postgres::Pool;
-use mime::Mime;
-use uuid::Uuid;
+use codes_iso_639::part_1::LanguageCode;
+use deadpool_postgres::{Pool, PoolError};
+use tokio::spawn;
+use uuid::{NoContext, Timestamp, Uuid};
 
 use super::{
     curr_time::get_current_time,
+    markdown::render_message,
+    mentions::parse_mentions,
     types::{
+        attachment::Attachment,
         comm::{
             community::{Communityinfo, DbCommunity},
+            community_ban::CommunityBan,
             community_membership::CommMembership,
+            permissions::Permissions,
+            role::{Role, RoleInfo},
+            role_membership::RoleMembership,
+            room_override::RoomOverride,
         },
-        instance::Instance,
-        message::{DbMessage, Messageinfo},
+        custom_emoji::CustomEmoji,
+        emoji_pack::{EmojiPack, EmojiPackInfo},
+        file::DbFile,
+        file_thumbnail::FileThumbnail,
+        instance::{Instance, InstanceKey},
+        live_event::LiveEvent,
+        mention::{DbMention, MentionTarget},
+        message::{DbMessage, Messageinfo, TextFormat},
+        message_edit::MessageEdit,
+        message_search::MessageSearch,
+        outbox::OutboxDelivery,
+        pin::Pin,
+        reaction::DbReaction,
+        read_state::ReadState,
         registered_device::{DeviceInfo, RegisteredDevice},
         room::RoomInfo,
-        tokens::auth_token::{AuthToken, DBAuthToken},
+        room_membership::RoomMembership,
+        system_event::SystemEvent,
+        tokens::{
+            auth_token::{AuthToken, DBAuthToken},
+            join_token::JoinToken,
+        },
         user::DbUser,
     },
 };
 
 pub const MAX_PAGENATION: i64 = 40;
+pub const MAX_ATTACHMENTS: usize = 10;
+const RENDER_BATCH_SIZE: i64 = 500;
+/// how many messages are loaded on each side of a message when jumping to it
+pub const AROUND_COUNT: usize = (MAX_PAGENATION / 2) as usize;
+/// threads without replies for this long are no longer listed as active
+pub const ACTIVE_THREAD_AGE: i64 = 7 * 24 * 60 * 60 * 1000;
 
 #[derive(Clone, Debug)]
 pub struct PgConn {
     pub db: Pool,
+    /// where this instance is reached, used for the urls of attachments.
+    /// see [`crate::config::Config::base_url`]
+    pub base_url: String,
 }
 
 mod embedded {
@@ -39,6 +85,15 @@ mod embedded {
     embed_migrations!("./migrations");
 }
 
+/// the permissions needed to pin and unpin messages in a room. every member of
+/// a dm or group chat can pin, community rooms require moderators
+fn pin_permissions(room: &Room) -> Permissions {
+    match room.community {
+        Some(_) => Permissions::VIEW_ROOMS | Permissions::MANAGE_MESSAGES,
+        None => Permissions::VIEW_ROOMS,
+    }
+}
+
 impl PgConn {
     /// apply database migrations
     pub async fn init(&self) -> Result<(), String> {
@@ -62,28 +117,174 @@ impl PgConn {
                 }
                 Ok(())
             }
-            Err(x) => {
-                return Err(x.to_string());
+            Err(x) => Err(x.to_string()),
+        }
+    }
+    /// render the messages sent before messages were rendered in the background,
+    /// should be run on startup after migrations. messages are rendered in batches
+    /// so the server can start while a large backlog is rendered
+    pub fn render_message_backlog(&self) {
+        let conn = self.clone();
+        spawn(async move {
+            let mut after = Uuid::nil();
+            loop {
+                match conn.render_message_batch(&after).await {
+                    Ok(Some(last)) => after = last,
+                    Ok(None) => break,
+                    Err(x) => {
+                        eprintln!("failed to render message backlog: {x}");
+                        break;
+                    }
+                }
             }
+        });
+    }
+    /// render a batch of the messages after `after` that haven't been rendered yet,
+    /// returns the last one rendered or none once there are none left
+    async fn render_message_batch(&self, after: &Uuid) -> Result<Option<Uuid>, PoolError> {
+        let client = self.db.get().await?;
+        let sesh = Sesh::Client(client);
+        let messages = sesh.get_unrendered_messages(after, RENDER_BATCH_SIZE).await;
+        for message in &messages {
+            let result = render_message(&message.info.content, message.info.format);
+            sesh.set_rendered(&message.id, result).await;
         }
+        Ok(messages.last().map(|x| x.id))
     }
-    /// gets the main instance if exists or creates a new
+    /// gets the main instance if exists or creates a new, the key the instance
+    /// signs federation requests with is generated if it has none and if it is in
+    /// allowlist only mode is updated from the config.
     /// should be run on startup to ensure db is ready
-    pub async fn get_or_init_main_instance(&self, domain: &str) -> Instance {
+    pub async fn get_or_init_main_instance(
+        &self,
+        domain: &str,
+        algorithm: SigningAlgorithm,
+        allowlist_only: bool,
+    ) -> Instance {
         let mut client = self.db.get().await.expect("failed to get client");
         let transaction = client
             .transaction()
             .await
             .expect("failed to begin transaction");
         let sesh = Sesh::Transaction(transaction);
-        if let Some(instance) = sesh.get_instance(domain).await {
+        let instance = match sesh.get_instance(domain).await {
+            Some(instance) => instance,
+            //init the instance
+            None => sesh.create_instance(domain, true, false, None, true).await,
+        };
+        sesh.set_allowlist_only(domain, allowlist_only).await;
+        if instance
+            .key
+            .as_ref()
+            .is_some_and(|x| x.private_key.is_some())
+        {
+            sesh.commit().await;
             return instance;
         }
-        //init the instance
-        let instance = sesh.create_instance(domain, true, false, None, true).await;
+        let key = PrivateKey::generate(algorithm);
+        let key = InstanceKey {
+            algorithm,
+            public_key: key.public_key().to_pem(),
+            private_key: Some(key.to_pem()),
+            fetched_at: None,
+        };
+        let instance = sesh.set_instance_key(domain, &key).await;
         sesh.commit().await;
         instance
     }
+    /// store the public key fetched from a remote instance
+    pub async fn set_remote_key(&self, domain: &str, key: InstanceKey) {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.create_remote_instance(domain).await;
+        sesh.set_instance_key(domain, &key).await;
+    }
+    pub async fn get_instance(&self, domain: &str) -> Option<Instance> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_instance(domain).await
+    }
+
+    /// if we federate with an instance, unknown instances are treated as neither
+    /// blocked nor allowlisted
+    pub async fn federates_with(&self, domain: &str, allowlist_only: bool) -> bool {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        match sesh.get_instance(domain).await {
+            Some(instance) => instance.federates(allowlist_only),
+            None => !allowlist_only,
+        }
+    }
+    /// block an instance, it is removed from the allowlist. errors for our own instance
+    pub async fn block_instance(
+        &self,
+        domain: &str,
+        reason: Option<String>,
+    ) -> Result<Instance, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.set_instance_policy(domain, true, false, &reason)
+            .await
+            .ok_or(())
+    }
+    /// unblock an instance, returns none if it wasn't blocked
+    pub async fn unblock_instance(&self, domain: &str) -> Option<Instance> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let instance = sesh.get_instance(domain).await?;
+        if !instance.blocked {
+            return None;
+        }
+        sesh.set_instance_policy(domain, false, false, &None).await
+    }
+    /// allowlist an instance, it is unblocked. errors for our own instance
+    pub async fn allowlist_instance(
+        &self,
+        domain: &str,
+        reason: Option<String>,
+    ) -> Result<Instance, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.set_instance_policy(domain, false, true, &reason)
+            .await
+            .ok_or(())
+    }
+    /// remove an instance from the allowlist, returns none if it wasn't allowlisted
+    pub async fn unallowlist_instance(&self, domain: &str) -> Option<Instance> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let instance = sesh.get_instance(domain).await?;
+        if !instance.allowlisted {
+            return None;
+        }
+        sesh.set_instance_policy(domain, false, false, &None).await
+    }
+    /// block many instances at once, returns the instances that were blocked.
+    /// our own instance is skipped
+    pub async fn import_blocklist(&self, entries: Vec<(String, Option<String>)>) -> Vec<Instance> {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let mut blocked = Vec::with_capacity(entries.len());
+        for (domain, reason) in entries {
+            if let Some(instance) = sesh
+                .set_instance_policy(&domain, true, false, &reason)
+                .await
+            {
+                blocked.push(instance);
+            }
+        }
+        sesh.commit().await;
+        blocked
+    }
+    pub async fn get_instance_policies(&self) -> Vec<Instance> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_instance_policies().await
+    }
 
     pub async fn get_user(&self, username: &str, domain: &str) -> Option<DbUser> {
         let client = self.db.get().await.expect("failed to get client");
@@ -95,6 +296,13 @@ impl PgConn {
         let sesh = Sesh::Client(client);
         sesh.get_user_uuid(uid).await
     }
+    /// get the ids of every user sharing a community or a room with the user,
+    /// these are the users that may see their presence
+    pub async fn get_related_user_ids(&self, uid: Uuid) -> Vec<Uuid> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_related_user_ids(&uid).await
+    }
 
     pub async fn try_signup_user(
         &self,
@@ -124,7 +332,7 @@ impl PgConn {
                 Some(token) => token,
                 None => return Err(SignupResult::InvalidToken),
             };
-            let Some(token) = sesh.get_signup_token(&token).await else {
+            let Some(token) = sesh.get_signup_token(token).await else {
                 return Err(SignupResult::InvalidToken);
             };
             sesh.delete_signup_token(&token.id).await;
@@ -192,8 +400,22 @@ impl PgConn {
             info,
             created: get_current_time(),
             owner: owner.id,
+            fetched_at: None,
         };
         let community = sesh.create_community(community).await;
+        let _default_role = sesh
+            .create_role(Role {
+                id: Uuid::now_v7(),
+                com_id: community.id,
+                is_default: true,
+                created: get_current_time(),
+                info: RoleInfo {
+                    name: "everyone".to_string(),
+                    permissions: Permissions::DEFAULT,
+                    position: 0,
+                },
+            })
+            .await;
         let room_id = Uuid::now_v7();
         let room = Room {
             id: room_id,
@@ -235,25 +457,47 @@ impl PgConn {
         let sesh = Sesh::Client(client);
         sesh.get_comm_membership(&com_id, &uid).await
     }
-    /// get all rooms from a community if it exists and the user is in the community
+    /// get all rooms the user is able to view from a community if it exists and the
+    /// user is in the community
     /// - caching here might be useful
-    pub async fn get_comm_rooms(&self, com_id: Uuid, uid: Uuid) -> Result<Vec<Room>, ()> {
+    pub async fn get_comm_rooms(&self, com_id: Uuid, uid: Uuid) -> Result<Vec<ApiRoom>, ()> {
         let client = self.db.get().await.expect("failed to get client");
         let sesh = Sesh::Client(client);
+        let Some(community) = sesh.get_community(&com_id).await else {
+            return Err(());
+        };
         let Some(_membership) = sesh.get_comm_membership(&com_id, &uid).await else {
             return Err(());
         };
-        Ok(sesh.get_all_comm_rooms(&com_id).await)
+        let rooms = sesh.get_visible_comm_rooms(&community, &uid).await;
+        Ok(sesh.rooms_with_unreads(&uid, rooms).await)
     }
-    /// get all communities a user is a member of
+    /// get all communities a user is a member of along with their unread
+    /// counts across the rooms they can view
     pub async fn get_all_joined(&self, uid: Uuid) -> Vec<ApiCommunity> {
         let client = self.db.get().await.expect("failed to get client");
         let sesh = Sesh::Client(client);
-        sesh.get_all_user_comms(&uid)
+        let communities: Vec<DbCommunity> = sesh
+            .get_all_user_comms(&uid)
             .await
             .into_iter()
-            .map(|x| DbCommunity::from(x).into())
-            .collect()
+            .map(DbCommunity::from)
+            .collect();
+        let mut joined = Vec::with_capacity(communities.len());
+        for community in communities {
+            let rooms: Vec<Uuid> = sesh
+                .get_visible_comm_rooms(&community, &uid)
+                .await
+                .into_iter()
+                .map(|x| x.id)
+                .collect();
+            let unreads = sesh.get_room_unreads(&uid, &rooms).await;
+            let mut community = ApiCommunity::from(community);
+            community.unread_count = Some(unreads.iter().map(|x| x.unread_count).sum());
+            community.mention_count = Some(unreads.iter().map(|x| x.mention_count).sum());
+            joined.push(community);
+        }
+        joined
     }
     /// get all members from a community if it exists and the user is in the community
     /// - caching here might be useful
@@ -285,12 +529,15 @@ impl PgConn {
         user: Uuid,
         info: RoomInfo,
     ) -> Result<Room, ()> {
-        // todo create role system and more fine grained permissions
-        if community.owner != user {
-            return Err(());
-        }
         let client = self.db.get().await.expect("failed to get client");
         let sesh = Sesh::Client(client);
+        if !sesh
+            .get_comm_permissions(community, &user)
+            .await
+            .contains(Permissions::MANAGE_ROOMS)
+        {
+            return Err(());
+        }
         let room_id = Uuid::now_v7();
         let room = Room {
             id: room_id,
@@ -314,27 +561,36 @@ impl PgConn {
     }
     /// attempt to send message to given room
     /// returns err if room does not exist or not authorized to post in room
-    /// todo: add fine grained channel controls
     /// todo: add more descriptive errors and use them in the api
     pub async fn send_message(&self, user: &DbUser, message: Messageinfo) -> Result<DbMessage, ()> {
-        let client = self.db.get().await.expect("failed to get client");
-        let sesh = Sesh::Client(client);
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
         let Some(room) = sesh.get_room(&message.room).await else {
             return Err(());
         };
         // ensure the user is allowed to post in the given room
-        match room.community {
-            // being posted to a room in a community
-            Some(com_id) => {
-                let Some(_) = sesh.get_comm_membership(&com_id, &user.id).await else {
-                    return Err(());
-                };
+        let permissions = sesh.get_room_permissions(&room, &user.id).await;
+        if !permissions.contains(Permissions::VIEW_ROOMS | Permissions::SEND_MESSAGES) {
+            return Err(());
+        }
+
+        // threads are started by a message in this room that isn't itself in a thread
+        if let Some(thread) = message.thread {
+            let Some(parent) = sesh.get_message(&thread).await else {
+                return Err(());
+            };
+            if parent.info.room != message.room || parent.info.thread.is_some() {
+                return Err(());
             }
-            // being posted to a dm or group chat
-            None => return Err(()),
-        };
+        }
 
-        // ensure that the message is replying to a message that exists and in this channel
+        // ensure that the message is replying to a message that exists and in this channel.
+        // replies stay in the thread of the message they reply to, though messages in a
+        // thread may reply to the message that started it
         if let Some(reply) = message.in_reply_to {
             let Some(reply) = sesh.get_message(&reply).await else {
                 return Err(());
@@ -342,9 +598,39 @@ impl PgConn {
             if reply.info.room != message.room {
                 return Err(());
             }
+            if reply.info.thread != message.thread && Some(reply.id) != message.thread {
+                return Err(());
+            }
+        }
+
+        // users may only attach files they uploaded and each file only once
+        if !message.attachments.is_empty() {
+            if !permissions.contains(Permissions::ATTACH_FILES)
+                || message.attachments.len() > MAX_ATTACHMENTS
+            {
+                return Err(());
+            }
+            let unique: HashSet<Uuid> = message.attachments.iter().map(|x| x.file).collect();
+            if unique.len() != message.attachments.len() {
+                return Err(());
+            }
+            for attachment in &message.attachments {
+                let Some(file) = sesh.get_file(&attachment.file).await else {
+                    return Err(());
+                };
+                if file.owner != Some(user.id) {
+                    return Err(());
+                }
+            }
         }
 
+        let tokens = parse_mentions(&message.content, message.format);
+        let mentions = sesh
+            .resolve_mentions(&room, user, permissions, tokens)
+            .await;
+
         let id = Uuid::now_v7();
+        let attachments = message.attachments.clone();
         let message = DbMessage {
             id,
             external_id: id,
@@ -353,72 +639,2188 @@ impl PgConn {
             published: get_current_time(),
             edited: None,
             fetched_at: None,
+            system_event: None,
             info: message,
         };
-        Ok(sesh.create_message(message).await)
+        let message = sesh.create_message(message).await;
+        for (position, attachment) in attachments.into_iter().enumerate() {
+            sesh.create_attachment(Attachment {
+                m_id: id,
+                file_id: attachment.file,
+                position: position as i64,
+                description: attachment.description,
+            })
+            .await;
+        }
+        let mentions_everyone = mentions.contains(&MentionTarget::Everyone);
+        for (position, target) in mentions.into_iter().enumerate() {
+            sesh.create_mention(DbMention {
+                m_id: id,
+                position: position as i64,
+                target,
+            })
+            .await;
+        }
+        sesh.federate_message(&room, &message, mentions_everyone, None)
+            .await;
+        // users have read everything up to their own message, messages in threads
+        // don't mark the rest of the room as read
+        if message.info.thread.is_none() {
+            sesh.set_read_state(ReadState {
+                uid: user.id,
+                room_id: message.info.room,
+                last_read: id,
+                updated: message.published,
+            })
+            .await;
+        }
+        sesh.commit().await;
+        Ok(message)
     }
 
-    pub async fn get_api_message(&self, m_id: Uuid) -> Option<ApiMessage> {
+    /// edit the content of a message, allowed for the author of the message and
+    /// users able to manage messages in the room. the replaced content is kept in
+    /// the message's edit history and mentions are resolved again from the new
+    /// content with the permissions of the author. edits in community rooms are
+    /// sent to the other instances like new messages
+    pub async fn edit_message(
+        &self,
+        uid: Uuid,
+        m_id: Uuid,
+        content: String,
+        format: TextFormat,
+        language: Option<LanguageCode>,
+    ) -> Result<DbMessage, ()> {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let Some(message) = sesh.get_message(&m_id).await else {
+            return Err(());
+        };
+        if message.system_event.is_some() {
+            return Err(());
+        }
+        let Some(room) = sesh.get_room(&message.info.room).await else {
+            return Err(());
+        };
+        let permissions = sesh.get_room_permissions(&room, &uid).await;
+        let allowed = match message.user == uid {
+            true => permissions.contains(Permissions::VIEW_ROOMS | Permissions::SEND_MESSAGES),
+            false => permissions.contains(Permissions::VIEW_ROOMS | Permissions::MANAGE_MESSAGES),
+        };
+        if !allowed {
+            return Err(());
+        }
+        let Some(author) = sesh.get_user_uuid(&message.user).await else {
+            return Err(());
+        };
+        let permissions = match message.user == uid {
+            true => permissions,
+            false => sesh.get_room_permissions(&room, &author.id).await,
+        };
+        let mut edited = message.clone();
+        edited.edited = Some(get_current_time());
+        edited.info.content = content;
+        edited.info.format = format;
+        edited.info.language = language;
+        let (message, mentions) = sesh
+            .replace_message(&room, &author, permissions, message, edited)
+            .await;
+        let mentions_everyone = mentions.contains(&MentionTarget::Everyone);
+        sesh.federate_edit(&room, &message, mentions_everyone, None)
+            .await;
+        sesh.commit().await;
+        Ok(message)
+    }
+    /// delete a message, allowed for the author of the message and users
+    /// able to manage messages in the room. returns the deleted message
+    pub async fn delete_message(&self, uid: Uuid, m_id: Uuid) -> Result<DbMessage, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(message) = sesh.get_message(&m_id).await else {
+            return Err(());
+        };
+        let Some(room) = sesh.get_room(&message.info.room).await else {
+            return Err(());
+        };
+        let permissions = sesh.get_room_permissions(&room, &uid).await;
+        if !permissions.contains(Permissions::VIEW_ROOMS) {
+            return Err(());
+        }
+        if message.user != uid && !permissions.contains(Permissions::MANAGE_MESSAGES) {
+            return Err(());
+        }
+        sesh.delete_message(&m_id).await;
+        sesh.federate_delete(&room, &message, None).await;
+        Ok(message)
+    }
+    /// get the previous versions of a message if the user is able to view it
+    pub async fn get_message_edits(&self, uid: Uuid, m_id: Uuid) -> Result<Vec<MessageEdit>, ()> {
         let client = self.db.get().await.expect("failed to get client");
         let sesh = Sesh::Client(client);
-        sesh.get_api_message(&m_id).await
+        let Some(message) = sesh.get_message(&m_id).await else {
+            return Err(());
+        };
+        let Some(room) = sesh.get_room(&message.info.room).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS)
+        {
+            return Err(());
+        }
+        Ok(sesh.get_message_edits(&m_id).await)
     }
 
-    pub async fn get_room_messages(&self, room_id: Uuid, uid: Uuid) -> Result<Vec<ApiMessage>, ()> {
+    /// react to a message with a unicode emoji or the shortcode of a custom emoji.
+    /// returns none if the user already reacted with the emoji
+    pub async fn add_reaction(
+        &self,
+        uid: Uuid,
+        m_id: Uuid,
+        emoji: String,
+        emoji_id: Option<Uuid>,
+    ) -> Result<Option<ApiReaction>, ()> {
         let client = self.db.get().await.expect("failed to get client");
         let sesh = Sesh::Client(client);
-        let Some(room) = sesh.get_room(&room_id).await else {
+        let Some(message) = sesh.get_message(&m_id).await else {
             return Err(());
         };
-        match room.community {
-            Some(com_id) => {
-                let Some(_membership) = sesh.get_comm_membership(&com_id, &uid).await else {
-                    return Err(());
-                };
+        let Some(room) = sesh.get_room(&message.info.room).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS | Permissions::ADD_REACTIONS)
+        {
+            return Err(());
+        }
+        if let Some(emoji_id) = emoji_id {
+            if sesh.get_custom_emoji(&emoji_id).await.is_none() {
+                return Err(());
             }
-            None => todo!(),
+        }
+        let reaction = sesh
+            .create_reaction(DbReaction {
+                uid,
+                m_id,
+                emoji,
+                emoji_id,
+                archive_id: emoji_id,
+                published: get_current_time(),
+            })
+            .await;
+        Ok(reaction.map(|x| ApiReaction {
+            message: m_id,
+            room: room.id,
+            user: uid,
+            emoji: x.emoji,
+            emoji_id: x.archive_id,
+        }))
+    }
+    /// remove a user's own reaction. returns none if the user had not
+    /// reacted with the emoji
+    pub async fn remove_reaction(
+        &self,
+        uid: Uuid,
+        m_id: Uuid,
+        emoji: String,
+        emoji_id: Option<Uuid>,
+    ) -> Result<Option<ApiReaction>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(message) = sesh.get_message(&m_id).await else {
+            return Err(());
+        };
+        let Some(room) = sesh.get_room(&message.info.room).await else {
+            return Err(());
         };
-        Ok(sesh.get_room_messages(&room_id, MAX_PAGENATION).await)
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS)
+        {
+            return Err(());
+        }
+        let reaction = sesh.delete_reaction(&uid, &m_id, &emoji, &emoji_id).await;
+        Ok(reaction.map(|x| ApiReaction {
+            message: m_id,
+            room: room.id,
+            user: uid,
+            emoji: x.emoji,
+            emoji_id: x.archive_id,
+        }))
     }
-    pub async fn get_room_messages_in_relation(
+    /// get the users that reacted to a message with an emoji
+    pub async fn get_reaction_users(
         &self,
-        room_id: Uuid,
         uid: Uuid,
-        post: Uuid,
-        inclusive: bool,
-        // get posts before or after the post, older or newer
-        before: bool,
-    ) -> Result<Vec<ApiMessage>, ()> {
+        m_id: Uuid,
+        emoji: String,
+        emoji_id: Option<Uuid>,
+    ) -> Result<Vec<ApiUser>, ()> {
         let client = self.db.get().await.expect("failed to get client");
         let sesh = Sesh::Client(client);
-        let Some(room) = sesh.get_room(&room_id).await else {
+        let Some(message) = sesh.get_message(&m_id).await else {
             return Err(());
         };
-        match room.community {
-            Some(com_id) => {
-                let Some(_membership) = sesh.get_comm_membership(&com_id, &uid).await else {
-                    return Err(());
-                };
-            }
-            None => todo!(),
+        let Some(room) = sesh.get_room(&message.info.room).await else {
+            return Err(());
         };
-        match before {
-            true => Ok(sesh
-                .get_room_messages_before(room_id, MAX_PAGENATION, post, inclusive)
-                .await),
-            false => Ok(sesh
-                .get_room_messages_after(room_id, MAX_PAGENATION, post, inclusive)
-                .await),
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS)
+        {
+            return Err(());
         }
+        let users = sesh.get_reaction_users(&m_id, &emoji, &emoji_id).await;
+        Ok(users.into_iter().map(|x| x.into()).collect())
     }
 
-    pub async fn username_taken(&self, username: &str, domain: &str) -> bool {
+    /// pin a message in its room, requires the manage messages permission in
+    /// community rooms, see [`pin_permissions`].
+    /// returns the pin and its announcement or none if the message was already pinned
+    pub async fn pin_message(
+        &self,
+        user: &DbUser,
+        m_id: Uuid,
+    ) -> Result<Option<(Pin, DbMessage)>, ()> {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let Some(message) = sesh.get_message(&m_id).await else {
+            return Err(());
+        };
+        let Some(room) = sesh.get_room(&message.info.room).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_room_permissions(&room, &user.id)
+            .await
+            .contains(pin_permissions(&room))
+        {
+            return Err(());
+        }
+        let Some(pin) = sesh
+            .create_pin(Pin {
+                room_id: room.id,
+                m_id,
+                uid: user.id,
+                created: get_current_time(),
+            })
+            .await
+        else {
+            return Ok(None);
+        };
+        let id = Uuid::now_v7();
+        let name = user
+            .info
+            .display_name
+            .clone()
+            .unwrap_or(user.info.username.clone());
+        let announcement = sesh
+            .create_message(DbMessage {
+                id,
+                external_id: id,
+                domain: room.domain.clone(),
+                user: user.id,
+                published: get_current_time(),
+                edited: None,
+                fetched_at: None,
+                system_event: Some(SystemEvent::MessagePinned { message: m_id }),
+                info: Messageinfo {
+                    is_reply: true,
+                    in_reply_to: Some(m_id),
+                    proxy_id: None,
+                    content: format!("{name} pinned a message"),
+                    format: TextFormat::Plain,
+                    language: None,
+                    room: room.id,
+                    thread: None,
+                    attachments: Vec::new(),
+                },
+            })
+            .await;
+        sesh.commit().await;
+        Ok(Some((pin, announcement)))
+    }
+    /// unpin a message, requires the same permissions as pinning it.
+    /// returns none if the message was not pinned
+    pub async fn unpin_message(&self, uid: Uuid, m_id: Uuid) -> Result<Option<Pin>, ()> {
         let client = self.db.get().await.expect("failed to get client");
         let sesh = Sesh::Client(client);
-        sesh.username_taken(username, domain).await
+        let Some(message) = sesh.get_message(&m_id).await else {
+            return Err(());
+        };
+        let Some(room) = sesh.get_room(&message.info.room).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(pin_permissions(&room))
+        {
+            return Err(());
+        }
+        Ok(sesh.delete_pin(&room.id, &m_id).await)
     }
-
-    pub async fn register_file(&self, owner: Option<Uuid>, description: Option<String>, path: Option<String>, file_type: Mime) -> Uuid {
-        todo!()
+    /// get a page of the pinned messages in a room, most recently pinned first.
+    /// pass the last message of the previous page as before to get the next page
+    pub async fn get_room_pins(
+        &self,
+        room_id: Uuid,
+        uid: Uuid,
+        before: Option<Uuid>,
+    ) -> Result<Vec<ApiMessage>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(room) = sesh.get_room(&room_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS)
+        {
+            return Err(());
+        }
+        let messages = sesh
+            .get_pinned_messages(&room_id, &uid, &before, MAX_PAGENATION)
+            .await;
+        Ok(self.with_file_urls(messages))
+    }
+
+    /// get the ids of every user that is able to view the given room
+    pub async fn get_room_member_ids(&self, room: &Room) -> Vec<Uuid> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_room_member_ids(room).await
+    }
+    /// get all members of a room if the user is able to view the room
+    pub async fn user_get_room_members(
+        &self,
+        room_id: Uuid,
+        uid: Uuid,
+    ) -> Result<Vec<ApiUser>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(room) = sesh.get_room(&room_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS)
+        {
+            return Err(());
+        }
+        let users = match room.community {
+            Some(com_id) => {
+                let members = sesh.get_room_member_ids(&room).await;
+                sesh.get_all_comm_users(&com_id)
+                    .await
+                    .into_iter()
+                    .filter(|x| members.contains(&x.id))
+                    .collect()
+            }
+            None => sesh.get_all_room_users(&room_id).await,
+        };
+        Ok(users.into_iter().map(|x| x.into()).collect())
+    }
+    /// gets the direct message between the user and the target or
+    /// creates it if it does not exist yet
+    ///
+    /// returns err if the target does not exist or is the user
+    pub async fn open_dm(&self, user: &DbUser, target: Uuid) -> Result<Room, ()> {
+        if user.id == target {
+            return Err(());
+        }
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let Some(target) = sesh.get_user_uuid(&target).await else {
+            return Err(());
+        };
+        if let Some(room) = sesh.get_dm(&user.id, &target.id).await {
+            return Ok(room);
+        }
+        let room_id = Uuid::now_v7();
+        let room = Room {
+            id: room_id,
+            external_id: room_id,
+            domain: user.domain.clone(),
+            community: None,
+            system_channel: false,
+            created: get_current_time(),
+            is_dm: true,
+            user_a: Some(user.id),
+            user_b: Some(target.id),
+            info: RoomInfo {
+                // dms are displayed using the other user so they don't get a name
+                name: "".to_string(),
+                description: None,
+                category: None,
+                display_order: 0,
+            },
+            known_complete: true,
+        };
+        // the dm was opened at the same time by the other user
+        let Some(room) = sesh.create_dm(room).await else {
+            let room = sesh
+                .get_dm(&user.id, &target.id)
+                .await
+                .expect("conflicting direct message not found");
+            return Ok(room);
+        };
+        for uid in [user.id, target.id] {
+            sesh.create_room_membership(RoomMembership {
+                room_id,
+                uid,
+                joined: get_current_time(),
+            })
+            .await;
+        }
+        sesh.commit().await;
+        Ok(room)
+    }
+    /// creates a new group chat with the creator and all provided members
+    ///
+    /// returns err if any of the members do not exist
+    pub async fn create_group_chat(
+        &self,
+        creator: &DbUser,
+        info: RoomInfo,
+        members: Vec<Uuid>,
+    ) -> Result<Room, ()> {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let mut members: HashSet<Uuid> = members.into_iter().collect();
+        members.remove(&creator.id);
+        for member in &members {
+            if sesh.get_user_uuid(member).await.is_none() {
+                return Err(());
+            }
+        }
+        let room_id = Uuid::now_v7();
+        let room = Room {
+            id: room_id,
+            external_id: room_id,
+            domain: creator.domain.clone(),
+            community: None,
+            system_channel: false,
+            created: get_current_time(),
+            is_dm: false,
+            user_a: Some(creator.id),
+            user_b: None,
+            info,
+            known_complete: true,
+        };
+        let room = sesh.create_room(room).await;
+        for uid in std::iter::once(creator.id).chain(members) {
+            sesh.create_room_membership(RoomMembership {
+                room_id,
+                uid,
+                joined: get_current_time(),
+            })
+            .await;
+        }
+        sesh.commit().await;
+        Ok(room)
+    }
+    /// add a user to a group chat, the inviter must be a member of the
+    /// group chat and the invitee must not already be a member
+    pub async fn invite_to_group_chat(
+        &self,
+        room_id: Uuid,
+        inviter: Uuid,
+        invitee: Uuid,
+    ) -> Result<RoomMembership, ()> {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let Some(room) = sesh.get_room(&room_id).await else {
+            return Err(());
+        };
+        if room.is_dm || room.community.is_some() {
+            return Err(());
+        }
+        if sesh.get_room_membership(&room_id, &inviter).await.is_none() {
+            return Err(());
+        }
+        if sesh.get_user_uuid(&invitee).await.is_none() {
+            return Err(());
+        }
+        if sesh.get_room_membership(&room_id, &invitee).await.is_some() {
+            return Err(());
+        }
+        let membership = sesh
+            .create_room_membership(RoomMembership {
+                room_id,
+                uid: invitee,
+                joined: get_current_time(),
+            })
+            .await;
+        sesh.commit().await;
+        Ok(membership)
+    }
+    /// leave a group chat, the group chat is deleted once
+    /// its last member has left
+    pub async fn leave_group_chat(&self, room_id: Uuid, uid: Uuid) -> Result<(), ()> {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let Some(room) = sesh.get_room(&room_id).await else {
+            return Err(());
+        };
+        if room.is_dm || room.community.is_some() {
+            return Err(());
+        }
+        if sesh.get_room_membership(&room_id, &uid).await.is_none() {
+            return Err(());
+        }
+        sesh.delete_room_membership(&room_id, &uid).await;
+        if sesh.get_all_room_members(&room_id).await.is_empty() {
+            sesh.delete_room(&room_id).await;
+        }
+        sesh.commit().await;
+        Ok(())
+    }
+    /// get all direct messages a user is a part of, newest first
+    pub async fn get_user_dms(&self, uid: Uuid) -> Vec<ApiRoom> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let rooms = sesh.get_all_user_dms(&uid).await;
+        sesh.rooms_with_unreads(&uid, rooms).await
+    }
+    /// get all group chats a user is a member of, newest first
+    pub async fn get_user_group_chats(&self, uid: Uuid) -> Vec<ApiRoom> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let rooms = sesh.get_all_user_group_chats(&uid).await;
+        sesh.rooms_with_unreads(&uid, rooms).await
+    }
+
+    /// mark a message and everything before it in its room as read. read states
+    /// only move forward, acking an older message leaves it unchanged
+    pub async fn ack_message(&self, uid: Uuid, m_id: Uuid) -> Result<ApiReadState, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(message) = sesh.get_message(&m_id).await else {
+            return Err(());
+        };
+        let Some(room) = sesh.get_room(&message.info.room).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS)
+        {
+            return Err(());
+        }
+        sesh.set_read_state(ReadState {
+            uid,
+            room_id: room.id,
+            last_read: m_id,
+            updated: get_current_time(),
+        })
+        .await;
+        let unreads = sesh
+            .get_room_unreads(&uid, &[room.id])
+            .await
+            .pop()
+            .expect("unreads returned nothing");
+        Ok(unreads.into())
+    }
+
+    /// messages come from the database without attachment urls
+    fn with_file_urls(&self, mut messages: Vec<ApiMessage>) -> Vec<ApiMessage> {
+        for message in &mut messages {
+            message.set_file_urls(&self.base_url);
+        }
+        messages
+    }
+
+    /// get a message as seen by the viewer, does not check if the viewer is
+    /// able to see it. use [`Uuid::nil`] to get a message that isn't personalized
+    pub async fn get_api_message(&self, m_id: Uuid, viewer: Uuid) -> Option<ApiMessage> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let mut message = sesh.get_api_message(&m_id, &viewer).await?;
+        message.set_file_urls(&self.base_url);
+        Some(message)
+    }
+
+    /// users mentioned by a message directly or through one of their roles,
+    /// does not include those mentioned by @everyone
+    pub async fn get_mentioned_user_ids(&self, m_id: Uuid) -> Vec<Uuid> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_mentioned_user_ids(&m_id).await
+    }
+
+    pub async fn get_room_messages(&self, room_id: Uuid, uid: Uuid) -> Result<Vec<ApiMessage>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(room) = sesh.get_room(&room_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS)
+        {
+            return Err(());
+        }
+        let messages = sesh.get_room_messages(&room_id, &uid, MAX_PAGENATION).await;
+        Ok(self.with_file_urls(messages))
+    }
+    pub async fn get_room_messages_in_relation(
+        &self,
+        room_id: Uuid,
+        uid: Uuid,
+        post: Uuid,
+        inclusive: bool,
+        // get posts before or after the post, older or newer
+        before: bool,
+    ) -> Result<Vec<ApiMessage>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(room) = sesh.get_room(&room_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS)
+        {
+            return Err(());
+        }
+        let messages = match before {
+            true => {
+                sesh.get_room_messages_before(room_id, uid, MAX_PAGENATION, post, inclusive)
+                    .await
+            }
+            false => {
+                sesh.get_room_messages_after(room_id, uid, MAX_PAGENATION, post, inclusive)
+                    .await
+            }
+        };
+        Ok(self.with_file_urls(messages))
+    }
+
+    /// get the messages around a message in a room, up to [`AROUND_COUNT`] on each side.
+    /// errors if the message isn't in the room or is in a thread
+    pub async fn get_room_messages_around(
+        &self,
+        room_id: Uuid,
+        uid: Uuid,
+        post: Uuid,
+    ) -> Result<MessageWindow, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(room) = sesh.get_room(&room_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS)
+        {
+            return Err(());
+        }
+        let count = AROUND_COUNT as i64;
+        let after = sesh
+            .get_room_messages_after(room_id, uid, count + 2, post, true)
+            .await;
+        if after.first().is_none_or(|x| x.id != post) {
+            return Err(());
+        }
+        let before = sesh
+            .get_room_messages_before(room_id, uid, count + 1, post, false)
+            .await;
+        Ok(MessageWindow::new(
+            self.with_file_urls(before),
+            self.with_file_urls(after),
+            AROUND_COUNT,
+        ))
+    }
+
+    /// get the latest messages in a thread the user can view
+    pub async fn get_thread_messages(
+        &self,
+        thread_id: Uuid,
+        uid: Uuid,
+    ) -> Result<Vec<ApiMessage>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        if !sesh.can_view_thread(&thread_id, &uid).await {
+            return Err(());
+        }
+        let messages = sesh
+            .get_thread_messages(&thread_id, &uid, MAX_PAGENATION)
+            .await;
+        Ok(self.with_file_urls(messages))
+    }
+    pub async fn get_thread_messages_in_relation(
+        &self,
+        thread_id: Uuid,
+        uid: Uuid,
+        post: Uuid,
+        inclusive: bool,
+        // get posts before or after the post, older or newer
+        before: bool,
+    ) -> Result<Vec<ApiMessage>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        if !sesh.can_view_thread(&thread_id, &uid).await {
+            return Err(());
+        }
+        let messages = match before {
+            true => {
+                sesh.get_thread_messages_before(thread_id, uid, MAX_PAGENATION, post, inclusive)
+                    .await
+            }
+            false => {
+                sesh.get_thread_messages_after(thread_id, uid, MAX_PAGENATION, post, inclusive)
+                    .await
+            }
+        };
+        Ok(self.with_file_urls(messages))
+    }
+    /// get the messages around a message in a thread, up to [`AROUND_COUNT`] on each side
+    pub async fn get_thread_messages_around(
+        &self,
+        thread_id: Uuid,
+        uid: Uuid,
+        post: Uuid,
+    ) -> Result<MessageWindow, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        if !sesh.can_view_thread(&thread_id, &uid).await {
+            return Err(());
+        }
+        let count = AROUND_COUNT as i64;
+        let after = sesh
+            .get_thread_messages_after(thread_id, uid, count + 2, post, true)
+            .await;
+        if after.first().is_none_or(|x| x.id != post) {
+            return Err(());
+        }
+        let before = sesh
+            .get_thread_messages_before(thread_id, uid, count + 1, post, false)
+            .await;
+        Ok(MessageWindow::new(
+            self.with_file_urls(before),
+            self.with_file_urls(after),
+            AROUND_COUNT,
+        ))
+    }
+    /// get the messages that started threads in a room which have been replied
+    /// to within [`ACTIVE_THREAD_AGE`], most recently replied to first
+    pub async fn get_active_threads(
+        &self,
+        room_id: Uuid,
+        uid: Uuid,
+        before: Option<Uuid>,
+    ) -> Result<Vec<ApiMessage>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(room) = sesh.get_room(&room_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS)
+        {
+            return Err(());
+        }
+        let active_since = get_current_time() - ACTIVE_THREAD_AGE;
+        let messages = sesh
+            .get_active_threads(&room_id, &uid, active_since, &before, MAX_PAGENATION)
+            .await;
+        Ok(self.with_file_urls(messages))
+    }
+
+    /// search the messages of the rooms a user can view. the rooms searched are
+    /// narrowed to the given room or community, errors if the user can't view
+    /// the room or it isn't in the given community
+    pub async fn search_messages(
+        &self,
+        uid: Uuid,
+        search: MessageSearch,
+    ) -> Result<Vec<ApiMessage>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let rooms: Vec<Uuid> = match (search.room, search.community) {
+            (Some(room_id), community) => {
+                let Some(room) = sesh.get_room(&room_id).await else {
+                    return Err(());
+                };
+                if community.is_some_and(|x| room.community != Some(x)) {
+                    return Err(());
+                }
+                if !sesh
+                    .get_room_permissions(&room, &uid)
+                    .await
+                    .contains(Permissions::VIEW_ROOMS)
+                {
+                    return Err(());
+                }
+                vec![room.id]
+            }
+            (None, Some(com_id)) => {
+                let Some(community) = sesh.get_community(&com_id).await else {
+                    return Err(());
+                };
+                sesh.get_visible_comm_rooms(&community, &uid)
+                    .await
+                    .into_iter()
+                    .map(|x| x.id)
+                    .collect()
+            }
+            (None, None) => {
+                let mut rooms = Vec::new();
+                for community in sesh.get_all_user_comms(&uid).await {
+                    let community = DbCommunity::from(community);
+                    rooms.extend(
+                        sesh.get_visible_comm_rooms(&community, &uid)
+                            .await
+                            .into_iter()
+                            .map(|x| x.id),
+                    );
+                }
+                rooms.extend(sesh.get_all_user_dms(&uid).await.into_iter().map(|x| x.id));
+                rooms.extend(
+                    sesh.get_all_user_group_chats(&uid)
+                        .await
+                        .into_iter()
+                        .map(|x| x.id),
+                );
+                rooms
+            }
+        };
+        let messages = sesh
+            .search_messages(&rooms, &uid, &search, MAX_PAGENATION)
+            .await;
+        Ok(self.with_file_urls(messages))
+    }
+
+    /// resolve a user's permissions in a community
+    pub async fn get_comm_permissions(&self, com_id: Uuid, uid: Uuid) -> Option<Permissions> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let community = sesh.get_community(&com_id).await?;
+        Some(sesh.get_comm_permissions(&community, &uid).await)
+    }
+    /// resolve a user's permissions in a room
+    pub async fn get_room_permissions(&self, room_id: Uuid, uid: Uuid) -> Option<Permissions> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let room = sesh.get_room(&room_id).await?;
+        Some(sesh.get_room_permissions(&room, &uid).await)
+    }
+    /// get all roles of a community if the user is a member, highest first
+    pub async fn get_comm_roles(&self, com_id: Uuid, uid: Uuid) -> Result<Vec<Role>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(_membership) = sesh.get_comm_membership(&com_id, &uid).await else {
+            return Err(());
+        };
+        Ok(sesh.get_all_comm_roles(&com_id).await)
+    }
+    /// get all role assignments of a community if the user is a member
+    pub async fn get_comm_role_memberships(
+        &self,
+        com_id: Uuid,
+        uid: Uuid,
+    ) -> Result<Vec<RoleMembership>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(_membership) = sesh.get_comm_membership(&com_id, &uid).await else {
+            return Err(());
+        };
+        Ok(sesh.get_all_comm_role_memberships(&com_id).await)
+    }
+    /// create a new role in a community. requires [`Permissions::MANAGE_ROLES`],
+    /// the role must be below the user's highest role and may only grant
+    /// permissions the user has
+    pub async fn create_role(&self, com_id: Uuid, uid: Uuid, info: RoleInfo) -> Result<Role, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(community) = sesh.get_community(&com_id).await else {
+            return Err(());
+        };
+        let permissions = sesh.get_comm_permissions(&community, &uid).await;
+        if !permissions.contains(Permissions::MANAGE_ROLES | info.permissions.sanitized()) {
+            return Err(());
+        }
+        let role = Role {
+            id: Uuid::now_v7(),
+            com_id,
+            is_default: false,
+            created: get_current_time(),
+            info: RoleInfo {
+                permissions: info.permissions.sanitized(),
+                ..info
+            },
+        };
+        if role.info.position < 1 || !sesh.can_manage_role(&community, &uid, &role).await {
+            return Err(());
+        }
+        Ok(sesh.create_role(role).await)
+    }
+    /// update a role, follows the same rules as [`PgConn::create_role`].
+    /// the default role always stays at the bottom and as it is held by every
+    /// member editing it also requires [`Permissions::MANAGE_COMMUNITY`]
+    pub async fn update_role(&self, role_id: Uuid, uid: Uuid, info: RoleInfo) -> Result<Role, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(role) = sesh.get_role(&role_id).await else {
+            return Err(());
+        };
+        let Some(community) = sesh.get_community(&role.com_id).await else {
+            return Err(());
+        };
+        let permissions = sesh.get_comm_permissions(&community, &uid).await;
+        if !permissions.contains(Permissions::MANAGE_ROLES | info.permissions.sanitized()) {
+            return Err(());
+        }
+        let allowed = match role.is_default {
+            true => permissions.contains(Permissions::MANAGE_COMMUNITY),
+            false => sesh.can_manage_role(&community, &uid, &role).await,
+        };
+        if !allowed {
+            return Err(());
+        }
+        let updated = Role {
+            info: RoleInfo {
+                permissions: info.permissions.sanitized(),
+                position: match role.is_default {
+                    true => 0,
+                    false => info.position,
+                },
+                ..info
+            },
+            ..role
+        };
+        if !updated.is_default
+            && (updated.info.position < 1
+                || !sesh.can_manage_role(&community, &uid, &updated).await)
+        {
+            return Err(());
+        }
+        Ok(sesh.update_role(updated).await)
+    }
+    /// delete a role below the user's highest role, the default role may not be deleted
+    pub async fn delete_role(&self, role_id: Uuid, uid: Uuid) -> Result<(), ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(role) = sesh.get_role(&role_id).await else {
+            return Err(());
+        };
+        if role.is_default {
+            return Err(());
+        }
+        let Some(community) = sesh.get_community(&role.com_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_comm_permissions(&community, &uid)
+            .await
+            .contains(Permissions::MANAGE_ROLES)
+            || !sesh.can_manage_role(&community, &uid, &role).await
+        {
+            return Err(());
+        }
+        sesh.delete_role(&role_id).await;
+        Ok(())
+    }
+    /// give or take away a role from a member of the community. the role
+    /// must be below the actor's highest role
+    pub async fn set_role_assigned(
+        &self,
+        role_id: Uuid,
+        actor: Uuid,
+        target: Uuid,
+        assigned: bool,
+    ) -> Result<(), ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(role) = sesh.get_role(&role_id).await else {
+            return Err(());
+        };
+        if role.is_default {
+            return Err(());
+        }
+        let Some(community) = sesh.get_community(&role.com_id).await else {
+            return Err(());
+        };
+        if sesh
+            .get_comm_membership(&role.com_id, &target)
+            .await
+            .is_none()
+        {
+            return Err(());
+        }
+        if !sesh
+            .get_comm_permissions(&community, &actor)
+            .await
+            .contains(Permissions::MANAGE_ROLES)
+            || !sesh.can_manage_role(&community, &actor, &role).await
+        {
+            return Err(());
+        }
+        match assigned {
+            true => {
+                sesh.create_role_membership(RoleMembership {
+                    com_id: role.com_id,
+                    uid: target,
+                    role_id,
+                })
+                .await
+            }
+            false => sesh.delete_role_membership(&role_id, &target).await,
+        }
+        Ok(())
+    }
+    /// set the override of a role in a room. requires [`Permissions::MANAGE_ROOMS`]
+    /// and [`Permissions::MANAGE_ROLES`], the role must be below the user's highest
+    /// role and the override may only contain permissions the user has
+    pub async fn set_room_override(
+        &self,
+        uid: Uuid,
+        room_override: RoomOverride,
+    ) -> Result<RoomOverride, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(role) = sesh.get_role(&room_override.role_id).await else {
+            return Err(());
+        };
+        let Some(room) = sesh.get_room(&room_override.room_id).await else {
+            return Err(());
+        };
+        if room.community != Some(role.com_id) {
+            return Err(());
+        }
+        let Some(community) = sesh.get_community(&role.com_id).await else {
+            return Err(());
+        };
+        let room_override = RoomOverride {
+            allow: room_override.allow.sanitized(),
+            deny: room_override.deny.sanitized(),
+            ..room_override
+        };
+        let required = Permissions::MANAGE_ROOMS
+            | Permissions::MANAGE_ROLES
+            | room_override.allow
+            | room_override.deny;
+        if !sesh
+            .get_comm_permissions(&community, &uid)
+            .await
+            .contains(required)
+        {
+            return Err(());
+        }
+        if !role.is_default && !sesh.can_manage_role(&community, &uid, &role).await {
+            return Err(());
+        }
+        Ok(sesh.upsert_room_override(room_override).await)
+    }
+    /// remove the override of a role in a room, same rules as [`PgConn::set_room_override`]
+    pub async fn delete_room_override(
+        &self,
+        uid: Uuid,
+        room_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<(), ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(role) = sesh.get_role(&role_id).await else {
+            return Err(());
+        };
+        let Some(community) = sesh.get_community(&role.com_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_comm_permissions(&community, &uid)
+            .await
+            .contains(Permissions::MANAGE_ROOMS | Permissions::MANAGE_ROLES)
+        {
+            return Err(());
+        }
+        if !role.is_default && !sesh.can_manage_role(&community, &uid, &role).await {
+            return Err(());
+        }
+        sesh.delete_room_override(&room_id, &role_id).await;
+        Ok(())
+    }
+    /// get the overrides of a room if the user is able to view it
+    pub async fn get_room_overrides(
+        &self,
+        room_id: Uuid,
+        uid: Uuid,
+    ) -> Result<Vec<RoomOverride>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(room) = sesh.get_room(&room_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_room_permissions(&room, &uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS)
+        {
+            return Err(());
+        }
+        Ok(sesh.get_room_overrides(&room_id).await)
+    }
+    /// remove a member from a community, requires [`Permissions::KICK_MEMBERS`]
+    /// and to be above the member in the role hierarchy
+    pub async fn kick_member(&self, com_id: Uuid, actor: Uuid, target: Uuid) -> Result<(), ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(community) = sesh.get_community(&com_id).await else {
+            return Err(());
+        };
+        if sesh.get_comm_membership(&com_id, &target).await.is_none() {
+            return Err(());
+        }
+        if !sesh
+            .get_comm_permissions(&community, &actor)
+            .await
+            .contains(Permissions::KICK_MEMBERS)
+            || !sesh.can_manage_member(&community, &actor, &target).await
+        {
+            return Err(());
+        }
+        sesh.delete_comm_membership(&com_id, &target).await;
+        Ok(())
+    }
+    /// ban a user from a community removing their membership if they are a member.
+    /// requires [`Permissions::BAN_MEMBERS`] and to be above the member in the role
+    /// hierarchy
+    pub async fn ban_member(
+        &self,
+        com_id: Uuid,
+        actor: Uuid,
+        target: Uuid,
+        reason: Option<String>,
+    ) -> Result<CommunityBan, ()> {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let Some(community) = sesh.get_community(&com_id).await else {
+            return Err(());
+        };
+        if sesh.get_user_uuid(&target).await.is_none() {
+            return Err(());
+        }
+        if !sesh
+            .get_comm_permissions(&community, &actor)
+            .await
+            .contains(Permissions::BAN_MEMBERS)
+            || !sesh.can_manage_member(&community, &actor, &target).await
+        {
+            return Err(());
+        }
+        let Some(position) = sesh.get_highest_role(&community, &actor).await else {
+            return Err(());
+        };
+        // banning again replaces the existing ban, which would lower who may lift it
+        if sesh
+            .get_comm_ban(&com_id, &target)
+            .await
+            .is_some_and(|x| x.banned_position > position)
+        {
+            return Err(());
+        }
+        sesh.delete_comm_membership(&com_id, &target).await;
+        let ban = sesh
+            .create_comm_ban(CommunityBan {
+                com_id,
+                uid: target,
+                banned_by: Some(actor),
+                banned_position: position,
+                reason,
+                created: get_current_time(),
+            })
+            .await;
+        sesh.commit().await;
+        Ok(ban)
+    }
+    /// lift a ban, requires [`Permissions::BAN_MEMBERS`] and to be at least as high
+    /// in the role hierarchy as whoever issued the ban was when they issued it
+    pub async fn unban_member(&self, com_id: Uuid, actor: Uuid, target: Uuid) -> Result<(), ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(community) = sesh.get_community(&com_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_comm_permissions(&community, &actor)
+            .await
+            .contains(Permissions::BAN_MEMBERS)
+        {
+            return Err(());
+        }
+        let Some(ban) = sesh.get_comm_ban(&com_id, &target).await else {
+            return Ok(());
+        };
+        let Some(position) = sesh.get_highest_role(&community, &actor).await else {
+            return Err(());
+        };
+        if position < ban.banned_position {
+            return Err(());
+        }
+        sesh.delete_comm_ban(&com_id, &target).await;
+        Ok(())
+    }
+    pub async fn get_comm_bans(&self, com_id: Uuid, uid: Uuid) -> Result<Vec<CommunityBan>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(community) = sesh.get_community(&com_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_comm_permissions(&community, &uid)
+            .await
+            .contains(Permissions::BAN_MEMBERS)
+        {
+            return Err(());
+        }
+        Ok(sesh.get_all_comm_bans(&com_id).await)
+    }
+
+    /// create an invite to a community, requires [`Permissions::CREATE_INVITES`]
+    pub async fn create_invite(
+        &self,
+        com_id: Uuid,
+        uid: Uuid,
+        expiry: i64,
+        max_uses: Option<i64>,
+    ) -> Result<JoinToken, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(community) = sesh.get_community(&com_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_comm_permissions(&community, &uid)
+            .await
+            .contains(Permissions::CREATE_INVITES)
+        {
+            return Err(());
+        }
+        Ok(sesh
+            .create_join_token(&uid, &com_id, expiry, max_uses)
+            .await)
+    }
+    /// get a preview of the community an invite is for, returns none if the
+    /// invite does not exist or has expired
+    pub async fn get_invite_preview(&self, token_id: Uuid) -> Option<InvitePreview> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let token = sesh.get_join_token(&token_id).await?;
+        if token.expiry < get_current_time() {
+            return None;
+        }
+        let community = sesh.get_community(&token.community).await?;
+        Some(InvitePreview {
+            token: token.id,
+            member_count: sesh.count_comm_members(&community.id).await,
+            community: community.into(),
+            expiry: token.expiry,
+        })
+    }
+    /// join a community using an invite. if the community has a system channel
+    /// the join will be announced in it and the announcement returned.
+    ///
+    /// users that are already members will not use up the invite
+    pub async fn redeem_invite(
+        &self,
+        token_id: Uuid,
+        user: &DbUser,
+    ) -> Result<(DbCommunity, Option<DbMessage>), JoinErr> {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let Some(token) = sesh.get_join_token(&token_id).await else {
+            return Err(JoinErr::InvalidToken);
+        };
+        if token.expiry < get_current_time() {
+            sesh.delete_join_token(&token_id).await;
+            sesh.commit().await;
+            return Err(JoinErr::ExpiredToken);
+        }
+        let Some(community) = sesh.get_community(&token.community).await else {
+            return Err(JoinErr::InvalidToken);
+        };
+        if sesh
+            .get_comm_membership(&community.id, &user.id)
+            .await
+            .is_some()
+        {
+            return Ok((community, None));
+        }
+        if sesh.get_comm_ban(&community.id, &user.id).await.is_some() {
+            return Err(JoinErr::Banned);
+        }
+        // the use is counted first so concurrent joins can't exceed max uses
+        let Some(token) = sesh.use_join_token(&token_id).await else {
+            return Err(JoinErr::InvalidToken);
+        };
+        if token.max_uses == Some(token.uses) {
+            sesh.delete_join_token(&token_id).await;
+        }
+        sesh.create_comm_membership(CommMembership {
+            com_id: community.id,
+            uid: user.id,
+            joined: get_current_time(),
+        })
+        .await;
+
+        let announcement = match sesh.get_system_channel(&community.id).await {
+            Some(room) => {
+                let id = Uuid::now_v7();
+                let name = user
+                    .info
+                    .display_name
+                    .clone()
+                    .unwrap_or(user.info.username.clone());
+                // the announcement is made by us rather than the instance of the user
+                let message = DbMessage {
+                    id,
+                    external_id: id,
+                    domain: community.domain.clone(),
+                    user: user.id,
+                    published: get_current_time(),
+                    edited: None,
+                    fetched_at: None,
+                    system_event: Some(SystemEvent::MemberJoined {
+                        community: community.id,
+                    }),
+                    info: Messageinfo {
+                        is_reply: false,
+                        in_reply_to: None,
+                        proxy_id: None,
+                        content: format!("{name} joined the community"),
+                        format: TextFormat::Plain,
+                        language: None,
+                        room: room.id,
+                        thread: None,
+                        attachments: Vec::new(),
+                    },
+                };
+                let message = sesh.create_message(message).await;
+                sesh.federate_message(&room, &message, false, None).await;
+                Some(message)
+            }
+            None => None,
+        };
+        sesh.commit().await;
+        Ok((community, announcement))
+    }
+    /// get all unexpired invites of a community, requires [`Permissions::MANAGE_COMMUNITY`]
+    pub async fn get_comm_invites(&self, com_id: Uuid, uid: Uuid) -> Result<Vec<JoinToken>, ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(community) = sesh.get_community(&com_id).await else {
+            return Err(());
+        };
+        if !sesh
+            .get_comm_permissions(&community, &uid)
+            .await
+            .contains(Permissions::MANAGE_COMMUNITY)
+        {
+            return Err(());
+        }
+        sesh.delete_expired_comm_join_tokens(&com_id).await;
+        Ok(sesh.get_all_comm_join_tokens(&com_id).await)
+    }
+    /// revoke an invite, allowed for the creator of the invite and
+    /// users with [`Permissions::MANAGE_COMMUNITY`]
+    pub async fn revoke_invite(&self, token_id: Uuid, uid: Uuid) -> Result<(), ()> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(token) = sesh.get_join_token(&token_id).await else {
+            return Err(());
+        };
+        if token.creator != uid {
+            let Some(community) = sesh.get_community(&token.community).await else {
+                return Err(());
+            };
+            if !sesh
+                .get_comm_permissions(&community, &uid)
+                .await
+                .contains(Permissions::MANAGE_COMMUNITY)
+            {
+                return Err(());
+            }
+        }
+        sesh.delete_join_token(&token_id).await;
+        Ok(())
+    }
+    /// redeem an invite for a user of another instance, returns the community with
+    /// the rooms the user is able to view
+    pub async fn redeem_remote_invite(
+        &self,
+        token_id: Uuid,
+        user: &FederatedUser,
+    ) -> Result<(FederatedCommunity, Option<DbMessage>), JoinErr> {
+        let user = {
+            let client = self.db.get().await.expect("failed to get client");
+            let sesh = Sesh::Client(client);
+            match sesh.upsert_remote_user(user).await {
+                Some(user) => user,
+                None => return Err(JoinErr::InvalidToken),
+            }
+        };
+        let (community, announcement) = self.redeem_invite(token_id, &user).await?;
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(owner) = sesh.get_user_uuid(&community.owner).await else {
+            return Err(JoinErr::InvalidToken);
+        };
+        let rooms = sesh.get_visible_comm_rooms(&community, &user.id).await;
+        let community = FederatedCommunity::new(&community, &owner, &rooms);
+        Ok((community, announcement))
+    }
+    /// store a community a local user joined on another instance and make them a
+    /// member of it. the rooms are stored as not known complete as their history
+    /// is only received from when the first local member joined
+    pub async fn join_remote_community(
+        &self,
+        remote: FederatedCommunity,
+        user: &DbUser,
+    ) -> Result<DbCommunity, ()> {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let community = sesh.upsert_remote_community(remote, false).await?;
+        if sesh
+            .get_comm_membership(&community.id, &user.id)
+            .await
+            .is_none()
+        {
+            sesh.create_comm_membership(CommMembership {
+                com_id: community.id,
+                uid: user.id,
+                joined: get_current_time(),
+            })
+            .await;
+        }
+        sesh.commit().await;
+        Ok(community)
+    }
+
+    pub async fn username_taken(&self, username: &str, domain: &str) -> bool {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.username_taken(username, domain).await
+    }
+
+    pub async fn register_file(
+        &self,
+        file: DbFile,
+        thumbnails: Vec<FileThumbnail>,
+    ) -> (DbFile, Vec<FileThumbnail>) {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let file = sesh.create_file(file).await;
+        let mut created = Vec::with_capacity(thumbnails.len());
+        for thumbnail in thumbnails {
+            created.push(sesh.create_file_thumbnail(thumbnail).await);
+        }
+        sesh.commit().await;
+        (file, created)
+    }
+    pub async fn get_file_thumbnails(&self, file_id: Uuid) -> Vec<FileThumbnail> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_file_thumbnails(&file_id).await
+    }
+    /// get the smallest thumbnail of a file that is at least `size` pixels on its longest edge
+    pub async fn get_fitting_thumbnail(&self, file_id: Uuid, size: i64) -> Option<FileThumbnail> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_fitting_thumbnail(&file_id, size).await
+    }
+    pub async fn get_file(&self, file_id: Uuid) -> Option<DbFile> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_file(&file_id).await
+    }
+    /// get a file if the user is allowed to download it
+    pub async fn user_get_file(&self, file_id: Uuid, uid: Uuid) -> Option<DbFile> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let file = sesh.get_file(&file_id).await?;
+        if file.owner == Some(uid) {
+            return Some(file);
+        }
+        // attachments may be viewed by anyone able to view the message
+        for room in sesh.get_file_rooms(&file_id).await {
+            let Some(room) = sesh.get_room(&room).await else {
+                continue;
+            };
+            if sesh
+                .get_room_permissions(&room, &uid)
+                .await
+                .contains(Permissions::VIEW_ROOMS)
+            {
+                return Some(file);
+            }
+        }
+        None
+    }
+    pub async fn delete_file(&self, file_id: Uuid) {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.delete_file(&file_id).await
+    }
+    /// create an emoji pack hosted by our instance
+    pub async fn create_emoji_pack(
+        &self,
+        owner: Uuid,
+        info: EmojiPackInfo,
+        instance_domain: &str,
+    ) -> EmojiPack {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let id = Uuid::now_v7();
+        sesh.create_emoji_pack(&EmojiPack {
+            id,
+            external_id: id,
+            domain: Some(instance_domain.to_string()),
+            website: instance_domain.to_string(),
+            name: info.name,
+            description: info.description,
+            author_url: info.author_url,
+            license: info.license,
+            highlight_emoji: None,
+            last_updated: get_current_time(),
+            fetched_at: None,
+            owner: Some(owner),
+        })
+        .await
+    }
+    /// add an emoji to a pack owned by the user, the file must be theirs and
+    /// one of the [`EmojiFormat`]s. the first emoji becomes the pack's highlight
+    pub async fn add_custom_emoji(
+        &self,
+        uid: Uuid,
+        pack_id: Uuid,
+        shortcode: String,
+        file_id: Uuid,
+        description: Option<String>,
+    ) -> Result<CustomEmoji, EmojiErr> {
+        if !valid_shortcode(&shortcode) {
+            return Err(EmojiErr::InvalidShortcode);
+        }
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let Some(mut pack) = sesh.get_emoji_pack(&pack_id).await else {
+            return Err(EmojiErr::NotOwned);
+        };
+        let Some(file) = sesh.get_file(&file_id).await else {
+            return Err(EmojiErr::NotOwned);
+        };
+        if pack.owner != Some(uid) || file.owner != Some(uid) {
+            return Err(EmojiErr::NotOwned);
+        }
+        let Some(format) = EmojiFormat::from_mime(&file.mime) else {
+            return Err(EmojiErr::InvalidFormat);
+        };
+        if sesh
+            .get_emoji_shortcode(&pack_id, &shortcode)
+            .await
+            .is_some()
+        {
+            return Err(EmojiErr::DuplicateShortcode);
+        }
+        let emoji = sesh
+            .create_custom_emoji(&CustomEmoji {
+                id: Uuid::now_v7(),
+                pack_id,
+                shortcode,
+                file_id: Some(file_id),
+                url: None,
+                media_type: format.as_mime().to_string(),
+                description,
+            })
+            .await;
+        pack.highlight_emoji = pack
+            .highlight_emoji
+            .or_else(|| Some(emoji.shortcode.clone()));
+        pack.last_updated = get_current_time();
+        sesh.update_emoji_pack(&pack).await;
+        sesh.commit().await;
+        Ok(emoji)
+    }
+    pub async fn get_emoji_pack(&self, pack_id: Uuid) -> Option<(EmojiPack, Vec<CustomEmoji>)> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let pack = sesh.get_emoji_pack(&pack_id).await?;
+        let emoji = sesh.get_pack_emoji(&pack_id).await;
+        Some((pack, emoji))
+    }
+    pub async fn get_emoji_pack_external(
+        &self,
+        external_id: Uuid,
+        website: &str,
+    ) -> Option<EmojiPack> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_emoji_pack_external(&external_id, website).await
+    }
+    /// get one of our own emoji packs to federate, none if it has no emoji
+    pub async fn get_federated_emoji_pack(
+        &self,
+        config: &Config,
+        pack_id: Uuid,
+    ) -> Option<EmojiPackFederation> {
+        let (pack, emoji) = self.get_emoji_pack(pack_id).await?;
+        if pack.fetched_at.is_some() || pack.website != config.instance_domain {
+            return None;
+        }
+        EmojiPackFederation::from_db(config, &pack, &emoji)
+    }
+    /// get one of our own emoji by its pack and shortcode
+    pub async fn get_local_emoji(
+        &self,
+        instance_domain: &str,
+        pack_id: Uuid,
+        shortcode: &str,
+    ) -> Option<(EmojiPack, CustomEmoji)> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let pack = sesh.get_emoji_pack(&pack_id).await?;
+        if pack.fetched_at.is_some() || pack.website != instance_domain {
+            return None;
+        }
+        let emoji = sesh.get_emoji_shortcode(&pack_id, shortcode).await?;
+        Some((pack, emoji))
+    }
+    /// store an emoji pack fetched from an instance or static site. when the pack
+    /// has changed since it was last stored its emoji are replaced, emoji that
+    /// keep their shortcode keep their id
+    pub async fn store_remote_emoji_pack(
+        &self,
+        remote: EmojiPackFederation,
+        domain: Option<String>,
+        website: &str,
+    ) -> (EmojiPack, Vec<CustomEmoji>) {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let now = get_current_time();
+        let stored = sesh
+            .get_emoji_pack_external(&remote.external_pack_id, website)
+            .await;
+        if let Some(mut pack) = stored.clone() {
+            if pack.last_updated == remote.last_updated {
+                pack.fetched_at = Some(now);
+                let pack = sesh.update_emoji_pack(&pack).await;
+                let emoji = sesh.get_pack_emoji(&pack.id).await;
+                sesh.commit().await;
+                return (pack, emoji);
+            }
+        }
+        let pack = EmojiPack {
+            id: stored.as_ref().map(|x| x.id).unwrap_or_else(Uuid::now_v7),
+            external_id: remote.external_pack_id,
+            domain,
+            website: website.to_string(),
+            name: remote.pack_name,
+            description: remote.description,
+            author_url: remote.author_url.map(|x| x.to_string()),
+            license: remote.license,
+            highlight_emoji: Some(remote.highlight_emoji),
+            last_updated: remote.last_updated,
+            fetched_at: Some(now),
+            owner: None,
+        };
+        let pack = match stored {
+            Some(_) => sesh.update_emoji_pack(&pack).await,
+            None => {
+                if let Some(domain) = &pack.domain {
+                    sesh.create_remote_instance(domain).await;
+                }
+                sesh.create_emoji_pack(&pack).await
+            }
+        };
+        let shortcodes: Vec<String> = remote.emoji.iter().map(|x| x.shortcode.clone()).collect();
+        sesh.prune_pack_emoji(&pack.id, &shortcodes).await;
+        for emoji in remote.emoji {
+            sesh.upsert_remote_emoji(&CustomEmoji {
+                id: Uuid::now_v7(),
+                pack_id: pack.id,
+                shortcode: emoji.shortcode,
+                file_id: None,
+                url: Some(emoji.icon.url.to_string()),
+                media_type: emoji.icon.media_type.as_mime().to_string(),
+                description: emoji.icon.description,
+            })
+            .await;
+        }
+        let emoji = sesh.get_pack_emoji(&pack.id).await;
+        sesh.commit().await;
+        (pack, emoji)
+    }
+    /// store a message delivered by another instance. messages in our own communities
+    /// must be delivered by their author's instance and the author must be allowed to
+    /// send them, they are then relayed to the other instances. messages in remote
+    /// communities must be delivered by the community's instance and can only be
+    /// from one of our users if they are announcements. announcements are never
+    /// accepted in our own communities, we make those ourselves.
+    /// returns none if the message was already received
+    pub async fn receive_message(
+        &self,
+        origin: &str,
+        remote: FederatedMessage,
+    ) -> Result<Option<DbMessage>, InboxErr> {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        if sesh.is_local_domain(origin).await {
+            return Err(InboxErr::Forbidden);
+        }
+        let Some(room) = sesh.get_room_external(&remote.room).await else {
+            return Err(InboxErr::UnknownObject);
+        };
+        // announcements are made by the community's instance, other messages by
+        // the author's
+        let id_domain = match remote.system_event {
+            Some(_) => &room.domain,
+            None => &remote.author.domain,
+        };
+        if room.community.is_none() || remote.id.domain != *id_domain {
+            return Err(InboxErr::Forbidden);
+        }
+        let local_room = sesh.is_local_domain(&room.domain).await;
+        let allowed = match local_room {
+            true => origin == remote.id.domain,
+            false => origin == room.domain,
+        };
+        if !allowed {
+            return Err(InboxErr::Forbidden);
+        }
+        // announcements in our own rooms are only made by us, see [`PgConn::redeem_invite`]
+        if local_room && remote.system_event.is_some() {
+            return Err(InboxErr::Forbidden);
+        }
+        if sesh.get_message_external(&remote.id).await.is_some() {
+            return Ok(None);
+        }
+        // any message from another instance with our domain that we don't have is forged
+        if sesh.is_local_domain(&remote.id.domain).await {
+            return Err(InboxErr::Forbidden);
+        }
+        let Some(author) = sesh.resolve_federated_user(&remote.author).await else {
+            return Err(InboxErr::Forbidden);
+        };
+        let permissions = match local_room {
+            true => {
+                let permissions = sesh.get_room_permissions(&room, &author.id).await;
+                if !permissions.contains(Permissions::VIEW_ROOMS | Permissions::SEND_MESSAGES) {
+                    return Err(InboxErr::Forbidden);
+                }
+                permissions
+            }
+            false => match remote.mentions_everyone {
+                true => Permissions::MENTION_EVERYONE,
+                false => Permissions::NONE,
+            },
+        };
+        let (message, mentions) = sesh
+            .store_federated_message(Uuid::now_v7(), &room, &author, permissions, remote)
+            .await?;
+        let mentions_everyone = mentions.contains(&MentionTarget::Everyone);
+        if local_room {
+            sesh.federate_message(&room, &message, mentions_everyone, Some(origin))
+                .await;
+        }
+        sesh.commit().await;
+        Ok(Some(message))
+    }
+    /// apply an edit of a message from another instance, allowed for the same instances
+    /// that may delete it, see [`PgConn::receive_delete`]. edits in our own communities
+    /// must be by an author still allowed to send messages and are relayed to the other
+    /// instances. returns none if the message doesn't exist or the edit is older than
+    /// the version we have
+    pub async fn receive_edit(
+        &self,
+        origin: &str,
+        remote: FederatedMessage,
+    ) -> Result<Option<DbMessage>, InboxErr> {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let Some(message) = sesh.get_message_external(&remote.id).await else {
+            return Ok(None);
+        };
+        let Some(room) = sesh.get_room(&message.info.room).await else {
+            return Ok(None);
+        };
+        if room.external_id != remote.room.id || room.domain != remote.room.domain {
+            return Err(InboxErr::Forbidden);
+        }
+        let local_room = sesh.is_local_domain(&room.domain).await;
+        let allowed = match local_room {
+            true => origin == message.domain,
+            false => origin == room.domain,
+        };
+        if !allowed || sesh.is_local_domain(origin).await || message.system_event.is_some() {
+            return Err(InboxErr::Forbidden);
+        }
+        let Some(edited_at) = remote.edited else {
+            return Err(InboxErr::Forbidden);
+        };
+        if message.edited.is_some_and(|x| x >= edited_at) {
+            return Ok(None);
+        }
+        let Some(author) = sesh.get_user_uuid(&message.user).await else {
+            return Ok(None);
+        };
+        let permissions = match local_room {
+            true => {
+                let permissions = sesh.get_room_permissions(&room, &author.id).await;
+                if !permissions.contains(Permissions::VIEW_ROOMS | Permissions::SEND_MESSAGES) {
+                    return Err(InboxErr::Forbidden);
+                }
+                permissions
+            }
+            false => match remote.mentions_everyone {
+                true => Permissions::MENTION_EVERYONE,
+                false => Permissions::NONE,
+            },
+        };
+        let mut edited = message.clone();
+        edited.edited = Some(edited_at);
+        edited.info.content = remote.content;
+        edited.info.format = remote.format;
+        edited.info.language = remote.language;
+        let (message, mentions) = sesh
+            .replace_message(&room, &author, permissions, message, edited)
+            .await;
+        if local_room {
+            let mentions_everyone = mentions.contains(&MentionTarget::Everyone);
+            sesh.federate_edit(&room, &message, mentions_everyone, Some(origin))
+                .await;
+        }
+        sesh.commit().await;
+        Ok(Some(message))
+    }
+    /// delete a message for another instance, allowed for the instance of the message's
+    /// author in our own communities and the community's instance in remote ones.
+    /// returns none if the message doesn't exist
+    pub async fn receive_delete(
+        &self,
+        origin: &str,
+        remote: ObjectRef,
+    ) -> Result<Option<DbMessage>, InboxErr> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(message) = sesh.get_message_external(&remote).await else {
+            return Ok(None);
+        };
+        let Some(room) = sesh.get_room(&message.info.room).await else {
+            return Ok(None);
+        };
+        let local_room = sesh.is_local_domain(&room.domain).await;
+        let allowed = match local_room {
+            true => origin == message.domain,
+            false => origin == room.domain,
+        };
+        if !allowed || sesh.is_local_domain(origin).await {
+            return Err(InboxErr::Forbidden);
+        }
+        sesh.delete_message(&message.id).await;
+        if local_room {
+            sesh.federate_delete(&room, &message, Some(origin)).await;
+        }
+        Ok(Some(message))
+    }
+    /// a local user as they are fetched by other instances
+    pub async fn get_federated_user(&self, username: &str, domain: &str) -> Option<FederatedUser> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let user = sesh.get_user(username, domain).await?;
+        match user.local_info.is_some() && !user.banned {
+            true => Some((&user).into()),
+            false => None,
+        }
+    }
+    /// one of our communities as it is fetched by another instance, with the rooms
+    /// its members from that instance can view. the instance must have members in
+    /// the community
+    pub async fn get_federated_community(
+        &self,
+        com_id: Uuid,
+        domain: &str,
+    ) -> Result<FederatedCommunity, InboxErr> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(community) = sesh.get_community(&com_id).await else {
+            return Err(InboxErr::UnknownObject);
+        };
+        if !sesh.is_local_domain(&community.domain).await {
+            return Err(InboxErr::UnknownObject);
+        }
+        let members = sesh.get_all_comm_users(&community.id).await;
+        if !members.iter().any(|x| x.domain == domain) {
+            return Err(InboxErr::Forbidden);
+        }
+        let Some(owner) = sesh.get_user_uuid(&community.owner).await else {
+            return Err(InboxErr::UnknownObject);
+        };
+        let rooms = sesh.get_domain_visible_rooms(&community, domain).await;
+        Ok(FederatedCommunity::new(&community, &owner, &rooms))
+    }
+    /// a page of the history of one of our rooms for another instance, the
+    /// instance must have members able to view the room
+    pub async fn get_federated_history(
+        &self,
+        room_id: Uuid,
+        domain: &str,
+        before: Option<ObjectRef>,
+        limit: i64,
+    ) -> Result<FederatedHistory, InboxErr> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        let Some(room) = sesh.get_room(&room_id).await else {
+            return Err(InboxErr::UnknownObject);
+        };
+        if room.community.is_none() || !sesh.is_local_domain(&room.domain).await {
+            return Err(InboxErr::UnknownObject);
+        }
+        if !sesh
+            .get_viewing_domains(&room)
+            .await
+            .iter()
+            .any(|x| x == domain)
+        {
+            return Err(InboxErr::Forbidden);
+        }
+        let before = match before {
+            Some(before) => match sesh.get_message_external(&before).await {
+                Some(message) if message.info.room == room.id => Some(message.id),
+                _ => return Err(InboxErr::UnknownObject),
+            },
+            None => None,
+        };
+        Ok(sesh.get_federated_history(&room, before, limit).await)
+    }
+    pub async fn upsert_remote_user(&self, user: &FederatedUser) -> Option<DbUser> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.upsert_remote_user(user).await
+    }
+    pub async fn get_community_external(&self, community: &ObjectRef) -> Option<DbCommunity> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_community_external(community).await
+    }
+    /// update a community from another instance we have members in, rooms our
+    /// members can no longer view are removed
+    pub async fn refresh_remote_community(
+        &self,
+        remote: FederatedCommunity,
+    ) -> Result<DbCommunity, ()> {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let community = sesh.upsert_remote_community(remote, true).await?;
+        sesh.commit().await;
+        Ok(community)
+    }
+    /// the rooms of a community from another instance that are missing history
+    pub async fn get_incomplete_rooms(&self, com_id: Uuid) -> Vec<Room> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_all_comm_rooms(&com_id)
+            .await
+            .into_iter()
+            .filter(|x| !x.known_complete)
+            .collect()
+    }
+    /// the oldest message we have of a room, history is fetched from before it
+    pub async fn get_oldest_message_ref(&self, room_id: Uuid) -> Option<ObjectRef> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_oldest_room_message(&room_id)
+            .await
+            .map(|x| ObjectRef {
+                id: x.external_id,
+                domain: x.domain,
+            })
+    }
+    /// store a page of history fetched from the community's instance of a room,
+    /// messages are given ids from when they were published so they are ordered
+    /// among the messages we already have. no one is notified of them. messages
+    /// that can't be stored, such as replies in threads we don't have or messages
+    /// from our users that we don't have, are left out. when
+    /// `complete` is set the room is marked as known complete.
+    /// returns the number of messages stored
+    pub async fn store_history(
+        &self,
+        room_id: Uuid,
+        messages: Vec<FederatedMessage>,
+        complete: bool,
+    ) -> usize {
+        let mut client = self.db.get().await.expect("failed to get client");
+        let transaction = client
+            .transaction()
+            .await
+            .expect("failed to begin transaction");
+        let sesh = Sesh::Transaction(transaction);
+        let Some(room) = sesh.get_room(&room_id).await else {
+            return 0;
+        };
+        if room.community.is_none() || sesh.is_local_domain(&room.domain).await {
+            return 0;
+        }
+        let room_ref = ObjectRef {
+            id: room.external_id,
+            domain: room.domain.clone(),
+        };
+        let mut stored = 0;
+        // stored oldest first so replies and threads find what they refer to
+        for remote in messages.into_iter().rev() {
+            let id_domain = match remote.system_event {
+                Some(_) => &room.domain,
+                None => &remote.author.domain,
+            };
+            if remote.room != room_ref || remote.id.domain != *id_domain {
+                continue;
+            }
+            if sesh.get_message_external(&remote.id).await.is_some()
+                || sesh.is_local_domain(&remote.id.domain).await
+            {
+                continue;
+            }
+            let Some(author) = sesh.resolve_federated_user(&remote.author).await else {
+                continue;
+            };
+            let permissions = match remote.mentions_everyone {
+                true => Permissions::MENTION_EVERYONE,
+                false => Permissions::NONE,
+            };
+            let published = remote.published.max(0) as u64;
+            let timestamp = Timestamp::from_unix(
+                NoContext,
+                published / 1000,
+                (published % 1000) as u32 * 1_000_000,
+            );
+            let id = Uuid::new_v7(timestamp);
+            if sesh
+                .store_federated_message(id, &room, &author, permissions, remote)
+                .await
+                .is_ok()
+            {
+                stored += 1;
+            }
+        }
+        if complete {
+            sesh.update_room(Room {
+                known_complete: true,
+                ..room
+            })
+            .await;
+        }
+        sesh.commit().await;
+        stored
+    }
+    /// claim the activities that are due to be delivered to other instances
+    pub async fn claim_deliveries(&self, lease: i64, limit: i64) -> Vec<OutboxDelivery> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.claim_deliveries(lease, limit).await
+    }
+    pub async fn retry_delivery(&self, delivery_id: i64, next_attempt: i64) {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.retry_delivery(delivery_id, next_attempt).await
+    }
+    pub async fn delete_delivery(&self, delivery_id: i64) {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.delete_delivery(delivery_id).await
+    }
+    pub async fn notify(&self, channel: &str, payload: &str) {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.notify(channel, payload).await
+    }
+    pub async fn create_live_event(&self, payload: &str) -> LiveEvent {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.create_live_event(payload, get_current_time()).await
+    }
+    pub async fn get_live_event(&self, event_id: i64) -> Option<LiveEvent> {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.get_live_event(event_id).await
+    }
+    /// delete stored live events created more than `age` milliseconds ago
+    pub async fn delete_old_live_events(&self, age: i64) {
+        let client = self.db.get().await.expect("failed to get client");
+        let sesh = Sesh::Client(client);
+        sesh.delete_live_events_before(get_current_time() - age)
+            .await
     }
 }
@@ -0,0 +1,32 @@
+use uuid::Uuid;
+
+use crate::db::{pg_sesh::Sesh, types::attachment::Attachment};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn create_attachment(&self, attachment: Attachment) -> Attachment {
+        let result = self
+            .query(
+                Attachment::create_statement(),
+                &[
+                    &attachment.m_id,
+                    &attachment.file_id,
+                    &attachment.position,
+                    &attachment.description,
+                ],
+            )
+            .await
+            .expect("failed to create attachment")
+            .pop()
+            .expect("creating attachment returned nothing");
+        result.into()
+    }
+    /// get the ids of every room a file is attached to a message in
+    pub async fn get_file_rooms(&self, file_id: &Uuid) -> Vec<Uuid> {
+        let result = self
+            .query(Attachment::get_file_rooms(), &[file_id])
+            .await
+            .expect("failed to fetch file rooms");
+        result.into_iter().map(|x| x.get("room_id")).collect()
+    }
+}
@@ -55,4 +55,13 @@ impl Sesh<'_> {
             .expect("failed to fetch community users");
         result.into_iter().map(|x| x.into()).collect()
     }
+    pub async fn count_comm_members(&self, com_id: &Uuid) -> i64 {
+        let result = self
+            .query(CommMembership::count_comm_members(), &[com_id])
+            .await
+            .expect("failed to count community members")
+            .pop()
+            .expect("counting community members returned nothing");
+        result.get("count")
+    }
 }
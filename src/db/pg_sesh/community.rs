@@ -16,6 +16,7 @@ impl Sesh<'_> {
                     &community.info.description,
                     &community.created,
                     &community.owner,
+                    &community.fetched_at,
                 ],
             )
             .await
@@ -42,6 +43,7 @@ impl Sesh<'_> {
                     &communnity.owner,
                     &communnity.info.name,
                     &communnity.info.description,
+                    &communnity.fetched_at,
                     &communnity.id,
                 ],
             )
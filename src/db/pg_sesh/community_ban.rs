@@ -0,0 +1,47 @@
+use uuid::Uuid;
+
+use crate::db::{pg_sesh::Sesh, types::comm::community_ban::CommunityBan};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn create_comm_ban(&self, ban: CommunityBan) -> CommunityBan {
+        let result = self
+            .query(
+                CommunityBan::create_statement(),
+                &[
+                    &ban.com_id,
+                    &ban.uid,
+                    &ban.banned_by,
+                    &ban.reason,
+                    &ban.created,
+                    &ban.banned_position,
+                ],
+            )
+            .await
+            .expect("failed to create community ban")
+            .pop()
+            .expect("creating community ban returned nothing");
+        result.into()
+    }
+    pub async fn get_comm_ban(&self, com_id: &Uuid, uid: &Uuid) -> Option<CommunityBan> {
+        let result = self
+            .query(CommunityBan::read_statement(), &[com_id, uid])
+            .await
+            .expect("failed to fetch community ban")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn delete_comm_ban(&self, com_id: &Uuid, uid: &Uuid) {
+        let _result = self
+            .query(CommunityBan::delete_statement(), &[com_id, uid])
+            .await
+            .expect("failed to delete community ban");
+    }
+    pub async fn get_all_comm_bans(&self, com_id: &Uuid) -> Vec<CommunityBan> {
+        let result = self
+            .query(CommunityBan::get_all_comm_bans(), &[com_id])
+            .await
+            .expect("failed to fetch community bans");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+}
@@ -0,0 +1,84 @@
+use uuid::Uuid;
+
+use crate::db::{pg_sesh::Sesh, types::custom_emoji::CustomEmoji};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn create_custom_emoji(&self, emoji: &CustomEmoji) -> CustomEmoji {
+        let result = self
+            .query(
+                CustomEmoji::create_statement(),
+                &[
+                    &emoji.id,
+                    &emoji.pack_id,
+                    &emoji.shortcode,
+                    &emoji.file_id,
+                    &emoji.url,
+                    &emoji.media_type,
+                    &emoji.description,
+                ],
+            )
+            .await
+            .expect("failed to create custom emoji")
+            .pop()
+            .expect("creating custom emoji returned nothing");
+        result.into()
+    }
+    pub async fn upsert_remote_emoji(&self, emoji: &CustomEmoji) -> CustomEmoji {
+        let result = self
+            .query(
+                CustomEmoji::upsert_remote_statement(),
+                &[
+                    &emoji.id,
+                    &emoji.pack_id,
+                    &emoji.shortcode,
+                    &emoji.file_id,
+                    &emoji.url,
+                    &emoji.media_type,
+                    &emoji.description,
+                ],
+            )
+            .await
+            .expect("failed to upsert custom emoji")
+            .pop()
+            .expect("upserting custom emoji returned nothing");
+        result.into()
+    }
+    pub async fn get_custom_emoji(&self, emoji_id: &Uuid) -> Option<CustomEmoji> {
+        let result = self
+            .query(CustomEmoji::read_statement(), &[emoji_id])
+            .await
+            .expect("failed to fetch custom emoji")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn get_emoji_shortcode(
+        &self,
+        pack_id: &Uuid,
+        shortcode: &str,
+    ) -> Option<CustomEmoji> {
+        let result = self
+            .query(
+                CustomEmoji::read_shortcode_statement(),
+                &[pack_id, &shortcode],
+            )
+            .await
+            .expect("failed to fetch custom emoji")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn get_pack_emoji(&self, pack_id: &Uuid) -> Vec<CustomEmoji> {
+        let result = self
+            .query(CustomEmoji::get_pack_statement(), &[pack_id])
+            .await
+            .expect("failed to fetch pack emoji");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    /// remove the emoji of a pack that aren't in `keep`
+    pub async fn prune_pack_emoji(&self, pack_id: &Uuid, keep: &[String]) {
+        let _result = self
+            .query(CustomEmoji::prune_pack_statement(), &[pack_id, &keep])
+            .await
+            .expect("failed to prune pack emoji");
+    }
+}
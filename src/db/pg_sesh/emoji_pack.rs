@@ -0,0 +1,76 @@
+use uuid::Uuid;
+
+use crate::db::{pg_sesh::Sesh, types::emoji_pack::EmojiPack};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn create_emoji_pack(&self, pack: &EmojiPack) -> EmojiPack {
+        let result = self
+            .query(
+                EmojiPack::create_statement(),
+                &[
+                    &pack.id,
+                    &pack.external_id.to_string(),
+                    &pack.domain,
+                    &pack.website,
+                    &pack.name,
+                    &pack.description,
+                    &pack.author_url,
+                    &pack.license,
+                    &pack.highlight_emoji,
+                    &pack.last_updated,
+                    &pack.fetched_at,
+                    &pack.owner,
+                ],
+            )
+            .await
+            .expect("failed to create emoji pack")
+            .pop()
+            .expect("creating emoji pack returned nothing");
+        result.into()
+    }
+    pub async fn get_emoji_pack(&self, pack_id: &Uuid) -> Option<EmojiPack> {
+        let result = self
+            .query(EmojiPack::read_statement(), &[pack_id])
+            .await
+            .expect("failed to fetch emoji pack")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn get_emoji_pack_external(
+        &self,
+        external_id: &Uuid,
+        website: &str,
+    ) -> Option<EmojiPack> {
+        let result = self
+            .query(
+                EmojiPack::read_external_statement(),
+                &[&external_id.to_string(), &website],
+            )
+            .await
+            .expect("failed to fetch emoji pack")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn update_emoji_pack(&self, pack: &EmojiPack) -> EmojiPack {
+        let result = self
+            .query(
+                EmojiPack::update_statement(),
+                &[
+                    &pack.name,
+                    &pack.description,
+                    &pack.author_url,
+                    &pack.license,
+                    &pack.highlight_emoji,
+                    &pack.last_updated,
+                    &pack.fetched_at,
+                    &pack.id,
+                ],
+            )
+            .await
+            .expect("failed to update emoji pack")
+            .pop()
+            .expect("updating emoji pack returned nothing");
+        result.into()
+    }
+}
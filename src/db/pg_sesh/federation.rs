@@ -0,0 +1,482 @@
+use uuid::Uuid;
+
+use crate::{
+    db::{
+        curr_time::get_current_time,
+        mentions::parse_mentions,
+        pg_sesh::Sesh,
+        types::{
+            comm::{
+                community::{Communityinfo, DbCommunity},
+                permissions::Permissions,
+                role::{Role, RoleInfo},
+            },
+            instance::Instance,
+            mention::{DbMention, MentionTarget},
+            message::{DbMessage, Messageinfo},
+            room::{Room, RoomInfo},
+            system_event::SystemEvent,
+            user::DbUser,
+        },
+    },
+    federation::activity::{
+        Activity, FederatedCommunity, FederatedEvent, FederatedHistory, FederatedMessage,
+        FederatedUser, InboxErr, ObjectRef,
+    },
+};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    /// if the domain is the one this instance runs on
+    pub async fn is_local_domain(&self, domain: &str) -> bool {
+        self.get_instance(domain)
+            .await
+            .is_some_and(|x| x.is_authoratative)
+    }
+    pub async fn create_remote_instance(&self, domain: &str) {
+        let _result = self
+            .query(Instance::create_remote_statement(), &[&domain])
+            .await
+            .expect("failed to create remote instance");
+    }
+    /// store or refresh a remote user, returns none if the user is from this instance
+    pub async fn upsert_remote_user(&self, user: &FederatedUser) -> Option<DbUser> {
+        self.create_remote_instance(&user.domain).await;
+        let result = self
+            .query(
+                DbUser::upsert_remote_statement(),
+                &[
+                    &Uuid::now_v7(),
+                    &user.domain,
+                    &user.username,
+                    &user.display_name,
+                    &user.summary,
+                    &get_current_time(),
+                    &user.created,
+                ],
+            )
+            .await
+            .expect("failed to upsert remote user")
+            .pop();
+        result.map(|x| x.into())
+    }
+    /// get the user a federated user refers to, remote users are stored or refreshed
+    pub async fn resolve_federated_user(&self, user: &FederatedUser) -> Option<DbUser> {
+        match self.is_local_domain(&user.domain).await {
+            true => self.get_user(&user.username, &user.domain).await,
+            false => self.upsert_remote_user(user).await,
+        }
+    }
+    pub async fn get_message_external(&self, object: &ObjectRef) -> Option<DbMessage> {
+        let result = self
+            .query(
+                DbMessage::read_external_statement(),
+                &[&object.id, &object.domain],
+            )
+            .await
+            .expect("failed to fetch message")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn get_room_external(&self, object: &ObjectRef) -> Option<Room> {
+        let result = self
+            .query(
+                Room::read_external_statement(),
+                &[&object.id, &object.domain],
+            )
+            .await
+            .expect("failed to fetch room")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn get_community_external(&self, object: &ObjectRef) -> Option<DbCommunity> {
+        let result = self
+            .query(
+                DbCommunity::read_external_statement(),
+                &[&object.id, &object.domain],
+            )
+            .await
+            .expect("failed to fetch community")
+            .pop();
+        result.map(|x| x.into())
+    }
+    /// the domains of the remote members able to view a room
+    pub async fn get_viewing_domains(&self, room: &Room) -> Vec<String> {
+        let members = self
+            .get_room_members_with(room, Permissions::VIEW_ROOMS)
+            .await;
+        let result = self
+            .query(DbUser::remote_domains_statement(), &[&members])
+            .await
+            .expect("failed to fetch remote domains");
+        result.into_iter().map(|x| x.get("domain")).collect()
+    }
+    async fn message_ref(&self, m_id: &Uuid) -> Option<ObjectRef> {
+        self.get_message(m_id).await.map(|x| ObjectRef {
+            id: x.external_id,
+            domain: x.domain,
+        })
+    }
+    /// get a message as it is sent to other instances, none for messages
+    /// that aren't federated
+    pub async fn federated_message(
+        &self,
+        room: &Room,
+        message: &DbMessage,
+        mentions_everyone: bool,
+    ) -> Option<FederatedMessage> {
+        let system_event = match message.system_event {
+            None => None,
+            Some(SystemEvent::MemberJoined { .. }) => Some(FederatedEvent::MemberJoined),
+            Some(_) => return None,
+        };
+        let author = self.get_user_uuid(&message.user).await?;
+        let in_reply_to = match message.info.in_reply_to {
+            Some(m_id) => self.message_ref(&m_id).await,
+            None => None,
+        };
+        let thread = match message.info.thread {
+            Some(m_id) => Some(self.message_ref(&m_id).await?),
+            None => None,
+        };
+        Some(FederatedMessage {
+            id: ObjectRef {
+                id: message.external_id,
+                domain: message.domain.clone(),
+            },
+            room: ObjectRef {
+                id: room.external_id,
+                domain: room.domain.clone(),
+            },
+            author: (&author).into(),
+            published: message.published,
+            edited: message.edited,
+            is_reply: message.info.is_reply,
+            in_reply_to,
+            thread,
+            content: message.info.content.clone(),
+            format: message.info.format,
+            language: message.info.language,
+            mentions_everyone,
+            system_event,
+        })
+    }
+    /// queue an activity in a community room for the instances that should receive it.
+    /// activities in our own communities go to every instance with members able to view
+    /// the room other than the one it came from, those from our users in a remote
+    /// community go to the community's instance to be relayed
+    pub async fn federate_activity(
+        &self,
+        room: &Room,
+        author_domain: &str,
+        activity: &Activity,
+        exclude: Option<&str>,
+    ) {
+        if room.community.is_none() {
+            return;
+        }
+        if self.is_local_domain(&room.domain).await {
+            for domain in self.get_viewing_domains(room).await {
+                if Some(domain.as_str()) != exclude {
+                    self.create_delivery(&domain, activity).await;
+                }
+            }
+        } else if self.is_local_domain(author_domain).await {
+            self.create_delivery(&room.domain, activity).await;
+        }
+    }
+    pub async fn federate_message(
+        &self,
+        room: &Room,
+        message: &DbMessage,
+        mentions_everyone: bool,
+        exclude: Option<&str>,
+    ) {
+        if room.community.is_none() {
+            return;
+        }
+        let Some(federated) = self
+            .federated_message(room, message, mentions_everyone)
+            .await
+        else {
+            return;
+        };
+        let activity = Activity::Message(Box::new(federated));
+        self.federate_activity(room, &message.domain, &activity, exclude)
+            .await;
+    }
+    pub async fn federate_edit(
+        &self,
+        room: &Room,
+        message: &DbMessage,
+        mentions_everyone: bool,
+        exclude: Option<&str>,
+    ) {
+        if room.community.is_none() {
+            return;
+        }
+        let Some(federated) = self
+            .federated_message(room, message, mentions_everyone)
+            .await
+        else {
+            return;
+        };
+        let activity = Activity::Edit(Box::new(federated));
+        self.federate_activity(room, &message.domain, &activity, exclude)
+            .await;
+    }
+    pub async fn federate_delete(&self, room: &Room, message: &DbMessage, exclude: Option<&str>) {
+        let activity = Activity::Delete {
+            message: ObjectRef {
+                id: message.external_id,
+                domain: message.domain.clone(),
+            },
+        };
+        self.federate_activity(room, &message.domain, &activity, exclude)
+            .await;
+    }
+    /// store or refresh a community from another instance along with its rooms.
+    /// new rooms are stored as not known complete. when `prune_rooms` is set the
+    /// rooms that are no longer listed are removed, the listed rooms must then be
+    /// every room our members can view
+    pub async fn upsert_remote_community(
+        &self,
+        remote: FederatedCommunity,
+        prune_rooms: bool,
+    ) -> Result<DbCommunity, ()> {
+        if remote.owner.domain != remote.domain || self.is_local_domain(&remote.domain).await {
+            return Err(());
+        }
+        let Some(owner) = self.upsert_remote_user(&remote.owner).await else {
+            return Err(());
+        };
+        let external = ObjectRef {
+            id: remote.id,
+            domain: remote.domain.clone(),
+        };
+        let info = Communityinfo {
+            name: remote.name,
+            description: remote.description,
+        };
+        let community = match self.get_community_external(&external).await {
+            Some(community) => {
+                self.update_community(DbCommunity {
+                    info,
+                    owner: owner.id,
+                    fetched_at: Some(get_current_time()),
+                    ..community
+                })
+                .await
+            }
+            None => {
+                let community = self
+                    .create_community(DbCommunity {
+                        id: Uuid::now_v7(),
+                        external_id: remote.id,
+                        domain: remote.domain.clone(),
+                        info,
+                        created: remote.created,
+                        owner: owner.id,
+                        fetched_at: Some(get_current_time()),
+                    })
+                    .await;
+                // permissions are checked by the community's instance, locally
+                // members may only do what is federated
+                self.create_role(Role {
+                    id: Uuid::now_v7(),
+                    com_id: community.id,
+                    is_default: true,
+                    created: get_current_time(),
+                    info: RoleInfo {
+                        name: "everyone".to_string(),
+                        permissions: Permissions::VIEW_ROOMS | Permissions::SEND_MESSAGES,
+                        position: 0,
+                    },
+                })
+                .await;
+                community
+            }
+        };
+        let listed: Vec<Uuid> = remote.rooms.iter().map(|x| x.id).collect();
+        for remote_room in remote.rooms {
+            let external = ObjectRef {
+                id: remote_room.id,
+                domain: remote.domain.clone(),
+            };
+            let info = RoomInfo {
+                name: remote_room.name,
+                description: remote_room.description,
+                category: None,
+                display_order: remote_room.display_order,
+            };
+            match self.get_room_external(&external).await {
+                Some(room) if room.community == Some(community.id) => {
+                    self.update_room(Room {
+                        system_channel: remote_room.system_channel,
+                        info,
+                        ..room
+                    })
+                    .await;
+                }
+                Some(_) => return Err(()),
+                None => {
+                    self.create_room(Room {
+                        id: Uuid::now_v7(),
+                        external_id: remote_room.id,
+                        domain: remote.domain.clone(),
+                        community: Some(community.id),
+                        system_channel: remote_room.system_channel,
+                        created: remote_room.created,
+                        known_complete: false,
+                        is_dm: false,
+                        user_a: None,
+                        user_b: None,
+                        info,
+                    })
+                    .await;
+                }
+            }
+        }
+        if prune_rooms {
+            for room in self.get_all_comm_rooms(&community.id).await {
+                if !listed.contains(&room.external_id) {
+                    self.delete_room(&room.id).await;
+                }
+            }
+        }
+        Ok(community)
+    }
+    /// store a message from another instance in a room of a community, the caller
+    /// is responsible for checking that it may be stored. the message is given the
+    /// id it is stored with, returns the mentions it was stored with
+    pub async fn store_federated_message(
+        &self,
+        id: Uuid,
+        room: &Room,
+        author: &DbUser,
+        permissions: Permissions,
+        remote: FederatedMessage,
+    ) -> Result<(DbMessage, Vec<MentionTarget>), InboxErr> {
+        let Some(com_id) = room.community else {
+            return Err(InboxErr::Forbidden);
+        };
+        let thread = match &remote.thread {
+            Some(thread) => match self.get_message_external(thread).await {
+                Some(parent) if parent.info.room == room.id && parent.info.thread.is_none() => {
+                    Some(parent.id)
+                }
+                Some(_) => return Err(InboxErr::Forbidden),
+                None => return Err(InboxErr::UnknownObject),
+            },
+            None => None,
+        };
+        let in_reply_to = match &remote.in_reply_to {
+            Some(reply) => self
+                .get_message_external(reply)
+                .await
+                .filter(|x| x.info.room == room.id)
+                .map(|x| x.id),
+            None => None,
+        };
+        // announcements are worded by us so other instances can't put words in them
+        let name = author
+            .info
+            .display_name
+            .clone()
+            .unwrap_or(author.info.username.clone());
+        let (system_event, content) = match remote.system_event {
+            Some(FederatedEvent::MemberJoined) => (
+                Some(SystemEvent::MemberJoined { community: com_id }),
+                format!("{name} joined the community"),
+            ),
+            None => (None, remote.content),
+        };
+
+        let tokens = parse_mentions(&content, remote.format);
+        let mentions = self
+            .resolve_mentions(room, author, permissions, tokens)
+            .await;
+        let message = DbMessage {
+            id,
+            external_id: remote.id.id,
+            domain: remote.id.domain,
+            user: author.id,
+            published: remote.published,
+            edited: remote.edited,
+            fetched_at: Some(get_current_time()),
+            system_event,
+            info: Messageinfo {
+                is_reply: remote.is_reply,
+                in_reply_to,
+                proxy_id: None,
+                content,
+                format: remote.format,
+                language: remote.language,
+                room: room.id,
+                thread,
+                attachments: Vec::new(),
+            },
+        };
+        let message = self.create_message(message).await;
+        for (position, target) in mentions.iter().enumerate() {
+            self.create_mention(DbMention {
+                m_id: id,
+                position: position as i64,
+                target: *target,
+            })
+            .await;
+        }
+        Ok((message, mentions))
+    }
+    /// the rooms of one of our communities that members from an instance can view
+    pub async fn get_domain_visible_rooms(
+        &self,
+        community: &DbCommunity,
+        domain: &str,
+    ) -> Vec<Room> {
+        let mut visible = Vec::new();
+        for room in self.get_all_comm_rooms(&community.id).await {
+            if self
+                .get_viewing_domains(&room)
+                .await
+                .iter()
+                .any(|x| x == domain)
+            {
+                visible.push(room);
+            }
+        }
+        visible
+    }
+    /// a page of the messages of a room as they are sent to other instances from
+    /// newest to oldest, messages that aren't federated are left out of the page
+    pub async fn get_federated_history(
+        &self,
+        room: &Room,
+        before: Option<Uuid>,
+        limit: i64,
+    ) -> FederatedHistory {
+        let page = self.get_room_history(&room.id, &before, limit).await;
+        let before = match page.len() as i64 == limit {
+            true => page.last().map(|x| ObjectRef {
+                id: x.external_id,
+                domain: x.domain.clone(),
+            }),
+            false => None,
+        };
+        let mut messages = Vec::with_capacity(page.len());
+        for message in page {
+            let mentions_everyone = self
+                .get_mentions(&message.id)
+                .await
+                .iter()
+                .any(|x| x.target == MentionTarget::Everyone);
+            if let Some(federated) = self
+                .federated_message(room, &message, mentions_everyone)
+                .await
+            {
+                messages.push(federated);
+            }
+        }
+        FederatedHistory { messages, before }
+    }
+}
@@ -0,0 +1,45 @@
+use uuid::Uuid;
+
+use crate::db::{pg_sesh::Sesh, types::file::DbFile};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn create_file(&self, file: DbFile) -> DbFile {
+        let result = self
+            .query(
+                DbFile::create_statement(),
+                &[
+                    &file.id,
+                    &file.owner,
+                    &file.description,
+                    &file.path,
+                    &file.mime,
+                    &file.size,
+                    &file.file_name,
+                    &file.created,
+                    &file.width,
+                    &file.height,
+                    &file.blurhash,
+                ],
+            )
+            .await
+            .expect("failed to create file")
+            .pop()
+            .expect("creating file returned nothing");
+        result.into()
+    }
+    pub async fn get_file(&self, file_id: &Uuid) -> Option<DbFile> {
+        let result = self
+            .query(DbFile::read_statement(), &[file_id])
+            .await
+            .expect("failed to fetch file")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn delete_file(&self, file_id: &Uuid) {
+        let _result = self
+            .query(DbFile::delete_statement(), &[file_id])
+            .await
+            .expect("failed to delete file");
+    }
+}
@@ -0,0 +1,43 @@
+use uuid::Uuid;
+
+use crate::db::{pg_sesh::Sesh, types::file_thumbnail::FileThumbnail};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn create_file_thumbnail(&self, thumbnail: FileThumbnail) -> FileThumbnail {
+        let result = self
+            .query(
+                FileThumbnail::create_statement(),
+                &[
+                    &thumbnail.file_id,
+                    &thumbnail.size,
+                    &thumbnail.path,
+                    &thumbnail.mime,
+                    &thumbnail.width,
+                    &thumbnail.height,
+                    &thumbnail.file_size,
+                ],
+            )
+            .await
+            .expect("failed to create file thumbnail")
+            .pop()
+            .expect("creating file thumbnail returned nothing");
+        result.into()
+    }
+    pub async fn get_file_thumbnails(&self, file_id: &Uuid) -> Vec<FileThumbnail> {
+        let result = self
+            .query(FileThumbnail::get_all_statement(), &[file_id])
+            .await
+            .expect("failed to fetch file thumbnails");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    /// get the smallest thumbnail of a file that is at least `size` pixels on its longest edge
+    pub async fn get_fitting_thumbnail(&self, file_id: &Uuid, size: i64) -> Option<FileThumbnail> {
+        let result = self
+            .query(FileThumbnail::read_fitting_statement(), &[file_id, &size])
+            .await
+            .expect("failed to fetch file thumbnail")
+            .pop();
+        result.map(|x| x.into())
+    }
+}
@@ -1,4 +1,7 @@
-use crate::db::{pg_sesh::Sesh, types::instance::Instance};
+use crate::db::{
+    pg_sesh::Sesh,
+    types::instance::{Instance, InstanceKey},
+};
 
 #[allow(dead_code)]
 impl Sesh<'_> {
@@ -61,4 +64,57 @@ impl Sesh<'_> {
             .await
             .expect("failed to delete instance");
     }
+    pub async fn set_instance_key(&self, domain: &str, key: &InstanceKey) -> Instance {
+        let result = self
+            .query(
+                Instance::set_key_statement(),
+                &[
+                    &domain,
+                    &key.algorithm.as_str(),
+                    &key.public_key,
+                    &key.private_key,
+                    &key.fetched_at,
+                ],
+            )
+            .await
+            .expect("failed to set instance key")
+            .pop()
+            .expect("setting instance key returned nothing");
+        result.into()
+    }
+    /// returns none for our own instance
+    pub async fn set_instance_policy(
+        &self,
+        domain: &str,
+        blocked: bool,
+        allowlisted: bool,
+        reason: &Option<String>,
+    ) -> Option<Instance> {
+        let result = self
+            .query(
+                Instance::set_policy_statement(),
+                &[&domain, &blocked, &allowlisted, reason],
+            )
+            .await
+            .expect("failed to set instance policy")
+            .pop();
+        result.map(|x| x.into())
+    }
+    /// record if our own instance only federates with allowlisted instances
+    pub async fn set_allowlist_only(&self, domain: &str, allowlist_only: bool) {
+        let _result = self
+            .query(
+                Instance::set_allowlist_only_statement(),
+                &[&domain, &allowlist_only],
+            )
+            .await
+            .expect("failed to set allowlist only");
+    }
+    pub async fn get_instance_policies(&self) -> Vec<Instance> {
+        let result = self
+            .query(Instance::get_policies_statement(), &[])
+            .await
+            .expect("failed to fetch instance policies");
+        result.into_iter().map(|x| x.into()).collect()
+    }
 }
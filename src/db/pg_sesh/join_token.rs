@@ -0,0 +1,76 @@
+use uuid::Uuid;
+
+use crate::db::{curr_time::get_current_time, pg_sesh::Sesh, types::tokens::join_token::JoinToken};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn create_join_token(
+        &self,
+        creator: &Uuid,
+        com_id: &Uuid,
+        expiry: i64,
+        max_uses: Option<i64>,
+    ) -> JoinToken {
+        let id = Uuid::new_v4();
+        let result = self
+            .query(
+                JoinToken::create_statement(),
+                &[
+                    &id,
+                    creator,
+                    com_id,
+                    &expiry,
+                    &max_uses,
+                    &get_current_time(),
+                ],
+            )
+            .await
+            .expect("failed to create join token")
+            .pop()
+            .expect("creating join token returned nothing");
+        result.into()
+    }
+    pub async fn get_join_token(&self, token_id: &Uuid) -> Option<JoinToken> {
+        let result = self
+            .query(JoinToken::read_statement(), &[token_id])
+            .await
+            .expect("failed to fetch join token")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn delete_join_token(&self, token_id: &Uuid) {
+        let _result = self
+            .query(JoinToken::delete_statement(), &[token_id])
+            .await
+            .expect("failed to delete join token");
+    }
+    /// returns none if the token is gone or has been used up
+    pub async fn use_join_token(&self, token_id: &Uuid) -> Option<JoinToken> {
+        let result = self
+            .query(JoinToken::use_statement(), &[token_id])
+            .await
+            .expect("failed to use join token")
+            .pop();
+        result.map(|x| x.into())
+    }
+    /// gets all unexpired join tokens of a community, newest first
+    pub async fn get_all_comm_join_tokens(&self, com_id: &Uuid) -> Vec<JoinToken> {
+        let result = self
+            .query(
+                JoinToken::get_all_comm_tokens(),
+                &[com_id, &get_current_time()],
+            )
+            .await
+            .expect("failed to fetch community join tokens");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    pub async fn delete_expired_comm_join_tokens(&self, com_id: &Uuid) {
+        let _result = self
+            .query(
+                JoinToken::delete_expired_comm_tokens(),
+                &[com_id, &get_current_time()],
+            )
+            .await
+            .expect("failed to delete expired join tokens");
+    }
+}
@@ -0,0 +1,32 @@
+use crate::db::{pg_sesh::Sesh, types::live_event::LiveEvent};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn notify(&self, channel: &str, payload: &str) {
+        self.query(LiveEvent::notify_statement(), &[&channel, &payload])
+            .await
+            .expect("failed to send notification");
+    }
+    pub async fn create_live_event(&self, payload: &str, created: i64) -> LiveEvent {
+        let result = self
+            .query(LiveEvent::create_statement(), &[&payload, &created])
+            .await
+            .expect("failed to create live event")
+            .pop()
+            .expect("creating live event returned nothing");
+        result.into()
+    }
+    pub async fn get_live_event(&self, event_id: i64) -> Option<LiveEvent> {
+        let result = self
+            .query(LiveEvent::read_statement(), &[&event_id])
+            .await
+            .expect("failed to fetch live event")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn delete_live_events_before(&self, created: i64) {
+        self.query(LiveEvent::delete_before_statement(), &[&created])
+            .await
+            .expect("failed to delete live events");
+    }
+}
@@ -0,0 +1,93 @@
+use uuid::Uuid;
+
+use crate::db::{
+    mentions::MentionToken,
+    pg_sesh::Sesh,
+    types::{
+        comm::permissions::Permissions,
+        mention::{DbMention, MentionTarget},
+        room::Room,
+        user::DbUser,
+    },
+};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn create_mention(&self, mention: DbMention) -> DbMention {
+        let (uid, role_id) = match mention.target {
+            MentionTarget::User(uid) => (Some(uid), None),
+            MentionTarget::Role(role_id) => (None, Some(role_id)),
+            MentionTarget::Everyone => (None, None),
+        };
+        let result = self
+            .query(
+                DbMention::create_statement(),
+                &[&mention.m_id, &mention.position, &uid, &role_id],
+            )
+            .await
+            .expect("failed to create mention")
+            .pop()
+            .expect("creating mention returned nothing");
+        result.into()
+    }
+    pub async fn get_mentions(&self, m_id: &Uuid) -> Vec<DbMention> {
+        let result = self
+            .query(DbMention::get_all_statement(), &[m_id])
+            .await
+            .expect("failed to fetch mentions");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    pub async fn delete_mentions(&self, m_id: &Uuid) {
+        self.query(DbMention::delete_all_statement(), &[m_id])
+            .await
+            .expect("failed to delete mentions");
+    }
+    pub async fn get_mentioned_user_ids(&self, m_id: &Uuid) -> Vec<Uuid> {
+        let result = self
+            .query(DbMention::get_mentioned_users_statement(), &[m_id])
+            .await
+            .expect("failed to fetch mentioned users");
+        result.into_iter().map(|x| x.get("uid")).collect()
+    }
+    /// resolve parsed mentions against the users able to view the room. tokens
+    /// are checked against usernames before role names, @everyone and roles
+    /// require the mention everyone permission. unresolved mentions are left out
+    pub async fn resolve_mentions(
+        &self,
+        room: &Room,
+        author: &DbUser,
+        permissions: Permissions,
+        tokens: Vec<MentionToken>,
+    ) -> Vec<MentionTarget> {
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+        let members = self.get_room_member_ids(room).await;
+        let can_mention_everyone = permissions.contains(Permissions::MENTION_EVERYONE);
+        let roles = match (room.community, can_mention_everyone) {
+            (Some(com_id), true) => self.get_all_comm_roles(&com_id).await,
+            _ => Vec::new(),
+        };
+        let mut resolved = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let target = if token.is_everyone() {
+                can_mention_everyone.then_some(MentionTarget::Everyone)
+            } else {
+                let domain = token.domain.as_deref().unwrap_or(&author.domain);
+                match self.get_user(&token.name, domain).await {
+                    Some(user) if members.contains(&user.id) => Some(MentionTarget::User(user.id)),
+                    Some(_) => None,
+                    None if token.domain.is_none() => roles
+                        .iter()
+                        .find(|x| !x.is_default && x.info.name.eq_ignore_ascii_case(&token.name))
+                        .map(|x| MentionTarget::Role(x.id)),
+                    None => None,
+                }
+            };
+            if let Some(target) = target.filter(|x| !resolved.contains(x)) {
+                resolved.push(target);
+            }
+        }
+        resolved
+    }
+}
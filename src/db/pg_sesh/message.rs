@@ -1,13 +1,23 @@
+use tokio_postgres::types::Json;
 use uuid::Uuid;
 
 use crate::{
-    db::{pg_sesh::Sesh, types::message::DbMessage},
+    db::{
+        markdown::{render_message, RenderedMessage},
+        pg_sesh::Sesh,
+        types::{
+            comm::permissions::Permissions,
+            message::DbMessage,
+            message_search::{search_config, MessageSearch},
+        },
+    },
     routes::api::types::api_message::ApiMessage,
 };
 
 #[allow(dead_code)]
 impl Sesh<'_> {
     pub async fn create_message(&self, message: DbMessage) -> DbMessage {
+        let rendered = render_message(&message.info.content, message.info.format);
         let result = self
             .query(
                 DbMessage::create_statement(),
@@ -25,6 +35,11 @@ impl Sesh<'_> {
                     &message.info.content,
                     &message.info.format.as_str(),
                     &message.info.language.map(|x| x.to_string()),
+                    &message.system_event.map(Json),
+                    &search_config(message.info.language),
+                    &message.info.thread,
+                    &rendered.blocks.map(Json),
+                    &rendered.plain,
                 ],
             )
             .await
@@ -41,10 +56,35 @@ impl Sesh<'_> {
             .pop();
         result.map(|x| x.into())
     }
+    /// messages of a room older than `before` from newest to oldest, including
+    /// those in threads
+    pub async fn get_room_history(
+        &self,
+        room_id: &Uuid,
+        before: &Option<Uuid>,
+        limit: i64,
+    ) -> Vec<DbMessage> {
+        let result = self
+            .query(
+                DbMessage::room_history_statement(),
+                &[room_id, before, &limit],
+            )
+            .await
+            .expect("failed to fetch room history");
+        result.into_iter().map(DbMessage::from).collect()
+    }
+    pub async fn get_oldest_room_message(&self, room_id: &Uuid) -> Option<DbMessage> {
+        let result = self
+            .query(DbMessage::oldest_in_room_statement(), &[room_id])
+            .await
+            .expect("failed to fetch oldest message")
+            .pop();
+        result.map(|x| x.into())
+    }
     /// warning, this can be multiple operations for getting the preview
-    pub async fn get_api_message(&self, m_id: &Uuid) -> Option<ApiMessage> {
+    pub async fn get_api_message(&self, m_id: &Uuid, viewer: &Uuid) -> Option<ApiMessage> {
         let result = self
-            .query(DbMessage::read_joined_statement(), &[m_id])
+            .query(DbMessage::read_joined_statement(), &[viewer, m_id])
             .await
             .expect("failed to fetch message")
             .pop();
@@ -57,6 +97,7 @@ impl Sesh<'_> {
         }
     }
     pub async fn update_message(&self, message: DbMessage) -> DbMessage {
+        let rendered = render_message(&message.info.content, message.info.format);
         let result = self
             .query(
                 DbMessage::update_statement(),
@@ -66,6 +107,10 @@ impl Sesh<'_> {
                     &message.info.format.as_str(),
                     &message.info.language.map(|x| x.to_string()),
                     &message.fetched_at,
+                    &message.id,
+                    &search_config(message.info.language),
+                    &rendered.blocks.map(Json),
+                    &rendered.plain,
                 ],
             )
             .await
@@ -74,15 +119,37 @@ impl Sesh<'_> {
             .expect("updating message returned nothing");
         result.into()
     }
+    /// messages sent before markdown was rendered, oldest first
+    pub async fn get_unrendered_messages(&self, after: &Uuid, limit: i64) -> Vec<DbMessage> {
+        let result = self
+            .query(DbMessage::get_unrendered_statement(), &[after, &limit])
+            .await
+            .expect("failed to fetch unrendered messages");
+        result.into_iter().map(DbMessage::from).collect()
+    }
+    pub async fn set_rendered(&self, m_id: &Uuid, rendered: RenderedMessage) {
+        let _result = self
+            .query(
+                DbMessage::set_rendered_statement(),
+                &[m_id, &rendered.blocks.map(Json), &rendered.plain],
+            )
+            .await
+            .expect("failed to set rendered message");
+    }
     pub async fn delete_message(&self, m_id: &Uuid) {
         let _result = self
             .query(DbMessage::delete_statement(), &[m_id])
             .await
             .expect("failed to delete message");
     }
-    pub async fn get_room_messages(&self, room_id: &Uuid, limit: i64) -> Vec<ApiMessage> {
+    pub async fn get_room_messages(
+        &self,
+        room_id: &Uuid,
+        viewer: &Uuid,
+        limit: i64,
+    ) -> Vec<ApiMessage> {
         let result = self
-            .query(DbMessage::get_room_messages(), &[room_id, &limit])
+            .query(DbMessage::get_room_messages(), &[viewer, room_id, &limit])
             .await
             .expect("failed to fetch room messages");
 
@@ -98,6 +165,7 @@ impl Sesh<'_> {
     pub async fn get_room_messages_before(
         &self,
         room_id: Uuid,
+        viewer: Uuid,
         limit: i64,
         post: Uuid,
         inclusive: bool,
@@ -105,7 +173,7 @@ impl Sesh<'_> {
         let result = self
             .query(
                 DbMessage::get_messages_prior(inclusive),
-                &[&room_id, &post, &limit],
+                &[&viewer, &room_id, &post, &limit],
             )
             .await
             .expect("failed to fetch room messages");
@@ -122,6 +190,7 @@ impl Sesh<'_> {
     pub async fn get_room_messages_after(
         &self,
         room_id: Uuid,
+        viewer: Uuid,
         limit: i64,
         post: Uuid,
         inclusive: bool,
@@ -129,7 +198,7 @@ impl Sesh<'_> {
         let result = self
             .query(
                 DbMessage::get_messages_after(inclusive),
-                &[&room_id, &post, &limit],
+                &[&viewer, &room_id, &post, &limit],
             )
             .await
             .expect("failed to fetch room messages");
@@ -142,6 +211,173 @@ impl Sesh<'_> {
             })
             .collect()
     }
+    pub async fn get_pinned_messages(
+        &self,
+        room_id: &Uuid,
+        viewer: &Uuid,
+        before: &Option<Uuid>,
+        limit: i64,
+    ) -> Vec<ApiMessage> {
+        let result = self
+            .query(
+                DbMessage::get_pinned_messages(),
+                &[viewer, room_id, before, &limit],
+            )
+            .await
+            .expect("failed to fetch pinned messages");
+        result
+            .into_iter()
+            .map(|x| {
+                let json: tokio_postgres::types::Json<ApiMessage> = x.get("json_build_object");
+                json.0
+            })
+            .collect()
+    }
+    /// threads can be viewed by users that can view the room of the message that started them
+    pub async fn can_view_thread(&self, thread_id: &Uuid, uid: &Uuid) -> bool {
+        let Some(parent) = self.get_message(thread_id).await else {
+            return false;
+        };
+        if parent.info.thread.is_some() {
+            return false;
+        }
+        let Some(room) = self.get_room(&parent.info.room).await else {
+            return false;
+        };
+        self.get_room_permissions(&room, uid)
+            .await
+            .contains(Permissions::VIEW_ROOMS)
+    }
+    /// the latest messages of a thread from oldest to newest
+    pub async fn get_thread_messages(
+        &self,
+        thread_id: &Uuid,
+        viewer: &Uuid,
+        limit: i64,
+    ) -> Vec<ApiMessage> {
+        let result = self
+            .query(
+                DbMessage::get_thread_messages(),
+                &[viewer, thread_id, &limit],
+            )
+            .await
+            .expect("failed to fetch thread messages");
+        result
+            .into_iter()
+            .rev()
+            .map(|x| {
+                let json: tokio_postgres::types::Json<ApiMessage> = x.get("json_build_object");
+                json.0
+            })
+            .collect()
+    }
+    pub async fn get_thread_messages_before(
+        &self,
+        thread_id: Uuid,
+        viewer: Uuid,
+        limit: i64,
+        post: Uuid,
+        inclusive: bool,
+    ) -> Vec<ApiMessage> {
+        let result = self
+            .query(
+                DbMessage::get_thread_messages_prior(inclusive),
+                &[&viewer, &thread_id, &post, &limit],
+            )
+            .await
+            .expect("failed to fetch thread messages");
+        // we rev to make oldest to newest as the api expects
+        result
+            .into_iter()
+            .rev()
+            .map(|x| {
+                let json: tokio_postgres::types::Json<ApiMessage> = x.get("json_build_object");
+                json.0
+            })
+            .collect()
+    }
+    pub async fn get_thread_messages_after(
+        &self,
+        thread_id: Uuid,
+        viewer: Uuid,
+        limit: i64,
+        post: Uuid,
+        inclusive: bool,
+    ) -> Vec<ApiMessage> {
+        let result = self
+            .query(
+                DbMessage::get_thread_messages_after(inclusive),
+                &[&viewer, &thread_id, &post, &limit],
+            )
+            .await
+            .expect("failed to fetch thread messages");
+        result
+            .into_iter()
+            .map(|x| {
+                let json: tokio_postgres::types::Json<ApiMessage> = x.get("json_build_object");
+                json.0
+            })
+            .collect()
+    }
+    /// the messages that started threads with replies since `active_since`,
+    /// most recently replied to first
+    pub async fn get_active_threads(
+        &self,
+        room_id: &Uuid,
+        viewer: &Uuid,
+        active_since: i64,
+        before: &Option<Uuid>,
+        limit: i64,
+    ) -> Vec<ApiMessage> {
+        let result = self
+            .query(
+                DbMessage::get_active_threads(),
+                &[viewer, room_id, &active_since, before, &limit],
+            )
+            .await
+            .expect("failed to fetch active threads");
+        result
+            .into_iter()
+            .map(|x| {
+                let json: tokio_postgres::types::Json<ApiMessage> = x.get("json_build_object");
+                json.0
+            })
+            .collect()
+    }
+    /// search the given rooms, results are from newest to oldest
+    pub async fn search_messages(
+        &self,
+        rooms: &[Uuid],
+        viewer: &Uuid,
+        search: &MessageSearch,
+        limit: i64,
+    ) -> Vec<ApiMessage> {
+        let result = self
+            .query(
+                MessageSearch::search_statement(),
+                &[
+                    viewer,
+                    &rooms,
+                    &search.query,
+                    &search.author,
+                    &search.since,
+                    &search.until,
+                    &search.has_attachment,
+                    &search.is_reply,
+                    &search.older,
+                    &limit,
+                ],
+            )
+            .await
+            .expect("failed to search messages");
+        result
+            .into_iter()
+            .map(|x| {
+                let json: tokio_postgres::types::Json<ApiMessage> = x.get("json_build_object");
+                json.0
+            })
+            .collect()
+    }
     // pub async fn get_reply_preview(&self, m_id: Uuid) -> Option<ReplyPreview> {
     //     let result = self
     //         .query(DbMessage::read_statement(), &[&m_id])
@@ -0,0 +1,82 @@
+use uuid::Uuid;
+
+use crate::db::{
+    mentions::parse_mentions,
+    pg_sesh::Sesh,
+    types::{
+        comm::permissions::Permissions,
+        mention::{DbMention, MentionTarget},
+        message::DbMessage,
+        message_edit::MessageEdit,
+        room::Room,
+        user::DbUser,
+    },
+};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn create_message_edit(&self, edit: MessageEdit) -> MessageEdit {
+        let result = self
+            .query(
+                MessageEdit::create_statement(),
+                &[
+                    &edit.id,
+                    &edit.m_id,
+                    &edit.content,
+                    &edit.format.as_str(),
+                    &edit.language.map(|x| x.to_string()),
+                    &edit.published,
+                    &edit.replaced,
+                ],
+            )
+            .await
+            .expect("failed to create message edit")
+            .pop()
+            .expect("creating message edit returned nothing");
+        result.into()
+    }
+    pub async fn get_message_edits(&self, m_id: &Uuid) -> Vec<MessageEdit> {
+        let result = self
+            .query(MessageEdit::get_message_edits(), &[m_id])
+            .await
+            .expect("failed to fetch message edits");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    /// store an edit of a message, the previous version is kept in the message's
+    /// edit history and mentions are resolved again from the new content with the
+    /// given permissions of the author. returns the edited message and its mentions
+    pub async fn replace_message(
+        &self,
+        room: &Room,
+        author: &DbUser,
+        permissions: Permissions,
+        previous: DbMessage,
+        edited: DbMessage,
+    ) -> (DbMessage, Vec<MentionTarget>) {
+        self.create_message_edit(MessageEdit {
+            id: Uuid::now_v7(),
+            m_id: previous.id,
+            content: previous.info.content,
+            format: previous.info.format,
+            language: previous.info.language,
+            published: previous.edited.unwrap_or(previous.published),
+            replaced: edited.edited.unwrap_or(previous.published),
+        })
+        .await;
+        let message = self.update_message(edited).await;
+        let tokens = parse_mentions(&message.info.content, message.info.format);
+        let mentions = self
+            .resolve_mentions(room, author, permissions, tokens)
+            .await;
+        self.delete_mentions(&message.id).await;
+        for (position, target) in mentions.iter().enumerate() {
+            self.create_mention(DbMention {
+                m_id: message.id,
+                position: position as i64,
+                target: *target,
+            })
+            .await;
+        }
+        (message, mentions)
+    }
+}
@@ -1,14 +1,32 @@
 use deadpool_postgres::{Object, Transaction};
 use tokio_postgres::{types::ToSql, Statement};
 
+mod attachment;
 mod auth_token;
 mod comm_membership;
 mod community;
+mod community_ban;
+mod custom_emoji;
+mod emoji_pack;
+mod federation;
+mod file;
+mod file_thumbnail;
 mod instance;
+mod join_token;
+mod live_event;
+mod mention;
 mod message;
+mod message_edit;
+mod outbox;
+mod permissions;
+mod pin;
 mod proxy;
+mod reaction;
+mod read_state;
 mod registered_device;
+mod role;
 mod room;
+mod room_membership;
 mod signup_token;
 mod users;
 
@@ -0,0 +1,50 @@
+use tokio_postgres::types::Json;
+
+use crate::{
+    db::{curr_time::get_current_time, pg_sesh::Sesh, types::outbox::OutboxDelivery},
+    federation::activity::Activity,
+};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn create_delivery(&self, domain: &str, activity: &Activity) -> OutboxDelivery {
+        let result = self
+            .query(
+                OutboxDelivery::create_statement(),
+                &[&domain, &Json(activity), &get_current_time()],
+            )
+            .await
+            .expect("failed to create delivery")
+            .pop()
+            .expect("creating delivery returned nothing");
+        result.into()
+    }
+    pub async fn claim_deliveries(&self, lease: i64, limit: i64) -> Vec<OutboxDelivery> {
+        let now = get_current_time();
+        let result = self
+            .query(
+                OutboxDelivery::claim_statement(),
+                &[&now, &(now + lease), &limit],
+            )
+            .await
+            .expect("failed to claim deliveries");
+        let mut deliveries: Vec<OutboxDelivery> = result.into_iter().map(|x| x.into()).collect();
+        deliveries.sort_by_key(|x| x.delivery_id);
+        deliveries
+    }
+    pub async fn retry_delivery(&self, delivery_id: i64, next_attempt: i64) {
+        let _result = self
+            .query(
+                OutboxDelivery::retry_statement(),
+                &[&delivery_id, &next_attempt],
+            )
+            .await
+            .expect("failed to retry delivery");
+    }
+    pub async fn delete_delivery(&self, delivery_id: i64) {
+        let _result = self
+            .query(OutboxDelivery::delete_statement(), &[&delivery_id])
+            .await
+            .expect("failed to delete delivery");
+    }
+}
@@ -0,0 +1,194 @@
+//! the permission resolver, every check of what a user is allowed
+//! to do in a community or room should go through here
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::db::{
+    pg_sesh::Sesh,
+    types::{
+        comm::{
+            community::DbCommunity,
+            permissions::{resolve_community, resolve_room, Permissions},
+            role::Role,
+        },
+        room::Room,
+    },
+};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    /// gets the default role and the assigned roles of a member
+    async fn get_member_role_context(
+        &self,
+        com_id: &Uuid,
+        uid: &Uuid,
+    ) -> Option<(Role, Vec<Role>)> {
+        let default_role = self.get_default_role(com_id).await?;
+        let roles = self.get_member_roles(com_id, uid).await;
+        Some((default_role, roles))
+    }
+    /// resolve a user's permissions in a community, users that are
+    /// not members of the community have no permissions
+    pub async fn get_comm_permissions(&self, community: &DbCommunity, uid: &Uuid) -> Permissions {
+        if self.get_comm_membership(&community.id, uid).await.is_none() {
+            return Permissions::NONE;
+        }
+        if community.owner == *uid {
+            return Permissions::ALL;
+        }
+        let Some((default_role, roles)) = self.get_member_role_context(&community.id, uid).await
+        else {
+            return Permissions::NONE;
+        };
+        resolve_community(&default_role, &roles.iter().collect::<Vec<_>>())
+    }
+    /// resolve a user's permissions in a room, members of dms and group chats
+    /// always have [`Permissions::DIRECT`]
+    pub async fn get_room_permissions(&self, room: &Room, uid: &Uuid) -> Permissions {
+        let Some(com_id) = room.community else {
+            return match self.is_room_member(room, uid).await {
+                true => Permissions::DIRECT,
+                false => Permissions::NONE,
+            };
+        };
+        let Some(community) = self.get_community(&com_id).await else {
+            return Permissions::NONE;
+        };
+        if self.get_comm_membership(&com_id, uid).await.is_none() {
+            return Permissions::NONE;
+        }
+        if community.owner == *uid {
+            return Permissions::ALL;
+        }
+        let Some((default_role, roles)) = self.get_member_role_context(&com_id, uid).await else {
+            return Permissions::NONE;
+        };
+        let base = resolve_community(&default_role, &roles.iter().collect::<Vec<_>>());
+        let overrides = self.get_room_overrides(&room.id).await;
+        let role_ids: Vec<Uuid> = roles.iter().map(|x| x.id).collect();
+        resolve_room(base, default_role.id, &role_ids, &overrides)
+    }
+    /// the position of a member's highest role, the owner is above every role.
+    /// returns none if the user is not a member
+    pub async fn get_highest_role(&self, community: &DbCommunity, uid: &Uuid) -> Option<i64> {
+        self.get_comm_membership(&community.id, uid).await?;
+        if community.owner == *uid {
+            return Some(i64::MAX);
+        }
+        let roles = self.get_member_roles(&community.id, uid).await;
+        Some(roles.iter().map(|x| x.info.position).max().unwrap_or(0))
+    }
+    /// get every user that has the given permissions in a room. resolves all members
+    /// at once rather than a query per member, used for fanning out live events
+    pub async fn get_room_members_with(&self, room: &Room, permissions: Permissions) -> Vec<Uuid> {
+        let Some(com_id) = room.community else {
+            return match Permissions::DIRECT.contains(permissions) {
+                true => self
+                    .get_all_room_members(&room.id)
+                    .await
+                    .into_iter()
+                    .map(|x| x.uid)
+                    .collect(),
+                false => Vec::new(),
+            };
+        };
+        let Some(community) = self.get_community(&com_id).await else {
+            return Vec::new();
+        };
+        let Some(default_role) = self.get_default_role(&com_id).await else {
+            return Vec::new();
+        };
+        let roles: HashMap<Uuid, Role> = self
+            .get_all_comm_roles(&com_id)
+            .await
+            .into_iter()
+            .map(|x| (x.id, x))
+            .collect();
+        let mut member_roles: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for membership in self.get_all_comm_role_memberships(&com_id).await {
+            member_roles
+                .entry(membership.uid)
+                .or_default()
+                .push(membership.role_id);
+        }
+        let overrides = self.get_room_overrides(&room.id).await;
+
+        self.get_all_comm_members(&com_id)
+            .await
+            .into_iter()
+            .map(|x| x.uid)
+            .filter(|uid| {
+                if community.owner == *uid {
+                    return true;
+                }
+                let role_ids = member_roles.get(uid).cloned().unwrap_or_default();
+                let assigned: Vec<&Role> = role_ids.iter().filter_map(|x| roles.get(x)).collect();
+                let base = resolve_community(&default_role, &assigned);
+                resolve_room(base, default_role.id, &role_ids, &overrides).contains(permissions)
+            })
+            .collect()
+    }
+    /// get the rooms of a community that a user is able to view
+    pub async fn get_visible_comm_rooms(&self, community: &DbCommunity, uid: &Uuid) -> Vec<Room> {
+        let base = self.get_comm_permissions(community, uid).await;
+        if base == Permissions::NONE {
+            return Vec::new();
+        }
+        let rooms = self.get_all_comm_rooms(&community.id).await;
+        if base.contains(Permissions::ADMINISTRATOR) {
+            return rooms;
+        }
+        let Some((default_role, roles)) = self.get_member_role_context(&community.id, uid).await
+        else {
+            return Vec::new();
+        };
+        let role_ids: Vec<Uuid> = roles.iter().map(|x| x.id).collect();
+        let overrides = self.get_comm_overrides(&community.id).await;
+        rooms
+            .into_iter()
+            .filter(|room| {
+                let overrides: Vec<_> = overrides
+                    .iter()
+                    .filter(|x| x.room_id == room.id)
+                    .cloned()
+                    .collect();
+                resolve_room(base, default_role.id, &role_ids, &overrides)
+                    .contains(Permissions::VIEW_ROOMS)
+            })
+            .collect()
+    }
+    /// true if the actor is above the target in the role hierarchy.
+    /// nobody is able to manage the owner
+    pub async fn can_manage_member(
+        &self,
+        community: &DbCommunity,
+        actor: &Uuid,
+        target: &Uuid,
+    ) -> bool {
+        if community.owner == *target {
+            return false;
+        }
+        let Some(actor) = self.get_highest_role(community, actor).await else {
+            return false;
+        };
+        match self.get_highest_role(community, target).await {
+            Some(target) => actor > target,
+            // not a member, nothing to be above
+            None => true,
+        }
+    }
+    /// true if the role is below the actor's highest role
+    pub async fn can_manage_role(
+        &self,
+        community: &DbCommunity,
+        actor: &Uuid,
+        role: &Role,
+    ) -> bool {
+        match self.get_highest_role(community, actor).await {
+            Some(highest) => highest > role.info.position,
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use uuid::Uuid;
+
+use crate::db::{pg_sesh::Sesh, types::pin::Pin};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    /// returns none if the message is already pinned
+    pub async fn create_pin(&self, pin: Pin) -> Option<Pin> {
+        let result = self
+            .query(
+                Pin::create_statement(),
+                &[&pin.room_id, &pin.m_id, &pin.uid, &pin.created],
+            )
+            .await
+            .expect("failed to create pin")
+            .pop();
+        result.map(|x| x.into())
+    }
+    /// returns none if the message was not pinned
+    pub async fn delete_pin(&self, room_id: &Uuid, m_id: &Uuid) -> Option<Pin> {
+        let result = self
+            .query(Pin::delete_statement(), &[room_id, m_id])
+            .await
+            .expect("failed to delete pin")
+            .pop();
+        result.map(|x| x.into())
+    }
+}
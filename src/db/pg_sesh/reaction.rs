@@ -0,0 +1,62 @@
+use uuid::Uuid;
+
+use crate::db::{
+    pg_sesh::Sesh,
+    types::{reaction::DbReaction, user::DbUser},
+};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    /// returns none if the user already reacted with the emoji
+    pub async fn create_reaction(&self, reaction: DbReaction) -> Option<DbReaction> {
+        let result = self
+            .query(
+                DbReaction::create_statement(),
+                &[
+                    &reaction.uid,
+                    &reaction.m_id,
+                    &reaction.emoji,
+                    &reaction.emoji_id,
+                    &reaction.archive_id,
+                    &reaction.published,
+                ],
+            )
+            .await
+            .expect("failed to create reaction")
+            .pop();
+        result.map(|x| x.into())
+    }
+    /// returns none if the reaction did not exist
+    pub async fn delete_reaction(
+        &self,
+        uid: &Uuid,
+        m_id: &Uuid,
+        emoji: &str,
+        archive_id: &Option<Uuid>,
+    ) -> Option<DbReaction> {
+        let result = self
+            .query(
+                DbReaction::delete_statement(),
+                &[uid, m_id, &emoji, archive_id],
+            )
+            .await
+            .expect("failed to delete reaction")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn get_reaction_users(
+        &self,
+        m_id: &Uuid,
+        emoji: &str,
+        archive_id: &Option<Uuid>,
+    ) -> Vec<DbUser> {
+        let result = self
+            .query(
+                DbReaction::get_reaction_users(),
+                &[m_id, &emoji, archive_id],
+            )
+            .await
+            .expect("failed to fetch reaction users");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+}
@@ -0,0 +1,54 @@
+use uuid::Uuid;
+
+use crate::{
+    db::{
+        pg_sesh::Sesh,
+        types::{
+            read_state::{ReadState, RoomUnreads},
+            room::Room,
+        },
+    },
+    routes::api::types::api_room::ApiRoom,
+};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    /// returns none if the user has already read a newer message in the room
+    pub async fn set_read_state(&self, state: ReadState) -> Option<ReadState> {
+        let result = self
+            .query(
+                ReadState::upsert_statement(),
+                &[&state.uid, &state.room_id, &state.last_read, &state.updated],
+            )
+            .await
+            .expect("failed to set read state")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn get_read_state(&self, uid: &Uuid, room_id: &Uuid) -> Option<ReadState> {
+        let result = self
+            .query(ReadState::read_statement(), &[uid, room_id])
+            .await
+            .expect("failed to fetch read state")
+            .pop();
+        result.map(|x| x.into())
+    }
+    /// unread counts for each of the rooms in the order they were given
+    pub async fn get_room_unreads(&self, uid: &Uuid, rooms: &[Uuid]) -> Vec<RoomUnreads> {
+        let result = self
+            .query(ReadState::get_unreads_statement(), &[uid, &rooms])
+            .await
+            .expect("failed to fetch unreads");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    /// attach the user's unread counts to each room
+    pub async fn rooms_with_unreads(&self, uid: &Uuid, rooms: Vec<Room>) -> Vec<ApiRoom> {
+        let ids: Vec<Uuid> = rooms.iter().map(|x| x.id).collect();
+        let unreads = self.get_room_unreads(uid, &ids).await;
+        rooms
+            .into_iter()
+            .zip(unreads)
+            .map(|(room, unreads)| ApiRoom::new(room, unreads))
+            .collect()
+    }
+}
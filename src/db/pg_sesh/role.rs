@@ -0,0 +1,145 @@
+use uuid::Uuid;
+
+use crate::db::{
+    pg_sesh::Sesh,
+    types::comm::{role::Role, role_membership::RoleMembership, room_override::RoomOverride},
+};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn create_role(&self, role: Role) -> Role {
+        let result = self
+            .query(
+                Role::create_statement(),
+                &[
+                    &role.id,
+                    &role.com_id,
+                    &role.info.name,
+                    &role.info.permissions.0,
+                    &role.info.position,
+                    &role.is_default,
+                    &role.created,
+                ],
+            )
+            .await
+            .expect("failed to create role")
+            .pop()
+            .expect("creating role returned nothing");
+        result.into()
+    }
+    pub async fn get_role(&self, role_id: &Uuid) -> Option<Role> {
+        let result = self
+            .query(Role::read_statement(), &[role_id])
+            .await
+            .expect("failed to fetch role")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn update_role(&self, role: Role) -> Role {
+        let result = self
+            .query(
+                Role::update_statement(),
+                &[
+                    &role.info.name,
+                    &role.info.permissions.0,
+                    &role.info.position,
+                    &role.id,
+                ],
+            )
+            .await
+            .expect("failed to update role")
+            .pop()
+            .expect("updating role returned nothing");
+        result.into()
+    }
+    pub async fn delete_role(&self, role_id: &Uuid) {
+        let _result = self
+            .query(Role::delete_statement(), &[role_id])
+            .await
+            .expect("failed to delete role");
+    }
+    /// gets all roles of a community ordered from highest to lowest
+    pub async fn get_all_comm_roles(&self, com_id: &Uuid) -> Vec<Role> {
+        let result = self
+            .query(Role::get_all_comm_roles(), &[com_id])
+            .await
+            .expect("failed to fetch community roles");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    pub async fn get_default_role(&self, com_id: &Uuid) -> Option<Role> {
+        let result = self
+            .query(Role::get_default_role(), &[com_id])
+            .await
+            .expect("failed to fetch default role")
+            .pop();
+        result.map(|x| x.into())
+    }
+    /// gets the roles assigned to a member, does not include the default role
+    pub async fn get_member_roles(&self, com_id: &Uuid, uid: &Uuid) -> Vec<Role> {
+        let result = self
+            .query(Role::get_member_roles(), &[com_id, uid])
+            .await
+            .expect("failed to fetch member roles");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    pub async fn create_role_membership(&self, membership: RoleMembership) {
+        let _result = self
+            .query(
+                RoleMembership::create_statement(),
+                &[&membership.com_id, &membership.uid, &membership.role_id],
+            )
+            .await
+            .expect("failed to create role membership");
+    }
+    pub async fn delete_role_membership(&self, role_id: &Uuid, uid: &Uuid) {
+        let _result = self
+            .query(RoleMembership::delete_statement(), &[role_id, uid])
+            .await
+            .expect("failed to delete role membership");
+    }
+    pub async fn get_all_comm_role_memberships(&self, com_id: &Uuid) -> Vec<RoleMembership> {
+        let result = self
+            .query(RoleMembership::get_all_comm_role_memberships(), &[com_id])
+            .await
+            .expect("failed to fetch community role memberships");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    pub async fn upsert_room_override(&self, room_override: RoomOverride) -> RoomOverride {
+        let result = self
+            .query(
+                RoomOverride::upsert_statement(),
+                &[
+                    &room_override.room_id,
+                    &room_override.role_id,
+                    &room_override.allow.0,
+                    &room_override.deny.0,
+                ],
+            )
+            .await
+            .expect("failed to upsert room override")
+            .pop()
+            .expect("upserting room override returned nothing");
+        result.into()
+    }
+    pub async fn delete_room_override(&self, room_id: &Uuid, role_id: &Uuid) {
+        let _result = self
+            .query(RoomOverride::delete_statement(), &[room_id, role_id])
+            .await
+            .expect("failed to delete room override");
+    }
+    pub async fn get_room_overrides(&self, room_id: &Uuid) -> Vec<RoomOverride> {
+        let result = self
+            .query(RoomOverride::get_room_overrides(), &[room_id])
+            .await
+            .expect("failed to fetch room overrides");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    /// gets the overrides of every room in a community
+    pub async fn get_comm_overrides(&self, com_id: &Uuid) -> Vec<RoomOverride> {
+        let result = self
+            .query(RoomOverride::get_comm_overrides(), &[com_id])
+            .await
+            .expect("failed to fetch community room overrides");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+}
@@ -32,6 +32,33 @@ impl Sesh<'_> {
             .expect("creating room returned nothing");
         result.into()
     }
+    /// returns none if the two users already have a direct message
+    pub async fn create_dm(&self, room: Room) -> Option<Room> {
+        let result = self
+            .query(
+                Room::create_dm_statement(),
+                &[
+                    &room.id,
+                    &room.external_id,
+                    &room.domain,
+                    &room.community,
+                    &room.system_channel,
+                    &room.created,
+                    &room.known_complete,
+                    &room.is_dm,
+                    &room.user_a,
+                    &room.user_b,
+                    &room.info.name,
+                    &room.info.description,
+                    &room.info.category,
+                    &room.info.display_order,
+                ],
+            )
+            .await
+            .expect("failed to create direct message")
+            .pop();
+        result.map(|x| x.into())
+    }
     pub async fn get_room(&self, room_id: &Uuid) -> Option<Room> {
         let result = self
             .query(Room::read_statement(), &[room_id])
@@ -75,4 +102,35 @@ impl Sesh<'_> {
             .expect("failed to fetch community rooms");
         result.into_iter().map(|x| x.into()).collect()
     }
+    pub async fn get_system_channel(&self, com_id: &Uuid) -> Option<Room> {
+        let result = self
+            .query(Room::get_system_channel(), &[com_id])
+            .await
+            .expect("failed to fetch system channel")
+            .pop();
+        result.map(|x| x.into())
+    }
+    /// get the direct message between two users if it exists
+    pub async fn get_dm(&self, user_a: &Uuid, user_b: &Uuid) -> Option<Room> {
+        let result = self
+            .query(Room::get_dm(), &[user_a, user_b])
+            .await
+            .expect("failed to fetch direct message")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn get_all_user_dms(&self, uid: &Uuid) -> Vec<Room> {
+        let result = self
+            .query(Room::get_all_user_dms(), &[uid])
+            .await
+            .expect("failed to fetch user direct messages");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    pub async fn get_all_user_group_chats(&self, uid: &Uuid) -> Vec<Room> {
+        let result = self
+            .query(Room::get_all_user_group_chats(), &[uid])
+            .await
+            .expect("failed to fetch user group chats");
+        result.into_iter().map(|x| x.into()).collect()
+    }
 }
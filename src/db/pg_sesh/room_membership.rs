@@ -0,0 +1,65 @@
+use uuid::Uuid;
+
+use crate::db::{
+    pg_sesh::Sesh,
+    types::{
+        comm::permissions::Permissions, room::Room, room_membership::RoomMembership, user::DbUser,
+    },
+};
+
+#[allow(dead_code)]
+impl Sesh<'_> {
+    pub async fn create_room_membership(&self, membership: RoomMembership) -> RoomMembership {
+        let result = self
+            .query(
+                RoomMembership::create_statement(),
+                &[&membership.room_id, &membership.uid, &membership.joined],
+            )
+            .await
+            .expect("failed to create room membership")
+            .pop()
+            .expect("creating room membership returned nothing");
+        result.into()
+    }
+    pub async fn get_room_membership(&self, room_id: &Uuid, uid: &Uuid) -> Option<RoomMembership> {
+        let result = self
+            .query(RoomMembership::read_statement(), &[room_id, uid])
+            .await
+            .expect("failed to fetch room membership")
+            .pop();
+        result.map(|x| x.into())
+    }
+    pub async fn delete_room_membership(&self, room_id: &Uuid, uid: &Uuid) {
+        let _result = self
+            .query(RoomMembership::delete_statement(), &[room_id, uid])
+            .await
+            .expect("failed to delete room membership");
+    }
+    pub async fn get_all_room_members(&self, room_id: &Uuid) -> Vec<RoomMembership> {
+        let result = self
+            .query(RoomMembership::get_all_room_members(), &[room_id])
+            .await
+            .expect("failed to fetch room members");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    pub async fn get_all_room_users(&self, room_id: &Uuid) -> Vec<DbUser> {
+        let result = self
+            .query(RoomMembership::get_all_room_users(), &[room_id])
+            .await
+            .expect("failed to fetch room users");
+        result.into_iter().map(|x| x.into()).collect()
+    }
+    /// checks if a user is a member of a room, either through the community the
+    /// room is a part of or through a room membership for dms and group chats
+    pub async fn is_room_member(&self, room: &Room, uid: &Uuid) -> bool {
+        match room.community {
+            Some(com_id) => self.get_comm_membership(&com_id, uid).await.is_some(),
+            None => self.get_room_membership(&room.id, uid).await.is_some(),
+        }
+    }
+    /// get the ids of every user that is able to view the given room
+    pub async fn get_room_member_ids(&self, room: &Room) -> Vec<Uuid> {
+        self.get_room_members_with(room, Permissions::VIEW_ROOMS)
+            .await
+    }
+}
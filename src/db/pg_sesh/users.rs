@@ -100,4 +100,12 @@ impl Sesh<'_> {
             .pop();
         result.is_some()
     }
+    /// get the ids of every user sharing a community or a room with the user
+    pub async fn get_related_user_ids(&self, uid: &Uuid) -> Vec<Uuid> {
+        let result = self
+            .query(DbUser::related_users_statement(), &[uid])
+            .await
+            .expect("failed to fetch related users");
+        result.into_iter().map(|x| x.get("uid")).collect()
+    }
 }
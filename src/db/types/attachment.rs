@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// a previously uploaded file to attach to a new message
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewAttachment {
+    pub file: Uuid,
+    /// alt text for the attachment
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Attachment {
+    pub m_id: Uuid,
+    pub file_id: Uuid,
+    pub position: i64,
+    pub description: Option<String>,
+}
+
+impl From<tokio_postgres::Row> for Attachment {
+    fn from(row: tokio_postgres::Row) -> Self {
+        Attachment {
+            m_id: row.get("m_id"),
+            file_id: row.get("file_id"),
+            position: row.get("position"),
+            description: row.get("description"),
+        }
+    }
+}
+
+impl Attachment {
+    /// params:
+    /// - $1: m_id
+    /// - $2: file_id
+    /// - $3: position
+    /// - $4: description
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO message_attachments
+        (m_id, file_id, position, description)
+        VALUES
+        ($1, $2, $3, $4)
+        RETURNING *;
+        "#
+    }
+    /// gets every room a file is attached to a message in
+    pub const fn get_file_rooms() -> &'static str {
+        r#"
+        SELECT DISTINCT room_id FROM message_attachments
+        INNER JOIN messages USING (m_id)
+        WHERE file_id = $1;
+        "#
+    }
+}
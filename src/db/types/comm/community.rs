@@ -12,6 +12,8 @@ pub struct DbCommunity {
     pub info: Communityinfo,
     pub created: i64,
     pub owner: Uuid,
+    /// when a community from another instance was last fetched
+    pub fetched_at: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -32,6 +34,7 @@ impl From<tokio_postgres::Row> for DbCommunity {
             },
             created: row.get("created"),
             owner: row.get("owner"),
+            fetched_at: row.get("fetched_at"),
         }
     }
 }
@@ -47,11 +50,12 @@ impl DbCommunity {
             name,
             description,
             created,
-            owner
+            owner,
+            fetched_at
         )
         VALUES
         (
-            $1, $2, $3, $4, $5, $6, $7
+            $1, $2, $3, $4, $5, $6, $7, $8
         )
         RETURNING *;
         "#
@@ -69,11 +73,19 @@ impl DbCommunity {
         owner = $3,
         name = $4,
         description = $5,
-
-        WHERE com_id = $6
+        fetched_at = $6
+        WHERE com_id = $7
         RETURNING *;
         "#
     }
+    /// params:
+    /// - $1: external_id
+    /// - $2: domain
+    pub const fn read_external_statement() -> &'static str {
+        r#"
+        SELECT * FROM communities WHERE external_id = $1 AND domain = $2;
+        "#
+    }
     pub const fn delete_statement() -> &'static str {
         r#"
         DELETE FROM communities WHERE com_id = $1;
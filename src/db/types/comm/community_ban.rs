@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommunityBan {
+    pub com_id: Uuid,
+    pub uid: Uuid,
+    pub banned_by: Option<Uuid>,
+    /// the position of the highest role of whoever issued the ban,
+    /// only members at least as high may lift it
+    pub banned_position: i64,
+    pub reason: Option<String>,
+    pub created: i64,
+}
+
+impl From<tokio_postgres::Row> for CommunityBan {
+    fn from(row: tokio_postgres::Row) -> Self {
+        CommunityBan {
+            com_id: row.get("com_id"),
+            uid: row.get("uid"),
+            banned_by: row.get("banned_by"),
+            banned_position: row.get("banned_position"),
+            reason: row.get("reason"),
+            created: row.get("created"),
+        }
+    }
+}
+
+impl CommunityBan {
+    /// params:
+    /// - $1: com_id
+    /// - $2: uid
+    /// - $3: banned_by
+    /// - $4: reason
+    /// - $5: created
+    /// - $6: banned_position
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO community_bans
+        (com_id, uid, banned_by, reason, created, banned_position)
+        VALUES
+        ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (com_id, uid) DO UPDATE SET
+        banned_by = EXCLUDED.banned_by,
+        reason = EXCLUDED.reason,
+        banned_position = EXCLUDED.banned_position
+        RETURNING *;
+        "#
+    }
+    /// params:
+    /// - $1: com_id
+    /// - $2: uid
+    pub const fn read_statement() -> &'static str {
+        r#"
+        SELECT * FROM community_bans WHERE com_id = $1 AND uid = $2;
+        "#
+    }
+    /// params:
+    /// - $1: com_id
+    /// - $2: uid
+    pub const fn delete_statement() -> &'static str {
+        r#"
+        DELETE FROM community_bans WHERE com_id = $1 AND uid = $2;
+        "#
+    }
+    pub const fn get_all_comm_bans() -> &'static str {
+        r#"
+        SELECT * FROM community_bans WHERE com_id = $1 ORDER BY created DESC;
+        "#
+    }
+}
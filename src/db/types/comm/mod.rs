@@ -1,2 +1,7 @@
 pub mod community;
+pub mod community_ban;
 pub mod community_membership;
+pub mod permissions;
+pub mod role;
+pub mod role_membership;
+pub mod room_override;
@@ -0,0 +1,274 @@
+//! permissions are stored as a bitset on roles and room overrides.
+//!
+//! a member's permissions in a community are the union of the default
+//! role and every role they have been assigned. the owner and members
+//! with [`Permissions::ADMINISTRATOR`] always have every permission.
+//!
+//! permissions in a room are then resolved from the community permissions
+//! by applying the room's overrides, first the default role's override and
+//! then the combined overrides of the member's roles. in both cases deny is
+//! applied before allow
+
+use std::ops::{BitAnd, BitOr, Not};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{role::Role, room_override::RoomOverride};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(transparent)]
+pub struct Permissions(pub i64);
+
+impl Permissions {
+    pub const NONE: Permissions = Permissions(0);
+    pub const VIEW_ROOMS: Permissions = Permissions(1 << 0);
+    pub const SEND_MESSAGES: Permissions = Permissions(1 << 1);
+    /// edit and delete the messages of others and pin messages
+    pub const MANAGE_MESSAGES: Permissions = Permissions(1 << 2);
+    pub const MENTION_EVERYONE: Permissions = Permissions(1 << 3);
+    pub const ADD_REACTIONS: Permissions = Permissions(1 << 4);
+    pub const ATTACH_FILES: Permissions = Permissions(1 << 5);
+    pub const CREATE_INVITES: Permissions = Permissions(1 << 6);
+    /// create rooms and edit room overrides
+    pub const MANAGE_ROOMS: Permissions = Permissions(1 << 7);
+    /// create, edit and assign roles lower than the member's highest role
+    pub const MANAGE_ROLES: Permissions = Permissions(1 << 8);
+    /// edit the community and manage its invites
+    pub const MANAGE_COMMUNITY: Permissions = Permissions(1 << 9);
+    pub const KICK_MEMBERS: Permissions = Permissions(1 << 10);
+    pub const BAN_MEMBERS: Permissions = Permissions(1 << 11);
+    /// grants every permission and bypasses room overrides
+    pub const ADMINISTRATOR: Permissions = Permissions(1 << 12);
+
+    pub const ALL: Permissions = Permissions((1 << 13) - 1);
+    /// permissions given to the default role of new communities
+    pub const DEFAULT: Permissions = Permissions(
+        Self::VIEW_ROOMS.0
+            | Self::SEND_MESSAGES.0
+            | Self::ADD_REACTIONS.0
+            | Self::ATTACH_FILES.0
+            | Self::CREATE_INVITES.0,
+    );
+    /// permissions members of a dm or group chat have in it
+    pub const DIRECT: Permissions = Permissions(
+        Self::VIEW_ROOMS.0 | Self::SEND_MESSAGES.0 | Self::ADD_REACTIONS.0 | Self::ATTACH_FILES.0,
+    );
+
+    /// true if all permissions in other are present
+    pub fn contains(&self, other: Permissions) -> bool {
+        self.0 & other.0 == other.0
+    }
+    /// drop any bits that are not a known permission
+    pub fn sanitized(self) -> Permissions {
+        self & Self::ALL
+    }
+}
+
+impl BitOr for Permissions {
+    type Output = Permissions;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Permissions(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for Permissions {
+    type Output = Permissions;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Permissions(self.0 & rhs.0)
+    }
+}
+
+impl Not for Permissions {
+    type Output = Permissions;
+
+    fn not(self) -> Self::Output {
+        Permissions(!self.0)
+    }
+}
+
+/// resolve the permissions of a member in the community from the default role
+/// and the roles they have been assigned, does not account for ownership
+pub fn resolve_community(default_role: &Role, roles: &[&Role]) -> Permissions {
+    let permissions = roles.iter().fold(default_role.info.permissions, |acc, x| {
+        acc | x.info.permissions
+    });
+    match permissions.contains(Permissions::ADMINISTRATOR) {
+        true => Permissions::ALL,
+        false => permissions,
+    }
+}
+
+/// apply a room's overrides to a member's community permissions
+/// - `default_role` the id of the community's default role
+/// - `roles` the ids of the roles assigned to the member
+/// - `overrides` the overrides of the room, overrides of
+///   other rooms must not be passed
+pub fn resolve_room(
+    base: Permissions,
+    default_role: Uuid,
+    roles: &[Uuid],
+    overrides: &[RoomOverride],
+) -> Permissions {
+    if base.contains(Permissions::ADMINISTRATOR) {
+        return Permissions::ALL;
+    }
+    let mut permissions = base;
+    if let Some(default) = overrides.iter().find(|x| x.role_id == default_role) {
+        permissions = (permissions & !default.deny) | default.allow;
+    }
+    let (allow, deny) = overrides
+        .iter()
+        .filter(|x| roles.contains(&x.role_id))
+        .fold(
+            (Permissions::NONE, Permissions::NONE),
+            |(allow, deny), x| (allow | x.allow, deny | x.deny),
+        );
+    (permissions & !deny) | allow
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::types::comm::role::RoleInfo;
+
+    fn role(permissions: Permissions, position: i64, is_default: bool) -> Role {
+        Role {
+            id: Uuid::now_v7(),
+            com_id: Uuid::nil(),
+            is_default,
+            created: 0,
+            info: RoleInfo {
+                name: "role".to_string(),
+                permissions,
+                position,
+            },
+        }
+    }
+
+    fn room_override(role: &Role, allow: Permissions, deny: Permissions) -> RoomOverride {
+        RoomOverride {
+            room_id: Uuid::nil(),
+            role_id: role.id,
+            allow,
+            deny,
+        }
+    }
+
+    #[test]
+    fn contains_requires_every_bit() {
+        let permissions = Permissions::VIEW_ROOMS | Permissions::SEND_MESSAGES;
+        assert!(permissions.contains(Permissions::VIEW_ROOMS));
+        assert!(permissions.contains(Permissions::VIEW_ROOMS | Permissions::SEND_MESSAGES));
+        assert!(!permissions.contains(Permissions::VIEW_ROOMS | Permissions::MANAGE_ROLES));
+        assert!(permissions.contains(Permissions::NONE));
+    }
+
+    #[test]
+    fn sanitized_drops_unknown_bits() {
+        let permissions = Permissions(-1).sanitized();
+        assert_eq!(permissions, Permissions::ALL);
+        assert_eq!(Permissions(1 << 40).sanitized(), Permissions::NONE);
+    }
+
+    #[test]
+    fn community_permissions_are_the_union_of_roles() {
+        let default = role(Permissions::DEFAULT, 0, true);
+        let moderator = role(Permissions::KICK_MEMBERS, 2, false);
+        let pinner = role(Permissions::MANAGE_MESSAGES, 1, false);
+        assert_eq!(resolve_community(&default, &[]), Permissions::DEFAULT);
+        assert_eq!(
+            resolve_community(&default, &[&moderator, &pinner]),
+            Permissions::DEFAULT | Permissions::KICK_MEMBERS | Permissions::MANAGE_MESSAGES
+        );
+    }
+
+    #[test]
+    fn administrator_grants_everything() {
+        let default = role(Permissions::NONE, 0, true);
+        let admin = role(Permissions::ADMINISTRATOR, 1, false);
+        assert_eq!(resolve_community(&default, &[&admin]), Permissions::ALL);
+
+        let everyone_admin = role(Permissions::ADMINISTRATOR, 0, true);
+        assert_eq!(resolve_community(&everyone_admin, &[]), Permissions::ALL);
+    }
+
+    #[test]
+    fn room_without_overrides_keeps_community_permissions() {
+        let default = role(Permissions::DEFAULT, 0, true);
+        assert_eq!(
+            resolve_room(Permissions::DEFAULT, default.id, &[], &[]),
+            Permissions::DEFAULT
+        );
+    }
+
+    #[test]
+    fn default_override_applies_deny_then_allow() {
+        let default = role(Permissions::DEFAULT, 0, true);
+        let overrides = [room_override(
+            &default,
+            Permissions::SEND_MESSAGES,
+            Permissions::SEND_MESSAGES | Permissions::VIEW_ROOMS,
+        )];
+        let resolved = resolve_room(Permissions::DEFAULT, default.id, &[], &overrides);
+        assert!(!resolved.contains(Permissions::VIEW_ROOMS));
+        // allow wins over deny within the same override
+        assert!(resolved.contains(Permissions::SEND_MESSAGES));
+    }
+
+    #[test]
+    fn role_overrides_apply_after_the_default_override() {
+        let default = role(Permissions::DEFAULT, 0, true);
+        let member = role(Permissions::NONE, 1, false);
+        let overrides = [
+            room_override(&default, Permissions::NONE, Permissions::VIEW_ROOMS),
+            room_override(&member, Permissions::VIEW_ROOMS, Permissions::NONE),
+        ];
+        let without = resolve_room(Permissions::DEFAULT, default.id, &[], &overrides);
+        assert!(!without.contains(Permissions::VIEW_ROOMS));
+        let with = resolve_room(Permissions::DEFAULT, default.id, &[member.id], &overrides);
+        assert!(with.contains(Permissions::VIEW_ROOMS));
+    }
+
+    #[test]
+    fn role_overrides_combine_before_applying() {
+        let default = role(Permissions::DEFAULT, 0, true);
+        let allows = role(Permissions::NONE, 1, false);
+        let denies = role(Permissions::NONE, 2, false);
+        let overrides = [
+            room_override(&allows, Permissions::MANAGE_MESSAGES, Permissions::NONE),
+            room_override(&denies, Permissions::NONE, Permissions::MANAGE_MESSAGES),
+        ];
+        // deny of one role is applied before the allow of another
+        let resolved = resolve_room(
+            Permissions::DEFAULT,
+            default.id,
+            &[allows.id, denies.id],
+            &overrides,
+        );
+        assert!(resolved.contains(Permissions::MANAGE_MESSAGES));
+    }
+
+    #[test]
+    fn overrides_of_roles_not_held_are_ignored() {
+        let default = role(Permissions::DEFAULT, 0, true);
+        let other = role(Permissions::NONE, 1, false);
+        let overrides = [room_override(&other, Permissions::ALL, Permissions::NONE)];
+        assert_eq!(
+            resolve_room(Permissions::DEFAULT, default.id, &[], &overrides),
+            Permissions::DEFAULT
+        );
+    }
+
+    #[test]
+    fn administrator_bypasses_room_overrides() {
+        let default = role(Permissions::DEFAULT, 0, true);
+        let overrides = [room_override(&default, Permissions::NONE, Permissions::ALL)];
+        assert_eq!(
+            resolve_room(Permissions::ALL, default.id, &[], &overrides),
+            Permissions::ALL
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::permissions::Permissions;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Role {
+    pub id: Uuid,
+    pub com_id: Uuid,
+    /// the default role is held by every member of the
+    /// community and may not be deleted or assigned
+    pub is_default: bool,
+    pub created: i64,
+    pub info: RoleInfo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoleInfo {
+    pub name: String,
+    pub permissions: Permissions,
+    /// roles are ordered from lowest to highest, members may only
+    /// manage roles lower than their own highest role
+    pub position: i64,
+}
+
+impl From<tokio_postgres::Row> for Role {
+    fn from(row: tokio_postgres::Row) -> Self {
+        Role {
+            id: row.get("role_id"),
+            com_id: row.get("com_id"),
+            is_default: row.get("is_default"),
+            created: row.get("created"),
+            info: RoleInfo {
+                name: row.get("name"),
+                permissions: Permissions(row.get("permissions")),
+                position: row.get("position"),
+            },
+        }
+    }
+}
+
+impl Role {
+    /// params:
+    /// - $1: role_id
+    /// - $2: com_id
+    /// - $3: name
+    /// - $4: permissions
+    /// - $5: position
+    /// - $6: is_default
+    /// - $7: created
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO roles
+        (
+            role_id,
+            com_id,
+            name,
+            permissions,
+            position,
+            is_default,
+            created
+        )
+        VALUES
+        (
+            $1, $2, $3, $4, $5, $6, $7
+        )
+        RETURNING *;
+        "#
+    }
+    pub const fn read_statement() -> &'static str {
+        r#"
+        SELECT * FROM roles WHERE role_id = $1;
+        "#
+    }
+    pub const fn update_statement() -> &'static str {
+        r#"
+        UPDATE roles SET
+        name = $1,
+        permissions = $2,
+        position = $3
+        WHERE role_id = $4
+        RETURNING *;
+        "#
+    }
+    pub const fn delete_statement() -> &'static str {
+        r#"
+        DELETE FROM roles WHERE role_id = $1;
+        "#
+    }
+    pub const fn get_all_comm_roles() -> &'static str {
+        r#"
+        SELECT * FROM roles WHERE com_id = $1 ORDER BY position DESC;
+        "#
+    }
+    pub const fn get_default_role() -> &'static str {
+        r#"
+        SELECT * FROM roles WHERE com_id = $1 AND is_default = true;
+        "#
+    }
+    /// params:
+    /// - $1: com_id
+    /// - $2: uid
+    ///
+    /// does not include the default role
+    pub const fn get_member_roles() -> &'static str {
+        r#"
+        SELECT roles.* FROM role_membership INNER JOIN roles USING (role_id, com_id)
+        WHERE com_id = $1 AND uid = $2;
+        "#
+    }
+}
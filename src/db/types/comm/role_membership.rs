@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoleMembership {
+    pub com_id: Uuid,
+    pub uid: Uuid,
+    pub role_id: Uuid,
+}
+
+impl From<tokio_postgres::Row> for RoleMembership {
+    fn from(row: tokio_postgres::Row) -> Self {
+        RoleMembership {
+            com_id: row.get("com_id"),
+            uid: row.get("uid"),
+            role_id: row.get("role_id"),
+        }
+    }
+}
+
+impl RoleMembership {
+    /// params:
+    /// - $1: com_id
+    /// - $2: uid
+    /// - $3: role_id
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO role_membership
+        (com_id, uid, role_id)
+        VALUES
+        ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+        RETURNING *;
+        "#
+    }
+    /// params:
+    /// - $1: role_id
+    /// - $2: uid
+    pub const fn delete_statement() -> &'static str {
+        r#"
+        DELETE FROM role_membership WHERE role_id = $1 AND uid = $2;
+        "#
+    }
+    pub const fn get_all_comm_role_memberships() -> &'static str {
+        r#"
+        SELECT * FROM role_membership WHERE com_id = $1;
+        "#
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::permissions::Permissions;
+
+/// changes the permissions a role grants in a specific room.
+/// deny is applied before allow
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoomOverride {
+    pub room_id: Uuid,
+    pub role_id: Uuid,
+    pub allow: Permissions,
+    pub deny: Permissions,
+}
+
+impl From<tokio_postgres::Row> for RoomOverride {
+    fn from(row: tokio_postgres::Row) -> Self {
+        RoomOverride {
+            room_id: row.get("room_id"),
+            role_id: row.get("role_id"),
+            allow: Permissions(row.get("allow")),
+            deny: Permissions(row.get("deny")),
+        }
+    }
+}
+
+impl RoomOverride {
+    /// params:
+    /// - $1: room_id
+    /// - $2: role_id
+    /// - $3: allow
+    /// - $4: deny
+    pub const fn upsert_statement() -> &'static str {
+        r#"
+        INSERT INTO room_permission_overrides
+        (room_id, role_id, allow, deny)
+        VALUES
+        ($1, $2, $3, $4)
+        ON CONFLICT (room_id, role_id) DO UPDATE SET
+        allow = EXCLUDED.allow,
+        deny = EXCLUDED.deny
+        RETURNING *;
+        "#
+    }
+    /// params:
+    /// - $1: room_id
+    /// - $2: role_id
+    pub const fn delete_statement() -> &'static str {
+        r#"
+        DELETE FROM room_permission_overrides WHERE room_id = $1 AND role_id = $2;
+        "#
+    }
+    pub const fn get_room_overrides() -> &'static str {
+        r#"
+        SELECT * FROM room_permission_overrides WHERE room_id = $1;
+        "#
+    }
+    pub const fn get_comm_overrides() -> &'static str {
+        r#"
+        SELECT room_permission_overrides.* FROM room_permission_overrides
+        INNER JOIN rooms USING (room_id) WHERE community = $1;
+        "#
+    }
+}
@@ -1 +1,93 @@
-//! todo when implementing files and finishing custom emoji
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CustomEmoji {
+    pub id: Uuid,
+    pub pack_id: Uuid,
+    pub shortcode: String,
+    /// only present for our own emoji
+    pub file_id: Option<Uuid>,
+    /// where the media of emoji from other websites is located
+    pub url: Option<String>,
+    pub media_type: String,
+    pub description: Option<String>,
+}
+
+impl From<tokio_postgres::Row> for CustomEmoji {
+    fn from(row: tokio_postgres::Row) -> Self {
+        CustomEmoji {
+            id: row.get("emoji_id"),
+            pack_id: row.get("emoji_pack_id"),
+            shortcode: row.get("shortcode"),
+            file_id: row.get("file_id"),
+            url: row.get("url"),
+            media_type: row.get("media_type"),
+            description: row.get("description"),
+        }
+    }
+}
+
+impl CustomEmoji {
+    /// params:
+    /// - $1: emoji_id
+    /// - $2: emoji_pack_id
+    /// - $3: shortcode
+    /// - $4: file_id
+    /// - $5: url
+    /// - $6: media_type
+    /// - $7: description
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO custom_emoji
+        (emoji_id, emoji_pack_id, shortcode, file_id, url, media_type, description)
+        VALUES
+        ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *;
+        "#
+    }
+    /// insert an emoji of a pack from another website or update the one
+    /// with the same shortcode, the params are the same as [`Self::create_statement`]
+    pub const fn upsert_remote_statement() -> &'static str {
+        r#"
+        INSERT INTO custom_emoji
+        (emoji_id, emoji_pack_id, shortcode, file_id, url, media_type, description)
+        VALUES
+        ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (emoji_pack_id, shortcode) DO UPDATE SET
+        url = EXCLUDED.url,
+        media_type = EXCLUDED.media_type,
+        description = EXCLUDED.description
+        RETURNING *;
+        "#
+    }
+    pub const fn read_statement() -> &'static str {
+        r#"
+        SELECT * FROM custom_emoji WHERE emoji_id = $1;
+        "#
+    }
+    /// params:
+    /// - $1: emoji_pack_id
+    /// - $2: shortcode
+    pub const fn read_shortcode_statement() -> &'static str {
+        r#"
+        SELECT * FROM custom_emoji WHERE emoji_pack_id = $1 AND shortcode = $2;
+        "#
+    }
+    pub const fn get_pack_statement() -> &'static str {
+        r#"
+        SELECT * FROM custom_emoji WHERE emoji_pack_id = $1 ORDER BY shortcode;
+        "#
+    }
+    /// remove the emoji of a pack that aren't in the given shortcodes
+    ///
+    /// params:
+    /// - $1: emoji_pack_id
+    /// - $2: the shortcodes to keep
+    pub const fn prune_pack_statement() -> &'static str {
+        r#"
+        DELETE FROM custom_emoji
+        WHERE emoji_pack_id = $1 AND NOT (shortcode = ANY($2));
+        "#
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmojiPack {
+    pub id: Uuid,
+    /// the id of the pack on its website, our own packs use their id
+    pub external_id: Uuid,
+    /// none for packs hosted by static sites
+    pub domain: Option<String>,
+    /// the domain of the website hosting the pack
+    pub website: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub author_url: Option<String>,
+    pub license: Option<String>,
+    /// the shortcode of the emoji the pack is previewed with,
+    /// none until the pack has emoji
+    pub highlight_emoji: Option<String>,
+    pub last_updated: i64,
+    /// when a pack from another website was last fetched
+    pub fetched_at: Option<i64>,
+    /// the user that created one of our own packs
+    pub owner: Option<Uuid>,
+}
+
+/// the details of one of our own packs set by its owner
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmojiPackInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub author_url: Option<String>,
+    pub license: Option<String>,
+}
+
+impl From<tokio_postgres::Row> for EmojiPack {
+    fn from(row: tokio_postgres::Row) -> Self {
+        let external_id: String = row.get("emoji_pack_external_id");
+        EmojiPack {
+            id: row.get("emoji_pack_id"),
+            external_id: external_id.parse().unwrap_or_default(),
+            domain: row.get("domain"),
+            website: row.get("website"),
+            name: row.get("name"),
+            description: row.get("description"),
+            author_url: row.get("author_url"),
+            license: row.get("license"),
+            highlight_emoji: row.get("highlight_emoji"),
+            last_updated: row.get("last_updated"),
+            fetched_at: row.get("fetched_at"),
+            owner: row.get("uid"),
+        }
+    }
+}
+
+impl EmojiPack {
+    /// params:
+    /// - $1: emoji_pack_id
+    /// - $2: emoji_pack_external_id
+    /// - $3: domain
+    /// - $4: website
+    /// - $5: name
+    /// - $6: description
+    /// - $7: author_url
+    /// - $8: license
+    /// - $9: highlight_emoji
+    /// - $10: last_updated
+    /// - $11: fetched_at
+    /// - $12: uid
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO emoji_packs
+        (emoji_pack_id, emoji_pack_external_id, domain, website, name, description,
+        author_url, license, highlight_emoji, last_updated, fetched_at, uid)
+        VALUES
+        ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        RETURNING *;
+        "#
+    }
+    pub const fn read_statement() -> &'static str {
+        r#"
+        SELECT * FROM emoji_packs WHERE emoji_pack_id = $1;
+        "#
+    }
+    /// params:
+    /// - $1: emoji_pack_external_id
+    /// - $2: website
+    pub const fn read_external_statement() -> &'static str {
+        r#"
+        SELECT * FROM emoji_packs
+        WHERE emoji_pack_external_id = $1 AND website = $2;
+        "#
+    }
+    /// params:
+    /// - $1: name
+    /// - $2: description
+    /// - $3: author_url
+    /// - $4: license
+    /// - $5: highlight_emoji
+    /// - $6: last_updated
+    /// - $7: fetched_at
+    /// - $8: emoji_pack_id
+    pub const fn update_statement() -> &'static str {
+        r#"
+        UPDATE emoji_packs SET
+        name = $1,
+        description = $2,
+        author_url = $3,
+        license = $4,
+        highlight_emoji = $5,
+        last_updated = $6,
+        fetched_at = $7
+        WHERE emoji_pack_id = $8
+        RETURNING *;
+        "#
+    }
+}
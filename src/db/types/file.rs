@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DbFile {
+    pub id: Uuid,
+    /// none if the file is not owned by a user
+    pub owner: Option<Uuid>,
+    pub description: Option<String>,
+    /// path of the file within the storage backend
+    pub path: String,
+    pub mime: String,
+    /// size in bytes
+    pub size: i64,
+    /// the name of the file when it was uploaded
+    pub file_name: Option<String>,
+    pub created: i64,
+    /// dimensions in pixels for images and videos
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    /// placeholder for images while they load, see <https://blurha.sh>
+    pub blurhash: Option<String>,
+}
+
+impl From<tokio_postgres::Row> for DbFile {
+    fn from(row: tokio_postgres::Row) -> Self {
+        DbFile {
+            id: row.get("file_id"),
+            owner: row.get("uid"),
+            description: row.get("description"),
+            path: row.get("path"),
+            mime: row.get("mime"),
+            size: row.get("size"),
+            file_name: row.get("file_name"),
+            created: row.get("created"),
+            width: row.get("width"),
+            height: row.get("height"),
+            blurhash: row.get("blurhash"),
+        }
+    }
+}
+
+impl DbFile {
+    /// params:
+    /// - $1: file_id
+    /// - $2: uid
+    /// - $3: description
+    /// - $4: path
+    /// - $5: mime
+    /// - $6: size
+    /// - $7: file_name
+    /// - $8: created
+    /// - $9: width
+    /// - $10: height
+    /// - $11: blurhash
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO files
+        (file_id, uid, description, path, mime, size, file_name, created, width, height, blurhash)
+        VALUES
+        ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        RETURNING *;
+        "#
+    }
+    pub const fn read_statement() -> &'static str {
+        r#"
+        SELECT * FROM files WHERE file_id = $1;
+        "#
+    }
+    pub const fn delete_statement() -> &'static str {
+        r#"
+        DELETE FROM files WHERE file_id = $1;
+        "#
+    }
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// a resized copy of an image file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileThumbnail {
+    pub file_id: Uuid,
+    /// the longest edge the thumbnail was scaled to fit within
+    pub size: i64,
+    /// path of the thumbnail within the storage backend
+    pub path: String,
+    pub mime: String,
+    pub width: i64,
+    pub height: i64,
+    /// size in bytes
+    pub file_size: i64,
+}
+
+impl From<tokio_postgres::Row> for FileThumbnail {
+    fn from(row: tokio_postgres::Row) -> Self {
+        FileThumbnail {
+            file_id: row.get("file_id"),
+            size: row.get("size"),
+            path: row.get("path"),
+            mime: row.get("mime"),
+            width: row.get("width"),
+            height: row.get("height"),
+            file_size: row.get("file_size"),
+        }
+    }
+}
+
+impl FileThumbnail {
+    /// params:
+    /// - $1: file_id
+    /// - $2: size
+    /// - $3: path
+    /// - $4: mime
+    /// - $5: width
+    /// - $6: height
+    /// - $7: file_size
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO file_thumbnails
+        (file_id, size, path, mime, width, height, file_size)
+        VALUES
+        ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *;
+        "#
+    }
+    /// params:
+    /// - $1: file_id
+    pub const fn get_all_statement() -> &'static str {
+        r#"
+        SELECT * FROM file_thumbnails WHERE file_id = $1 ORDER BY size ASC;
+        "#
+    }
+    /// get the smallest thumbnail at least as large as the requested size
+    ///
+    /// params:
+    /// - $1: file_id
+    /// - $2: size
+    pub const fn read_fitting_statement() -> &'static str {
+        r#"
+        SELECT * FROM file_thumbnails WHERE file_id = $1 AND size >= $2
+        ORDER BY size ASC LIMIT 1;
+        "#
+    }
+}
@@ -1,9 +1,23 @@
+use std::str::FromStr;
+
+use crate::cryptography::keys::SigningAlgorithm;
+
 pub struct Instance {
     pub domain: String,
     pub is_authoratative: bool,
     pub blocked: bool,
     pub reason: Option<String>,
     pub allowlisted: bool,
+    pub key: Option<InstanceKey>,
+}
+
+/// the pem encoded signing key of an instance
+pub struct InstanceKey {
+    pub algorithm: SigningAlgorithm,
+    pub public_key: String,
+    /// only present for our own instance
+    pub private_key: Option<String>,
+    pub fetched_at: Option<i64>,
 }
 
 impl From<tokio_postgres::Row> for Instance {
@@ -14,11 +28,30 @@ impl From<tokio_postgres::Row> for Instance {
             blocked: row.get("blocked"),
             reason: row.get("reason"),
             allowlisted: row.get("allowlisted"),
+            key: InstanceKey::maybe_from_row(&row),
         }
     }
 }
 
+impl InstanceKey {
+    fn maybe_from_row(row: &tokio_postgres::Row) -> Option<Self> {
+        let algorithm: Option<&str> = row.get("key_algorithm");
+        let public_key: Option<String> = row.get("public_key");
+        Some(InstanceKey {
+            algorithm: SigningAlgorithm::from_str(algorithm?).ok()?,
+            public_key: public_key?,
+            private_key: row.get("private_key"),
+            fetched_at: row.get("key_fetched_at"),
+        })
+    }
+}
+
 impl Instance {
+    /// if we federate with the instance. blocked instances never are and
+    /// in allowlist only mode only allowlisted instances are
+    pub fn federates(&self, allowlist_only: bool) -> bool {
+        !self.blocked && (self.allowlisted || !allowlist_only)
+    }
     pub const fn create_statement() -> &'static str {
         r#"
         INSERT INTO instances
@@ -28,11 +61,80 @@ impl Instance {
         RETURNING *;
         "#
     }
+    /// adds a remote instance if it isn't known yet
+    ///
+    /// params:
+    /// - $1: domain
+    pub const fn create_remote_statement() -> &'static str {
+        r#"
+        INSERT INTO instances
+        (domain, is_authoratative, blocked, reason, allowlisted)
+        VALUES
+        ($1, false, false, NULL, false)
+        ON CONFLICT (domain) DO NOTHING;
+        "#
+    }
     pub const fn read_statement() -> &'static str {
         r#"
         SELECT * FROM instances WHERE domain = $1;
         "#
     }
+    /// params:
+    /// - $1: domain
+    /// - $2: key_algorithm
+    /// - $3: public_key
+    /// - $4: private_key
+    /// - $5: key_fetched_at
+    pub const fn set_key_statement() -> &'static str {
+        r#"
+        UPDATE instances SET
+        key_algorithm = $2,
+        public_key = $3,
+        private_key = $4,
+        key_fetched_at = $5
+        WHERE domain = $1
+        RETURNING *;
+        "#
+    }
+    /// set whether an instance is blocked or allowlisted, creating it if it
+    /// isn't known yet. our own instance is left unchanged
+    ///
+    /// params:
+    /// - $1: domain
+    /// - $2: blocked
+    /// - $3: allowlisted
+    /// - $4: reason
+    pub const fn set_policy_statement() -> &'static str {
+        r#"
+        INSERT INTO instances
+        (domain, is_authoratative, blocked, reason, allowlisted)
+        VALUES
+        ($1, false, $2, $4, $3)
+        ON CONFLICT (domain) DO UPDATE SET
+        blocked = EXCLUDED.blocked,
+        allowlisted = EXCLUDED.allowlisted,
+        reason = EXCLUDED.reason
+        WHERE NOT instances.is_authoratative
+        RETURNING *;
+        "#
+    }
+    /// params:
+    /// - $1: domain
+    /// - $2: allowlist_only
+    pub const fn set_allowlist_only_statement() -> &'static str {
+        r#"
+        UPDATE instances SET allowlist_only = $2
+        WHERE domain = $1 AND is_authoratative;
+        "#
+    }
+    /// the other instances that are blocked or allowlisted
+    pub const fn get_policies_statement() -> &'static str {
+        r#"
+        SELECT * FROM instances
+        WHERE NOT is_authoratative AND (blocked OR allowlisted)
+        ORDER BY domain;
+        "#
+    }
     pub const fn update_statement() -> &'static str {
         r#"
         UPDATE instances SET
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+/// a live server event stored in the database because it was too large
+/// to be sent in a notification
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LiveEvent {
+    pub event_id: i64,
+    pub payload: String,
+    pub created: i64,
+}
+
+impl From<tokio_postgres::Row> for LiveEvent {
+    fn from(row: tokio_postgres::Row) -> Self {
+        LiveEvent {
+            event_id: row.get("event_id"),
+            payload: row.get("payload"),
+            created: row.get("created"),
+        }
+    }
+}
+
+impl LiveEvent {
+    /// params:
+    /// - $1: channel
+    /// - $2: payload
+    pub const fn notify_statement() -> &'static str {
+        r#"
+        SELECT pg_notify($1, $2);
+        "#
+    }
+    /// params:
+    /// - $1: payload
+    /// - $2: created
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO live_events
+        (payload, created)
+        VALUES
+        ($1, $2)
+        RETURNING *;
+        "#
+    }
+    /// params:
+    /// - $1: event_id
+    pub const fn read_statement() -> &'static str {
+        r#"
+        SELECT * FROM live_events WHERE event_id = $1;
+        "#
+    }
+    /// params:
+    /// - $1: created
+    pub const fn delete_before_statement() -> &'static str {
+        r#"
+        DELETE FROM live_events WHERE created < $1;
+        "#
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MentionTarget {
+    User(Uuid),
+    Role(Uuid),
+    Everyone,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DbMention {
+    pub m_id: Uuid,
+    /// the order mentions appear in the message
+    pub position: i64,
+    pub target: MentionTarget,
+}
+
+impl From<tokio_postgres::Row> for DbMention {
+    fn from(row: tokio_postgres::Row) -> Self {
+        let uid: Option<Uuid> = row.get("uid");
+        let role_id: Option<Uuid> = row.get("role_id");
+        let target = match (uid, role_id) {
+            (Some(uid), _) => MentionTarget::User(uid),
+            (None, Some(role_id)) => MentionTarget::Role(role_id),
+            (None, None) => MentionTarget::Everyone,
+        };
+        DbMention {
+            m_id: row.get("m_id"),
+            position: row.get("position"),
+            target,
+        }
+    }
+}
+
+impl DbMention {
+    /// params:
+    /// - $1: m_id
+    /// - $2: position
+    /// - $3: uid
+    /// - $4: role_id
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO mentions
+        (m_id, position, uid, role_id)
+        VALUES
+        ($1, $2, $3, $4)
+        RETURNING *;
+        "#
+    }
+    /// params:
+    /// - $1: m_id
+    pub const fn get_all_statement() -> &'static str {
+        r#"
+        SELECT * FROM mentions WHERE m_id = $1 ORDER BY position ASC;
+        "#
+    }
+    /// params:
+    /// - $1: m_id
+    pub const fn delete_all_statement() -> &'static str {
+        r#"
+        DELETE FROM mentions WHERE m_id = $1;
+        "#
+    }
+    /// users mentioned directly or through one of their roles,
+    /// does not include users mentioned by @everyone
+    ///
+    /// params:
+    /// - $1: m_id
+    pub const fn get_mentioned_users_statement() -> &'static str {
+        r#"
+        SELECT uid FROM mentions WHERE m_id = $1 AND uid IS NOT NULL
+        UNION
+        SELECT rm.uid FROM mentions m
+        INNER JOIN role_membership rm ON rm.role_id = m.role_id
+        WHERE m.m_id = $1;
+        "#
+    }
+}
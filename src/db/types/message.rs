@@ -3,10 +3,12 @@ use std::str::FromStr;
 use codes_iso_639::part_1::LanguageCode;
 use const_format::formatcp;
 use serde::{Deserialize, Serialize};
+use tokio_postgres::types::Json;
 use uuid::Uuid;
 
-use crate::routes::api::types::{
-    api_message::ReplyPreview, api_user::ApiUser, proxy_user::ApiProxyUser,
+use crate::{
+    db::types::{attachment::NewAttachment, system_event::SystemEvent},
+    routes::api::types::{api_message::ReplyPreview, api_user::ApiUser, proxy_user::ApiProxyUser},
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
@@ -49,6 +51,8 @@ pub struct DbMessage {
     pub published: i64,
     pub edited: Option<i64>,
     pub fetched_at: Option<i64>,
+    /// only present for messages generated by the server
+    pub system_event: Option<SystemEvent>,
     pub info: Messageinfo,
 }
 
@@ -70,12 +74,20 @@ pub struct Messageinfo {
     pub format: TextFormat,
     pub language: Option<LanguageCode>,
     pub room: Uuid,
+    /// the message that started the thread this message is in, messages in
+    /// threads are left out of the room's messages
+    #[serde(default)]
+    pub thread: Option<Uuid>,
+    /// files to attach when sending a message, these are stored seperately
+    /// so this will be empty for messages read from the database
+    #[serde(default)]
+    pub attachments: Vec<NewAttachment>,
 }
 
 impl From<tokio_postgres::Row> for ReplyPreview {
     fn from(row: tokio_postgres::Row) -> Self {
         let language: Option<&str> = row.get("language");
-        let language = language.map(|x| LanguageCode::from_str(x).ok()).flatten();
+        let language = language.and_then(|x| LanguageCode::from_str(x).ok());
         Self {
             id: row.get("m_id"),
             proxy: ApiProxyUser::maybe_from_row(&row),
@@ -90,7 +102,8 @@ impl From<tokio_postgres::Row> for ReplyPreview {
 impl From<tokio_postgres::Row> for DbMessage {
     fn from(row: tokio_postgres::Row) -> Self {
         let language: Option<&str> = row.get("language");
-        let language = language.map(|x| LanguageCode::from_str(x).ok()).flatten();
+        let language = language.and_then(|x| LanguageCode::from_str(x).ok());
+        let system_event: Option<Json<SystemEvent>> = row.get("system_event");
         DbMessage {
             id: row.get("m_id"),
             external_id: row.get("external_id"),
@@ -99,6 +112,7 @@ impl From<tokio_postgres::Row> for DbMessage {
             published: row.get("published"),
             edited: row.get("edited"),
             fetched_at: row.get("fetched_at"),
+            system_event: system_event.map(|x| x.0),
 
             info: Messageinfo {
                 room: row.get("room_id"),
@@ -108,6 +122,8 @@ impl From<tokio_postgres::Row> for DbMessage {
                 proxy_id: row.get("proxy_id"),
                 format: TextFormat::from_str(row.get("format")).expect("unkown text format in db"),
                 language,
+                thread: row.get("thread_id"),
+                attachments: Vec::new(),
             },
         }
     }
@@ -117,7 +133,14 @@ impl From<tokio_postgres::Row> for DbMessage {
 // r#"SELECT * FROM messages
 //     INNER JOIN users USING (uid, domain) LEFT JOIN proxies USING (uid, proxy_id)"#;
 
-const SELECT_JOINED: &str = r#"SELECT
+/// selects messages as [`crate::routes::api::types::api_message::ApiMessage`] json,
+/// $1 is always the user viewing the messages so reactions can be personalized
+///
+/// languages are stored as their code (`en`) and serialized as the variant name (`En`).
+/// messages from blocked instances, and in allowlist only mode from instances that
+/// aren't allowlisted, are left out, as are previews of replies to them.
+/// attachments have no urls, they're added by [`crate::db::pg_conn::PgConn`]
+pub(super) const SELECT_JOINED: &str = r#"SELECT
 json_build_object(
 	'id', main.m_id,
 	'room', main.room_id,
@@ -169,17 +192,138 @@ json_build_object(
 						)
 					end
 				,
-				'content', prev.content,
-				'format', prev.format,
-				'language', prev.language
+				-- previews are the start of the plain text, kept in sync with
+				-- crate::routes::api::types::api_message::REPLY_PREVIEW_LENGTH
+				'content', CASE
+					when char_length(COALESCE(prev.plain_text, prev.content)) > 200
+						then left(COALESCE(prev.plain_text, prev.content), 199) || '…'
+					else COALESCE(prev.plain_text, prev.content)
+					end,
+				'format', 'Plain',
+				'language', initcap(prev.language)
 			)
 		end,
 	'content', main.content,
 	'format', main.format,
-	'language', main.language
+	'rendered', main.rendered,
+	'plain_text', COALESCE(main.plain_text, main.content),
+	'language', initcap(main.language),
+	'system_event', main.system_event,
+	'thread', main.thread_id,
+	'thread_summary', (
+		SELECT CASE
+			when count(*) = 0 then null
+			else json_build_object(
+				'reply_count', count(*),
+				'last_activity', max(tr.published)
+			)
+			end
+		FROM messages tr
+		WHERE tr.thread_id = main.m_id
+	),
+	'attachments', COALESCE(
+		(
+			SELECT json_agg(
+				json_build_object(
+					'id', f.file_id,
+					'mime', f.mime,
+					'size', f.size,
+					'file_name', f.file_name,
+					'description', a.description,
+					'width', f.width,
+					'height', f.height,
+					'blurhash', f.blurhash,
+					'thumbnails', COALESCE(
+						(
+							SELECT json_agg(
+								json_build_object(
+									'size', t.size,
+									'mime', t.mime,
+									'width', t.width,
+									'height', t.height
+								)
+								ORDER BY t.size
+							)
+							FROM file_thumbnails t
+							WHERE t.file_id = f.file_id
+						),
+						'[]'::json
+					)
+				)
+				ORDER BY a.position
+			)
+			FROM message_attachments a INNER JOIN files f USING (file_id)
+			WHERE a.m_id = main.m_id
+		),
+		'[]'::json
+	),
+	'mentions', COALESCE(
+		(
+			SELECT json_agg(
+				CASE
+					when mu.uid is not null then json_build_object(
+						'User', json_build_object(
+							'id', mu.uid,
+							'domain', mu.domain,
+							'username', mu.username,
+							'display_name', mu.display_name,
+							'summary', mu.summary,
+							'created', mu.created
+						)
+					)
+					when mr.role_id is not null then json_build_object(
+						'Role', json_build_object(
+							'id', mr.role_id,
+							'name', mr.name
+						)
+					)
+					else to_json('Everyone'::text)
+				end
+				ORDER BY mn.position
+			)
+			FROM mentions mn
+				LEFT JOIN users mu ON mu.uid = mn.uid
+				LEFT JOIN roles mr ON mr.role_id = mn.role_id
+			WHERE mn.m_id = main.m_id
+		),
+		'[]'::json
+	),
+	'reactions', COALESCE(
+		(
+			SELECT json_agg(
+				json_build_object(
+					'emoji', r.react_unicode,
+					'emoji_id', r.archive_id,
+					'count', r.count,
+					'me', r.me
+				)
+				ORDER BY r.first
+			)
+			FROM (
+				SELECT
+					react_unicode,
+					archive_id,
+					count(*) AS count,
+					bool_or(uid = $1) AS me,
+					min(published) AS first
+				FROM reactions
+				WHERE m_id = main.m_id
+				GROUP BY react_unicode, archive_id
+			) r
+		),
+		'[]'::json
+	)
 )
 FROM 
-messages main
+(
+    SELECT * FROM messages
+    WHERE domain NOT IN (
+        SELECT domain FROM instances WHERE blocked OR (
+            NOT allowlisted AND NOT is_authoratative
+            AND EXISTS (SELECT 1 FROM instances WHERE is_authoratative AND allowlist_only)
+        )
+    )
+) main
     INNER JOIN 
         users u USING (uid, domain) 
     LEFT JOIN 
@@ -191,7 +335,13 @@ LEFT JOIN
         users USING (uid, domain) 
     LEFT JOIN 
         proxies USING (uid, proxy_id)
-) prev ON main.in_reply_to = prev.m_id"#;
+) prev ON main.in_reply_to = prev.m_id
+    AND prev.domain NOT IN (
+        SELECT domain FROM instances WHERE blocked OR (
+            NOT allowlisted AND NOT is_authoratative
+            AND EXISTS (SELECT 1 FROM instances WHERE is_authoratative AND allowlist_only)
+        )
+    )"#;
 
 impl DbMessage {
     pub const fn create_statement() -> &'static str {
@@ -210,11 +360,18 @@ impl DbMessage {
             in_reply_to,
             content,
             format,
-            language
+            language,
+            system_event,
+            search_config,
+            search,
+            thread_id,
+            rendered,
+            plain_text
         )
         VALUES
         (
-            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14,
+            $15, to_tsvector($15::TEXT::regconfig, $18), $16, $17, $18
         )
         RETURNING *;
         "#
@@ -224,8 +381,35 @@ impl DbMessage {
         SELECT * FROM messages WHERE m_id = $1;
         "#
     }
+    /// params:
+    /// - $1: external_id
+    /// - $2: domain
+    pub const fn read_external_statement() -> &'static str {
+        r#"
+        SELECT * FROM messages WHERE external_id = $1 AND domain = $2;
+        "#
+    }
+    /// the messages of a room including those in threads, from newest to oldest
+    ///
+    /// params:
+    /// - $1: room_id
+    /// - $2: m_id of the oldest message of the previous page, null for the first page
+    /// - $3: LIMIT
+    pub const fn room_history_statement() -> &'static str {
+        r#"
+        SELECT * FROM messages WHERE room_id = $1 AND ($2::UUID IS NULL OR m_id < $2)
+        ORDER BY m_id DESC LIMIT $3;
+        "#
+    }
+    /// params:
+    /// - $1: room_id
+    pub const fn oldest_in_room_statement() -> &'static str {
+        r#"
+        SELECT * FROM messages WHERE room_id = $1 ORDER BY m_id ASC LIMIT 1;
+        "#
+    }
     pub const fn read_joined_statement() -> &'static str {
-        formatcp!(r#"{} WHERE main.m_id = $1;"#, SELECT_JOINED)
+        formatcp!(r#"{} WHERE main.m_id = $2;"#, SELECT_JOINED)
     }
     pub const fn update_statement() -> &'static str {
         r#"
@@ -235,12 +419,42 @@ impl DbMessage {
             content = $2,
             format = $3,
             language = $4,
-            fetched_at = $5
+            fetched_at = $5,
+            search_config = $7,
+            search = to_tsvector($7::TEXT::regconfig, $9),
+            rendered = $8,
+            plain_text = $9
         WHERE
             m_id = $6
         RETURNING *;
         "#
     }
+    /// params:
+    /// - $1: m_id the page starts after
+    /// - $2: LIMIT
+    pub const fn get_unrendered_statement() -> &'static str {
+        r#"
+        SELECT * FROM messages
+        WHERE plain_text IS NULL AND m_id > $1
+        ORDER BY m_id
+        LIMIT $2;
+        "#
+    }
+    /// params:
+    /// - $1: m_id
+    /// - $2: rendered
+    /// - $3: plain_text
+    pub const fn set_rendered_statement() -> &'static str {
+        r#"
+        UPDATE messages
+        SET
+            rendered = $2,
+            plain_text = $3,
+            search = to_tsvector(search_config::regconfig, $3)
+        WHERE
+            m_id = $1;
+        "#
+    }
     pub const fn delete_statement() -> &'static str {
         r#"
         DELETE FROM messages WHERE m_id = $1;
@@ -248,48 +462,147 @@ impl DbMessage {
     }
     pub const fn get_room_messages() -> &'static str {
         formatcp!(
-            r#"{} WHERE main.room_id = $1 ORDER BY main.published DESC LIMIT $2;"#,
+            r#"{} WHERE main.room_id = $2 AND main.thread_id IS NULL ORDER BY main.published DESC LIMIT $3;"#,
             SELECT_JOINED
         )
     }
-    /// gets messages older than a given message, messages in order of newest to oldest
-    /// 1. room_id
-    /// 2. m_id
-    /// 3. LIMIT
+    /// gets the pinned messages of a room from most to least recently pinned
+    /// 1. viewer
+    /// 2. room_id
+    /// 3. m_id of the last pin of the previous page, null for the first page
+    /// 4. LIMIT
+    pub const fn get_pinned_messages() -> &'static str {
+        formatcp!(
+            r#"{} INNER JOIN pins pin ON pin.m_id = main.m_id
+            WHERE pin.room_id = $2 AND (
+                $3::UUID IS NULL OR (pin.created, pin.m_id) < (
+                    SELECT created, m_id FROM pins WHERE room_id = $2 AND m_id = $3
+                )
+            )
+            ORDER BY pin.created DESC, pin.m_id DESC LIMIT $4;"#,
+            SELECT_JOINED
+        )
+    }
+    /// gets messages older than a given message, messages in order of newest to oldest.
+    /// messages in threads are left out
+    /// 1. viewer
+    /// 2. room_id
+    /// 3. m_id
+    /// 4. LIMIT
     pub const fn get_messages_prior(inclusive: bool) -> &'static str {
         match inclusive {
             true => {
                 formatcp!(
-                    r#"{} WHERE main.room_id = $1 AND main.m_id <= $2 ORDER BY main.published DESC LIMIT $3;"#,
+                    r#"{} WHERE main.room_id = $2 AND main.thread_id IS NULL AND main.m_id <= $3 ORDER BY main.published DESC LIMIT $4;"#,
                     SELECT_JOINED
                 )
             }
             false => {
                 formatcp!(
-                    r#"{} WHERE main.room_id = $1 AND main.m_id < $2 ORDER BY main.published DESC LIMIT $3;"#,
+                    r#"{} WHERE main.room_id = $2 AND main.thread_id IS NULL AND main.m_id < $3 ORDER BY main.published DESC LIMIT $4;"#,
                     SELECT_JOINED
                 )
             }
         }
     }
-    /// gets messages newer than a given message, messages in order of oldest to newest
-    /// 1. room_id
-    /// 2. m_id
-    /// 3. LIMIT
+    /// gets messages newer than a given message, messages in order of oldest to newest.
+    /// messages in threads are left out
+    /// 1. viewer
+    /// 2. room_id
+    /// 3. m_id
+    /// 4. LIMIT
     pub const fn get_messages_after(inclusive: bool) -> &'static str {
         match inclusive {
             true => {
                 formatcp!(
-                    r#"{} WHERE main.room_id = $1 AND main.m_id >= $2 ORDER BY main.published ASC LIMIT $3;"#,
+                    r#"{} WHERE main.room_id = $2 AND main.thread_id IS NULL AND main.m_id >= $3 ORDER BY main.published ASC LIMIT $4;"#,
+                    SELECT_JOINED
+                )
+            }
+            false => {
+                formatcp!(
+                    r#"{} WHERE main.room_id = $2 AND main.thread_id IS NULL AND main.m_id > $3 ORDER BY main.published ASC LIMIT $4;"#,
+                    SELECT_JOINED
+                )
+            }
+        }
+    }
+    /// gets the latest messages of a thread, messages in order of newest to oldest
+    /// 1. viewer
+    /// 2. thread_id
+    /// 3. LIMIT
+    pub const fn get_thread_messages() -> &'static str {
+        formatcp!(
+            r#"{} WHERE main.thread_id = $2 ORDER BY main.m_id DESC LIMIT $3;"#,
+            SELECT_JOINED
+        )
+    }
+    /// gets the messages of a thread older than a given message, messages in order
+    /// of newest to oldest
+    /// 1. viewer
+    /// 2. thread_id
+    /// 3. m_id
+    /// 4. LIMIT
+    pub const fn get_thread_messages_prior(inclusive: bool) -> &'static str {
+        match inclusive {
+            true => {
+                formatcp!(
+                    r#"{} WHERE main.thread_id = $2 AND main.m_id <= $3 ORDER BY main.m_id DESC LIMIT $4;"#,
                     SELECT_JOINED
                 )
             }
             false => {
                 formatcp!(
-                    r#"{} WHERE main.room_id = $1 AND main.m_id > $2 ORDER BY main.published ASC LIMIT $3;"#,
+                    r#"{} WHERE main.thread_id = $2 AND main.m_id < $3 ORDER BY main.m_id DESC LIMIT $4;"#,
                     SELECT_JOINED
                 )
             }
         }
     }
+    /// gets the messages of a thread newer than a given message, messages in order
+    /// of oldest to newest
+    /// 1. viewer
+    /// 2. thread_id
+    /// 3. m_id
+    /// 4. LIMIT
+    pub const fn get_thread_messages_after(inclusive: bool) -> &'static str {
+        match inclusive {
+            true => {
+                formatcp!(
+                    r#"{} WHERE main.thread_id = $2 AND main.m_id >= $3 ORDER BY main.m_id ASC LIMIT $4;"#,
+                    SELECT_JOINED
+                )
+            }
+            false => {
+                formatcp!(
+                    r#"{} WHERE main.thread_id = $2 AND main.m_id > $3 ORDER BY main.m_id ASC LIMIT $4;"#,
+                    SELECT_JOINED
+                )
+            }
+        }
+    }
+    /// gets the messages that started the threads of a room with replies since a given
+    /// time, from the most to least recently replied to
+    /// 1. viewer
+    /// 2. room_id
+    /// 3. time of the oldest reply a thread may have as its latest
+    /// 4. m_id of the last thread of the previous page, null for the first page
+    /// 5. LIMIT
+    pub const fn get_active_threads() -> &'static str {
+        formatcp!(
+            r#"{} INNER JOIN (
+                SELECT thread_id, max(published) AS last_activity
+                FROM messages
+                WHERE room_id = $2 AND thread_id IS NOT NULL
+                GROUP BY thread_id
+            ) thread ON thread.thread_id = main.m_id
+            WHERE thread.last_activity >= $3 AND (
+                $4::UUID IS NULL OR (thread.last_activity, thread.thread_id) < (
+                    SELECT max(published), $4 FROM messages WHERE thread_id = $4
+                )
+            )
+            ORDER BY thread.last_activity DESC, thread.thread_id DESC LIMIT $5;"#,
+            SELECT_JOINED
+        )
+    }
 }
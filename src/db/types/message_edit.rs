@@ -0,0 +1,63 @@
+use std::str::FromStr;
+
+use codes_iso_639::part_1::LanguageCode;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::message::TextFormat;
+
+/// a previous version of a message that has since been edited
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MessageEdit {
+    pub id: Uuid,
+    pub m_id: Uuid,
+    pub content: String,
+    pub format: TextFormat,
+    pub language: Option<LanguageCode>,
+    /// when this version was first visible
+    pub published: i64,
+    /// when this version was replaced by an edit
+    pub replaced: i64,
+}
+
+impl From<tokio_postgres::Row> for MessageEdit {
+    fn from(row: tokio_postgres::Row) -> Self {
+        let language: Option<&str> = row.get("language");
+        let language = language.and_then(|x| LanguageCode::from_str(x).ok());
+        MessageEdit {
+            id: row.get("edit_id"),
+            m_id: row.get("m_id"),
+            content: row.get("content"),
+            format: TextFormat::from_str(row.get("format")).expect("unkown text format in db"),
+            language,
+            published: row.get("published"),
+            replaced: row.get("replaced"),
+        }
+    }
+}
+
+impl MessageEdit {
+    /// params:
+    /// - $1: edit_id
+    /// - $2: m_id
+    /// - $3: content
+    /// - $4: format
+    /// - $5: language
+    /// - $6: published
+    /// - $7: replaced
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO message_edits
+        (edit_id, m_id, content, format, language, published, replaced)
+        VALUES
+        ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *;
+        "#
+    }
+    /// gets all previous versions of a message from oldest to newest
+    pub const fn get_message_edits() -> &'static str {
+        r#"
+        SELECT * FROM message_edits WHERE m_id = $1 ORDER BY replaced ASC;
+        "#
+    }
+}
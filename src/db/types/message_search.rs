@@ -0,0 +1,107 @@
+use codes_iso_639::part_1::LanguageCode;
+use const_format::formatcp;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::message::SELECT_JOINED;
+
+/// search terms longer than this are rejected
+pub const MAX_SEARCH_LENGTH: usize = 512;
+
+/// a search over the messages in the rooms a user can view, every filter
+/// that is present must match
+#[derive(Deserialize, Debug, Clone)]
+pub struct MessageSearch {
+    /// search terms in postgres websearch syntax, words are matched
+    /// regardless of form, `"quoted phrases"` are matched in order,
+    /// `or` matches either side and `-word` excludes a word
+    pub query: String,
+    /// only search the rooms of this community
+    pub community: Option<Uuid>,
+    /// only search this room
+    pub room: Option<Uuid>,
+    /// only messages sent by this user
+    pub author: Option<Uuid>,
+    /// only messages published at or after this time
+    pub since: Option<i64>,
+    /// only messages published before this time
+    pub until: Option<i64>,
+    pub has_attachment: Option<bool>,
+    pub is_reply: Option<bool>,
+    /// a message id, get the results older than this message
+    pub older: Option<Uuid>,
+}
+
+/// the postgres text search config used to stem the words of a message. languages
+/// postgres has no stemmer for and messages without a language use `simple`, which
+/// only lowercases words.
+///
+/// changing this only affects new and edited messages, `V12__message_search.sql`
+/// used the same mapping for the messages that existed before search
+pub fn search_config(language: Option<LanguageCode>) -> &'static str {
+    let Some(language) = language else {
+        return "simple";
+    };
+    match language {
+        LanguageCode::Ar => "arabic",
+        LanguageCode::Da => "danish",
+        LanguageCode::De => "german",
+        LanguageCode::El => "greek",
+        LanguageCode::En => "english",
+        LanguageCode::Es => "spanish",
+        LanguageCode::Fi => "finnish",
+        LanguageCode::Fr => "french",
+        LanguageCode::Ga => "irish",
+        LanguageCode::Hu => "hungarian",
+        LanguageCode::Id => "indonesian",
+        LanguageCode::It => "italian",
+        LanguageCode::Lt => "lithuanian",
+        LanguageCode::Ne => "nepali",
+        LanguageCode::Nl => "dutch",
+        LanguageCode::No | LanguageCode::Nb | LanguageCode::Nn => "norwegian",
+        LanguageCode::Pt => "portuguese",
+        LanguageCode::Ro => "romanian",
+        LanguageCode::Ru => "russian",
+        LanguageCode::Sv => "swedish",
+        LanguageCode::Ta => "tamil",
+        LanguageCode::Tr => "turkish",
+        _ => "simple",
+    }
+}
+
+impl MessageSearch {
+    /// searches messages from newest to oldest, the query is parsed with the
+    /// config of each message so it is stemmed the same way as the message.
+    /// messages are first found with the search index using the query parsed
+    /// with every config, any message matching its own config matches this
+    /// 1. viewer
+    /// 2. room ids to search
+    /// 3. query
+    /// 4. author
+    /// 5. since
+    /// 6. until
+    /// 7. has attachment
+    /// 8. is reply
+    /// 9. m_id of the last result of the previous page, null for the first page
+    /// 10. LIMIT
+    pub const fn search_statement() -> &'static str {
+        formatcp!(
+            r#"{} WHERE main.room_id = ANY($2)
+            AND main.search @@ (
+                SELECT tsquery_or_agg(websearch_to_tsquery(oid::regconfig, $3))
+                FROM pg_ts_config
+            )
+            AND main.search @@ websearch_to_tsquery(main.search_config::regconfig, $3)
+            AND ($4::UUID IS NULL OR main.uid = $4)
+            AND ($5::BIGINT IS NULL OR main.published >= $5)
+            AND ($6::BIGINT IS NULL OR main.published < $6)
+            AND ($7::BOOLEAN IS NULL OR $7 = EXISTS (
+                SELECT 1 FROM message_attachments WHERE m_id = main.m_id
+            ))
+            AND ($8::BOOLEAN IS NULL OR main.is_reply = $8)
+            AND ($9::UUID IS NULL OR main.m_id < $9)
+            ORDER BY main.m_id DESC LIMIT $10;"#,
+            SELECT_JOINED
+        )
+    }
+}
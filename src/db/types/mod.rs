@@ -1,8 +1,22 @@
+pub mod attachment;
 pub mod comm;
 pub mod custom_emoji;
+pub mod emoji_pack;
+pub mod file;
+pub mod file_thumbnail;
 pub mod instance;
+pub mod live_event;
+pub mod mention;
 pub mod message;
+pub mod message_edit;
+pub mod message_search;
+pub mod outbox;
+pub mod pin;
+pub mod reaction;
+pub mod read_state;
 pub mod registered_device;
 pub mod room;
+pub mod room_membership;
+pub mod system_event;
 pub mod tokens;
 pub mod user;
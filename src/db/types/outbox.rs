@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use tokio_postgres::types::Json;
+
+use crate::federation::activity::Activity;
+
+/// an activity waiting to be delivered to the inbox of a remote instance
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OutboxDelivery {
+    pub delivery_id: i64,
+    pub domain: String,
+    pub activity: Activity,
+    pub attempts: i64,
+    pub next_attempt: i64,
+    pub created: i64,
+}
+
+impl From<tokio_postgres::Row> for OutboxDelivery {
+    fn from(row: tokio_postgres::Row) -> Self {
+        let activity: Json<Activity> = row.get("activity");
+        OutboxDelivery {
+            delivery_id: row.get("delivery_id"),
+            domain: row.get("domain"),
+            activity: activity.0,
+            attempts: row.get("attempts"),
+            next_attempt: row.get("next_attempt"),
+            created: row.get("created"),
+        }
+    }
+}
+
+impl OutboxDelivery {
+    /// params:
+    /// - $1: domain
+    /// - $2: activity
+    /// - $3: created, also the first attempt
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO outbox
+        (domain, activity, next_attempt, created)
+        VALUES
+        ($1, $2, $3, $3)
+        RETURNING *;
+        "#
+    }
+    /// claims the deliveries that are due by pushing back their next attempt,
+    /// rows claimed by another instance are skipped
+    ///
+    /// params:
+    /// - $1: current time
+    /// - $2: when the deliveries may be claimed again if they are never finished
+    /// - $3: LIMIT
+    pub const fn claim_statement() -> &'static str {
+        r#"
+        UPDATE outbox SET next_attempt = $2
+        WHERE delivery_id IN (
+            SELECT delivery_id FROM outbox WHERE next_attempt <= $1
+            ORDER BY delivery_id LIMIT $3
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING *;
+        "#
+    }
+    /// params:
+    /// - $1: delivery_id
+    /// - $2: next_attempt
+    pub const fn retry_statement() -> &'static str {
+        r#"
+        UPDATE outbox SET attempts = attempts + 1, next_attempt = $2
+        WHERE delivery_id = $1
+        RETURNING *;
+        "#
+    }
+    /// params:
+    /// - $1: delivery_id
+    pub const fn delete_statement() -> &'static str {
+        r#"
+        DELETE FROM outbox WHERE delivery_id = $1;
+        "#
+    }
+}
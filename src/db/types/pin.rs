@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pin {
+    pub room_id: Uuid,
+    pub m_id: Uuid,
+    /// the user that pinned the message
+    pub uid: Uuid,
+    pub created: i64,
+}
+
+impl From<tokio_postgres::Row> for Pin {
+    fn from(row: tokio_postgres::Row) -> Self {
+        Pin {
+            room_id: row.get("room_id"),
+            m_id: row.get("m_id"),
+            uid: row.get("uid"),
+            created: row.get("created"),
+        }
+    }
+}
+
+impl Pin {
+    /// returns nothing if the message is already pinned
+    ///
+    /// params:
+    /// - $1: room_id
+    /// - $2: m_id
+    /// - $3: uid
+    /// - $4: created
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO pins
+        (room_id, m_id, uid, created)
+        VALUES
+        ($1, $2, $3, $4)
+        ON CONFLICT DO NOTHING
+        RETURNING *;
+        "#
+    }
+    /// returns nothing if the message was not pinned
+    ///
+    /// params:
+    /// - $1: room_id
+    /// - $2: m_id
+    pub const fn delete_statement() -> &'static str {
+        r#"
+        DELETE FROM pins WHERE room_id = $1 AND m_id = $2 RETURNING *;
+        "#
+    }
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DbReaction {
+    pub uid: Uuid,
+    pub m_id: Uuid,
+    /// the unicode emoji or the shortcode of a custom emoji
+    pub emoji: String,
+    /// the custom emoji, none if unicode or the emoji was deleted
+    pub emoji_id: Option<Uuid>,
+    /// the id of the custom emoji used at the time of reacting, kept
+    /// so that the reaction stays unique after the emoji is deleted
+    pub archive_id: Option<Uuid>,
+    pub published: i64,
+}
+
+impl From<tokio_postgres::Row> for DbReaction {
+    fn from(row: tokio_postgres::Row) -> Self {
+        DbReaction {
+            uid: row.get("uid"),
+            m_id: row.get("m_id"),
+            emoji: row.get("react_unicode"),
+            emoji_id: row.get("emoji_id"),
+            archive_id: row.get("archive_id"),
+            published: row.get("published"),
+        }
+    }
+}
+
+impl DbReaction {
+    /// returns nothing if the user already reacted with the emoji
+    ///
+    /// params:
+    /// - $1: uid
+    /// - $2: m_id
+    /// - $3: react_unicode
+    /// - $4: emoji_id
+    /// - $5: archive_id
+    /// - $6: published
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO reactions
+        (uid, m_id, react_unicode, emoji_id, archive_id, published)
+        VALUES
+        ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT DO NOTHING
+        RETURNING *;
+        "#
+    }
+    /// returns nothing if the reaction did not exist
+    ///
+    /// params:
+    /// - $1: uid
+    /// - $2: m_id
+    /// - $3: react_unicode
+    /// - $4: archive_id
+    pub const fn delete_statement() -> &'static str {
+        r#"
+        DELETE FROM reactions
+        WHERE uid = $1 AND m_id = $2 AND react_unicode = $3 AND archive_id IS NOT DISTINCT FROM $4
+        RETURNING *;
+        "#
+    }
+    /// gets the users that reacted to a message with an emoji, oldest reaction first
+    ///
+    /// params:
+    /// - $1: m_id
+    /// - $2: react_unicode
+    /// - $3: archive_id
+    pub const fn get_reaction_users() -> &'static str {
+        r#"
+        SELECT users.* FROM reactions INNER JOIN users USING (uid)
+        WHERE m_id = $1 AND react_unicode = $2 AND archive_id IS NOT DISTINCT FROM $3
+        ORDER BY reactions.published ASC;
+        "#
+    }
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// the latest message a user has read in a room
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReadState {
+    pub uid: Uuid,
+    pub room_id: Uuid,
+    pub last_read: Uuid,
+    pub updated: i64,
+}
+
+impl From<tokio_postgres::Row> for ReadState {
+    fn from(row: tokio_postgres::Row) -> Self {
+        ReadState {
+            uid: row.get("uid"),
+            room_id: row.get("room_id"),
+            last_read: row.get("last_read"),
+            updated: row.get("updated"),
+        }
+    }
+}
+
+/// messages from other users in a room the user has not read yet
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoomUnreads {
+    pub room_id: Uuid,
+    /// none if the user has never read the room
+    pub last_read: Option<Uuid>,
+    pub unread_count: i64,
+    /// unread messages that mention the user
+    pub mention_count: i64,
+}
+
+impl From<tokio_postgres::Row> for RoomUnreads {
+    fn from(row: tokio_postgres::Row) -> Self {
+        RoomUnreads {
+            room_id: row.get("room_id"),
+            last_read: row.get("last_read"),
+            unread_count: row.get("unread_count"),
+            mention_count: row.get("mention_count"),
+        }
+    }
+}
+
+impl ReadState {
+    /// read states only move forward, returns nothing if the user
+    /// has already read a newer message
+    ///
+    /// params:
+    /// - $1: uid
+    /// - $2: room_id
+    /// - $3: last_read
+    /// - $4: updated
+    pub const fn upsert_statement() -> &'static str {
+        r#"
+        INSERT INTO read_states
+        (uid, room_id, last_read, updated)
+        VALUES
+        ($1, $2, $3, $4)
+        ON CONFLICT (uid, room_id) DO UPDATE SET
+        last_read = EXCLUDED.last_read,
+        updated = EXCLUDED.updated
+        WHERE read_states.last_read < EXCLUDED.last_read
+        RETURNING *;
+        "#
+    }
+    /// params:
+    /// - $1: uid
+    /// - $2: room_id
+    pub const fn read_statement() -> &'static str {
+        r#"
+        SELECT * FROM read_states WHERE uid = $1 AND room_id = $2;
+        "#
+    }
+    /// replies to the user's messages count as mentions alongside @everyone
+    /// and mentions of the user or one of their roles. messages in threads
+    /// only count when they mention the user
+    ///
+    /// params:
+    /// - $1: uid
+    /// - $2: room ids
+    pub const fn get_unreads_statement() -> &'static str {
+        r#"
+        SELECT room.room_id, state.last_read,
+        (
+            SELECT COUNT(*) FROM messages m
+            WHERE m.room_id = room.room_id AND m.uid <> $1
+            AND (state.last_read IS NULL OR m.m_id > state.last_read)
+            AND m.thread_id IS NULL
+        ) AS unread_count,
+        (
+            SELECT COUNT(*) FROM messages m
+            WHERE m.room_id = room.room_id AND m.uid <> $1
+            AND (state.last_read IS NULL OR m.m_id > state.last_read)
+            AND (
+                EXISTS (
+                    SELECT 1 FROM messages parent
+                    WHERE parent.m_id = m.in_reply_to AND parent.uid = $1
+                )
+                OR EXISTS (
+                    SELECT 1 FROM mentions mn
+                    WHERE mn.m_id = m.m_id AND (
+                        mn.uid = $1
+                        OR (mn.uid IS NULL AND mn.role_id IS NULL)
+                        OR mn.role_id IN (SELECT role_id FROM role_membership WHERE uid = $1)
+                    )
+                )
+            )
+        ) AS mention_count
+        FROM UNNEST($2::UUID[]) WITH ORDINALITY AS room(room_id, position)
+        LEFT JOIN read_states state ON state.room_id = room.room_id AND state.uid = $1
+        ORDER BY room.position;
+        "#
+    }
+}
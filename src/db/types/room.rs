@@ -81,11 +81,49 @@ impl Room {
         RETURNING *;
         "#
     }
+    /// the same as [`Room::create_statement`] but nothing is returned if
+    /// the two users already have a direct message
+    pub const fn create_dm_statement() -> &'static str {
+        r#"
+        INSERT INTO rooms 
+        (
+            room_id,
+            external_id,
+            domain,
+            community,
+            system_channel,
+            created,
+            known_complete,
+            is_dm,
+            user_a,
+            user_b,
+            name,
+            description,
+            category,
+            display_order
+        )
+        VALUES
+        (
+            $1, $2, $3, $4, $5, $6, $7, $8,
+            $9, $10, $11, $12, $13, $14
+        )
+        ON CONFLICT DO NOTHING
+        RETURNING *;
+        "#
+    }
     pub const fn read_statement() -> &'static str {
         r#"
         SELECT * FROM rooms WHERE room_id = $1;
         "#
     }
+    /// params:
+    /// - $1: external_id
+    /// - $2: domain
+    pub const fn read_external_statement() -> &'static str {
+        r#"
+        SELECT * FROM rooms WHERE external_id = $1 AND domain = $2;
+        "#
+    }
     pub const fn update_statement() -> &'static str {
         r#"
         UPDATE rooms SET
@@ -94,7 +132,7 @@ impl Room {
         system_channel = $3,
         name = $4,
         description = $5,
-        known_complete = $7
+        known_complete = $6,
         category = $7,
         display_order = $8
 
@@ -112,4 +150,34 @@ impl Room {
         SELECT * FROM rooms WHERE community = $1;
         "#
     }
+    pub const fn get_system_channel() -> &'static str {
+        r#"
+        SELECT * FROM rooms WHERE community = $1 AND system_channel = true;
+        "#
+    }
+    /// params:
+    /// - $1: uid
+    /// - $2: uid
+    ///
+    /// order of users does not matter
+    pub const fn get_dm() -> &'static str {
+        r#"
+        SELECT * FROM rooms WHERE is_dm = true AND (
+            (user_a = $1 AND user_b = $2) OR (user_a = $2 AND user_b = $1)
+        );
+        "#
+    }
+    pub const fn get_all_user_dms() -> &'static str {
+        r#"
+        SELECT * FROM rooms WHERE is_dm = true AND (user_a = $1 OR user_b = $1)
+        ORDER BY created DESC;
+        "#
+    }
+    pub const fn get_all_user_group_chats() -> &'static str {
+        r#"
+        SELECT * FROM rooms INNER JOIN room_membership USING (room_id)
+        WHERE uid = $1 AND is_dm = false AND community IS NULL
+        ORDER BY created DESC;
+        "#
+    }
 }
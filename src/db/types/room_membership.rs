@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// membership of a room that is not part of a community,
+/// used for both direct messages and group chats
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoomMembership {
+    pub room_id: Uuid,
+    pub uid: Uuid,
+    pub joined: i64,
+}
+
+impl From<tokio_postgres::Row> for RoomMembership {
+    fn from(row: tokio_postgres::Row) -> Self {
+        RoomMembership {
+            room_id: row.get("room_id"),
+            uid: row.get("uid"),
+            joined: row.get("joined"),
+        }
+    }
+}
+
+impl RoomMembership {
+    /// params:
+    /// - $1: room_id
+    /// - $2: uid
+    /// - $3: joined
+    pub const fn create_statement() -> &'static str {
+        r#"
+        INSERT INTO room_membership
+        (
+            room_id,
+            uid,
+            joined
+        )
+        VALUES
+        (
+            $1, $2, $3
+        )
+        RETURNING *;
+        "#
+    }
+    /// params:
+    /// - $1: room_id
+    /// - $2: uid
+    pub const fn read_statement() -> &'static str {
+        r#"
+        SELECT * FROM room_membership WHERE room_id = $1 AND uid = $2;
+        "#
+    }
+    /// params:
+    /// - $1: room_id
+    /// - $2: uid
+    pub const fn delete_statement() -> &'static str {
+        r#"
+        DELETE FROM room_membership WHERE room_id = $1 AND uid = $2;
+        "#
+    }
+    pub const fn get_all_room_members() -> &'static str {
+        r#"
+        SELECT * FROM room_membership WHERE room_id = $1;
+        "#
+    }
+    pub const fn get_all_room_users() -> &'static str {
+        r#"
+        SELECT * FROM room_membership INNER JOIN users USING (uid) WHERE room_id = $1;
+        "#
+    }
+}
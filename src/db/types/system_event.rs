@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// events that are announced in a room as a message by the server,
+/// the message's user is the one that caused the event and its
+/// content is a plain text fallback for clients that don't know
+/// the event
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum SystemEvent {
+    MemberJoined {
+        community: Uuid,
+    },
+    /// the pinned message will also be what the announcement is in reply to
+    MessagePinned {
+        message: Uuid,
+    },
+}
@@ -1,11 +1,18 @@
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// used to join a community
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct JoinToken {
     pub id: Uuid,
     pub creator: Uuid,
-    pub commmunity: Uuid,
+    pub community: Uuid,
     pub expiry: i64,
+    /// the token is removed once it has been used this many times,
+    /// unlimited if none
+    pub max_uses: Option<i64>,
+    pub uses: i64,
+    pub created: i64,
 }
 
 impl From<tokio_postgres::Row> for JoinToken {
@@ -13,19 +20,29 @@ impl From<tokio_postgres::Row> for JoinToken {
         JoinToken {
             id: row.get("token_id"),
             creator: row.get("creator"),
-            commmunity: row.get("com_id"),
+            community: row.get("com_id"),
             expiry: row.get("expiry"),
+            max_uses: row.get("max_uses"),
+            uses: row.get("uses"),
+            created: row.get("created"),
         }
     }
 }
 
 impl JoinToken {
+    /// params:
+    /// - $1: token_id
+    /// - $2: creator
+    /// - $3: com_id
+    /// - $4: expiry
+    /// - $5: max_uses
+    /// - $6: created
     pub const fn create_statement() -> &'static str {
         r#"
         INSERT INTO join_token
-        (token_id, creator, com_id, expiry)
+        (token_id, creator, com_id, expiry, max_uses, created)
         VALUES
-        ($1, $2, $3, $4)
+        ($1, $2, $3, $4, $5, $6)
         RETURNING *;
         "#
     }
@@ -39,4 +56,29 @@ impl JoinToken {
         DELETE FROM join_token WHERE token_id = $1;
         "#
     }
+    /// incriments the uses of a token, nothing is returned if the token is gone
+    /// or has been used up
+    pub const fn use_statement() -> &'static str {
+        r#"
+        UPDATE join_token SET uses = uses + 1
+        WHERE token_id = $1 AND (max_uses IS NULL OR uses < max_uses)
+        RETURNING *;
+        "#
+    }
+    /// params:
+    /// - $1: com_id
+    /// - $2: current time, expired tokens are not returned
+    pub const fn get_all_comm_tokens() -> &'static str {
+        r#"
+        SELECT * FROM join_token WHERE com_id = $1 AND expiry > $2 ORDER BY created DESC;
+        "#
+    }
+    /// params:
+    /// - $1: com_id
+    /// - $2: current time
+    pub const fn delete_expired_comm_tokens() -> &'static str {
+        r#"
+        DELETE FROM join_token WHERE com_id = $1 AND expiry <= $2;
+        "#
+    }
 }
@@ -122,6 +122,31 @@ impl DbUser {
         RETURNING *;
         "#
     }
+    /// create a user from another instance or update the profile we have
+    /// of them, returns nothing if the user is one of our own
+    ///
+    /// params:
+    /// - $1: uid, only used when creating the user
+    /// - $2: domain
+    /// - $3: username
+    /// - $4: display_name
+    /// - $5: summary
+    /// - $6: fetched_at
+    /// - $7: created
+    pub const fn upsert_remote_statement() -> &'static str {
+        r#"
+        INSERT INTO users
+        (uid, domain, username, display_name, summary, fetched_at, is_authoratative, created)
+        VALUES
+        ($1, $2, $3, $4, $5, $6, false, $7)
+        ON CONFLICT (domain, username) DO UPDATE SET
+        display_name = EXCLUDED.display_name,
+        summary = EXCLUDED.summary,
+        fetched_at = EXCLUDED.fetched_at
+        WHERE NOT users.is_authoratative
+        RETURNING *;
+        "#
+    }
     pub const fn read_uid_statement() -> &'static str {
         r#"
         SELECT * FROM users WHERE uid = $1;
@@ -156,4 +181,30 @@ impl DbUser {
         DELETE FROM users WHERE uid = $1;
         "#
     }
+    /// the domains of the remote users among the given users
+    ///
+    /// params:
+    /// - $1: uids
+    pub const fn remote_domains_statement() -> &'static str {
+        r#"
+        SELECT DISTINCT domain FROM users WHERE uid = ANY($1) AND NOT is_authoratative;
+        "#
+    }
+    /// every user sharing a community or a room with the given user, including themselves
+    ///
+    /// params:
+    /// - $1: uid
+    pub const fn related_users_statement() -> &'static str {
+        r#"
+        SELECT uid FROM community_membership WHERE com_id IN (
+            SELECT com_id FROM community_membership WHERE uid = $1
+        )
+        UNION
+        SELECT uid FROM room_membership WHERE room_id IN (
+            SELECT room_id FROM room_membership WHERE uid = $1
+        )
+        UNION
+        SELECT $1;
+        "#
+    }
 }
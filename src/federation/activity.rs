@@ -0,0 +1,189 @@
+//! server to server delivery of messages in communities, located at
+//! defined spaces with the prefix /.well-known/bayou
+//!
+//! - `post /.well-known/bayou/join` a remote user redeems an invite, the
+//!   community is returned as a [`FederatedCommunity`]
+//! - `post /.well-known/bayou/inbox` receives an [`Envelope`] containing
+//!   an [`Activity`]
+//! - `get /.well-known/bayou/key` the instance's public key, requests to the
+//!   other endpoints are signed with it, see [`super::signatures`]
+//! - `get /.well-known/bayou/users/{username}` a [`FederatedUser`]
+//! - `get /.well-known/bayou/communities/{id}` a [`FederatedCommunity`] with the
+//!   rooms members from the requesting instance can view
+//! - `get /.well-known/bayou/rooms/{id}/history` a page of a room's messages as
+//!   a [`FederatedHistory`], used to backfill rooms
+//!
+//! the instance a community is on is authoritative over it. instances with
+//! members in the community keep a copy of it and send the messages of their
+//! users to the community's instance, which checks their permissions and
+//! relays them to every other instance with members that can view the room.
+//! the instance of a message's author never receives its own message back
+//!
+//! rooms are referred to by their id on the community's instance along with
+//! its domain, messages by the id they were given when sent along with their
+//! author's domain, or the community's domain for announcements. these are
+//! stored as their `external_id` and `domain`. users are referred to by their
+//! username and domain
+//!
+//! the profiles of remote users and communities are fetched again once they
+//! are older than the configured ttl, see [`super::resolve`]. reactions, attachments
+//! and changes to the community's rooms are not pushed yet
+
+use codes_iso_639::part_1::LanguageCode;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::types::{
+    comm::community::DbCommunity, message::TextFormat, room::Room, user::DbUser,
+};
+
+/// an object by the id it has on the instance that created it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ObjectRef {
+    pub id: Uuid,
+    pub domain: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FederatedUser {
+    pub domain: String,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub summary: Option<String>,
+    pub created: i64,
+}
+
+impl From<&DbUser> for FederatedUser {
+    fn from(user: &DbUser) -> Self {
+        FederatedUser {
+            domain: user.domain.clone(),
+            username: user.info.username.clone(),
+            display_name: user.info.display_name.clone(),
+            summary: user.info.summary.clone(),
+            created: user.info.created,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FederatedRoom {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub system_channel: bool,
+    pub display_order: i64,
+    pub created: i64,
+}
+
+impl From<&Room> for FederatedRoom {
+    fn from(room: &Room) -> Self {
+        FederatedRoom {
+            id: room.external_id,
+            name: room.info.name.clone(),
+            description: room.info.description.clone(),
+            system_channel: room.system_channel,
+            display_order: room.info.display_order,
+            created: room.created,
+        }
+    }
+}
+
+/// a community and the rooms its remote members are able to view
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FederatedCommunity {
+    pub id: Uuid,
+    pub domain: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub created: i64,
+    pub owner: FederatedUser,
+    pub rooms: Vec<FederatedRoom>,
+}
+
+impl FederatedCommunity {
+    pub fn new(community: &DbCommunity, owner: &DbUser, rooms: &[Room]) -> Self {
+        FederatedCommunity {
+            id: community.external_id,
+            domain: community.domain.clone(),
+            name: community.info.name.clone(),
+            description: community.info.description.clone(),
+            created: community.created,
+            owner: owner.into(),
+            rooms: rooms.iter().map(FederatedRoom::from).collect(),
+        }
+    }
+}
+
+/// the federated subset of [`crate::db::types::system_event::SystemEvent`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum FederatedEvent {
+    MemberJoined,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FederatedMessage {
+    /// the message's domain is always its author's
+    pub id: ObjectRef,
+    pub room: ObjectRef,
+    pub author: FederatedUser,
+    pub published: i64,
+    pub edited: Option<i64>,
+    pub is_reply: bool,
+    /// left out when the message being replied to was never federated
+    pub in_reply_to: Option<ObjectRef>,
+    pub thread: Option<ObjectRef>,
+    pub content: String,
+    pub format: TextFormat,
+    pub language: Option<LanguageCode>,
+    /// the community's instance allowed the author to mention everyone,
+    /// mentions are otherwise resolved by each instance from the content
+    pub mentions_everyone: bool,
+    pub system_event: Option<FederatedEvent>,
+}
+
+/// a page of a room's history from newest to oldest
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FederatedHistory {
+    pub messages: Vec<FederatedMessage>,
+    /// the message to get the next page before, none once the start of
+    /// the room has been reached
+    pub before: Option<ObjectRef>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Activity {
+    /// a new message, sent by the author's instance to the community's
+    /// instance and relayed from there
+    Message(Box<FederatedMessage>),
+    /// the new version of an edited message, delivered the same way as new
+    /// messages. edits made by moderators come from the community's instance
+    Edit(Box<FederatedMessage>),
+    /// sent by the author's instance or relayed by the community's instance
+    Delete { message: ObjectRef },
+}
+
+/// the body posted to an inbox
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Envelope {
+    /// the domain of the instance delivering the activity
+    pub origin: String,
+    pub activity: Activity,
+}
+
+/// why an inbox refused an activity
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum InboxErr {
+    /// the activity refers to a room or message the instance doesn't have,
+    /// it may be delivered again later
+    UnknownObject,
+    /// the sending instance may not deliver the activity
+    Forbidden,
+}
+
+/// the body of a remote join, the user must be from the instance making
+/// the request
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemoteJoin {
+    pub token: Uuid,
+    pub user: FederatedUser,
+}
@@ -0,0 +1,353 @@
+//! requests made to other instances
+
+use std::{sync::OnceLock, time::Duration};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::{header::CONTENT_TYPE, Response, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use url::{Host, Url};
+use uuid::Uuid;
+
+use crate::{config::Config, db::types::user::DbUser, routes::api::types::join_err::JoinErr};
+
+use super::{
+    activity::{
+        Activity, Envelope, FederatedCommunity, FederatedHistory, FederatedUser, ObjectRef,
+        RemoteJoin,
+    },
+    emoji::EmojiPackFederation,
+    signatures::{FederatedKey, InstanceSigner},
+};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("failed to build federation client")
+    })
+}
+
+/// the url of a federation endpoint on another instance
+pub fn federation_url(config: &Config, domain: &str, path: &str) -> String {
+    format!("{}://{domain}/.well-known/bayou{path}", config.scheme())
+}
+
+/// if a domain is a bare host with an optional port. domains are put into the
+/// urls of requests we make so anything else could reach other hosts or paths.
+/// hostnames may only have letters, digits, dots and hyphens, which also
+/// leaves out wildcards and the obfuscated domains of some blocklists
+pub fn valid_domain(domain: &str) -> bool {
+    let Ok(url) = Url::parse(&format!("http://{domain}")) else {
+        return false;
+    };
+    let hostname = match url.host() {
+        Some(Host::Domain(hostname)) => hostname,
+        Some(_) => "",
+        None => return false,
+    };
+    if !hostname
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    {
+        return false;
+    }
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let normalized = match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    };
+    url.username().is_empty()
+        && url.password().is_none()
+        && url.path() == "/"
+        && url.query().is_none()
+        && url.fragment().is_none()
+        && normalized == domain
+}
+
+/// characters left as they are in a path segment
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// percent encode a value put into the path of a federation url
+pub fn encode_segment(segment: &str) -> String {
+    utf8_percent_encode(segment, SEGMENT).to_string()
+}
+
+/// post json to a federation endpoint on another instance, signed with our key
+async fn signed_post<T: Serialize>(
+    config: &Config,
+    signer: &InstanceSigner,
+    domain: &str,
+    path: &str,
+    body: &T,
+) -> Result<Response, ()> {
+    if !valid_domain(domain) {
+        return Err(());
+    }
+    let url = Url::parse(&federation_url(config, domain, path)).map_err(|_| ())?;
+    let body = serde_json::to_vec(body).expect("failed to serialize body");
+    let signature = signer.sign("post", &url, &body);
+    client()
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .header("Date", signature.date)
+        .header("Digest", signature.digest)
+        .header("Signature", signature.signature)
+        .body(body)
+        .send()
+        .await
+        .map_err(|_| ())
+}
+
+/// get json from a federation endpoint on another instance, signed with our key
+async fn signed_get<T: DeserializeOwned>(
+    config: &Config,
+    signer: &InstanceSigner,
+    domain: &str,
+    path: &str,
+) -> Option<T> {
+    if !valid_domain(domain) {
+        return None;
+    }
+    let url = Url::parse(&federation_url(config, domain, path)).ok()?;
+    let signature = signer.sign("get", &url, &[]);
+    let response = client()
+        .get(url)
+        .header("Date", signature.date)
+        .header("Digest", signature.digest)
+        .header("Signature", signature.signature)
+        .send()
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.json().await.ok()
+}
+
+/// fetch the profile of a user from their instance
+pub async fn fetch_user(
+    config: &Config,
+    signer: &InstanceSigner,
+    domain: &str,
+    username: &str,
+) -> Option<FederatedUser> {
+    let path = format!("/users/{}", encode_segment(username));
+    let user: FederatedUser = signed_get(config, signer, domain, &path).await?;
+    match user.domain == domain && user.username == username {
+        true => Some(user),
+        false => None,
+    }
+}
+
+/// fetch a community from its instance
+pub async fn fetch_community(
+    config: &Config,
+    signer: &InstanceSigner,
+    community: &ObjectRef,
+) -> Option<FederatedCommunity> {
+    let path = format!("/communities/{}", community.id);
+    let remote: FederatedCommunity = signed_get(config, signer, &community.domain, &path).await?;
+    match remote.domain == community.domain && remote.id == community.id {
+        true => Some(remote),
+        false => None,
+    }
+}
+
+/// fetch a page of a room's history from the community's instance
+pub async fn fetch_history(
+    config: &Config,
+    signer: &InstanceSigner,
+    room: &ObjectRef,
+    before: Option<&ObjectRef>,
+    limit: i64,
+) -> Option<FederatedHistory> {
+    let path = match before {
+        Some(before) => format!(
+            "/rooms/{}/history?limit={limit}&before_id={}&before_domain={}",
+            room.id, before.id, before.domain
+        ),
+        None => format!("/rooms/{}/history?limit={limit}", room.id),
+    };
+    signed_get(config, signer, &room.domain, &path).await
+}
+
+/// fetch the public key of another instance
+pub async fn fetch_key(config: &Config, domain: &str) -> Option<FederatedKey> {
+    if !valid_domain(domain) {
+        return None;
+    }
+    let response = client()
+        .get(federation_url(config, domain, "/key"))
+        .send()
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.json().await.ok()
+}
+
+/// fetch an emoji pack from an instance or static site, packs are public
+/// so the request isn't signed
+pub async fn fetch_emoji_pack(
+    config: &Config,
+    website: &str,
+    pack_id: Uuid,
+) -> Option<EmojiPackFederation> {
+    if !valid_domain(website) {
+        return None;
+    }
+    let response = client()
+        .get(federation_url(
+            config,
+            website,
+            &format!("/emoji/{pack_id}"),
+        ))
+        .send()
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.json().await.ok()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum DeliveryErr {
+    /// the instance could not be reached or may accept the activity later
+    Failed,
+    /// the instance will never accept the activity
+    Rejected,
+}
+
+/// post an activity to the inbox of another instance
+pub async fn deliver(
+    config: &Config,
+    signer: &InstanceSigner,
+    domain: &str,
+    activity: &Activity,
+) -> Result<(), DeliveryErr> {
+    let envelope = Envelope {
+        origin: config.instance_domain.clone(),
+        activity: activity.clone(),
+    };
+    let response = signed_post(config, signer, domain, "/inbox", &envelope)
+        .await
+        .map_err(|_| DeliveryErr::Failed)?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    // unknown objects may arrive after the activity, such as rooms of a
+    // community being joined
+    if status.is_client_error()
+        && status != StatusCode::NOT_FOUND
+        && status != StatusCode::TOO_MANY_REQUESTS
+    {
+        return Err(DeliveryErr::Rejected);
+    }
+    Err(DeliveryErr::Failed)
+}
+
+/// redeem an invite on another instance for a local user
+pub async fn join_remote(
+    config: &Config,
+    signer: &InstanceSigner,
+    domain: &str,
+    token: Uuid,
+    user: &DbUser,
+) -> Result<FederatedCommunity, JoinErr> {
+    let join = RemoteJoin {
+        token,
+        user: user.into(),
+    };
+    let response = signed_post(config, signer, domain, "/join", &join)
+        .await
+        .map_err(|_| JoinErr::Unreachable)?;
+    match response.status() {
+        StatusCode::OK => {
+            let community: FederatedCommunity =
+                response.json().await.map_err(|_| JoinErr::Unreachable)?;
+            match community.domain == domain {
+                true => Ok(community),
+                false => Err(JoinErr::Unreachable),
+            }
+        }
+        StatusCode::BAD_REQUEST => Err(response.json().await.unwrap_or(JoinErr::Unreachable)),
+        StatusCode::FORBIDDEN => Err(JoinErr::Blocked),
+        _ => Err(JoinErr::Unreachable),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_hosts_are_valid_domains() {
+        for domain in [
+            "example.com",
+            "sub.example.com",
+            "localhost:8020",
+            "127.0.0.1",
+            "[::1]:8020",
+            "xn--bcher-kva.example",
+        ] {
+            assert!(valid_domain(domain), "{domain} should be valid");
+        }
+    }
+
+    #[test]
+    fn anything_but_a_host_is_an_invalid_domain() {
+        for domain in [
+            "",
+            "example.com/",
+            "example.com/path",
+            "example.com?query",
+            "example.com#fragment",
+            "user@example.com",
+            "user:password@example.com",
+            "evil.com@example.com",
+            "example.com:notaport",
+            "example.com:99999",
+            "https://example.com",
+            "example.com\\@evil.com",
+            "exa mple.com",
+            // only the normalized form is accepted so a domain has one spelling
+            "Example.com",
+            "bücher.example",
+            "example.com:80",
+            "example.com.:8020/../",
+            "*.example.com",
+            "ex**le.com",
+        ] {
+            assert!(!valid_domain(domain), "{domain:?} should be invalid");
+        }
+    }
+
+    #[test]
+    fn segments_are_percent_encoded() {
+        assert_eq!(encode_segment("user_name-1.2~"), "user_name-1.2~");
+        assert_eq!(encode_segment("../admin"), "..%2Fadmin");
+        assert_eq!(encode_segment("a b?c#d%e"), "a%20b%3Fc%23d%25e");
+        assert_eq!(encode_segment("ü"), "%C3%BC");
+        // a segment can't change the path or add a query
+        let url = format!(
+            "https://example.com/users/{}",
+            encode_segment("a/../../b?x=1")
+        );
+        let parsed = Url::parse(&url).unwrap();
+        assert_eq!(parsed.path(), "/users/a%2F..%2F..%2Fb%3Fx%3D1");
+        assert_eq!(parsed.query(), None);
+    }
+}
@@ -4,13 +4,13 @@
 //!
 //! - packs will be located at `/.well-known/bayou/emoji/{pack id}`
 //! - custom emoji will then be located at
-//! `/.well-known/bayou/emoji/{pack id}/{shortcode}`
+//!   `/.well-known/bayou/emoji/{pack id}/{shortcode}`
 //!
 //! custom emoji should only link to media of the supported emoji
 //! as listed in [`EmojiFormat`] to guarentee support
 //!
-//! shortcodes must be lowercase and they must not contain
-//! whitespace or colons. colons will be used as delimiters in
+//! shortcodes may only contain lowercase letters, numbers, `_`
+//! and `+`. colons will be used as delimiters in
 //! inline text such that a simple replace all `:shortcode:`
 //! with the emoji listed in the items used custom emoji should
 //! work.
@@ -25,24 +25,101 @@
 //! Messages that contain custom emoji will have a list of
 //! [`InlineEmojiFederation`] which allow for them to change
 //! the shortcodes as necessary to prevent overlap
+//!
+//! packs may also be hosted by static sites at the same paths,
+//! [`EmojiFederation::domain`] is then the site's domain. packs are
+//! fetched without signatures and imported packs are synced when
+//! their [`EmojiPackFederation::last_updated`] changes. the media
+//! of our own emoji is served at
+//! `/.well-known/bayou/emoji/{pack id}/{shortcode}/media`
+
+use std::collections::HashSet;
 
 use serde::{Deserialize, Serialize};
 use url::Url;
 use uuid::Uuid;
 
-use super::media::Media;
+use crate::{
+    config::Config,
+    db::{
+        markdown::MAX_SHORTCODE_LENGTH,
+        types::{custom_emoji::CustomEmoji, emoji_pack::EmojiPack},
+    },
+};
+
+use super::{
+    client::{encode_segment, federation_url},
+    media::Media,
+};
 
 /// these will be the only supported formats for custom emoji
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EmojiFormat {
     #[serde(rename = "image/png")]
     PNG,
-    #[serde(rename = "image/jpg")]
+    #[serde(rename = "image/jpg", alias = "image/jpeg")]
     JPG,
     #[serde(rename = "image/gif")]
     GIF,
 }
 
+impl EmojiFormat {
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        match mime {
+            "image/png" => Some(EmojiFormat::PNG),
+            "image/jpg" | "image/jpeg" => Some(EmojiFormat::JPG),
+            "image/gif" => Some(EmojiFormat::GIF),
+            _ => None,
+        }
+    }
+    pub const fn as_mime(&self) -> &'static str {
+        match self {
+            EmojiFormat::PNG => "image/png",
+            EmojiFormat::JPG => "image/jpg",
+            EmojiFormat::GIF => "image/gif",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum EmojiErr {
+    /// a shortcode is empty, too long or has characters other than
+    /// lowercase letters, numbers, `_` and `+`
+    InvalidShortcode,
+    /// the shortcode is already used in the pack
+    DuplicateShortcode,
+    /// the media of an emoji isn't one of the [`EmojiFormat`]s
+    InvalidFormat,
+    /// the highlight emoji isn't in the pack, an emoji belongs to
+    /// another pack or links to media that isn't on the web
+    InvalidPack,
+    /// the pack's website could not be reached or sent back an invalid pack
+    Unreachable,
+    /// we don't federate with the pack's website
+    Blocked,
+    /// the website isn't a host with an optional port
+    InvalidWebsite,
+    /// the pack or file doesn't exist or belongs to another user
+    NotOwned,
+}
+
+/// if a shortcode may be used for an [`EmojiFederation`], only lowercase
+/// letters, numbers, `_` and `+` are allowed so they can be found in text,
+/// see [`crate::db::markdown`]
+pub fn valid_shortcode(shortcode: &str) -> bool {
+    valid_inline_shortcode(shortcode) && !shortcode.contains('-')
+}
+
+/// if a shortcode may be used as the effective shortcode of an
+/// [`InlineEmojiFederation`], these may also contain hyphens
+pub fn valid_inline_shortcode(shortcode: &str) -> bool {
+    !shortcode.is_empty()
+        && shortcode.len() <= MAX_SHORTCODE_LENGTH
+        && shortcode
+            .chars()
+            .all(|x| matches!(x, 'a'..='z' | '0'..='9' | '_' | '+' | '-'))
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EmojiPackFederation {
     pub external_pack_id: Uuid,
@@ -51,12 +128,57 @@ pub struct EmojiPackFederation {
     pub pack_name: String,
     pub description: Option<String>,
     pub author_url: Option<Url>,
+    #[serde(default)]
+    pub license: Option<String>,
+    /// when the pack was last changed in milliseconds, packs are
+    /// only synced again once this changes
+    pub last_updated: i64,
     pub emoji: Vec<EmojiFederation>,
 }
 
+impl EmojiPackFederation {
+    /// none if the pack has no emoji
+    pub fn from_db(config: &Config, pack: &EmojiPack, emoji: &[CustomEmoji]) -> Option<Self> {
+        Some(EmojiPackFederation {
+            external_pack_id: pack.external_id,
+            highlight_emoji: pack.highlight_emoji.clone()?,
+            pack_name: pack.name.clone(),
+            description: pack.description.clone(),
+            author_url: pack.author_url.as_ref().and_then(|x| Url::parse(x).ok()),
+            license: pack.license.clone(),
+            last_updated: pack.last_updated,
+            emoji: emoji
+                .iter()
+                .filter_map(|x| EmojiFederation::from_db(config, pack, x))
+                .collect(),
+        })
+    }
+    /// check the shortcodes and media of a pack fetched from the given domain
+    pub fn validate(&self, domain: &str) -> Result<(), EmojiErr> {
+        let mut shortcodes = HashSet::with_capacity(self.emoji.len());
+        for emoji in &self.emoji {
+            if !valid_shortcode(&emoji.shortcode) {
+                return Err(EmojiErr::InvalidShortcode);
+            }
+            if !shortcodes.insert(emoji.shortcode.as_str()) {
+                return Err(EmojiErr::DuplicateShortcode);
+            }
+            let web = matches!(emoji.icon.url.scheme(), "http" | "https");
+            if emoji.external_pack_id != self.external_pack_id || emoji.domain != domain || !web {
+                return Err(EmojiErr::InvalidPack);
+            }
+        }
+        match shortcodes.contains(self.highlight_emoji.as_str()) {
+            true => Ok(()),
+            false => Err(EmojiErr::InvalidPack),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EmojiFederation {
     pub external_pack_id: Uuid,
+    /// the domain of the instance or static site hosting the pack
     pub domain: String,
     /// must be unique within the emoji pack, no dupes!
     /// no whitespace, no colons, all lowercase.
@@ -66,6 +188,34 @@ pub struct EmojiFederation {
     pub icon: Media<EmojiFormat>,
 }
 
+impl EmojiFederation {
+    /// none if the emoji's media isn't a valid url or format
+    pub fn from_db(config: &Config, pack: &EmojiPack, emoji: &CustomEmoji) -> Option<Self> {
+        let url = match &emoji.url {
+            Some(url) => url.clone(),
+            None => federation_url(
+                config,
+                &pack.website,
+                &format!(
+                    "/emoji/{}/{}/media",
+                    pack.external_id,
+                    encode_segment(&emoji.shortcode)
+                ),
+            ),
+        };
+        Some(EmojiFederation {
+            external_pack_id: pack.external_id,
+            domain: pack.website.clone(),
+            shortcode: emoji.shortcode.clone(),
+            icon: Media {
+                description: emoji.description.clone(),
+                media_type: EmojiFormat::from_mime(&emoji.media_type)?,
+                url: Url::parse(&url).ok()?,
+            },
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InlineEmojiFederation {
     pub emoji: EmojiFederation,
@@ -1,2 +1,8 @@
+pub mod activity;
+pub mod client;
 pub mod emoji;
 pub mod media;
+pub mod outbox;
+pub mod policy;
+pub mod resolve;
+pub mod signatures;
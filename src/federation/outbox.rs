@@ -0,0 +1,114 @@
+//! delivers the activities queued in the outbox to the inboxes of other
+//! instances. deliveries to an instance are made in the order they were
+//! queued, when one fails the rest waiting for that instance are retried
+//! along with it. deliveries to instances we no longer federate with are dropped
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use futures_util::future::join_all;
+use tokio::{
+    spawn,
+    time::{interval, MissedTickBehavior},
+};
+
+use crate::{
+    config::Config,
+    db::{curr_time::get_current_time, pg_conn::PgConn, types::outbox::OutboxDelivery},
+};
+
+use super::{
+    client::{deliver, DeliveryErr},
+    policy::federates_with,
+    signatures::InstanceSigner,
+};
+
+const DELIVERY_INTERVAL: Duration = Duration::from_secs(1);
+const DELIVERY_BATCH: i64 = 100;
+/// how long claimed deliveries are held before another instance may
+/// claim them, in case this one stops while delivering
+const DELIVERY_LEASE: Duration = Duration::from_secs(5 * 60);
+/// the delay before the first retry, doubled for every failed attempt
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+/// deliveries are given up on after this many attempts, about 4 hours
+const MAX_ATTEMPTS: i64 = 10;
+
+/// deliver queued activities for as long as the server runs
+pub fn start_outbox(conn: PgConn, config: Config, signer: Arc<InstanceSigner>) {
+    spawn(async move {
+        let mut ticks = interval(DELIVERY_INTERVAL);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            // delivered in their own task so a database error doesn't stop the outbox
+            let conn = conn.clone();
+            let config = config.clone();
+            let signer = signer.clone();
+            let _ = spawn(async move { deliver_batch(&conn, &config, &signer).await }).await;
+        }
+    });
+}
+
+async fn deliver_batch(conn: &PgConn, config: &Config, signer: &InstanceSigner) {
+    let deliveries = conn
+        .claim_deliveries(DELIVERY_LEASE.as_millis() as i64, DELIVERY_BATCH)
+        .await;
+    let mut by_domain: HashMap<String, Vec<OutboxDelivery>> = HashMap::new();
+    for delivery in deliveries {
+        by_domain
+            .entry(delivery.domain.clone())
+            .or_default()
+            .push(delivery);
+    }
+    join_all(
+        by_domain
+            .into_iter()
+            .map(|(domain, deliveries)| deliver_domain(conn, config, signer, domain, deliveries)),
+    )
+    .await;
+}
+
+async fn deliver_domain(
+    conn: &PgConn,
+    config: &Config,
+    signer: &InstanceSigner,
+    domain: String,
+    deliveries: Vec<OutboxDelivery>,
+) {
+    if !federates_with(conn, config, &domain).await {
+        for delivery in deliveries {
+            conn.delete_delivery(delivery.delivery_id).await;
+        }
+        return;
+    }
+    let mut deliveries = deliveries.into_iter();
+    for delivery in deliveries.by_ref() {
+        match deliver(config, signer, &domain, &delivery.activity).await {
+            Ok(()) => conn.delete_delivery(delivery.delivery_id).await,
+            Err(DeliveryErr::Rejected) => {
+                eprintln!("{domain} rejected delivery {}", delivery.delivery_id);
+                conn.delete_delivery(delivery.delivery_id).await;
+            }
+            Err(DeliveryErr::Failed) => {
+                retry(conn, &delivery).await;
+                break;
+            }
+        }
+    }
+    for delivery in deliveries {
+        retry(conn, &delivery).await;
+    }
+}
+
+async fn retry(conn: &PgConn, delivery: &OutboxDelivery) {
+    if delivery.attempts + 1 >= MAX_ATTEMPTS {
+        eprintln!(
+            "giving up on delivery {} to {}",
+            delivery.delivery_id, delivery.domain
+        );
+        conn.delete_delivery(delivery.delivery_id).await;
+        return;
+    }
+    let delay = RETRY_DELAY.as_millis() as i64 * (1 << delivery.attempts);
+    conn.retry_delivery(delivery.delivery_id, get_current_time() + delay)
+        .await;
+}
@@ -0,0 +1,124 @@
+//! which instances we federate with. blocked instances are never federated
+//! with and when [`Config::allowlist_only`] is set only allowlisted instances
+//! are. requests from instances we don't federate with are rejected, nothing
+//! is sent or fetched from them and content they've already sent is hidden
+//!
+//! blocklists can be imported as csv, either a `domain,reason` per line or
+//! a mastodon export with `#domain` and `#public_comment` columns
+
+use crate::{config::Config, db::pg_conn::PgConn};
+
+use super::client::valid_domain;
+
+/// if we federate with the instance at the given domain
+pub async fn federates_with(conn: &PgConn, config: &Config, domain: &str) -> bool {
+    if domain == config.instance_domain {
+        return false;
+    }
+    conn.federates_with(domain, config.allowlist_only).await
+}
+
+/// parse a csv blocklist into domains and reasons. lines that are empty or
+/// start with `#` are skipped unless they are a header naming the columns,
+/// as are domains that aren't valid such as wildcards or obfuscated domains
+pub fn parse_blocklist(csv: &str) -> Vec<(String, Option<String>)> {
+    let mut domain_col = 0;
+    let mut reason_col = Some(1);
+    let mut blocklist = Vec::new();
+    for (index, line) in csv.lines().enumerate() {
+        let fields = split_line(line);
+        if index == 0 {
+            let header: Vec<String> = fields
+                .iter()
+                .map(|x| x.trim_start_matches('#').to_lowercase())
+                .collect();
+            if let Some(col) = header.iter().position(|x| x == "domain") {
+                domain_col = col;
+                reason_col = header
+                    .iter()
+                    .position(|x| x == "public_comment" || x == "reason");
+                continue;
+            }
+        }
+        let Some(domain) = fields.get(domain_col) else {
+            continue;
+        };
+        let domain = domain.trim().to_lowercase();
+        if !valid_domain(&domain) {
+            continue;
+        }
+        let reason = reason_col
+            .and_then(|x| fields.get(x))
+            .map(|x| x.trim().to_string())
+            .filter(|x| !x.is_empty());
+        blocklist.push((domain, reason));
+    }
+    blocklist
+}
+
+/// split a csv line into fields, fields may be quoted with `"`
+fn split_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_may_be_quoted() {
+        assert_eq!(split_line("a,b"), ["a", "b"]);
+        assert_eq!(split_line("\"a,b\",c"), ["a,b", "c"]);
+        assert_eq!(split_line("\"say \"\"hi\"\"\","), ["say \"hi\"", ""]);
+    }
+
+    #[test]
+    fn domain_and_reason_lines_are_parsed() {
+        let csv =
+            "# blocked instances\n\nSpam.example,spam\nquiet.example\nother.example,\"rude, mean\"";
+        assert_eq!(
+            parse_blocklist(csv),
+            [
+                ("spam.example".to_string(), Some("spam".to_string())),
+                ("quiet.example".to_string(), None),
+                ("other.example".to_string(), Some("rude, mean".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn mastodon_exports_are_parsed() {
+        let csv = "#domain,#severity,#reject_media,#reject_reports,#public_comment,#obfuscate\n\
+            spam.example,suspend,false,false,spam,false\n\
+            quiet.example,suspend,false,false,,false";
+        assert_eq!(
+            parse_blocklist(csv),
+            [
+                ("spam.example".to_string(), Some("spam".to_string())),
+                ("quiet.example".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_domains_are_skipped() {
+        let csv = "ex**le.com,obfuscated\n*.example.com,wildcard\nexa mple.com\nexample.com/path";
+        assert!(parse_blocklist(csv).is_empty());
+    }
+}
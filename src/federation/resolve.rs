@@ -0,0 +1,218 @@
+//! looking up users and communities from other instances. profiles are
+//! stored with the time they were fetched and fetched again from their
+//! instance once they are older than the configured ttl, the stored profile
+//! is used when the instance can't be reached
+//!
+//! rooms of remote communities only have the messages received since our
+//! first member joined, their history is backfilled from the community's
+//! instance until the start of the room is reached
+//!
+//! emoji packs are imported from instances or static sites, they are synced
+//! once stale but only replaced when their `last_updated` has changed
+//!
+//! nothing is fetched from instances we don't federate with and their users
+//! aren't resolved, see [`super::policy`]
+
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{
+        curr_time::get_current_time,
+        pg_conn::PgConn,
+        types::{
+            comm::community::DbCommunity, custom_emoji::CustomEmoji, emoji_pack::EmojiPack,
+            room::Room, user::DbUser,
+        },
+    },
+};
+
+use super::{
+    activity::ObjectRef,
+    client::{
+        fetch_community, fetch_emoji_pack, fetch_history, fetch_key, fetch_user, valid_domain,
+    },
+    emoji::EmojiErr,
+    policy::federates_with,
+    signatures::InstanceSigner,
+};
+
+/// messages fetched per page of history
+const HISTORY_PAGE: i64 = 100;
+/// pages of history fetched for a room at a time, the rest is fetched the
+/// next time the room is backfilled
+const BACKFILL_PAGES: usize = 10;
+
+/// split a handle of the form `username@domain`, a leading `@` is allowed.
+/// the domain must be a host with an optional port
+pub fn parse_handle(handle: &str) -> Option<(&str, &str)> {
+    let handle = handle.strip_prefix('@').unwrap_or(handle);
+    let (username, domain) = handle.split_once('@')?;
+    match username.is_empty() || !valid_domain(domain) {
+        true => None,
+        false => Some((username, domain)),
+    }
+}
+
+fn is_stale(config: &Config, fetched_at: Option<i64>) -> bool {
+    match fetched_at {
+        Some(fetched_at) => get_current_time() - fetched_at > config.remote_profile_ttl_millis(),
+        None => true,
+    }
+}
+
+/// get a user by their username and domain, fetching them from their instance
+/// if we don't have them or they are stale
+pub async fn resolve_user(
+    conn: &PgConn,
+    config: &Config,
+    signer: &InstanceSigner,
+    username: &str,
+    domain: &str,
+) -> Option<DbUser> {
+    let stored = conn.get_user(username, domain).await;
+    if domain == config.instance_domain {
+        return stored;
+    }
+    if !federates_with(conn, config, domain).await {
+        return None;
+    }
+    if let Some(user) = &stored {
+        if user.local_info.is_some() || !is_stale(config, user.fetched_at) {
+            return stored;
+        }
+    }
+    match fetch_user(config, signer, domain, username).await {
+        Some(remote) => conn.upsert_remote_user(&remote).await,
+        None => stored,
+    }
+}
+
+/// get a community from another instance, it is fetched again if it is stale.
+/// communities are only fetched once one of our users has joined them
+pub async fn resolve_community(
+    conn: &PgConn,
+    config: &Config,
+    signer: &InstanceSigner,
+    community: &ObjectRef,
+) -> Option<DbCommunity> {
+    let stored = conn.get_community_external(community).await?;
+    if community.domain == config.instance_domain || !is_stale(config, stored.fetched_at) {
+        return Some(stored);
+    }
+    if !federates_with(conn, config, &community.domain).await {
+        return Some(stored);
+    }
+    let Some(remote) = fetch_community(config, signer, community).await else {
+        return Some(stored);
+    };
+    match conn.refresh_remote_community(remote).await {
+        Ok(community) => Some(community),
+        Err(()) => Some(stored),
+    }
+}
+
+/// backfill the history of every room of a remote community that isn't
+/// known complete
+pub async fn backfill_community(
+    conn: &PgConn,
+    config: &Config,
+    signer: &InstanceSigner,
+    com_id: Uuid,
+) {
+    for room in conn.get_incomplete_rooms(com_id).await {
+        backfill_room(conn, config, signer, &room).await;
+    }
+}
+
+/// fetch the history of a remote room from before the oldest message we have
+pub async fn backfill_room(conn: &PgConn, config: &Config, signer: &InstanceSigner, room: &Room) {
+    if room.known_complete || room.community.is_none() {
+        return;
+    }
+    if !federates_with(conn, config, &room.domain).await {
+        return;
+    }
+    let room_ref = ObjectRef {
+        id: room.external_id,
+        domain: room.domain.clone(),
+    };
+    let mut before = conn.get_oldest_message_ref(room.id).await;
+    for _ in 0..BACKFILL_PAGES {
+        let Some(page) =
+            fetch_history(config, signer, &room_ref, before.as_ref(), HISTORY_PAGE).await
+        else {
+            return;
+        };
+        let complete = page.before.is_none();
+        let mut messages = Vec::with_capacity(page.messages.len());
+        for message in page.messages {
+            let author = &message.author.domain;
+            if *author == room.domain
+                || *author == config.instance_domain
+                || federates_with(conn, config, author).await
+            {
+                messages.push(message);
+            }
+        }
+        conn.store_history(room.id, messages, complete).await;
+        if complete {
+            return;
+        }
+        before = page.before;
+    }
+}
+
+/// import an emoji pack from the website hosting it, packs we already have are
+/// synced. websites serving an instance key are stored as the pack's domain
+pub async fn import_emoji_pack(
+    conn: &PgConn,
+    config: &Config,
+    website: &str,
+    pack_id: Uuid,
+) -> Result<(EmojiPack, Vec<CustomEmoji>), EmojiErr> {
+    if !valid_domain(website) {
+        return Err(EmojiErr::InvalidWebsite);
+    }
+    if website == config.instance_domain {
+        return conn
+            .get_emoji_pack(pack_id)
+            .await
+            .ok_or(EmojiErr::Unreachable);
+    }
+    if !federates_with(conn, config, website).await {
+        return Err(EmojiErr::Blocked);
+    }
+    let remote = fetch_emoji_pack(config, website, pack_id)
+        .await
+        .ok_or(EmojiErr::Unreachable)?;
+    if remote.external_pack_id != pack_id {
+        return Err(EmojiErr::InvalidPack);
+    }
+    remote.validate(website)?;
+    let domain = match conn.get_emoji_pack_external(pack_id, website).await {
+        Some(stored) => stored.domain,
+        None => fetch_key(config, website)
+            .await
+            .filter(|x| x.domain == website)
+            .map(|x| x.domain),
+    };
+    Ok(conn.store_remote_emoji_pack(remote, domain, website).await)
+}
+
+/// get an emoji pack, packs from other websites are synced if they are stale
+pub async fn resolve_emoji_pack(
+    conn: &PgConn,
+    config: &Config,
+    pack_id: Uuid,
+) -> Option<(EmojiPack, Vec<CustomEmoji>)> {
+    let stored = conn.get_emoji_pack(pack_id).await?;
+    let (pack, _) = &stored;
+    if pack.fetched_at.is_none() || !is_stale(config, pack.fetched_at) {
+        return Some(stored);
+    }
+    match import_emoji_pack(conn, config, &pack.website, pack.external_id).await {
+        Ok(synced) => Some(synced),
+        Err(_) => Some(stored),
+    }
+}
@@ -0,0 +1,336 @@
+//! every request made to another instance's `/.well-known/bayou` endpoints
+//! is signed with the sending instance's key, see
+//! [`crate::cryptography::http_signatures`]
+//!
+//! - public keys are located at `/.well-known/bayou/key` as a [`FederatedKey`]
+//!   and the signature's `keyId` is the url of the key
+//! - the `Date` of a request must be within [`REPLAY_WINDOW`] of the time it
+//!   is received and a signature is only accepted once
+//!
+//! the keys of other instances are cached and fetched again once they are
+//! older than [`KEY_TTL`] or fail to verify a request
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use actix_web::{http::StatusCode, HttpRequest};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    config::Config,
+    cryptography::{
+        http_signatures::{digest, sign_request, RequestSignature, SignatureHeader, SignedParts},
+        keys::{PrivateKey, PublicKey, SigningAlgorithm},
+    },
+    db::{
+        curr_time::get_current_time,
+        pg_conn::PgConn,
+        types::instance::{Instance, InstanceKey},
+    },
+};
+
+use super::{
+    client::{federation_url, fetch_key},
+    policy::federates_with,
+};
+
+/// how far the date of a request may be from the time it is received
+pub const REPLAY_WINDOW: Duration = Duration::from_secs(5 * 60);
+/// how long the key of another instance is used before fetching it again
+pub const KEY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// a key that fails to verify a request is only fetched again if it is older than this
+const KEY_REFETCH_INTERVAL: Duration = Duration::from_secs(60);
+const KEY_PATH: &str = "/key";
+
+/// the public key of an instance
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FederatedKey {
+    pub domain: String,
+    pub algorithm: SigningAlgorithm,
+    /// spki pem encoded
+    pub public_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum SignatureErr {
+    /// the request has no signature or is missing a signed header
+    Missing,
+    /// the signature is malformed, doesn't cover the required headers
+    /// or doesn't match the request
+    Invalid,
+    /// the date of the request is outside of the replay window
+    Expired,
+    /// the signature has already been used
+    Replayed,
+    /// the key of the signing instance could not be fetched
+    UnknownKey,
+    /// we don't federate with the signing instance
+    Blocked,
+}
+
+impl SignatureErr {
+    /// the status to reject a request with
+    pub fn status(&self) -> StatusCode {
+        match self {
+            SignatureErr::Blocked => StatusCode::FORBIDDEN,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+/// signs requests with our instance's key
+pub struct InstanceSigner {
+    key_id: String,
+    key: PrivateKey,
+    /// published at the key endpoint
+    pub public_key: FederatedKey,
+}
+
+impl InstanceSigner {
+    /// panics if the instance has no private key, see
+    /// [`PgConn::get_or_init_main_instance`]
+    pub fn new(config: &Config, instance: &Instance) -> Self {
+        let stored = instance.key.as_ref().expect("instance has no key");
+        let pem = stored
+            .private_key
+            .as_ref()
+            .expect("instance has no private key");
+        let key = PrivateKey::from_pem(stored.algorithm, pem).expect("invalid instance key");
+        InstanceSigner {
+            key_id: federation_url(config, &instance.domain, KEY_PATH),
+            public_key: FederatedKey {
+                domain: instance.domain.clone(),
+                algorithm: stored.algorithm,
+                public_key: key.public_key().to_pem(),
+            },
+            key,
+        }
+    }
+    /// sign a request to the given url
+    pub fn sign(&self, method: &str, url: &Url, body: &[u8]) -> RequestSignature {
+        let host = match url.port() {
+            Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let path = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        };
+        sign_request(&self.key, &self.key_id, method, &path, &host, body)
+    }
+}
+
+struct CachedKey {
+    key: PublicKey,
+    fetched_at: i64,
+}
+
+/// signatures that have been accepted, kept until their date could no
+/// longer be within the replay window
+#[derive(Default)]
+struct SeenSignatures {
+    expiries: VecDeque<(i64, Vec<u8>)>,
+    signatures: HashSet<Vec<u8>>,
+}
+
+impl SeenSignatures {
+    /// returns false if the signature has already been seen
+    fn insert(&mut self, signature: &[u8], now: i64) -> bool {
+        while let Some((expiry, _)) = self.expiries.front() {
+            if *expiry > now {
+                break;
+            }
+            let (_, expired) = self.expiries.pop_front().expect("front exists");
+            self.signatures.remove(&expired);
+        }
+        if !self.signatures.insert(signature.to_vec()) {
+            return false;
+        }
+        let expiry = now + 2 * REPLAY_WINDOW.as_millis() as i64;
+        self.expiries.push_back((expiry, signature.to_vec()));
+        true
+    }
+}
+
+/// verifies signed requests from other instances. replays are only tracked
+/// in memory so each process sharing a database keeps its own
+#[derive(Default)]
+pub struct KeyStore {
+    keys: Mutex<HashMap<String, CachedKey>>,
+    seen: Mutex<SeenSignatures>,
+}
+
+impl KeyStore {
+    /// verify the signature of a request, returns the domain of the instance
+    /// that signed it
+    pub async fn verify_request(
+        &self,
+        req: &HttpRequest,
+        body: &[u8],
+        conn: &PgConn,
+        config: &Config,
+    ) -> Result<String, SignatureErr> {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|x| x.to_str().ok())
+                .ok_or(SignatureErr::Missing)
+        };
+        let signature =
+            SignatureHeader::parse(header("signature")?).ok_or(SignatureErr::Invalid)?;
+        if !signature.covers_required() {
+            return Err(SignatureErr::Invalid);
+        }
+        let domain = key_domain(config, &signature.key_id).ok_or(SignatureErr::Invalid)?;
+        if domain == config.instance_domain {
+            return Err(SignatureErr::Invalid);
+        }
+        if !federates_with(conn, config, &domain).await {
+            return Err(SignatureErr::Blocked);
+        }
+
+        let date = header("date")?;
+        let sent = httpdate::parse_http_date(date).map_err(|_| SignatureErr::Invalid)?;
+        let now = SystemTime::now();
+        let age = match now.duration_since(sent) {
+            Ok(age) => age,
+            Err(x) => x.duration(),
+        };
+        if age > REPLAY_WINDOW {
+            return Err(SignatureErr::Expired);
+        }
+        let body_digest = header("digest")?;
+        if body_digest != digest(body) {
+            return Err(SignatureErr::Invalid);
+        }
+        let path = req
+            .uri()
+            .path_and_query()
+            .map(|x| x.as_str())
+            .unwrap_or(req.path());
+        // a request signed for another instance can't be replayed to us
+        let host = header("host")?;
+        if host != config.instance_domain {
+            return Err(SignatureErr::Invalid);
+        }
+        let method = req.method().as_str().to_lowercase();
+        let parts = SignedParts {
+            method: &method,
+            path,
+            host,
+            date,
+            digest: body_digest,
+        };
+
+        let (key, fetched_at) = self.get_key(conn, config, &domain, false).await?;
+        if !signature.verify(&key, &parts) {
+            // the instance may have changed its key
+            let refetch = get_current_time() - fetched_at > KEY_REFETCH_INTERVAL.as_millis() as i64;
+            if !refetch {
+                return Err(SignatureErr::Invalid);
+            }
+            let (key, _) = self.get_key(conn, config, &domain, true).await?;
+            if !signature.verify(&key, &parts) {
+                return Err(SignatureErr::Invalid);
+            }
+        }
+        let mut seen = self.seen.lock().expect("poisoned");
+        if !seen.insert(&signature.signature, get_current_time()) {
+            return Err(SignatureErr::Replayed);
+        }
+        Ok(domain)
+    }
+    /// get the key of an instance from the cache, the database or the instance
+    async fn get_key(
+        &self,
+        conn: &PgConn,
+        config: &Config,
+        domain: &str,
+        refresh: bool,
+    ) -> Result<(PublicKey, i64), SignatureErr> {
+        let now = get_current_time();
+        let fresh = |fetched_at: i64| now - fetched_at < KEY_TTL.as_millis() as i64;
+        if !refresh {
+            if let Some(cached) = self.keys.lock().expect("poisoned").get(domain) {
+                if fresh(cached.fetched_at) {
+                    return Ok((cached.key.clone(), cached.fetched_at));
+                }
+            }
+            let stored = conn.get_instance(domain).await.and_then(|x| x.key);
+            if let Some(stored) = stored {
+                let key = PublicKey::from_pem(stored.algorithm, &stored.public_key);
+                if let (Some(key), Some(fetched_at)) = (key, stored.fetched_at) {
+                    if fresh(fetched_at) {
+                        self.cache(domain, key.clone(), fetched_at);
+                        return Ok((key, fetched_at));
+                    }
+                }
+            }
+        }
+        let fetched = fetch_key(config, domain)
+            .await
+            .filter(|x| x.domain == domain)
+            .ok_or(SignatureErr::UnknownKey)?;
+        let key = PublicKey::from_pem(fetched.algorithm, &fetched.public_key)
+            .ok_or(SignatureErr::UnknownKey)?;
+        conn.set_remote_key(
+            domain,
+            InstanceKey {
+                algorithm: fetched.algorithm,
+                public_key: fetched.public_key,
+                private_key: None,
+                fetched_at: Some(now),
+            },
+        )
+        .await;
+        self.cache(domain, key.clone(), now);
+        Ok((key, now))
+    }
+    fn cache(&self, domain: &str, key: PublicKey, fetched_at: i64) {
+        self.keys
+            .lock()
+            .expect("poisoned")
+            .insert(domain.to_string(), CachedKey { key, fetched_at });
+    }
+}
+
+/// the domain of the instance a key id belongs to
+fn key_domain(config: &Config, key_id: &str) -> Option<String> {
+    let url = Url::parse(key_id).ok()?;
+    let domain = match url.port() {
+        Some(port) => format!("{}:{port}", url.host_str()?),
+        None => url.host_str()?.to_string(),
+    };
+    (key_id == federation_url(config, &domain, KEY_PATH)).then_some(domain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_are_only_accepted_once() {
+        let mut seen = SeenSignatures::default();
+        assert!(seen.insert(b"a", 0));
+        assert!(seen.insert(b"b", 0));
+        assert!(!seen.insert(b"a", 1));
+        assert!(!seen.insert(b"b", 1));
+    }
+
+    #[test]
+    fn seen_signatures_are_forgotten_after_the_replay_window() {
+        let window = REPLAY_WINDOW.as_millis() as i64;
+        let mut seen = SeenSignatures::default();
+        assert!(seen.insert(b"a", 0));
+        assert!(seen.insert(b"b", window));
+        // kept for as long as a request with the same date could be accepted
+        assert!(!seen.insert(b"a", 2 * window - 1));
+        assert!(seen.insert(b"a", 2 * window));
+        assert!(!seen.insert(b"b", 2 * window));
+        assert_eq!(seen.signatures.len(), seen.expiries.len());
+    }
+}
@@ -0,0 +1,283 @@
+//! metadata extraction and thumbnail generation for uploaded images
+
+use std::io::Cursor;
+
+use image::{
+    codecs::jpeg::JpegEncoder, metadata::Orientation, DynamicImage, ImageDecoder, ImageFormat,
+    ImageReader, Limits,
+};
+
+/// thumbnails are generated to fit within each of these sizes when the image is larger
+pub const THUMBNAIL_SIZES: [u32; 2] = [256, 1024];
+/// images larger than this in either dimension are stored without being processed
+const MAX_DIMENSION: u32 = 16384;
+const THUMBNAIL_JPEG_QUALITY: u8 = 80;
+const BLURHASH_COMPONENTS: (u32, u32) = (4, 3);
+
+pub struct ImageInfo {
+    /// dimensions after the image's orientation has been applied
+    pub width: u32,
+    pub height: u32,
+    pub blurhash: Option<String>,
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+pub struct Thumbnail {
+    /// the size from [`THUMBNAIL_SIZES`] this thumbnail fits within
+    pub size: u32,
+    pub width: u32,
+    pub height: u32,
+    pub mime: mime::Mime,
+    pub data: Vec<u8>,
+}
+
+/// if the file is an image we strip metadata from and generate thumbnails for
+pub fn is_supported(mime: &mime::Mime) -> bool {
+    supported_format(mime).is_some()
+}
+
+fn supported_format(mime: &mime::Mime) -> Option<ImageFormat> {
+    match mime.essence_str() {
+        "image/png" => Some(ImageFormat::Png),
+        "image/jpeg" => Some(ImageFormat::Jpeg),
+        "image/gif" => Some(ImageFormat::Gif),
+        "image/webp" => Some(ImageFormat::WebP),
+        _ => None,
+    }
+}
+
+/// strips exif and xmp metadata, which among other things contains the location a
+/// photo was taken. jpegs keep their orientation.
+///
+/// returns none if there was nothing to remove
+pub fn strip_metadata(data: &[u8], mime: &mime::Mime) -> Option<Vec<u8>> {
+    match supported_format(mime)? {
+        ImageFormat::Jpeg => {
+            let orientation = reader(data, ImageFormat::Jpeg)
+                .into_decoder()
+                .ok()
+                .and_then(|mut x| x.orientation().ok())
+                .unwrap_or(Orientation::NoTransforms);
+            strip_jpeg(data, orientation)
+        }
+        ImageFormat::Png => strip_png(data),
+        ImageFormat::WebP => strip_webp(data),
+        _ => None,
+    }
+}
+
+/// get the dimensions, blurhash and thumbnails of an image.
+/// returns none if the image could not be decoded
+pub fn image_info(data: &[u8], mime: &mime::Mime) -> Option<ImageInfo> {
+    let mut decoder = reader(data, supported_format(mime)?).into_decoder().ok()?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder).ok()?;
+    image.apply_orientation(orientation);
+
+    let blurhash = {
+        let small = image.thumbnail(32, 32).to_rgba8();
+        blurhash::encode(
+            BLURHASH_COMPONENTS.0,
+            BLURHASH_COMPONENTS.1,
+            small.width(),
+            small.height(),
+            small.as_raw(),
+        )
+        .ok()
+    };
+    let thumbnails = THUMBNAIL_SIZES
+        .iter()
+        .filter(|size| image.width().max(image.height()) > **size)
+        .filter_map(|size| thumbnail(&image, *size))
+        .collect();
+
+    Some(ImageInfo {
+        width: image.width(),
+        height: image.height(),
+        blurhash,
+        thumbnails,
+    })
+}
+
+fn reader(data: &[u8], format: ImageFormat) -> ImageReader<Cursor<&[u8]>> {
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    reader.limits(limits);
+    reader
+}
+
+/// images with transparency become pngs, everything else becomes a jpeg
+fn thumbnail(image: &DynamicImage, size: u32) -> Option<Thumbnail> {
+    let resized = image.thumbnail(size, size);
+    let mut data = Vec::new();
+    let mime = match resized.color().has_alpha() {
+        true => {
+            resized
+                .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+                .ok()?;
+            mime::IMAGE_PNG
+        }
+        false => {
+            let encoder = JpegEncoder::new_with_quality(&mut data, THUMBNAIL_JPEG_QUALITY);
+            resized.to_rgb8().write_with_encoder(encoder).ok()?;
+            mime::IMAGE_JPEG
+        }
+    };
+    Some(Thumbnail {
+        size,
+        width: resized.width(),
+        height: resized.height(),
+        mime,
+        data,
+    })
+}
+
+/// removes exif and xmp app1 segments, if the image was rotated a minimal exif
+/// segment containing only the orientation takes the place of the original.
+/// returns none if nothing was removed or the jpeg could not be parsed
+fn strip_jpeg(data: &[u8], orientation: Orientation) -> Option<Vec<u8>> {
+    const EXIF: &[u8] = b"Exif\0\0";
+    const XMP: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(&data[..2]);
+    let mut orientation = match orientation {
+        Orientation::NoTransforms => None,
+        x => Some(x),
+    };
+    let mut removed = false;
+    let mut pos = 2;
+    loop {
+        if data.get(pos) != Some(&0xFF) {
+            return None;
+        }
+        let marker = *data.get(pos + 1)?;
+        // markers without a length
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) || marker == 0xFF {
+            let end = match marker {
+                0xFF => pos + 1,
+                _ => pos + 2,
+            };
+            output.extend_from_slice(&data[pos..end]);
+            pos = end;
+            continue;
+        }
+        // start of scan, everything after is image data
+        if marker == 0xDA || marker == 0xD9 {
+            output.extend_from_slice(&data[pos..]);
+            break;
+        }
+        let length = u16::from_be_bytes([*data.get(pos + 2)?, *data.get(pos + 3)?]) as usize;
+        if length < 2 {
+            return None;
+        }
+        let end = pos + 2 + length;
+        let segment = data.get(pos..end)?;
+        let payload = &segment[4..];
+        if marker == 0xE1 && (payload.starts_with(EXIF) || payload.starts_with(XMP)) {
+            removed = true;
+            if let Some(orientation) = orientation.take() {
+                output.extend_from_slice(&orientation_segment(orientation));
+            }
+        } else {
+            output.extend_from_slice(segment);
+        }
+        pos = end;
+    }
+    match removed {
+        true => Some(output),
+        false => None,
+    }
+}
+
+/// an app1 segment with a single ifd holding the orientation tag
+fn orientation_segment(orientation: Orientation) -> Vec<u8> {
+    let mut tiff: Vec<u8> = Vec::new();
+    // big endian header with the first ifd directly after it
+    tiff.extend_from_slice(b"MM\0\x2A\0\0\0\x08");
+    // one entry, tag 0x0112 of type short with a count of 1
+    tiff.extend_from_slice(&[0x00, 0x01, 0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01]);
+    tiff.extend_from_slice(&[0x00, orientation.to_exif(), 0x00, 0x00]);
+    // no next ifd
+    tiff.extend_from_slice(&[0x00; 4]);
+
+    let length = (2 + 6 + tiff.len()) as u16;
+    let mut segment = vec![0xFF, 0xE1];
+    segment.extend_from_slice(&length.to_be_bytes());
+    segment.extend_from_slice(b"Exif\0\0");
+    segment.extend_from_slice(&tiff);
+    segment
+}
+
+/// removes exif and text chunks, which is where xmp and exif stored
+/// by older software end up
+fn strip_png(data: &[u8]) -> Option<Vec<u8>> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    const REMOVED: [&[u8]; 4] = [b"eXIf", b"tEXt", b"zTXt", b"iTXt"];
+
+    if !data.starts_with(SIGNATURE) {
+        return None;
+    }
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(SIGNATURE);
+    let mut removed = false;
+    let mut pos = SIGNATURE.len();
+    while pos < data.len() {
+        let length = u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?) as usize;
+        // length, type, data and crc
+        let end = pos.checked_add(12 + length)?;
+        let chunk = data.get(pos..end)?;
+        match REMOVED.contains(&&chunk[4..8]) {
+            true => removed = true,
+            false => output.extend_from_slice(chunk),
+        }
+        pos = end;
+    }
+    match removed {
+        true => Some(output),
+        false => None,
+    }
+}
+
+/// removes exif and xmp chunks and clears their flags in the extended header
+fn strip_webp(data: &[u8]) -> Option<Vec<u8>> {
+    const VP8X_XMP: u8 = 0x04;
+    const VP8X_EXIF: u8 = 0x08;
+
+    if data.len() < 12 || &data[..4] != b"RIFF" || &data[8..12] != b"WEBP" {
+        return None;
+    }
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(&data[..12]);
+    let mut removed = false;
+    let mut pos = 12;
+    while pos < data.len() {
+        let length = u32::from_le_bytes(data.get(pos + 4..pos + 8)?.try_into().ok()?) as usize;
+        // chunks are padded to an even length
+        let end = pos.checked_add(8 + length + (length & 1))?.min(data.len());
+        let chunk = data.get(pos..end)?;
+        match &chunk[..4] {
+            b"EXIF" | b"XMP " => removed = true,
+            b"VP8X" => {
+                let start = output.len();
+                output.extend_from_slice(chunk);
+                if let Some(flags) = output.get_mut(start + 8) {
+                    *flags &= !(VP8X_XMP | VP8X_EXIF);
+                }
+            }
+            _ => output.extend_from_slice(chunk),
+        }
+        pos = end;
+    }
+    if !removed {
+        return None;
+    }
+    let riff_size = (output.len() - 8) as u32;
+    output[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Some(output)
+}
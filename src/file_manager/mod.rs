@@ -1,57 +1,318 @@
-use std::{fs::File, io::{Read, Write}, str::FromStr};
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom, Write},
+    str::FromStr,
+};
 
+use actix_files::NamedFile;
 use actix_multipart::form::tempfile::TempFile;
-use actix_web::web::Data;
+use actix_web::{
+    http::header::{self, ContentDisposition, DispositionParam, DispositionType, HeaderValue},
+    web::{self, Data},
+    HttpRequest, HttpResponse,
+};
 use mime::Mime;
 use mime2ext::mime2ext;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::db::pg_conn::PgConn;
+use crate::db::{
+    curr_time::get_current_time,
+    pg_conn::PgConn,
+    types::{file::DbFile, file_thumbnail::FileThumbnail},
+};
+
+use self::{media::ImageInfo, s3::S3Bucket};
+
+pub mod media;
+pub mod s3;
+
+/// files never change once uploaded so clients may cache them for as long as they
+/// want. they require authorization or a per user file token to view so shared
+/// caches may not store them
+const CACHE_CONTROL: &str = "private, max-age=31536000, immutable";
+
+/// if a file of this type can be displayed by browsers without running anything
+/// from it. anything else, such as html or svg, is sent as an attachment so it
+/// can't run on our origin
+fn displays_inline(content_type: &Mime) -> bool {
+    match content_type.type_() {
+        mime::IMAGE => matches!(
+            content_type.subtype().as_str(),
+            "png" | "jpeg" | "gif" | "webp" | "avif" | "bmp"
+        ),
+        mime::VIDEO | mime::AUDIO => true,
+        mime::TEXT => content_type.subtype() == mime::PLAIN,
+        _ => false,
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(tag = "type", content = "args")]
 pub enum FileManager {
     Local {
-        base_path: String
-    }
+        base_path: String,
+    },
+    /// any s3 compatible object storage
+    S3(S3Bucket),
 }
 
 impl FileManager {
-    pub async fn create_user_file(&self, conn: Data<PgConn>, file: TempFile, owner: Uuid, description: Option<String>) -> Result<Uuid, ()> {
-        let content_type = file.content_type.clone().unwrap_or(Mime::from_str("application/octet-stream").unwrap());
-        
-        let file_id = conn.register_file(Some(owner), description, None, content_type.clone()).await;
-        
+    /// store a file uploaded by a user. images have their metadata stripped and
+    /// get thumbnails generated
+    pub async fn create_user_file(
+        &self,
+        conn: Data<PgConn>,
+        file: TempFile,
+        owner: Uuid,
+        description: Option<String>,
+    ) -> Result<(DbFile, Vec<FileThumbnail>), ()> {
+        let content_type = file
+            .content_type
+            .clone()
+            .unwrap_or(Mime::from_str("application/octet-stream").unwrap());
+
+        let (file, image) = match media::is_supported(&content_type) {
+            true => {
+                let content_type = content_type.clone();
+                web::block(move || FileManager::process_image(file, &content_type))
+                    .await
+                    .map_err(|_| ())?
+                    .map_err(|_| ())?
+            }
+            false => (file, None),
+        };
+
+        let file_id = Uuid::now_v7();
         let extension = FileManager::get_extension(content_type.clone());
-        let path = format!("{}/files/{file_id}/{file_id}{extension}", owner.as_simple().to_string());
-        self.write_file(path, file)?;
+        let directory = format!("{}/files/{file_id}", owner.as_simple());
+        let path = format!("{directory}/{file_id}{extension}");
+        let file_name = file.file_name.clone();
+        let size = file.size as i64;
+        self.write_file(path.clone(), file, &content_type).await?;
+
+        let mut thumbnails = Vec::new();
+        if let Some(image) = &image {
+            for thumbnail in &image.thumbnails {
+                let extension = FileManager::get_extension(thumbnail.mime.clone());
+                let path = format!("{directory}/{}{extension}", thumbnail.size);
+                self.write_bytes(path.clone(), thumbnail.data.clone(), &thumbnail.mime)
+                    .await?;
+                thumbnails.push(FileThumbnail {
+                    file_id,
+                    size: thumbnail.size as i64,
+                    path,
+                    mime: thumbnail.mime.essence_str().to_string(),
+                    width: thumbnail.width as i64,
+                    height: thumbnail.height as i64,
+                    file_size: thumbnail.data.len() as i64,
+                });
+            }
+        }
 
-        Ok(file_id)
+        let file = conn
+            .register_file(
+                DbFile {
+                    id: file_id,
+                    owner: Some(owner),
+                    description,
+                    path,
+                    mime: content_type.essence_str().to_string(),
+                    size,
+                    file_name,
+                    created: get_current_time(),
+                    width: image.as_ref().map(|x| x.width as i64),
+                    height: image.as_ref().map(|x| x.height as i64),
+                    blurhash: image.and_then(|x| x.blurhash),
+                },
+                thumbnails,
+            )
+            .await;
+        Ok(file)
+    }
+    /// remove a file and its thumbnails from storage and the database
+    pub async fn delete_user_file(&self, conn: Data<PgConn>, file: DbFile) -> Result<(), ()> {
+        let thumbnails = conn.get_file_thumbnails(file.id).await;
+        conn.delete_file(file.id).await;
+        for thumbnail in thumbnails {
+            self.delete_file(&thumbnail.path).await?;
+        }
+        self.delete_file(&file.path).await
+    }
+    /// respond with the file's content or one of its thumbnails, supports range
+    /// requests and conditional requests
+    pub async fn serve_file(
+        &self,
+        req: &HttpRequest,
+        file: &DbFile,
+        thumbnail: Option<&FileThumbnail>,
+    ) -> Result<HttpResponse, ()> {
+        let (path, mime) = match thumbnail {
+            Some(thumbnail) => (&thumbnail.path, &thumbnail.mime),
+            None => (&file.path, &file.mime),
+        };
+        let content_type = Mime::from_str(mime).unwrap_or(mime::APPLICATION_OCTET_STREAM);
+        let mut disposition = ContentDisposition {
+            disposition: match displays_inline(&content_type) {
+                true => DispositionType::Inline,
+                false => DispositionType::Attachment,
+            },
+            parameters: vec![],
+        };
+        if let (Some(file_name), None) = (&file.file_name, thumbnail) {
+            disposition
+                .parameters
+                .push(DispositionParam::Filename(file_name.clone()));
+        }
+
+        let mut response = match self {
+            FileManager::Local { base_path } => {
+                let disk_path = format!("{base_path}/{path}");
+                NamedFile::open_async(disk_path)
+                    .await
+                    .map_err(|_| ())?
+                    .set_content_type(content_type)
+                    .set_content_disposition(disposition)
+                    .into_response(req)
+            }
+            FileManager::S3(bucket) => {
+                let mut response = bucket.serve_object(path, req).await?;
+                let headers = response.headers_mut();
+                headers.insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_str(content_type.as_ref()).map_err(|_| ())?,
+                );
+                headers.insert(
+                    header::CONTENT_DISPOSITION,
+                    HeaderValue::from_str(&disposition.to_string()).map_err(|_| ())?,
+                );
+                response
+            }
+        };
+        let headers = response.headers_mut();
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(CACHE_CONTROL),
+        );
+        // browsers must use the content type we send instead of guessing one
+        headers.insert(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        );
+        Ok(response)
     }
     fn get_extension(content_type: Mime) -> String {
         let extension = mime2ext(content_type);
-        let extension = match extension {
-            Some(x) => ".".to_owned()+x,
+        match extension {
+            Some(x) => ".".to_owned() + x,
             None => "".to_owned(),
-        };
-        extension
+        }
     }
-    fn write_file(&self, path: String, uploaded_file: TempFile) -> Result<(),()> {
+    async fn write_file(
+        &self,
+        path: String,
+        uploaded_file: TempFile,
+        content_type: &Mime,
+    ) -> Result<(), ()> {
         match self {
             FileManager::Local { base_path } => {
                 let disk_path = format!("{base_path}/{path}");
-                FileManager::write_file_local(disk_path, uploaded_file).map_err(|_| ())?;
-                Ok(())
-            },
+                web::block(move || FileManager::write_file_local(disk_path, uploaded_file))
+                    .await
+                    .map_err(|_| ())?
+                    .map_err(|_| ())
+            }
+            FileManager::S3(bucket) => {
+                let size = uploaded_file.size as u64;
+                bucket
+                    .put_file(
+                        &path,
+                        uploaded_file.file.into_file(),
+                        size,
+                        content_type.essence_str(),
+                    )
+                    .await
+            }
+        }
+    }
+    async fn write_bytes(
+        &self,
+        path: String,
+        data: Vec<u8>,
+        content_type: &Mime,
+    ) -> Result<(), ()> {
+        match self {
+            FileManager::Local { base_path } => {
+                let disk_path = format!("{base_path}/{path}");
+                web::block(move || FileManager::write_bytes_local(disk_path, data))
+                    .await
+                    .map_err(|_| ())?
+                    .map_err(|_| ())
+            }
+            FileManager::S3(bucket) => {
+                let size = data.len() as u64;
+                bucket
+                    .put_object(&path, data.into(), size, content_type.essence_str())
+                    .await
+            }
+        }
+    }
+    async fn delete_file(&self, path: &str) -> Result<(), ()> {
+        match self {
+            FileManager::Local { base_path } => {
+                let disk_path = format!("{base_path}/{path}");
+                web::block(move || FileManager::delete_file_local(disk_path))
+                    .await
+                    .map_err(|_| ())?
+                    .map_err(|_| ())
+            }
+            FileManager::S3(bucket) => bucket.delete_object(path).await,
         }
     }
-    fn write_file_local(disk_path: String, mut uploaded_file: TempFile) -> std::io::Result<()> {
+    fn write_file_local(disk_path: String, mut uploaded_file: TempFile) -> io::Result<()> {
+        if let Some(parent) = std::path::Path::new(&disk_path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
         let mut file = File::create(disk_path)?;
-        let mut buffer = Vec::new();
-        uploaded_file.file.read_to_end(&mut buffer)?;
-        file.write_all(&buffer)?;
-        file.flush()?;
+        io::copy(&mut uploaded_file.file, &mut file)?;
+        file.sync_all()?;
+        Ok(())
+    }
+    fn delete_file_local(disk_path: String) -> io::Result<()> {
+        std::fs::remove_file(&disk_path)?;
+        // each file gets its own directory, this only succeeds once it's empty
+        if let Some(parent) = std::path::Path::new(&disk_path).parent() {
+            let _ = std::fs::remove_dir(parent);
+        }
         Ok(())
     }
-}
\ No newline at end of file
+    fn write_bytes_local(disk_path: String, data: Vec<u8>) -> io::Result<()> {
+        if let Some(parent) = std::path::Path::new(&disk_path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = File::create(disk_path)?;
+        file.write_all(&data)?;
+        file.sync_all()?;
+        Ok(())
+    }
+    /// strip the metadata of an uploaded image in place and get its info
+    fn process_image(
+        mut uploaded_file: TempFile,
+        content_type: &Mime,
+    ) -> io::Result<(TempFile, Option<ImageInfo>)> {
+        let file = uploaded_file.file.as_file_mut();
+        let mut data = Vec::with_capacity(uploaded_file.size);
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_end(&mut data)?;
+        if let Some(stripped) = media::strip_metadata(&data, content_type) {
+            data = stripped;
+            file.set_len(0)?;
+            file.seek(SeekFrom::Start(0))?;
+            file.write_all(&data)?;
+            uploaded_file.size = data.len();
+        }
+        file.seek(SeekFrom::Start(0))?;
+        let info = media::image_info(&data, content_type);
+        Ok((uploaded_file, info))
+    }
+}
@@ -3,6 +3,6 @@ pub mod config;
 pub mod cryptography;
 pub mod db;
 pub mod federation;
+pub mod file_manager;
 pub mod live_server;
 pub mod routes;
-pub mod file_manager;
\ No newline at end of file
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::routes::api::types::api_presence::Status;
+
+/// messages sent by the client over the websocket once it has authenticated
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum ClientMsg {
+    /// the user is typing in a room. should be repeated every few seconds
+    /// while they are still typing as typing indicators expire
+    TypingStart {
+        room: Uuid,
+    },
+    TypingStop {
+        room: Uuid,
+    },
+    /// applies to every connection of the user and lasts until changed or the
+    /// server restarts
+    SetPresence {
+        status: Status,
+        custom_status: Option<String>,
+    },
+    /// start receiving full events for rooms the client is viewing. events for
+    /// new messages in other rooms are sent as
+    /// [`super::socket_msg::SocketMsg::UnreadMessage`], other events for them are
+    /// not sent. responded to with [`super::socket_msg::SocketMsg::Subscribed`]
+    Subscribe {
+        rooms: Vec<Uuid>,
+    },
+    Unsubscribe {
+        rooms: Vec<Uuid>,
+    },
+    /// mark a message and everything before it in its room as read, the user's
+    /// connections receive a [`super::socket_msg::SocketMsg::ReadStateUpdated`]
+    Ack {
+        message: Uuid,
+    },
+    /// get the presence of users sharing a community or room with you, each is
+    /// sent back as a [`super::socket_msg::SocketMsg::PresenceUpdate`]
+    GetPresence {
+        users: Vec<Uuid>,
+    },
+}
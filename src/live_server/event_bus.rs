@@ -0,0 +1,245 @@
+//! a bus between bayou instances sharing a database so that events sent on
+//! one instance reach the websockets connected to every other instance.
+//! events are published with postgres `NOTIFY`, those too large to fit in a
+//! notification are stored in the database and fetched by each instance
+
+use std::time::Duration;
+
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    spawn,
+    sync::mpsc,
+    time::{interval, sleep},
+};
+use tokio_postgres::{AsyncMessage, NoTls, Notification};
+use uuid::Uuid;
+
+use crate::{db::pg_conn::PgConn, routes::api::types::api_presence::Status};
+
+use super::server::{ChatServerHandle, MessageTarget, Msg, RoomId, UserId, WeakChatServerHandle};
+
+/// the postgres channel events are published on
+const CHANNEL: &str = "bayou_live_events";
+/// postgres rejects notification payloads of 8000 bytes or more
+const MAX_NOTIFY_PAYLOAD: usize = 7999;
+/// how long stored events are kept for instances to fetch
+const STORED_EVENT_LIFETIME: Duration = Duration::from_secs(60);
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+/// how long to wait before reconnecting when the listening connection is lost
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// an event for the chat server of every instance to deliver to their
+/// local connections, messages are serialized once by the publisher
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum BusEvent {
+    Message {
+        recipients: MessageTarget,
+        msg: Msg,
+        /// see [`super::socket_msg::SocketMsg::is_ephemeral`]
+        #[serde(default)]
+        ephemeral: bool,
+    },
+    RoomMessage {
+        room: RoomId,
+        members: Vec<UserId>,
+        msg: Msg,
+        unsubscribed_msg: Option<Msg>,
+        #[serde(default)]
+        ephemeral: bool,
+    },
+    /// the sessions connected to the publishing instance
+    Presence {
+        users: Vec<UserSessions>,
+        /// every user with sessions on the instance is included
+        full: bool,
+    },
+    /// ask every instance to publish the presence of all their sessions
+    PresenceRequest,
+}
+
+/// how many sessions a user has on an instance and the status they chose
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UserSessions {
+    pub user: UserId,
+    pub sessions: usize,
+    pub status: Status,
+    pub custom_status: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+enum Payload {
+    Event(BusEvent),
+    /// the id of a stored event containing the whole envelope
+    Stored(i64),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Envelope {
+    /// the instance that published the event, it has already
+    /// delivered the event to its own connections
+    origin: Uuid,
+    payload: Payload,
+}
+
+/// publishes events to the other instances
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    publisher: mpsc::UnboundedSender<BusEvent>,
+}
+
+impl EventBus {
+    /// start publishing and listening for events, events published by other
+    /// instances are passed to the chat server
+    pub fn start(
+        conn: PgConn,
+        pg_config: tokio_postgres::Config,
+        server: ChatServerHandle,
+    ) -> Self {
+        let origin = Uuid::new_v4();
+        let (publisher, events) = mpsc::unbounded_channel();
+        let bus = EventBus { publisher };
+        spawn(publish(conn.clone(), origin, events));
+        spawn(listen(
+            conn,
+            pg_config,
+            origin,
+            server.downgrade(),
+            bus.clone(),
+        ));
+        bus
+    }
+    /// events are published in the order they are given
+    pub fn publish(&self, event: BusEvent) {
+        let _ = self.publisher.send(event);
+    }
+}
+
+async fn publish(conn: PgConn, origin: Uuid, mut events: mpsc::UnboundedReceiver<BusEvent>) {
+    let mut cleanup = interval(CLEANUP_INTERVAL);
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+                // published in their own task so a database error doesn't stop the
+                // publisher, awaiting it keeps the events in order
+                let conn = conn.clone();
+                let _ = spawn(async move { publish_event(&conn, origin, event).await }).await;
+            }
+            _ = cleanup.tick() => {
+                let conn = conn.clone();
+                spawn(async move {
+                    conn.delete_old_live_events(STORED_EVENT_LIFETIME.as_millis() as i64)
+                        .await
+                });
+            }
+        }
+    }
+}
+
+async fn publish_event(conn: &PgConn, origin: Uuid, event: BusEvent) {
+    let envelope = Envelope {
+        origin,
+        payload: Payload::Event(event),
+    };
+    let notification = serde_json::to_string(&envelope).expect("failed to serialize");
+    if notification.len() <= MAX_NOTIFY_PAYLOAD {
+        conn.notify(CHANNEL, &notification).await;
+        return;
+    }
+    let stored = conn.create_live_event(&notification).await;
+    let envelope = Envelope {
+        origin,
+        payload: Payload::Stored(stored.event_id),
+    };
+    let notification = serde_json::to_string(&envelope).expect("failed to serialize");
+    conn.notify(CHANNEL, &notification).await;
+}
+
+/// listen for events from other instances for as long as the server runs,
+/// reconnecting whenever the connection is lost
+async fn listen(
+    conn: PgConn,
+    pg_config: tokio_postgres::Config,
+    origin: Uuid,
+    server: WeakChatServerHandle,
+    bus: EventBus,
+) {
+    let mut reconnecting = false;
+    loop {
+        let result = spawn(listen_connection(
+            conn.clone(),
+            pg_config.clone(),
+            origin,
+            server.clone(),
+            bus.clone(),
+            reconnecting,
+        ))
+        .await;
+        match result {
+            Ok(Ok(())) => eprintln!("live event listener disconnected"),
+            Ok(Err(x)) => eprintln!("live event listener disconnected: {x}"),
+            Err(x) => eprintln!("live event listener failed: {x}"),
+        }
+        reconnecting = true;
+        sleep(RECONNECT_INTERVAL).await;
+    }
+}
+
+async fn listen_connection(
+    conn: PgConn,
+    pg_config: tokio_postgres::Config,
+    origin: Uuid,
+    server: WeakChatServerHandle,
+    bus: EventBus,
+    reconnecting: bool,
+) -> Result<(), tokio_postgres::Error> {
+    let (client, mut connection) = pg_config.connect(NoTls).await?;
+    let (tx, mut notifications) = mpsc::unbounded_channel::<Notification>();
+    // notifications are only received while the connection is polled
+    let connection = spawn(async move {
+        let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+        while let Some(message) = messages.next().await {
+            if let AsyncMessage::Notification(notification) = message? {
+                let _ = tx.send(notification);
+            }
+        }
+        Ok(())
+    });
+    client.batch_execute(&format!("LISTEN {CHANNEL};")).await?;
+    // learn which users are connected to the other instances
+    bus.publish(BusEvent::PresenceRequest);
+    if reconnecting {
+        // events published while we were disconnected are gone
+        server.resync_all();
+    }
+
+    while let Some(notification) = notifications.recv().await {
+        let Ok(envelope) = serde_json::from_str::<Envelope>(notification.payload()) else {
+            continue;
+        };
+        if envelope.origin == origin {
+            continue;
+        }
+        let event = match envelope.payload {
+            Payload::Event(event) => event,
+            Payload::Stored(event_id) => {
+                let Some(stored) = conn.get_live_event(event_id).await else {
+                    continue;
+                };
+                match serde_json::from_str::<Envelope>(&stored.payload) {
+                    Ok(Envelope {
+                        payload: Payload::Event(event),
+                        ..
+                    }) => event,
+                    _ => continue,
+                }
+            }
+        };
+        server.deliver_remote(envelope.origin, event);
+    }
+    drop(client);
+    connection.await.unwrap_or(Ok(()))
+}
@@ -0,0 +1,220 @@
+//! numbering of the events sent to each user so that clients can resume
+//! after reconnecting and have the events they missed replayed
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use super::server::RoomId;
+
+/// how many of the latest events are kept for each user
+pub const REPLAY_BUFFER_SIZE: usize = 512;
+
+/// how long events are kept for a user after their last connection closes
+pub const RESUME_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+pub struct BufferedEvent {
+    pub seq: u64,
+    /// room events are sent to connections depending on their subscriptions
+    pub room: Option<RoomId>,
+    pub msg: String,
+    /// sent to connections not subscribed to the room
+    pub unsubscribed_msg: Option<String>,
+}
+
+impl BufferedEvent {
+    /// the form of the event a connection should receive, if any
+    pub fn for_conn(&self, is_subscribed: impl Fn(&RoomId) -> bool) -> Option<&str> {
+        msg_for_conn(
+            self.room,
+            &self.msg,
+            self.unsubscribed_msg.as_deref(),
+            is_subscribed,
+        )
+    }
+}
+
+/// the form of an event a connection should receive, if any. room events are
+/// sent depending on the connection's subscriptions
+pub fn msg_for_conn<'a>(
+    room: Option<RoomId>,
+    msg: &'a str,
+    unsubscribed_msg: Option<&'a str>,
+    is_subscribed: impl Fn(&RoomId) -> bool,
+) -> Option<&'a str> {
+    match &room {
+        Some(room) if !is_subscribed(room) => unsubscribed_msg,
+        _ => Some(msg),
+    }
+}
+
+pub struct EventStream {
+    next_seq: u64,
+    events: VecDeque<BufferedEvent>,
+    /// when the user's last connection closed, none while they are connected
+    pub disconnected: Option<Instant>,
+}
+
+impl Default for EventStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventStream {
+    pub fn new() -> Self {
+        // sequence numbers start from the current time so they keep increasing between
+        // streams. clients resuming an expired stream or from before a restart will
+        // always be behind the buffer and be told to resync. this stays below 2^53 so
+        // javascript clients don't lose precision
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_millis() as u64)
+            .unwrap_or_default();
+        EventStream {
+            next_seq: millis << 10,
+            events: VecDeque::new(),
+            disconnected: None,
+        }
+    }
+    /// the sequence number of the latest event
+    pub fn latest_seq(&self) -> u64 {
+        self.next_seq - 1
+    }
+    /// number an event and buffer it, the oldest event is dropped once the buffer is full
+    pub fn push(
+        &mut self,
+        room: Option<RoomId>,
+        msg: &str,
+        unsubscribed_msg: Option<&str>,
+    ) -> &BufferedEvent {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        if self.events.len() >= REPLAY_BUFFER_SIZE {
+            self.events.pop_front();
+        }
+        self.events.push_back(BufferedEvent {
+            seq,
+            room,
+            msg: with_seq(seq, msg),
+            unsubscribed_msg: unsubscribed_msg.map(|x| with_seq(seq, x)),
+        });
+        self.events.back().expect("event was just buffered")
+    }
+    /// every event after `last_seq`, none if any of them are no longer buffered
+    pub fn since(&self, last_seq: u64) -> Option<impl Iterator<Item = &BufferedEvent>> {
+        let oldest = self.events.front().map_or(self.next_seq, |x| x.seq);
+        if last_seq.checked_add(1)? < oldest || last_seq >= self.next_seq {
+            return None;
+        }
+        Some(self.events.iter().filter(move |x| x.seq > last_seq))
+    }
+    pub fn expired(&self) -> bool {
+        self.disconnected
+            .is_some_and(|x| x.elapsed() > RESUME_WINDOW)
+    }
+}
+
+/// add the sequence number to a serialized [`super::socket_msg::SocketMsg`] rather
+/// than serializing it again for every user
+fn with_seq(seq: u64, msg: &str) -> String {
+    // socket messages always serialize to a non empty object
+    format!("{{\"seq\":{seq},{}", &msg[1..])
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn pushed(stream: &mut EventStream, count: usize) -> Vec<u64> {
+        (0..count)
+            .map(|x| stream.push(None, &format!("{{\"n\":{x}}}"), None).seq)
+            .collect()
+    }
+
+    #[test]
+    fn events_are_numbered_in_order() {
+        let mut stream = EventStream::new();
+        let seqs = pushed(&mut stream, 3);
+        assert!(seqs.windows(2).all(|x| x[1] == x[0] + 1));
+        assert_eq!(stream.latest_seq(), seqs[2]);
+
+        let event = stream.push(None, r#"{"a":1}"#, Some(r#"{"b":2}"#));
+        assert_eq!(event.msg, format!(r#"{{"seq":{},"a":1}}"#, event.seq));
+        assert_eq!(
+            event.unsubscribed_msg.as_deref(),
+            Some(format!(r#"{{"seq":{},"b":2}}"#, event.seq).as_str())
+        );
+    }
+
+    #[test]
+    fn new_streams_continue_after_old_ones() {
+        let mut old = EventStream::new();
+        let seqs = pushed(&mut old, 10);
+        std::thread::sleep(Duration::from_millis(2));
+        let new = EventStream::new();
+        assert!(new.latest_seq() >= seqs[9]);
+        // resuming an old stream from before a restart needs a resync
+        assert!(new.since(seqs[9]).is_none());
+        assert!(new.latest_seq() < 1 << 53);
+    }
+
+    #[test]
+    fn replays_events_after_the_last_seen() {
+        let mut stream = EventStream::new();
+        let before = stream.latest_seq();
+        let seqs = pushed(&mut stream, 5);
+
+        let replayed: Vec<u64> = stream.since(seqs[1]).unwrap().map(|x| x.seq).collect();
+        assert_eq!(replayed, seqs[2..]);
+        let replayed: Vec<u64> = stream.since(before).unwrap().map(|x| x.seq).collect();
+        assert_eq!(replayed, seqs);
+        assert_eq!(stream.since(seqs[4]).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn dropped_or_unknown_events_require_a_resync() {
+        let mut stream = EventStream::new();
+        let before = stream.latest_seq();
+        let seqs = pushed(&mut stream, REPLAY_BUFFER_SIZE + 2);
+
+        // the first two events were dropped from the buffer
+        assert!(stream.since(before).is_none());
+        assert!(stream.since(seqs[0]).is_none());
+        assert_eq!(stream.since(seqs[1]).unwrap().count(), REPLAY_BUFFER_SIZE);
+        // events that were never sent
+        assert!(stream.since(stream.latest_seq() + 1).is_none());
+        assert!(stream.since(u64::MAX).is_none());
+    }
+
+    #[test]
+    fn streams_expire_after_the_resume_window() {
+        let mut stream = EventStream::new();
+        assert!(!stream.expired());
+        stream.disconnected = Some(Instant::now());
+        assert!(!stream.expired());
+        stream.disconnected = Instant::now().checked_sub(RESUME_WINDOW + Duration::from_secs(1));
+        assert!(stream.expired());
+    }
+
+    #[test]
+    fn room_events_depend_on_subscriptions() {
+        let room = Uuid::now_v7();
+        let subscribed = |x: &RoomId| *x == room;
+        let unsubscribed = |_: &RoomId| false;
+
+        assert_eq!(msg_for_conn(None, "a", None, unsubscribed), Some("a"));
+        assert_eq!(
+            msg_for_conn(Some(room), "a", Some("b"), subscribed),
+            Some("a")
+        );
+        assert_eq!(
+            msg_for_conn(Some(room), "a", Some("b"), unsubscribed),
+            Some("b")
+        );
+        assert_eq!(msg_for_conn(Some(room), "a", None, unsubscribed), None);
+    }
+}
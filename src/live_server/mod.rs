@@ -1,3 +1,6 @@
+pub mod client_msg;
+pub mod event_bus;
+pub mod event_stream;
 pub mod server;
 pub mod socket_handler;
 pub mod socket_msg;
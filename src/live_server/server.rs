@@ -1,19 +1,40 @@
 use std::{
     collections::{HashMap, HashSet},
     io,
+    time::{Duration, Instant},
 };
 
-use tokio::sync::{mpsc, oneshot};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::interval,
+};
 use uuid::Uuid;
 
-use super::socket_msg::SocketMsg;
+use crate::routes::api::types::api_presence::{ApiPresence, PresenceStatus, Status};
+
+use super::{
+    event_bus::{BusEvent, EventBus, UserSessions},
+    event_stream::{msg_for_conn, EventStream},
+    socket_msg::SocketMsg,
+};
 
 pub type ConnId = Uuid;
 pub type RoomId = Uuid;
 pub type UserId = Uuid;
 
+/// the most rooms a single connection may be subscribed to at once
+pub const MAX_SUBSCRIPTIONS: usize = 50;
+
+/// how often event streams of users that disconnected are checked for expiry
+const STREAM_CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+/// how often the sessions connected to this instance are published to the others
+const PRESENCE_INTERVAL: Duration = Duration::from_secs(30);
+/// sessions of instances that stop publishing them are forgotten after this
+const REMOTE_SESSIONS_EXPIRY: Duration = Duration::from_secs(90);
+
 /// serialized message to be sent to the client
-type Msg = String;
+pub type Msg = String;
 
 impl From<SocketMsg> for Msg {
     fn from(value: SocketMsg) -> Self {
@@ -27,6 +48,7 @@ impl From<SocketMsg> for Msg {
 /// should all be handled before the messages come here
 /// since the message server is just effectively
 /// single threadded for now
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum MessageTarget {
     All,
     List(Vec<UserId>),
@@ -36,34 +58,117 @@ pub enum Command {
     Connect {
         conn_sender: mpsc::UnboundedSender<Msg>,
         user: UserId,
+        /// rooms to subscribe to before any events are sent
+        subscribe: Option<Vec<RoomId>>,
+        /// the last event the client received before reconnecting,
+        /// every event after it is replayed
+        last_seq: Option<u64>,
         /// used to pass the conn id back to the websocket
         /// handler once we have registered them, this is needed
         /// so that they may send a disconnect when they are
-        /// disconnected
-        response_handle: oneshot::Sender<ConnId>,
+        /// disconnected. also contains the user's presence if
+        /// they just came online
+        response_handle: oneshot::Sender<(ConnId, Option<ApiPresence>)>,
     },
     Disconnect {
         user: UserId,
         conn: ConnId,
+        /// contains the user's presence if they just went offline
+        response_handle: oneshot::Sender<Option<ApiPresence>>,
+    },
+    SetPresence {
+        user: UserId,
+        status: Status,
+        custom_status: Option<String>,
+        /// contains the user's new presence if it visibly changed
+        response_handle: oneshot::Sender<Option<ApiPresence>>,
+    },
+    GetPresence {
+        users: Vec<UserId>,
+        response_handle: oneshot::Sender<Vec<ApiPresence>>,
+    },
+    Subscribe {
+        conn: ConnId,
+        rooms: Vec<RoomId>,
+        /// contains all rooms the connection is subscribed to
+        response_handle: oneshot::Sender<Vec<RoomId>>,
+    },
+    Unsubscribe {
+        conn: ConnId,
+        rooms: Vec<RoomId>,
+        /// contains all rooms the connection is subscribed to
+        response_handle: oneshot::Sender<Vec<RoomId>>,
     },
     BroadcastMessage {
-        msg: SocketMsg,
+        msg: Msg,
         recipients: MessageTarget,
+        /// see [`SocketMsg::is_ephemeral`]
+        ephemeral: bool,
+        /// used to notify upon completion
+        response_handle: oneshot::Sender<()>,
+    },
+    /// send a message to the connections of the given members that are
+    /// subscribed to the room
+    BroadcastRoomMessage {
+        room: RoomId,
+        msg: Msg,
+        /// sent to connections of members that are not subscribed to the room
+        unsubscribed_msg: Option<Msg>,
+        members: Vec<UserId>,
+        ephemeral: bool,
         /// used to notify upon completion
         response_handle: oneshot::Sender<()>,
-    }, // todo create a join room command for room status information
-       // (users online and their statusses, currently typing etc)
+    },
+    /// an event published by another instance
+    Remote { origin: Uuid, event: BusEvent },
+    /// publish the presence of local sessions on the bus
+    UseEventBus(EventBus),
+    /// tell every connection to resync, used when events from
+    /// other instances may have been missed
+    ResyncAll,
+}
+
+/// the status a user has chosen, their presence is derived from this
+/// and whether they have any sessions
+#[derive(Debug, Clone, Default)]
+struct UserStatus {
+    status: Status,
+    custom_status: Option<String>,
+}
+
+/// the sessions connected to another instance sharing the database
+struct RemoteSessions {
+    /// session counts of the users with any
+    users: HashMap<UserId, usize>,
+    refreshed: Instant,
 }
 
 /// this is largely based off https://github.com/actix/examples/blob/master/websockets/chat-actorless/src/server.rs
-/// down the road we're going to need to figure out how to better parallelize it
+/// down the road we're going to need to figure out how to better parallelize it.
+/// each instance runs its own chat server for the websockets connected to it,
+/// broadcasts reach the other instances through the [`EventBus`], as do the
+/// session counts and statuses that presence is derived from
 pub struct ChatServer {
     /// all active websocket connections
     sessions: HashMap<ConnId, mpsc::UnboundedSender<Msg>>,
     /// used to get sessions spawned by the user, if none exist the user
     /// should be removed from this map
     user_sessions: HashMap<UserId, HashSet<ConnId>>,
+    /// statuses are kept after users disconnect so that they
+    /// persist between sessions until the server restarts
+    user_statuses: HashMap<UserId, UserStatus>,
+    /// sessions connected to each other instance, keyed by the instance
+    remote_sessions: HashMap<Uuid, RemoteSessions>,
+    /// connections viewing each room, rooms without any should be removed
+    room_subscribers: HashMap<RoomId, HashSet<ConnId>>,
+    /// rooms each connection is subscribed to, used to clean up
+    /// [`ChatServer::room_subscribers`] when they disconnect
+    conn_subscriptions: HashMap<ConnId, HashSet<RoomId>>,
+    /// numbered events for each user that is connected or
+    /// disconnected within [`super::event_stream::RESUME_WINDOW`]
+    streams: HashMap<UserId, EventStream>,
     cmd_reciever: mpsc::UnboundedReceiver<Command>,
+    bus: Option<EventBus>,
 }
 
 impl ChatServer {
@@ -72,36 +177,114 @@ impl ChatServer {
         let new = Self {
             sessions: HashMap::new(),
             user_sessions: HashMap::new(),
+            user_statuses: HashMap::new(),
+            remote_sessions: HashMap::new(),
+            room_subscribers: HashMap::new(),
+            conn_subscriptions: HashMap::new(),
+            streams: HashMap::new(),
             cmd_reciever: cmd_rx,
+            bus: None,
         };
-        (new, ChatServerHandle { cmd_tx })
+        (new, ChatServerHandle { cmd_tx, bus: None })
     }
-    async fn handle_message(&self, recipients: MessageTarget, msg: impl Into<Msg>) {
+    async fn handle_message(
+        &mut self,
+        recipients: MessageTarget,
+        msg: impl Into<Msg>,
+        ephemeral: bool,
+    ) {
         let msg: Msg = msg.into();
-        match recipients {
-            MessageTarget::All => {
-                for (_user, sender) in &self.sessions {
-                    let _ = sender.send(msg.clone());
-                }
-            }
-            MessageTarget::List(uuids) => {
-                for user in uuids {
-                    if let Some(conns) = self.user_sessions.get(&user) {
-                        // user is online, send to all connections
-                        for conn in conns {
-                            if let Some(sender) = self.sessions.get(conn) {
-                                let _ = sender.send(msg.clone());
-                            }
-                        }
-                    }
-                }
+        let users: Vec<UserId> = match recipients {
+            MessageTarget::All => self.streams.keys().copied().collect(),
+            MessageTarget::List(uuids) => uuids,
+        };
+        for user in users {
+            self.deliver(user, None, &msg, None, ephemeral);
+        }
+    }
+    async fn handle_room_message(
+        &mut self,
+        room: RoomId,
+        members: Vec<UserId>,
+        msg: impl Into<Msg>,
+        unsubscribed_msg: Option<Msg>,
+        ephemeral: bool,
+    ) {
+        let msg: Msg = msg.into();
+        for user in members {
+            self.deliver(
+                user,
+                Some(room),
+                &msg,
+                unsubscribed_msg.as_deref(),
+                ephemeral,
+            );
+        }
+    }
+    /// number the event for the user and send it to each of their connections,
+    /// room events are sent depending on the connection's subscriptions.
+    /// ephemeral events are sent as they are and never replayed
+    fn deliver(
+        &mut self,
+        user: UserId,
+        room: Option<RoomId>,
+        msg: &str,
+        unsubscribed_msg: Option<&str>,
+        ephemeral: bool,
+    ) {
+        let Some(stream) = self.streams.get_mut(&user) else {
+            return;
+        };
+        let (msg, unsubscribed_msg) = match ephemeral {
+            true => (msg, unsubscribed_msg),
+            false => {
+                let event = stream.push(room, msg, unsubscribed_msg);
+                (event.msg.as_str(), event.unsubscribed_msg.as_deref())
+            }
+        };
+        let Some(conns) = self.user_sessions.get(&user) else {
+            // the user is able to resume so the event is only buffered
+            return;
+        };
+        for conn in conns {
+            let Some(sender) = self.sessions.get(conn) else {
+                continue;
+            };
+            let is_subscribed = |room: &RoomId| {
+                self.room_subscribers
+                    .get(room)
+                    .is_some_and(|x| x.contains(conn))
+            };
+            if let Some(msg) = msg_for_conn(room, msg, unsubscribed_msg, is_subscribed) {
+                let _ = sender.send(msg.to_string());
+            }
+        }
+    }
+    /// send every connection a resync with the latest event of their user
+    fn resync_all(&self) {
+        for (user, conns) in &self.user_sessions {
+            let Some(stream) = self.streams.get(user) else {
+                continue;
+            };
+            let resync: Msg = SocketMsg::ResyncRequired {
+                seq: stream.latest_seq(),
+            }
+            .into();
+            for sender in conns.iter().filter_map(|x| self.sessions.get(x)) {
+                let _ = sender.send(resync.clone());
             }
         }
     }
     /// todo, pivot to using rwlocks for the sessions
-    async fn connect(&mut self, conn_sender: mpsc::UnboundedSender<Msg>, user: ConnId) -> ConnId {
+    async fn connect(
+        &mut self,
+        conn_sender: mpsc::UnboundedSender<Msg>,
+        user: UserId,
+        subscribe: Option<Vec<RoomId>>,
+        last_seq: Option<u64>,
+    ) -> ConnId {
         let id: ConnId = Uuid::new_v4();
-        self.sessions.insert(id, conn_sender);
+        self.sessions.insert(id, conn_sender.clone());
         match self.user_sessions.get_mut(&user) {
             Some(sessions) => {
                 sessions.insert(id);
@@ -112,46 +295,328 @@ impl ChatServer {
                 self.user_sessions.insert(user, set);
             }
         }
+        if let Some(rooms) = subscribe {
+            let rooms = self.subscribe(id, rooms);
+            let _ = conn_sender.send(SocketMsg::Subscribed { rooms }.into());
+        }
+
+        let stream = self.streams.entry(user).or_default();
+        stream.disconnected = None;
+        let Some(last_seq) = last_seq else {
+            return id;
+        };
+        match stream.since(last_seq) {
+            Some(events) => {
+                let subscriptions = self.conn_subscriptions.get(&id);
+                for event in events {
+                    let is_subscribed =
+                        |room: &RoomId| subscriptions.is_some_and(|x| x.contains(room));
+                    if let Some(msg) = event.for_conn(is_subscribed) {
+                        let _ = conn_sender.send(msg.to_string());
+                    }
+                }
+            }
+            None => {
+                let resync = SocketMsg::ResyncRequired {
+                    seq: stream.latest_seq(),
+                };
+                let _ = conn_sender.send(resync.into());
+            }
+        }
         id
     }
     async fn disconnect(&mut self, user: UserId, conn: ConnId) {
         self.sessions.remove(&conn);
-        match self.user_sessions.get_mut(&user) {
-            Some(sessions) => {
-                sessions.remove(&conn);
-                if sessions.is_empty() {
-                    self.user_sessions.remove(&user);
+        let rooms: Vec<RoomId> = self
+            .conn_subscriptions
+            .get(&conn)
+            .map(|x| x.iter().copied().collect())
+            .unwrap_or_default();
+        self.unsubscribe(conn, rooms);
+        // if the user has no sessions something has gone pretty wrong
+        // and it should prob be logged
+        if let Some(sessions) = self.user_sessions.get_mut(&user) {
+            sessions.remove(&conn);
+            if sessions.is_empty() {
+                self.user_sessions.remove(&user);
+                // keep buffering events for a while so the user may resume
+                if let Some(stream) = self.streams.get_mut(&user) {
+                    stream.disconnected = Some(Instant::now());
                 }
             }
-            // this shouldn't happen and should prob be logged if it has
-            // as something has gone pretty wrong if so
-            None => {}
+        }
+    }
+    /// returns every room the connection is subscribed to. rooms past
+    /// [`MAX_SUBSCRIPTIONS`] are ignored
+    fn subscribe(&mut self, conn: ConnId, rooms: Vec<RoomId>) -> Vec<RoomId> {
+        let subscriptions = self.conn_subscriptions.entry(conn).or_default();
+        for room in rooms {
+            if subscriptions.len() >= MAX_SUBSCRIPTIONS {
+                break;
+            }
+            subscriptions.insert(room);
+            self.room_subscribers.entry(room).or_default().insert(conn);
+        }
+        subscriptions.iter().copied().collect()
+    }
+    /// returns every room the connection is still subscribed to
+    fn unsubscribe(&mut self, conn: ConnId, rooms: Vec<RoomId>) -> Vec<RoomId> {
+        for room in rooms {
+            if let Some(subscribers) = self.room_subscribers.get_mut(&room) {
+                subscribers.remove(&conn);
+                if subscribers.is_empty() {
+                    self.room_subscribers.remove(&room);
+                }
+            }
+            if let Some(subscriptions) = self.conn_subscriptions.get_mut(&conn) {
+                subscriptions.remove(&room);
+            }
+        }
+        match self.conn_subscriptions.get(&conn) {
+            Some(subscriptions) if !subscriptions.is_empty() => {
+                subscriptions.iter().copied().collect()
+            }
+            _ => {
+                self.conn_subscriptions.remove(&conn);
+                Vec::new()
+            }
+        }
+    }
+    /// the user's presence as seen by other users
+    fn presence(&self, user: UserId) -> ApiPresence {
+        let offline = ApiPresence {
+            user,
+            status: PresenceStatus::Offline,
+            custom_status: None,
+        };
+        let connected = self.user_sessions.contains_key(&user)
+            || self
+                .remote_sessions
+                .values()
+                .any(|x| x.users.contains_key(&user));
+        if !connected {
+            return offline;
+        }
+        let status = self.user_statuses.get(&user).cloned().unwrap_or_default();
+        let presence = match status.status {
+            Status::Online => PresenceStatus::Online,
+            Status::Idle => PresenceStatus::Idle,
+            Status::DoNotDisturb => PresenceStatus::DoNotDisturb,
+            Status::Invisible => return offline,
+        };
+        ApiPresence {
+            user,
+            status: presence,
+            custom_status: status.custom_status,
+        }
+    }
+    fn set_status(&mut self, user: UserId, status: UserStatus) -> Option<ApiPresence> {
+        let old = self.presence(user);
+        self.user_statuses.insert(user, status);
+        let new = self.presence(user);
+        (old != new).then_some(new)
+    }
+    /// let the other instances know how many sessions the users have here
+    fn publish_presence(&self, users: Vec<UserId>, full: bool) {
+        let Some(bus) = &self.bus else {
+            return;
+        };
+        let users = users
+            .into_iter()
+            .map(|user| {
+                let status = self.user_statuses.get(&user).cloned().unwrap_or_default();
+                UserSessions {
+                    user,
+                    sessions: self.user_sessions.get(&user).map_or(0, |x| x.len()),
+                    status: status.status,
+                    custom_status: status.custom_status,
+                }
+            })
+            .collect();
+        bus.publish(BusEvent::Presence { users, full });
+    }
+    /// sessions published by another instance, it has already
+    /// broadcast any presence that changed so there's nothing to send
+    fn remote_presence(&mut self, origin: Uuid, users: Vec<UserSessions>, full: bool) {
+        let remote = self
+            .remote_sessions
+            .entry(origin)
+            .or_insert_with(|| RemoteSessions {
+                users: HashMap::new(),
+                refreshed: Instant::now(),
+            });
+        remote.refreshed = Instant::now();
+        if full {
+            remote.users.clear();
+        }
+        for x in users {
+            match x.sessions {
+                0 => remote.users.remove(&x.user),
+                sessions => remote.users.insert(x.user, sessions),
+            };
+            let status = UserStatus {
+                status: x.status,
+                custom_status: x.custom_status,
+            };
+            // statuses in full updates may be older than ones we already know
+            if full {
+                self.user_statuses.entry(x.user).or_insert(status);
+            } else {
+                self.user_statuses.insert(x.user, status);
+            }
         }
     }
     pub async fn run(mut self) -> io::Result<()> {
-        while let Some(cmd) = self.cmd_reciever.recv().await {
+        let mut cleanup = interval(STREAM_CLEANUP_INTERVAL);
+        let mut presence = interval(PRESENCE_INTERVAL);
+        loop {
+            let cmd = tokio::select! {
+                cmd = self.cmd_reciever.recv() => match cmd {
+                    Some(cmd) => cmd,
+                    None => break,
+                },
+                _ = cleanup.tick() => {
+                    self.streams.retain(|_, x| !x.expired());
+                    continue;
+                }
+                _ = presence.tick() => {
+                    self.remote_sessions
+                        .retain(|_, x| x.refreshed.elapsed() < REMOTE_SESSIONS_EXPIRY);
+                    self.publish_presence(self.user_sessions.keys().copied().collect(), true);
+                    continue;
+                }
+            };
             match cmd {
                 Command::Connect {
                     conn_sender,
                     user,
+                    subscribe,
+                    last_seq,
                     response_handle,
                 } => {
-                    let conn_id = self.connect(conn_sender, user).await;
-                    let _ = response_handle.send(conn_id);
+                    let old = self.presence(user);
+                    let conn_id = self.connect(conn_sender, user, subscribe, last_seq).await;
+                    let new = self.presence(user);
+                    self.publish_presence(vec![user], false);
+                    let _ = response_handle.send((conn_id, (old != new).then_some(new)));
                 }
 
-                Command::Disconnect { conn, user } => {
+                Command::Disconnect {
+                    conn,
+                    user,
+                    response_handle,
+                } => {
+                    let old = self.presence(user);
                     self.disconnect(user, conn).await;
+                    let new = self.presence(user);
+                    self.publish_presence(vec![user], false);
+                    let _ = response_handle.send((old != new).then_some(new));
+                }
+
+                Command::SetPresence {
+                    user,
+                    status,
+                    custom_status,
+                    response_handle,
+                } => {
+                    let changed = self.set_status(
+                        user,
+                        UserStatus {
+                            status,
+                            custom_status,
+                        },
+                    );
+                    self.publish_presence(vec![user], false);
+                    let _ = response_handle.send(changed);
+                }
+
+                Command::GetPresence {
+                    users,
+                    response_handle,
+                } => {
+                    let presences = users.into_iter().map(|x| self.presence(x)).collect();
+                    let _ = response_handle.send(presences);
+                }
+
+                Command::Subscribe {
+                    conn,
+                    rooms,
+                    response_handle,
+                } => {
+                    let _ = response_handle.send(self.subscribe(conn, rooms));
+                }
+
+                Command::Unsubscribe {
+                    conn,
+                    rooms,
+                    response_handle,
+                } => {
+                    let _ = response_handle.send(self.unsubscribe(conn, rooms));
                 }
 
                 Command::BroadcastMessage {
                     msg,
                     recipients,
+                    ephemeral,
                     response_handle,
                 } => {
-                    self.handle_message(recipients, msg).await;
+                    self.handle_message(recipients, msg, ephemeral).await;
                     let _ = response_handle.send(());
                 }
+
+                Command::BroadcastRoomMessage {
+                    room,
+                    msg,
+                    unsubscribed_msg,
+                    members,
+                    ephemeral,
+                    response_handle,
+                } => {
+                    self.handle_room_message(room, members, msg, unsubscribed_msg, ephemeral)
+                        .await;
+                    let _ = response_handle.send(());
+                }
+
+                Command::Remote {
+                    event:
+                        BusEvent::Message {
+                            recipients,
+                            msg,
+                            ephemeral,
+                        },
+                    ..
+                } => {
+                    self.handle_message(recipients, msg, ephemeral).await;
+                }
+
+                Command::Remote {
+                    event:
+                        BusEvent::RoomMessage {
+                            room,
+                            members,
+                            msg,
+                            unsubscribed_msg,
+                            ephemeral,
+                        },
+                    ..
+                } => {
+                    self.handle_room_message(room, members, msg, unsubscribed_msg, ephemeral)
+                        .await;
+                }
+
+                Command::Remote {
+                    origin,
+                    event: BusEvent::Presence { users, full },
+                } => self.remote_presence(origin, users, full),
+
+                Command::Remote {
+                    event: BusEvent::PresenceRequest,
+                    ..
+                } => self.publish_presence(self.user_sessions.keys().copied().collect(), true),
+
+                Command::UseEventBus(bus) => self.bus = Some(bus),
+
+                Command::ResyncAll => self.resync_all(),
             }
         }
 
@@ -166,10 +631,26 @@ impl ChatServer {
 #[derive(Debug, Clone)]
 pub struct ChatServerHandle {
     cmd_tx: mpsc::UnboundedSender<Command>,
+    /// publishes broadcasts to other instances when running more than one
+    bus: Option<EventBus>,
 }
 
 impl ChatServerHandle {
-    pub async fn connect(&self, conn_sender: mpsc::UnboundedSender<Msg>, user: UserId) -> ConnId {
+    /// publish broadcasts on the bus so they are delivered by every instance
+    pub fn with_event_bus(mut self, bus: EventBus) -> Self {
+        // the server publishes the presence of its own sessions
+        self.cmd_tx.send(Command::UseEventBus(bus.clone())).unwrap();
+        self.bus = Some(bus);
+        self
+    }
+    /// returns the user's presence alongside the connection if they just came online
+    pub async fn connect(
+        &self,
+        conn_sender: mpsc::UnboundedSender<Msg>,
+        user: UserId,
+        subscribe: Option<Vec<RoomId>>,
+        last_seq: Option<u64>,
+    ) -> (ConnId, Option<ApiPresence>) {
         let (res_tx, res_rx) = oneshot::channel();
         // unwraps used as the server should run until all chat server handles are dropped
         // and then nicely shutdown itself. the server should always be shutting down after
@@ -178,25 +659,158 @@ impl ChatServerHandle {
             .send(Command::Connect {
                 conn_sender,
                 user,
+                subscribe,
+                last_seq,
+                response_handle: res_tx,
+            })
+            .unwrap();
+        res_rx.await.unwrap()
+    }
+    /// returns the user's presence if they just went offline
+    pub async fn disconnect(&self, conn: ConnId, user: UserId) -> Option<ApiPresence> {
+        let (res_tx, res_rx) = oneshot::channel();
+        self.cmd_tx
+            .send(Command::Disconnect {
+                user,
+                conn,
                 response_handle: res_tx,
             })
             .unwrap();
         res_rx.await.unwrap()
     }
-    pub fn disconnect(&self, conn: ConnId, user: UserId) {
+    /// returns the user's new presence if it visibly changed
+    pub async fn set_presence(
+        &self,
+        user: UserId,
+        status: Status,
+        custom_status: Option<String>,
+    ) -> Option<ApiPresence> {
+        let (res_tx, res_rx) = oneshot::channel();
         self.cmd_tx
-            .send(Command::Disconnect { user, conn })
+            .send(Command::SetPresence {
+                user,
+                status,
+                custom_status,
+                response_handle: res_tx,
+            })
             .unwrap();
+        res_rx.await.unwrap()
+    }
+    pub async fn get_presence(&self, users: Vec<UserId>) -> Vec<ApiPresence> {
+        let (res_tx, res_rx) = oneshot::channel();
+        self.cmd_tx
+            .send(Command::GetPresence {
+                users,
+                response_handle: res_tx,
+            })
+            .unwrap();
+        res_rx.await.unwrap()
     }
     pub async fn send_message(&self, msg: SocketMsg, recipients: MessageTarget) {
+        let ephemeral = msg.is_ephemeral();
+        let msg: Msg = msg.into();
+        if let Some(bus) = &self.bus {
+            bus.publish(BusEvent::Message {
+                recipients: recipients.clone(),
+                msg: msg.clone(),
+                ephemeral,
+            });
+        }
         let (res_tx, res_rx) = oneshot::channel();
         self.cmd_tx
             .send(Command::BroadcastMessage {
                 msg,
                 recipients,
+                ephemeral,
+                response_handle: res_tx,
+            })
+            .unwrap();
+        res_rx.await.unwrap()
+    }
+    /// returns all rooms the connection is subscribed to
+    pub async fn subscribe(&self, conn: ConnId, rooms: Vec<RoomId>) -> Vec<RoomId> {
+        let (res_tx, res_rx) = oneshot::channel();
+        self.cmd_tx
+            .send(Command::Subscribe {
+                conn,
+                rooms,
+                response_handle: res_tx,
+            })
+            .unwrap();
+        res_rx.await.unwrap()
+    }
+    /// returns all rooms the connection is still subscribed to
+    pub async fn unsubscribe(&self, conn: ConnId, rooms: Vec<RoomId>) -> Vec<RoomId> {
+        let (res_tx, res_rx) = oneshot::channel();
+        self.cmd_tx
+            .send(Command::Unsubscribe {
+                conn,
+                rooms,
                 response_handle: res_tx,
             })
             .unwrap();
         res_rx.await.unwrap()
     }
+    /// send a message to the connections of members that are subscribed to the room,
+    /// the rest receive `unsubscribed_msg` if there is one
+    pub async fn send_room_message(
+        &self,
+        room: RoomId,
+        members: Vec<UserId>,
+        msg: SocketMsg,
+        unsubscribed_msg: Option<SocketMsg>,
+    ) {
+        let ephemeral = msg.is_ephemeral();
+        let msg: Msg = msg.into();
+        let unsubscribed_msg: Option<Msg> = unsubscribed_msg.map(|x| x.into());
+        if let Some(bus) = &self.bus {
+            bus.publish(BusEvent::RoomMessage {
+                room,
+                members: members.clone(),
+                msg: msg.clone(),
+                unsubscribed_msg: unsubscribed_msg.clone(),
+                ephemeral,
+            });
+        }
+        let (res_tx, res_rx) = oneshot::channel();
+        self.cmd_tx
+            .send(Command::BroadcastRoomMessage {
+                room,
+                msg,
+                unsubscribed_msg,
+                members,
+                ephemeral,
+                response_handle: res_tx,
+            })
+            .unwrap();
+        res_rx.await.unwrap()
+    }
+    /// a handle that doesn't keep the chat server running
+    pub fn downgrade(&self) -> WeakChatServerHandle {
+        WeakChatServerHandle {
+            cmd_tx: self.cmd_tx.downgrade(),
+        }
+    }
+}
+
+/// used by tasks that run alongside the chat server, commands are
+/// dropped once every [`ChatServerHandle`] is gone and the server stops
+#[derive(Debug, Clone)]
+pub struct WeakChatServerHandle {
+    cmd_tx: mpsc::WeakUnboundedSender<Command>,
+}
+
+impl WeakChatServerHandle {
+    fn send(&self, cmd: Command) {
+        if let Some(cmd_tx) = self.cmd_tx.upgrade() {
+            let _ = cmd_tx.send(cmd);
+        }
+    }
+    /// deliver an event published by another instance
+    pub fn deliver_remote(&self, origin: Uuid, event: BusEvent) {
+        self.send(Command::Remote { origin, event });
+    }
+    pub fn resync_all(&self) {
+        self.send(Command::ResyncAll);
+    }
 }
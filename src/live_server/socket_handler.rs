@@ -1,11 +1,25 @@
-use crate::db::{pg_conn::PgConn, types::tokens::auth_token::AuthToken};
+use crate::{
+    db::{
+        pg_conn::PgConn,
+        types::{comm::permissions::Permissions, tokens::auth_token::AuthToken},
+    },
+    routes::api::types::api_presence::{ApiPresence, Status},
+};
 
-use super::server::ChatServerHandle;
+use super::{
+    client_msg::ClientMsg,
+    server::{ChatServerHandle, ConnId, MessageTarget, RoomId, UserId, MAX_SUBSCRIPTIONS},
+    socket_msg::SocketMsg,
+};
 
-use std::time::{Duration, Instant};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
 use actix_ws::{AggregatedMessage, CloseReason};
 use futures_util::StreamExt as _;
+use serde::Deserialize;
 use tokio::{
     sync::mpsc,
     time::{interval, Interval},
@@ -17,12 +31,52 @@ const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 /// How long before lack of client response causes a timeout
 const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// repeated typing notifications for the same room within this time are not
+/// sent to other users
+const TYPING_THROTTLE: Duration = Duration::from_secs(5);
+
+/// clients stop showing a user as typing this long after they were last notified,
+/// see [`SocketMsg::Typing`]. a stop is sent for any room notified within this time
+const TYPING_EXPIRY: Duration = Duration::from_secs(10);
+
+/// minimum time between presence changes from a connection, changes made
+/// sooner are held and only the latest is applied once the time is up
+const PRESENCE_THROTTLE: Duration = Duration::from_secs(5);
+
+/// custom statuses are cut down to this many characters
+const MAX_CUSTOM_STATUS: usize = 128;
+
+/// the most users whose presence may be requested at once
+const MAX_PRESENCE_QUERY: usize = 200;
+
+/// the first message sent by the client after connecting
+#[derive(Deserialize, Debug)]
+struct Handshake {
+    #[serde(flatten)]
+    token: AuthToken,
+    /// the `seq` of the last event received before reconnecting,
+    /// every event missed since then is replayed
+    last_seq: Option<u64>,
+    /// rooms to subscribe to before any events are sent, so that
+    /// replayed events for them are sent in full
+    subscribe: Option<Vec<RoomId>>,
+}
+
+/// state used to throttle a connection's typing and presence updates
+#[derive(Default)]
+struct Throttle {
+    /// rooms the user is typing in and when other users were last notified
+    typing: HashMap<RoomId, Instant>,
+    last_presence: Option<Instant>,
+    pending_presence: Option<(Status, Option<String>)>,
+}
+
 async fn await_token(
     session: &mut actix_ws::Session,
     msg_stream: &mut actix_ws::AggregatedMessageStream,
     last_heartbeat: &mut Instant,
     interval: &mut Interval,
-) -> Result<AuthToken, Option<CloseReason>> {
+) -> Result<Handshake, Option<CloseReason>> {
     let pre_auth = loop {
         tokio::select! {
             Some(Ok(msg)) = msg_stream.next() => {
@@ -66,7 +120,7 @@ pub async fn ws_handler(
         .aggregate_continuations()
         .max_continuation_size(2 * 1024 * 1024);
 
-    let token = match await_token(
+    let handshake = match await_token(
         &mut session,
         &mut msg_stream,
         &mut last_heartbeat,
@@ -74,24 +128,40 @@ pub async fn ws_handler(
     )
     .await
     {
-        Ok(token) => token,
+        Ok(handshake) => handshake,
         Err(_) => {
             let _ = session.close(None).await;
             return;
         }
     };
+    let token = handshake.token;
     if conn.validate_auth_token(&token).await.is_err() {
         let _ = session.close(None).await;
         return;
     }
+    let subscribe = match handshake.subscribe {
+        Some(rooms) => Some(viewable_rooms(&conn, token.uid, rooms).await),
+        None => None,
+    };
 
     let (conn_tx, mut outbound_messages) = mpsc::unbounded_channel();
     // unwrap: chat server is not dropped before the HTTP server
-    let conn_id = chat_server.connect(conn_tx, token.uid).await;
+    let (conn_id, presence) = chat_server
+        .connect(conn_tx, token.uid, subscribe, handshake.last_seq)
+        .await;
+    if let Some(presence) = presence {
+        broadcast_presence(&chat_server, &conn, presence).await;
+    }
+    let mut throttle = Throttle::default();
 
     let close_reason = loop {
         tokio::select! {
-            Some(Ok(msg)) = msg_stream.next() => {
+            msg = msg_stream.next() => {
+                // the stream ends when the client disconnects without closing
+                // the socket, stop now rather than waiting for the heartbeat
+                let Some(Ok(msg)) = msg else {
+                    break None;
+                };
 
                 match msg {
                     AggregatedMessage::Ping(bytes) => {
@@ -103,12 +173,21 @@ pub async fn ws_handler(
                         last_heartbeat = Instant::now();
                     }
 
-                    // currently not used but will be in the
-                    // future for things such as status information
-                    // and any "live" communication
-                    // may also be used for "subscribing" to
-                    // a room
-                    AggregatedMessage::Text(_text) => {}
+                    AggregatedMessage::Text(text) => {
+                        // malformed messages are ignored
+                        if let Ok(msg) = serde_json::from_str::<ClientMsg>(&text) {
+                            handle_client_msg(
+                                &chat_server,
+                                &conn,
+                                &mut session,
+                                conn_id,
+                                token.uid,
+                                msg,
+                                &mut throttle,
+                            )
+                            .await;
+                        }
+                    }
 
                     // not allowed
                     AggregatedMessage::Binary(_bin) => break None,
@@ -125,6 +204,10 @@ pub async fn ws_handler(
                     break None;
                 }
                 let _ = session.ping(b"").await;
+                throttle.typing.retain(|_, sent| sent.elapsed() < TYPING_EXPIRY);
+                if let Some((status, custom_status)) = throttle.pending_presence.take() {
+                    set_presence(&chat_server, &conn, token.uid, status, custom_status, &mut throttle).await;
+                }
             }
 
             else => {
@@ -133,8 +216,162 @@ pub async fn ws_handler(
         }
     };
 
-    chat_server.disconnect(conn_id, token.uid);
+    if let Some(presence) = chat_server.disconnect(conn_id, token.uid).await {
+        broadcast_presence(&chat_server, &conn, presence).await;
+    }
 
     // attempt to close connection gracefully
     let _ = session.close(close_reason).await;
 }
+
+async fn handle_client_msg(
+    chat_server: &ChatServerHandle,
+    conn: &PgConn,
+    session: &mut actix_ws::Session,
+    conn_id: ConnId,
+    uid: UserId,
+    msg: ClientMsg,
+    throttle: &mut Throttle,
+) {
+    match msg {
+        ClientMsg::TypingStart { room } => {
+            if throttle
+                .typing
+                .get(&room)
+                .is_some_and(|sent| sent.elapsed() < TYPING_THROTTLE)
+            {
+                return;
+            }
+            let can_send = conn
+                .get_room_permissions(room, uid)
+                .await
+                .is_some_and(|x| x.contains(Permissions::VIEW_ROOMS | Permissions::SEND_MESSAGES));
+            if !can_send {
+                return;
+            }
+            throttle.typing.insert(room, Instant::now());
+            broadcast_typing(chat_server, conn, room, uid, true).await;
+        }
+        ClientMsg::TypingStop { room } => {
+            if throttle.typing.remove(&room).is_some() {
+                broadcast_typing(chat_server, conn, room, uid, false).await;
+            }
+        }
+        ClientMsg::SetPresence {
+            status,
+            custom_status,
+        } => {
+            let custom_status = custom_status
+                .map(|x| x.trim().chars().take(MAX_CUSTOM_STATUS).collect::<String>())
+                .filter(|x| !x.is_empty());
+            if throttle
+                .last_presence
+                .is_some_and(|last| last.elapsed() < PRESENCE_THROTTLE)
+            {
+                throttle.pending_presence = Some((status, custom_status));
+                return;
+            }
+            set_presence(chat_server, conn, uid, status, custom_status, throttle).await;
+        }
+        ClientMsg::Subscribe { rooms } => {
+            let rooms = viewable_rooms(conn, uid, rooms).await;
+            let rooms = chat_server.subscribe(conn_id, rooms).await;
+            let _ = session
+                .text(String::from(SocketMsg::Subscribed { rooms }))
+                .await;
+        }
+        ClientMsg::Unsubscribe { rooms } => {
+            let rooms = chat_server.unsubscribe(conn_id, rooms).await;
+            let _ = session
+                .text(String::from(SocketMsg::Subscribed { rooms }))
+                .await;
+        }
+        ClientMsg::Ack { message } => {
+            if let Ok(read_state) = conn.ack_message(uid, message).await {
+                chat_server
+                    .send_message(
+                        SocketMsg::ReadStateUpdated(read_state),
+                        MessageTarget::List(vec![uid]),
+                    )
+                    .await;
+            }
+        }
+        ClientMsg::GetPresence { mut users } => {
+            users.truncate(MAX_PRESENCE_QUERY);
+            let related = conn.get_related_user_ids(uid).await;
+            users.retain(|x| related.contains(x));
+            for presence in chat_server.get_presence(users).await {
+                let _ = session
+                    .text(String::from(SocketMsg::PresenceUpdate(presence)))
+                    .await;
+            }
+        }
+    }
+}
+
+/// filter rooms requested for subscription down to those the user can view
+async fn viewable_rooms(conn: &PgConn, uid: UserId, mut rooms: Vec<RoomId>) -> Vec<RoomId> {
+    rooms.sort();
+    rooms.dedup();
+    rooms.truncate(MAX_SUBSCRIPTIONS);
+    let mut viewable = Vec::with_capacity(rooms.len());
+    for room in rooms {
+        if conn
+            .get_room_permissions(room, uid)
+            .await
+            .is_some_and(|x| x.contains(Permissions::VIEW_ROOMS))
+        {
+            viewable.push(room);
+        }
+    }
+    viewable
+}
+
+async fn set_presence(
+    chat_server: &ChatServerHandle,
+    conn: &PgConn,
+    uid: UserId,
+    status: Status,
+    custom_status: Option<String>,
+    throttle: &mut Throttle,
+) {
+    throttle.last_presence = Some(Instant::now());
+    throttle.pending_presence = None;
+    if let Some(presence) = chat_server.set_presence(uid, status, custom_status).await {
+        broadcast_presence(chat_server, conn, presence).await;
+    }
+}
+
+/// send a presence update to everyone sharing a community or room with the user
+async fn broadcast_presence(chat_server: &ChatServerHandle, conn: &PgConn, presence: ApiPresence) {
+    let recipients = conn.get_related_user_ids(presence.user).await;
+    chat_server
+        .send_message(
+            SocketMsg::PresenceUpdate(presence),
+            MessageTarget::List(recipients),
+        )
+        .await;
+}
+
+/// notify everyone else able to view the room that the user started or stopped typing
+async fn broadcast_typing(
+    chat_server: &ChatServerHandle,
+    conn: &PgConn,
+    room: RoomId,
+    user: UserId,
+    typing: bool,
+) {
+    let Some(room_info) = conn.get_room(room).await else {
+        return;
+    };
+    let mut recipients = conn.get_room_member_ids(&room_info).await;
+    recipients.retain(|x| *x != user);
+    chat_server
+        .send_room_message(
+            room,
+            recipients,
+            SocketMsg::Typing { room, user, typing },
+            None,
+        )
+        .await;
+}
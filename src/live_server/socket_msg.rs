@@ -1,9 +1,70 @@
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::routes::api::types::api_message::ApiMessage;
+use crate::routes::api::types::{
+    api_message::ApiMessage, api_presence::ApiPresence, api_reaction::ApiReaction,
+    api_read_state::ApiReadState,
+};
 
+/// events sent to users are given a top level `seq` alongside the variant,
+/// responses to a connection's own requests and ephemeral events are not.
+/// variants must serialize to an object for this, see [`super::event_stream`]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum SocketMsg {
-    NewMessage(ApiMessage),
+    NewMessage(Box<ApiMessage>),
+    /// sent instead of [`SocketMsg::NewMessage`] to connections not subscribed to the room
+    UnreadMessage {
+        room: Uuid,
+        id: Uuid,
+        user: Uuid,
+    },
+    /// sent to users mentioned by a new message, whether or not they are
+    /// subscribed to the room, in addition to the message itself
+    Mentioned(Box<ApiMessage>),
+    /// contains the message after the edit was applied
+    MessageEdited(Box<ApiMessage>),
+    MessageDeleted {
+        id: Uuid,
+        room: Uuid,
+    },
+    ReactionAdded(ApiReaction),
+    ReactionRemoved(ApiReaction),
+    /// a message was pinned or unpinned in the room
+    PinsUpdated {
+        room: Uuid,
+    },
+    /// a user started or stopped typing in a room. clients should treat typing
+    /// as stopped if it is not repeated within 10 seconds
+    Typing {
+        room: Uuid,
+        user: Uuid,
+        typing: bool,
+    },
+    /// events after the `last_seq` given when connecting are no longer available,
+    /// state should be fetched again. contains the sequence number of the latest
+    /// event to resume from in the future
+    ResyncRequired {
+        seq: u64,
+    },
+    /// the rooms the connection is now subscribed to after a subscribe or
+    /// unsubscribe request, rooms the user is unable to view are left out
+    Subscribed {
+        rooms: Vec<Uuid>,
+    },
+    /// the user read a room from one of their connections
+    ReadStateUpdated(ApiReadState),
+    /// a user came online, went offline or changed their status
+    PresenceUpdate(ApiPresence),
     SystemMessage(String),
 }
+
+impl SocketMsg {
+    /// events only useful as they happen, they are sent to the connections open
+    /// at the time and are not numbered or replayed after reconnecting
+    pub fn is_ephemeral(&self) -> bool {
+        matches!(
+            self,
+            SocketMsg::Typing { .. } | SocketMsg::PresenceUpdate(_)
+        )
+    }
+}
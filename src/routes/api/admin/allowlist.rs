@@ -0,0 +1,55 @@
+//! `post /api/bayou_v1/admin/instances/allowlist`
+//!
+//! allowlist an instance, expects an auth token in the authorization header and a body
+//! with an [`crate::routes::api::admin::block::InstancePolicy`]. when
+//! [`crate::config::Config::allowlist_only`] is set only allowlisted instances are
+//! federated with. allowlisting unblocks the instance
+//! - ok (200) should contain a json [`crate::routes::api::types::api_instance::ApiInstance`]
+//! - bad request (400) the domain is our own or isn't a valid domain
+//! - unauthorized (401) included token is not valid or not an instance admin or moderator
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+
+use crate::{
+    db::pg_conn::PgConn,
+    federation::client::valid_domain,
+    routes::api::{
+        admin::block::InstancePolicy, types::api_instance::ApiInstance,
+        utilities::instance_mod::is_instance_mod,
+    },
+};
+
+#[post("/instances/allowlist")]
+pub async fn allowlist(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    policy: web::Json<InstancePolicy>,
+) -> Result<HttpResponse> {
+    if !is_instance_mod(&req, &conn).await {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let policy = policy.into_inner();
+    let domain = policy.domain.to_lowercase();
+    if !valid_domain(&domain) {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(instance) = conn.allowlist_instance(&domain, policy.reason).await else {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(
+            serde_json::to_string(&ApiInstance::from(instance))
+                .expect("failed to serialize instance"),
+        ))
+}
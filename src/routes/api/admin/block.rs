@@ -0,0 +1,58 @@
+//! `post /api/bayou_v1/admin/instances/block`
+//!
+//! block an instance, expects an auth token in the authorization header and a body with
+//! an [`InstancePolicy`]. requests from the instance are refused, nothing is sent to it
+//! and the messages of its users are hidden. blocking removes it from the allowlist
+//! - ok (200) should contain a json [`crate::routes::api::types::api_instance::ApiInstance`]
+//! - bad request (400) the domain is our own or isn't a valid domain
+//! - unauthorized (401) included token is not valid or not an instance admin or moderator
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::pg_conn::PgConn,
+    federation::client::valid_domain,
+    routes::api::{types::api_instance::ApiInstance, utilities::instance_mod::is_instance_mod},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstancePolicy {
+    pub domain: String,
+    pub reason: Option<String>,
+}
+
+#[post("/instances/block")]
+pub async fn block(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    policy: web::Json<InstancePolicy>,
+) -> Result<HttpResponse> {
+    if !is_instance_mod(&req, &conn).await {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let policy = policy.into_inner();
+    let domain = policy.domain.to_lowercase();
+    if !valid_domain(&domain) {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(instance) = conn.block_instance(&domain, policy.reason).await else {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(
+            serde_json::to_string(&ApiInstance::from(instance))
+                .expect("failed to serialize instance"),
+        ))
+}
@@ -0,0 +1,48 @@
+//! `post /api/bayou_v1/admin/instances/import`
+//!
+//! block every instance in a csv blocklist, expects an auth token in the authorization
+//! header and the csv as the body, see [`crate::federation::policy::parse_blocklist`]
+//! for the accepted formats. instances that are already blocked have their reason updated
+//! - ok (200) should contain a json array of the blocked
+//!   [`crate::routes::api::types::api_instance::ApiInstance`]
+//! - bad request (400) the body is not utf-8
+//! - unauthorized (401) included token is not valid or not an instance admin or moderator
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+
+use crate::{
+    db::pg_conn::PgConn,
+    federation::policy::parse_blocklist,
+    routes::api::{types::api_instance::ApiInstance, utilities::instance_mod::is_instance_mod},
+};
+
+#[post("/instances/import")]
+pub async fn import_blocklist(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    body: web::Bytes,
+) -> Result<HttpResponse> {
+    if !is_instance_mod(&req, &conn).await {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(csv) = std::str::from_utf8(&body) else {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let blocked: Vec<ApiInstance> = conn
+        .import_blocklist(parse_blocklist(csv))
+        .await
+        .into_iter()
+        .map(|x| x.into())
+        .collect();
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&blocked).expect("failed to serialize instances")))
+}
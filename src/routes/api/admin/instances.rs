@@ -0,0 +1,31 @@
+//! `get /api/bayou_v1/admin/instances`
+//!
+//! get the instances that are blocked or allowlisted, expects an auth token in the
+//! authorization header
+//! - ok (200) should contain a json array of [`crate::routes::api::types::api_instance::ApiInstance`]
+//! - unauthorized (401) included token is not valid or not an instance admin or moderator
+
+use actix_web::{get, web::Data, HttpRequest, HttpResponse, Result};
+
+use crate::{
+    db::pg_conn::PgConn,
+    routes::api::{types::api_instance::ApiInstance, utilities::instance_mod::is_instance_mod},
+};
+
+#[get("/instances")]
+pub async fn instances(req: HttpRequest, conn: Data<PgConn>) -> Result<HttpResponse> {
+    if !is_instance_mod(&req, &conn).await {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let instances: Vec<ApiInstance> = conn
+        .get_instance_policies()
+        .await
+        .into_iter()
+        .map(|x| x.into())
+        .collect();
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&instances).expect("failed to serialize instances")))
+}
@@ -0,0 +1,11 @@
+//! `/api/bayou_v1/admin/...`
+//! managing our instance, every endpoint requires an instance admin or moderator.
+//! which instances we federate with is set here, see [`crate::federation::policy`]
+
+pub mod allowlist;
+pub mod block;
+pub mod import_blocklist;
+pub mod instances;
+pub(super) mod routes;
+pub mod unallowlist;
+pub mod unblock;
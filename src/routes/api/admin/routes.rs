@@ -0,0 +1,14 @@
+use super::{
+    allowlist::allowlist, block::block, import_blocklist::import_blocklist, instances::instances,
+    unallowlist::unallowlist, unblock::unblock,
+};
+
+pub fn get_admin_routes() -> actix_web::Scope {
+    actix_web::web::scope("/admin")
+        .service(instances)
+        .service(block)
+        .service(unblock)
+        .service(allowlist)
+        .service(unallowlist)
+        .service(import_blocklist)
+}
@@ -0,0 +1,48 @@
+//! `post /api/bayou_v1/admin/instances/unallowlist`
+//!
+//! remove an instance from the allowlist, expects an auth token in the authorization
+//! header and a body with an [`crate::routes::api::admin::unblock::InstanceDomain`]
+//! - ok (200) should contain a json [`crate::routes::api::types::api_instance::ApiInstance`]
+//! - not found (404) the instance isn't allowlisted
+//! - unauthorized (401) included token is not valid or not an instance admin or moderator
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+
+use crate::{
+    db::pg_conn::PgConn,
+    routes::api::{
+        admin::unblock::InstanceDomain, types::api_instance::ApiInstance,
+        utilities::instance_mod::is_instance_mod,
+    },
+};
+
+#[post("/instances/unallowlist")]
+pub async fn unallowlist(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    instance: web::Json<InstanceDomain>,
+) -> Result<HttpResponse> {
+    if !is_instance_mod(&req, &conn).await {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Some(instance) = conn
+        .unallowlist_instance(&instance.domain.to_lowercase())
+        .await
+    else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(
+            serde_json::to_string(&ApiInstance::from(instance))
+                .expect("failed to serialize instance"),
+        ))
+}
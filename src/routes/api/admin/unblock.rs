@@ -0,0 +1,48 @@
+//! `post /api/bayou_v1/admin/instances/unblock`
+//!
+//! unblock an instance, expects an auth token in the authorization header and a body
+//! with an [`InstanceDomain`]. messages already stored from the instance are shown again
+//! - ok (200) should contain a json [`crate::routes::api::types::api_instance::ApiInstance`]
+//! - not found (404) the instance isn't blocked
+//! - unauthorized (401) included token is not valid or not an instance admin or moderator
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::pg_conn::PgConn,
+    routes::api::{types::api_instance::ApiInstance, utilities::instance_mod::is_instance_mod},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstanceDomain {
+    pub domain: String,
+}
+
+#[post("/instances/unblock")]
+pub async fn unblock(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    instance: web::Json<InstanceDomain>,
+) -> Result<HttpResponse> {
+    if !is_instance_mod(&req, &conn).await {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Some(instance) = conn.unblock_instance(&instance.domain.to_lowercase()).await else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(
+            serde_json::to_string(&ApiInstance::from(instance))
+                .expect("failed to serialize instance"),
+        ))
+}
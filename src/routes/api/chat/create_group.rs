@@ -0,0 +1,67 @@
+//! `post /api/bayou_v1/chat/group/create`
+//!
+//! create a new group chat, expects an auth token in the authorization header
+//! and a body with a [`NewGroup`]. the creator is always added as a member
+//! - ok (200) should contain a json [`crate::db::types::room::Room`]
+//!   in the body
+//! - unauthorized (401) included token is not valid
+//! - bad request (400) one of the provided members does not exist
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    db::{pg_conn::PgConn, types::room::RoomInfo},
+    routes::api::utilities::auth_header::get_auth_header,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewGroup {
+    pub name: String,
+    pub description: Option<String>,
+    pub members: Vec<Uuid>,
+}
+
+#[post("/group/create")]
+pub async fn create_group(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    new_group: web::Json<NewGroup>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Some(user) = conn.get_user_uid(&token.uid).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let new_group = new_group.into_inner();
+    let info = RoomInfo {
+        name: new_group.name,
+        description: new_group.description,
+        category: None,
+        display_order: 0,
+    };
+    let Ok(room) = conn.create_group_chat(&user, info, new_group.members).await else {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&room).expect("failed to serialize room")))
+}
@@ -0,0 +1,27 @@
+//! `get /api/bayou_v1/chat/dms`
+//!
+//! get all of a user's direct messages, expects an auth token in the authorization header
+//! - ok (200) should contain an array of [`crate::routes::api::types::api_room::ApiRoom`]
+//!   present in the body, newest first
+//! - unauthorized (401) included token is not valid
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+use actix_web::{get, web::Data, HttpRequest, HttpResponse, Result};
+
+#[get("/dms")]
+pub async fn get_dms(conn: Data<PgConn>, req: HttpRequest) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let dms = conn.get_user_dms(token.uid).await;
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&dms).expect("failed to serialize rooms")))
+}
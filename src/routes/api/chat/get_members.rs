@@ -0,0 +1,43 @@
+//! `get /api/bayou_v1/chat/members/{room_id}`
+//!
+//! get all members of a room, expects an auth token in the authorization header
+//! - ok (200) should contain an array of [`crate::routes::api::types::api_user::ApiUser`]
+//!   present in the body
+//! - unauthorized (401) included token is not valid or not a member of the room
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use uuid::Uuid;
+
+#[get("/members/{room_id}")]
+pub async fn get_members(
+    conn: Data<PgConn>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(users) = conn
+        .user_get_room_members(path.into_inner(), token.uid)
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&users).expect("failed to serialize users")))
+}
@@ -0,0 +1,54 @@
+//! `post /api/bayou_v1/chat/group/invite`
+//!
+//! add a user to a group chat, expects an auth token in the authorization header
+//! and a body with a [`GroupInvite`]
+//! - ok (200) user was added to the group chat, a json
+//!   [`crate::db::types::room_membership::RoomMembership`] in the body
+//! - unauthorized (401) included token is not valid or not a member of the group chat,
+//!   the invited user does not exist or is already a member
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GroupInvite {
+    pub room: Uuid,
+    pub user: Uuid,
+}
+
+#[post("/group/invite")]
+pub async fn invite(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    invite: web::Json<GroupInvite>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(membership) = conn
+        .invite_to_group_chat(invite.room, token.uid, invite.user)
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&membership).expect("failed to serialize room membership")))
+}
@@ -0,0 +1,49 @@
+//! `post /api/bayou_v1/chat/group/leave`
+//!
+//! leave a group chat, expects an auth token in the authorization header
+//! and a body with a [`LeaveGroup`]. the group chat is deleted once
+//! all of its members have left
+//! - ok (200) user has left the group chat
+//! - unauthorized (401) included token is not valid or not a member of the group chat
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LeaveGroup {
+    pub room: Uuid,
+}
+
+#[post("/group/leave")]
+pub async fn leave(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    group: web::Json<LeaveGroup>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    if conn.leave_group_chat(group.room, token.uid).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
@@ -0,0 +1,11 @@
+//! `/api/bayou_v1/chat/...`
+//! direct message and group chat specific methods such as opening a dm
+//! or inviting users to a group chat
+pub mod create_group;
+pub mod get_dms;
+pub mod get_groups;
+pub mod get_members;
+pub mod invite;
+pub mod leave;
+pub mod open_dm;
+pub(super) mod routes;
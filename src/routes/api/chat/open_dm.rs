@@ -0,0 +1,56 @@
+//! `post /api/bayou_v1/chat/dm`
+//!
+//! open a direct message with a user, expects an auth token in the authorization header
+//! and a body with a [`OpenDm`]. if a dm already exists between the users it is returned
+//! instead of creating a new one
+//! - ok (200) should contain a json [`crate::db::types::room::Room`]
+//!   in the body
+//! - unauthorized (401) included token is not valid
+//! - bad request (400) the target user does not exist or is the requesting user
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenDm {
+    pub user: Uuid,
+}
+
+#[post("/dm")]
+pub async fn open_dm(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    target: web::Json<OpenDm>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Some(user) = conn.get_user_uid(&token.uid).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let Ok(room) = conn.open_dm(&user, target.user).await else {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&room).expect("failed to serialize room")))
+}
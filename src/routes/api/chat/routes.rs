@@ -0,0 +1,15 @@
+use super::{
+    create_group::create_group, get_dms::get_dms, get_groups::get_groups, get_members::get_members,
+    invite::invite, leave::leave, open_dm::open_dm,
+};
+
+pub fn get_chat_routes() -> actix_web::Scope {
+    actix_web::web::scope("/chat")
+        .service(open_dm)
+        .service(get_dms)
+        .service(create_group)
+        .service(invite)
+        .service(leave)
+        .service(get_groups)
+        .service(get_members)
+}
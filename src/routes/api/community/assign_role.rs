@@ -0,0 +1,52 @@
+//! `post /api/bayou_v1/community/roles/assign`
+//!
+//! give a role to a member of the community, expects an auth token in the authorization header
+//! and a body with a [`RoleAssignment`]. the role must be below the user's highest role
+//! - ok (200) role was assigned
+//! - unauthorized (401) included token is not valid or not allowed to assign the role
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoleAssignment {
+    pub role: Uuid,
+    pub user: Uuid,
+}
+
+#[post("/roles/assign")]
+pub async fn assign_role(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    assignment: web::Json<RoleAssignment>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    if conn
+        .set_role_assigned(assignment.role, token.uid, assignment.user, true)
+        .await
+        .is_err()
+    {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
@@ -0,0 +1,55 @@
+//! `post /api/bayou_v1/community/ban`
+//!
+//! ban a user from a community, expects an auth token in the authorization header
+//! and a body with a [`Ban`]. requires the ban members permission and to be above the
+//! member in the role hierarchy. users do not need to be a member to be banned
+//! - ok (200) should contain a json [`crate::db::types::comm::community_ban::CommunityBan`]
+//!   in the body
+//! - unauthorized (401) included token is not valid or not allowed to ban the user
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Ban {
+    pub community: Uuid,
+    pub user: Uuid,
+    pub reason: Option<String>,
+}
+
+#[post("/ban")]
+pub async fn ban(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    ban: web::Json<Ban>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let ban = ban.into_inner();
+    let Ok(ban) = conn
+        .ban_member(ban.community, token.uid, ban.user, ban.reason)
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&ban).expect("failed to serialize community ban")))
+}
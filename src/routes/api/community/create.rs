@@ -3,7 +3,7 @@
 //! create a new community, expects a [`crate::db::types::comm::community::Communityinfo`] and
 //! a token in the header
 //! - ok (200) community successfully created and a [`crate::routes::api::types::api_community::ApiCommunity`]
-//! should be present in the body
+//!   should be present in the body
 //! - unauthorized (401) included token is not valid, community not created
 
 use actix_web::{
@@ -0,0 +1,74 @@
+//! `post /api/bayou_v1/community/invites/create`
+//!
+//! create an invite to a community, expects an auth token in the authorization header
+//! and a body with a [`NewInvite`]. requires the create invites permission
+//! - ok (200) should contain a json [`crate::db::types::tokens::join_token::JoinToken`]
+//!   in the body, its id is the token used to join
+//! - unauthorized (401) included token is not valid or not allowed to create invites
+//! - bad request (400) max uses is less than 1
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    db::{curr_time::get_current_time, pg_conn::PgConn},
+    routes::api::utilities::auth_header::get_auth_header,
+};
+
+/// invites expire after a week unless specified
+const DEFAULT_EXPIRY_HOURS: i64 = 24 * 7;
+/// invites may not last longer than 30 days
+const MAX_EXPIRY_HOURS: i64 = 24 * 30;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewInvite {
+    pub community: Uuid,
+    /// hours until the invite expires, clamped between 1 hour and 30 days
+    pub expires_in: Option<i64>,
+    /// unlimited uses if none
+    pub max_uses: Option<i64>,
+}
+
+#[post("/invites/create")]
+pub async fn create_invite(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    invite: web::Json<NewInvite>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    if invite.max_uses.is_some_and(|x| x < 1) {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let hours = invite
+        .expires_in
+        .unwrap_or(DEFAULT_EXPIRY_HOURS)
+        .clamp(1, MAX_EXPIRY_HOURS);
+    let expiry = get_current_time() + hours * 60 * 60 * 1000;
+    let Ok(token) = conn
+        .create_invite(invite.community, token.uid, expiry, invite.max_uses)
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&token).expect("failed to serialize join token")))
+}
@@ -0,0 +1,57 @@
+//! `post /api/bayou_v1/community/roles/create`
+//!
+//! create a role in a community, expects an auth token in the authorization header
+//! and a body with a [`NewRole`]. requires the manage roles permission, the role must be
+//! positioned above the default role (0) and below the user's highest role and may only
+//! grant permissions the user has
+//! - ok (200) should contain a json [`crate::db::types::comm::role::Role`] in the body
+//! - unauthorized (401) included token is not valid or not allowed to create the role
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    db::{pg_conn::PgConn, types::comm::role::RoleInfo},
+    routes::api::utilities::auth_header::get_auth_header,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewRole {
+    pub community: Uuid,
+    pub info: RoleInfo,
+}
+
+#[post("/roles/create")]
+pub async fn create_role(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    new_role: web::Json<NewRole>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let new_role = new_role.into_inner();
+    let Ok(role) = conn
+        .create_role(new_role.community, token.uid, new_role.info)
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&role).expect("failed to serialize role")))
+}
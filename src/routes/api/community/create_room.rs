@@ -3,9 +3,9 @@
 //! get all rooms in a community, expects an auth token in the authorization header
 //! and a body with a [`NewRoom`]
 //! - ok (200) should contain a json [`crate::db::types::room::Room`]
-//! in the body
+//!   in the body
 //! - unauthorized (401) included token is not valid or authorized to create
-//! rooms in the given community
+//!   rooms in the given community
 
 use actix_web::{
     post,
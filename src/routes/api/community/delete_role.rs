@@ -0,0 +1,47 @@
+//! `post /api/bayou_v1/community/roles/delete`
+//!
+//! delete a role, expects an auth token in the authorization header and a body with
+//! a [`DeleteRole`]. the default role can not be deleted
+//! - ok (200) role was deleted
+//! - unauthorized (401) included token is not valid or not allowed to delete the role
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeleteRole {
+    pub role: Uuid,
+}
+
+#[post("/roles/delete")]
+pub async fn delete_role(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    role: web::Json<DeleteRole>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    if conn.delete_role(role.role, token.uid).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
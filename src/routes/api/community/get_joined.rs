@@ -2,7 +2,7 @@
 //!
 //! get all of a user's communities, expects an [`crate::db::types::tokens::auth_token::AuthToken`]
 //! - ok (200) should contain an array of [`crate::routes::api::types::api_community::ApiCommunity`]
-//! present in the body
+//!   present in the body
 //! - unauthorized (401) included token is not valid
 
 use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
@@ -0,0 +1,44 @@
+//! `get /api/bayou_v1/community/role_members/{comm_id}`
+//!
+//! get every role assignment in a community, expects an auth token in the authorization header.
+//! members that are not listed only have the default role
+//! - ok (200) should contain an array of [`crate::db::types::comm::role_membership::RoleMembership`]
+//!   present in the body
+//! - unauthorized (401) included token is not valid or not a member of the community
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use uuid::Uuid;
+
+#[get("/role_members/{comm_id}")]
+pub async fn get_role_members(
+    conn: Data<PgConn>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(memberships) = conn
+        .get_comm_role_memberships(path.into_inner(), token.uid)
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&memberships).expect("failed to serialize role memberships")))
+}
@@ -1,8 +1,8 @@
 //! `get /api/bayou_v1/community/rooms/{comm_id}`
 //!
 //! get all rooms in a community, expects an auth token in the authorization header
-//! - ok (200) should contain an array of [`crate::db::types::room::Room`]
-//! should be present in the body
+//! - ok (200) should contain an array of [`crate::routes::api::types::api_room::ApiRoom`]
+//!   should be present in the body
 //! - unauthorized (401) included token is not valid
 
 use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
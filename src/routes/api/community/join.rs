@@ -0,0 +1,119 @@
+//! `post /api/bayou_v1/community/join`
+//!
+//! join a community using an invite, expects an auth token in the authorization header
+//! and a body with a [`JoinCommunity`]. the join is announced in the community's system
+//! channel. invites from other instances are redeemed on that instance and the community
+//! is stored locally, the history of its rooms is then fetched in the background
+//! - ok (200) should contain a json [`crate::routes::api::types::api_community::ApiCommunity`]
+//!   in the body
+//! - unauthorized (401) included token is not valid
+//! - bad request (400) the invite could not be used, a [`crate::routes::api::types::join_err::JoinErr`]
+//!   will be in the body
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use tokio::task::spawn_local;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::pg_conn::PgConn,
+    federation::{
+        client::join_remote, policy::federates_with, resolve::backfill_community,
+        signatures::InstanceSigner,
+    },
+    live_server::server::ChatServerHandle,
+    routes::api::{
+        message::send_message::message_notifyer,
+        types::{api_community::ApiCommunity, join_err::JoinErr},
+        utilities::auth_header::get_auth_header,
+    },
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JoinCommunity {
+    pub token: Uuid,
+    /// the domain of the instance the invite is from, left out for our own invites
+    #[serde(default)]
+    pub domain: Option<String>,
+}
+
+#[post("/join")]
+pub async fn join(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    signer: Data<InstanceSigner>,
+    invite: web::Json<JoinCommunity>,
+    chat_server: web::Data<ChatServerHandle>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Some(user) = conn.get_user_uid(&token.uid).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    if let Some(domain) = invite
+        .domain
+        .as_ref()
+        .filter(|x| **x != config.instance_domain)
+    {
+        if !federates_with(&conn, &config, domain).await {
+            return Ok(HttpResponse::BadRequest()
+                .content_type("application/json; charset=utf-8")
+                .body(
+                    serde_json::to_string(&JoinErr::Blocked).expect("failed to serialize JoinErr"),
+                ));
+        }
+        let community = match join_remote(&config, &signer, domain, invite.token, &user).await {
+            Ok(x) => x,
+            Err(err) => {
+                return Ok(HttpResponse::BadRequest()
+                    .content_type("application/json; charset=utf-8")
+                    .body(serde_json::to_string(&err).expect("failed to serialize JoinErr")));
+            }
+        };
+        let Ok(community) = conn.join_remote_community(community, &user).await else {
+            return Ok(HttpResponse::BadRequest()
+                .content_type("application/json; charset=utf-8")
+                .body(
+                    serde_json::to_string(&JoinErr::Unreachable)
+                        .expect("failed to serialize JoinErr"),
+                ));
+        };
+        let com_id = community.id;
+        spawn_local(async move { backfill_community(&conn, &config, &signer, com_id).await });
+        let community: ApiCommunity = community.into();
+        return Ok(HttpResponse::Ok()
+            .content_type("application/json; charset=utf-8")
+            .body(serde_json::to_string(&community).expect("failed to serialize community")));
+    }
+    let (community, announcement) = match conn.redeem_invite(invite.token, &user).await {
+        Ok(x) => x,
+        Err(err) => {
+            return Ok(HttpResponse::BadRequest()
+                .content_type("application/json; charset=utf-8")
+                .body(serde_json::to_string(&err).expect("failed to serialize JoinErr")));
+        }
+    };
+    if let Some(announcement) = announcement {
+        spawn_local(message_notifyer(chat_server, conn, announcement));
+    }
+    let community: ApiCommunity = community.into();
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&community).expect("failed to serialize community")))
+}
@@ -0,0 +1,53 @@
+//! `post /api/bayou_v1/community/kick`
+//!
+//! remove a member from a community, expects an auth token in the authorization header
+//! and a body with a [`Kick`]. requires the kick members permission and to be above the
+//! member in the role hierarchy
+//! - ok (200) member was removed
+//! - unauthorized (401) included token is not valid or not allowed to kick the member
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Kick {
+    pub community: Uuid,
+    pub user: Uuid,
+}
+
+#[post("/kick")]
+pub async fn kick(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    kick: web::Json<Kick>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    if conn
+        .kick_member(kick.community, token.uid, kick.user)
+        .await
+        .is_err()
+    {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
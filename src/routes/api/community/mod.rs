@@ -1,8 +1,26 @@
 //! `/api/bayou_v1/community/...`
 //! community specific methods such as creating and joining communities
+//! as well as managing roles, members and invites
+pub mod assign_role;
+pub mod ban;
 pub mod create;
+pub mod create_invite;
+pub mod create_role;
 pub mod create_room;
+pub mod delete_role;
+pub mod get_bans;
+pub mod get_invites;
 pub mod get_joined;
 pub mod get_members;
+pub mod get_permissions;
+pub mod get_role_members;
+pub mod get_roles;
 pub mod get_rooms;
+pub mod join;
+pub mod kick;
+pub mod preview_invite;
+pub mod revoke_invite;
 pub(super) mod routes;
+pub mod unassign_role;
+pub mod unban;
+pub mod update_role;
@@ -0,0 +1,26 @@
+//! `get /api/bayou_v1/community/invite/{token}`
+//!
+//! preview the community an invite is for, does not require authorization
+//! - ok (200) should contain a json [`crate::routes::api::types::invite_preview::InvitePreview`]
+//!   in the body
+//! - not found (404) invite does not exist or has expired
+
+use crate::db::pg_conn::PgConn;
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse, Result,
+};
+use uuid::Uuid;
+
+#[get("/invite/{token}")]
+pub async fn preview_invite(conn: Data<PgConn>, path: web::Path<Uuid>) -> Result<HttpResponse> {
+    let Some(preview) = conn.get_invite_preview(path.into_inner()).await else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&preview).expect("failed to serialize invite preview")))
+}
@@ -0,0 +1,48 @@
+//! `post /api/bayou_v1/community/invites/revoke`
+//!
+//! revoke an invite, expects an auth token in the authorization header and a body with
+//! a [`RevokeInvite`]. allowed for the creator of the invite and users with the manage
+//! community permission
+//! - ok (200) invite was revoked
+//! - unauthorized (401) included token is not valid or not allowed to revoke the invite
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RevokeInvite {
+    pub token: Uuid,
+}
+
+#[post("/invites/revoke")]
+pub async fn revoke_invite(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    invite: web::Json<RevokeInvite>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    if conn.revoke_invite(invite.token, token.uid).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
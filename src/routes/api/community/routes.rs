@@ -1,6 +1,11 @@
 use super::{
-    create::create, create_room::create_room, get_joined::get_joined, get_members::get_members,
-    get_rooms::get_rooms,
+    assign_role::assign_role, ban::ban, create::create, create_invite::create_invite,
+    create_role::create_role, create_room::create_room, delete_role::delete_role,
+    get_bans::get_bans, get_invites::get_invites, get_joined::get_joined, get_members::get_members,
+    get_permissions::get_permissions, get_role_members::get_role_members, get_roles::get_roles,
+    get_rooms::get_rooms, join::join, kick::kick, preview_invite::preview_invite,
+    revoke_invite::revoke_invite, unassign_role::unassign_role, unban::unban,
+    update_role::update_role,
 };
 
 pub fn get_community_routes() -> actix_web::Scope {
@@ -10,4 +15,21 @@ pub fn get_community_routes() -> actix_web::Scope {
         .service(get_rooms)
         .service(get_members)
         .service(get_joined)
+        .service(get_roles)
+        .service(get_role_members)
+        .service(create_role)
+        .service(update_role)
+        .service(delete_role)
+        .service(assign_role)
+        .service(unassign_role)
+        .service(get_permissions)
+        .service(kick)
+        .service(ban)
+        .service(unban)
+        .service(get_bans)
+        .service(create_invite)
+        .service(get_invites)
+        .service(revoke_invite)
+        .service(preview_invite)
+        .service(join)
 }
@@ -0,0 +1,49 @@
+//! `post /api/bayou_v1/community/roles/unassign`
+//!
+//! take a role away from a member of the community, expects an auth token in the authorization header
+//! and a body with a [`RoleAssignment`]. the role must be below the user's highest role
+//! - ok (200) role was unassigned
+//! - unauthorized (401) included token is not valid or not allowed to unassign the role
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+
+use crate::{
+    db::pg_conn::PgConn,
+    routes::api::{
+        community::assign_role::RoleAssignment, utilities::auth_header::get_auth_header,
+    },
+};
+
+#[post("/roles/unassign")]
+pub async fn unassign_role(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    assignment: web::Json<RoleAssignment>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    if conn
+        .set_role_assigned(assignment.role, token.uid, assignment.user, false)
+        .await
+        .is_err()
+    {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
@@ -0,0 +1,53 @@
+//! `post /api/bayou_v1/community/unban`
+//!
+//! lift a user's ban from a community, expects an auth token in the authorization header
+//! and a body with a [`Unban`]. requires the ban members permission and to be at least as
+//! high in the role hierarchy as whoever issued the ban was when they issued it
+//! - ok (200) user is no longer banned
+//! - unauthorized (401) included token is not valid or not allowed to unban
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Unban {
+    pub community: Uuid,
+    pub user: Uuid,
+}
+
+#[post("/unban")]
+pub async fn unban(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    unban: web::Json<Unban>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    if conn
+        .unban_member(unban.community, token.uid, unban.user)
+        .await
+        .is_err()
+    {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
@@ -0,0 +1,54 @@
+//! `post /api/bayou_v1/community/roles/update`
+//!
+//! update a role, expects an auth token in the authorization header and a body with
+//! a [`UpdateRole`]. follows the same rules as creating a role, the position of the
+//! default role can not be changed. as every member holds the default role editing
+//! it also requires the manage community permission
+//! - ok (200) should contain the updated json [`crate::db::types::comm::role::Role`] in the body
+//! - unauthorized (401) included token is not valid or not allowed to update the role
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    db::{pg_conn::PgConn, types::comm::role::RoleInfo},
+    routes::api::utilities::auth_header::get_auth_header,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdateRole {
+    pub role: Uuid,
+    pub info: RoleInfo,
+}
+
+#[post("/roles/update")]
+pub async fn update_role(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    update: web::Json<UpdateRole>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let update = update.into_inner();
+    let Ok(role) = conn.update_role(update.role, token.uid, update.info).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&role).expect("failed to serialize role")))
+}
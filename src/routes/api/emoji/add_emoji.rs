@@ -0,0 +1,86 @@
+//! `post /api/bayou_v1/emoji/packs/add`
+//!
+//! add an emoji to one of the user's packs, expects an auth token in the authorization
+//! header and a body with an [`AddEmoji`]. the file must be an uploaded png, jpg or gif
+//! owned by the user and the shortcode may only have lowercase letters, numbers, `_` and
+//! `+`. the first emoji added becomes the pack's highlight
+//! - ok (200) should contain a json [`crate::routes::api::types::api_emoji::ApiEmoji`]
+//! - bad request (400) the emoji is invalid, a [`crate::federation::emoji::EmojiErr`]
+//!   will be in the body
+//! - unauthorized (401) included token is not valid or the pack or file isn't the user's
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::pg_conn::PgConn,
+    federation::emoji::EmojiErr,
+    routes::api::{types::api_emoji::ApiEmoji, utilities::auth_header::get_auth_header},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AddEmoji {
+    pub pack: Uuid,
+    pub shortcode: String,
+    pub file: Uuid,
+    /// alt text for the emoji
+    pub description: Option<String>,
+}
+
+#[post("/packs/add")]
+pub async fn add_emoji(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    emoji: web::Json<AddEmoji>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let emoji = emoji.into_inner();
+    let created = conn
+        .add_custom_emoji(
+            token.uid,
+            emoji.pack,
+            emoji.shortcode,
+            emoji.file,
+            emoji.description,
+        )
+        .await;
+    let emoji = match created {
+        Ok(x) => x,
+        Err(EmojiErr::NotOwned) => {
+            return Ok(HttpResponse::Unauthorized()
+                .content_type("application/json; charset=utf-8")
+                .body(""));
+        }
+        Err(err) => {
+            return Ok(HttpResponse::BadRequest()
+                .content_type("application/json; charset=utf-8")
+                .body(serde_json::to_string(&err).expect("failed to serialize EmojiErr")));
+        }
+    };
+    let (pack, _) = conn
+        .get_emoji_pack(emoji.pack_id)
+        .await
+        .expect("emoji pack was just updated");
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(
+            serde_json::to_string(&ApiEmoji::from_db(&config, &pack, &emoji))
+                .expect("failed to serialize emoji"),
+        ))
+}
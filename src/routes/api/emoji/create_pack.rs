@@ -0,0 +1,47 @@
+//! `post /api/bayou_v1/emoji/packs/create`
+//!
+//! create an emoji pack hosted by our instance, expects an auth token in the authorization
+//! header and a body with an [`crate::db::types::emoji_pack::EmojiPackInfo`]. packs are
+//! federated once they have emoji
+//! - ok (200) should contain a json [`crate::routes::api::types::api_emoji::ApiEmojiPack`]
+//! - unauthorized (401) included token is not valid
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+
+use crate::{
+    config::Config,
+    db::{pg_conn::PgConn, types::emoji_pack::EmojiPackInfo},
+    routes::api::{types::api_emoji::ApiEmojiPack, utilities::auth_header::get_auth_header},
+};
+
+#[post("/packs/create")]
+pub async fn create_pack(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    info: web::Json<EmojiPackInfo>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let pack = conn
+        .create_emoji_pack(token.uid, info.into_inner(), &config.instance_domain)
+        .await;
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(
+            serde_json::to_string(&ApiEmojiPack::from_db(&config, pack, Vec::new()))
+                .expect("failed to serialize emoji pack"),
+        ))
+}
@@ -0,0 +1,51 @@
+//! `get /api/bayou_v1/emoji/packs/{pack id}`
+//!
+//! get an emoji pack, expects an auth token in the authorization header. packs from
+//! other websites are synced first if they are stale
+//! - ok (200) should contain a json [`crate::routes::api::types::api_emoji::ApiEmojiPack`]
+//! - not found (404) the pack doesn't exist
+//! - unauthorized (401) included token is not valid
+
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::pg_conn::PgConn,
+    federation::resolve::resolve_emoji_pack,
+    routes::api::{types::api_emoji::ApiEmojiPack, utilities::auth_header::get_auth_header},
+};
+
+#[get("/packs/{pack_id}")]
+pub async fn get_pack(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Some((pack, emoji)) = resolve_emoji_pack(&conn, &config, path.into_inner()).await else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(
+            serde_json::to_string(&ApiEmojiPack::from_db(&config, pack, emoji))
+                .expect("failed to serialize emoji pack"),
+        ))
+}
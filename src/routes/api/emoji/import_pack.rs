@@ -0,0 +1,62 @@
+//! `post /api/bayou_v1/emoji/packs/import`
+//!
+//! import an emoji pack from another instance or a static site, expects an auth token of
+//! an instance admin or moderator in the authorization header and a body with an
+//! [`ImportPack`]. importing a pack we already have syncs it, its emoji are only replaced
+//! if it has changed since it was last fetched
+//! - ok (200) should contain a json [`crate::routes::api::types::api_emoji::ApiEmojiPack`]
+//! - bad request (400) the pack could not be imported, a [`crate::federation::emoji::EmojiErr`]
+//!   will be in the body
+//! - unauthorized (401) included token is not valid or not an instance admin or moderator
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::pg_conn::PgConn,
+    federation::resolve::import_emoji_pack,
+    routes::api::{types::api_emoji::ApiEmojiPack, utilities::instance_mod::is_instance_mod},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImportPack {
+    /// the domain of the instance or static site hosting the pack
+    pub website: String,
+    /// the id of the pack on the website
+    pub pack: Uuid,
+}
+
+#[post("/packs/import")]
+pub async fn import_pack(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    import: web::Json<ImportPack>,
+) -> Result<HttpResponse> {
+    if !is_instance_mod(&req, &conn).await {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let website = import.website.to_lowercase();
+    let (pack, emoji) = match import_emoji_pack(&conn, &config, &website, import.pack).await {
+        Ok(x) => x,
+        Err(err) => {
+            return Ok(HttpResponse::BadRequest()
+                .content_type("application/json; charset=utf-8")
+                .body(serde_json::to_string(&err).expect("failed to serialize EmojiErr")));
+        }
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(
+            serde_json::to_string(&ApiEmojiPack::from_db(&config, pack, emoji))
+                .expect("failed to serialize emoji pack"),
+        ))
+}
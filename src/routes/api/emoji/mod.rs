@@ -0,0 +1,9 @@
+//! `/api/bayou_v1/emoji/...`
+//! custom emoji packs, packs are created by our users or imported from
+//! other instances and static sites, see [`crate::federation::emoji`]
+
+pub mod add_emoji;
+pub mod create_pack;
+pub mod get_pack;
+pub mod import_pack;
+pub(super) mod routes;
@@ -0,0 +1,11 @@
+use super::{
+    add_emoji::add_emoji, create_pack::create_pack, get_pack::get_pack, import_pack::import_pack,
+};
+
+pub fn get_emoji_routes() -> actix_web::Scope {
+    actix_web::web::scope("/emoji")
+        .service(create_pack)
+        .service(add_emoji)
+        .service(import_pack)
+        .service(get_pack)
+}
@@ -0,0 +1,55 @@
+//! `post /api/bayou_v1/files/delete`
+//!
+//! delete a file you uploaded, expects a [`DeleteFile`] with a token in the auth header
+//! - ok (200) file was deleted
+//! - unauthorized (401) included token is not valid
+//! - not found (404) file does not exist or is not owned by the user
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeleteFile {
+    pub file: Uuid,
+}
+
+#[post("/delete")]
+pub async fn delete_file(
+    state: Data<crate::config::Config>,
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    file: web::Json<DeleteFile>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Some(file) = conn.get_file(file.file).await else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    if file.owner != Some(token.uid) {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    // the database entry is gone either way so we don't fail if storage does
+    let _ = state.storage_options.delete_user_file(conn, file).await;
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
@@ -0,0 +1,38 @@
+//! `post /api/bayou_v1/files/token`
+//!
+//! get a token for downloading files without the auth header, expects a token in the
+//! auth header. it is passed as the `token` query parameter of file urls so they can
+//! be used by elements such as `<img>` and `<video>`. see
+//! [`crate::cryptography::file_tokens`] for how long tokens last
+//! - ok (200) should contain a json [`crate::cryptography::file_tokens::FileToken`]
+//! - unauthorized (401) included token is not valid
+
+use actix_web::{post, web::Data, HttpRequest, HttpResponse, Result};
+
+use crate::{
+    cryptography::file_tokens::FileTokens,
+    db::{curr_time::get_current_time, pg_conn::PgConn},
+    routes::api::utilities::auth_header::get_auth_header,
+};
+
+#[post("/token")]
+pub async fn file_token(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    tokens: Data<FileTokens>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let file_token = tokens.issue(&token.uid, get_current_time());
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&file_token).expect("failed to serialize file token")))
+}
@@ -1,21 +1,62 @@
-//! `/api/bayou_v1/files/new`
+//! `post /api/bayou_v1/files/new`
+//!
+//! upload a file, expects a multipart form with a `file` and an optional `description`
+//! with a token in the auth header. exif and xmp metadata is removed from png, jpeg and
+//! webp images and thumbnails are generated for images larger than the thumbnail sizes
+//! - ok (200) should contain a json [`crate::routes::api::types::api_file::ApiFile`]
+//!   in the body
+//! - unauthorized (401) included token is not valid
+//! - payload too large (413) file is larger than the user's max upload size. the body
+//!   is refused without being read if its content length is too large and otherwise
+//!   as soon as it goes over
+//! - bad request (400) the body is not a valid form
+//! - internal server error (500) file could not be stored
 
-use actix_multipart::form::{json::Json as MPJson, tempfile::TempFile, MultipartForm};
-use actix_web::{post, web, web::Data, HttpRequest, HttpResponse, Result};
-use serde::Deserialize;
+use std::{cell::Cell, rc::Rc};
 
-use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
+use actix_web::{
+    dev,
+    error::PayloadError,
+    http::header::CONTENT_LENGTH,
+    post,
+    web::{self, Data},
+    FromRequest, HttpRequest, HttpResponse, Result,
+};
+use futures_util::StreamExt;
 
-#[derive(Debug, Deserialize)]
-struct Metadata {
-    name: String,
-}
+use crate::{
+    db::pg_conn::PgConn,
+    routes::api::{types::api_file::ApiFile, utilities::auth_header::get_auth_header},
+};
 
 #[derive(Debug, MultipartForm)]
 pub struct UploadForm {
-    // #[multipart(limit = "100MB")]
     file: TempFile,
-    // json: MPJson<Metadata>,
+    /// alt text for the file
+    description: Option<Text<String>>,
+}
+
+/// room for the rest of the form on top of the file
+pub const FORM_OVERHEAD: usize = 100 * 1024;
+
+/// end the payload with an overflow once more than `limit` bytes have been read,
+/// `overflowed` is set when it does
+fn limit_payload(payload: web::Payload, limit: usize, overflowed: Rc<Cell<bool>>) -> dev::Payload {
+    let limited = payload.scan(0usize, move |read, chunk| {
+        let chunk = chunk.and_then(|chunk| {
+            *read += chunk.len();
+            match *read > limit {
+                true => Err(PayloadError::Overflow),
+                false => Ok(chunk),
+            }
+        });
+        if matches!(chunk, Err(PayloadError::Overflow)) {
+            overflowed.set(true);
+        }
+        std::future::ready(Some(chunk))
+    });
+    dev::Payload::from(limited.boxed_local())
 }
 
 #[post("/new")]
@@ -23,16 +64,8 @@ pub async fn upload_file(
     state: Data<crate::config::Config>,
     req: HttpRequest,
     conn: Data<PgConn>,
-    MultipartForm(form): MultipartForm<UploadForm>
+    payload: web::Payload,
 ) -> Result<HttpResponse> {
-    println!(
-        "Uploaded file {}, with size: {}, with mime: {}",
-        // form.json.name, 
-        form.file.file_name.unwrap_or("[no name provided]".to_string()),
-        form.file.size,
-        form.file.content_type.map(|x| x.essence_str().to_string()).unwrap_or("missing mime type".to_string())
-    );
-    
     let Some(token) = get_auth_header(&req) else {
         return Ok(HttpResponse::Unauthorized()
             .content_type("application/json; charset=utf-8")
@@ -43,6 +76,63 @@ pub async fn upload_file(
             .content_type("application/json; charset=utf-8")
             .body(""));
     }
-    
-    Ok(HttpResponse::Ok().body(""))
-}
\ No newline at end of file
+    let Some(user) = conn.get_user_uid(&token.uid).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let superuser = user
+        .local_info
+        .as_ref()
+        .is_some_and(|x| x.is_admin || x.instance_mod);
+    let max_size = match superuser {
+        true => state
+            .max_superuser_upload_size
+            .unwrap_or(state.max_standard_upload_size),
+        false => state.max_standard_upload_size,
+    };
+    let max_size = max_size * 1024 * 1024;
+    let too_large = || {
+        Ok(HttpResponse::PayloadTooLarge()
+            .content_type("application/json; charset=utf-8")
+            .body(""))
+    };
+    let content_length = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|x| x.to_str().ok())
+        .and_then(|x| x.parse::<usize>().ok());
+    if content_length.is_some_and(|x| x > max_size + FORM_OVERHEAD) {
+        return too_large();
+    }
+    // the form is only read once we know how large the user's uploads may be
+    let overflowed = Rc::new(Cell::new(false));
+    let mut payload = limit_payload(payload, max_size + FORM_OVERHEAD, overflowed.clone());
+    let form = match MultipartForm::<UploadForm>::from_request(&req, &mut payload).await {
+        Ok(MultipartForm(form)) => form,
+        Err(_) if overflowed.get() => return too_large(),
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest()
+                .content_type("application/json; charset=utf-8")
+                .body(""));
+        }
+    };
+    if form.file.size > max_size {
+        return too_large();
+    }
+
+    let description = form.description.map(|x| x.into_inner());
+    let Ok((file, thumbnails)) = state
+        .storage_options
+        .create_user_file(conn, form.file, user.id, description)
+        .await
+    else {
+        return Ok(HttpResponse::InternalServerError()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let file = ApiFile::from_db(file, thumbnails, &state.base_url());
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&file).expect("failed to serialize file")))
+}
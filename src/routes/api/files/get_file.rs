@@ -0,0 +1,80 @@
+//! `get /api/bayou_v1/files/{file_id}`
+//!
+//! download a file, expects a token in the auth header or a file token from
+//! [`super::file_token`] as the `token` query parameter. supports range requests
+//! and conditional requests with etags or last modified. files browsers could run
+//! code from, such as html or svg, are sent as attachments.
+//!
+//! images may be requested with `?thumbnail={size}` to get the smallest thumbnail
+//! at least `size` pixels on its longest edge, the original is sent if there is none
+//! - ok (200) the file's content with its content type
+//! - partial content (206) the requested range of the file
+//! - unauthorized (401) included token is not valid or the file token has expired
+//! - not found (404) file does not exist or the user is not allowed to view it
+
+use crate::{
+    cryptography::file_tokens::FileTokens,
+    db::{curr_time::get_current_time, pg_conn::PgConn},
+    routes::api::utilities::auth_header::get_auth_header,
+};
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileQuery {
+    pub thumbnail: Option<i64>,
+    /// used instead of the auth header
+    pub token: Option<String>,
+}
+
+#[get("/{file_id}")]
+pub async fn get_file(
+    state: Data<crate::config::Config>,
+    conn: Data<PgConn>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    query: web::Query<FileQuery>,
+    file_tokens: Data<FileTokens>,
+) -> Result<HttpResponse> {
+    let uid = match (get_auth_header(&req), &query.token) {
+        (Some(token), _) => match conn.validate_auth_token(&token).await {
+            Ok(()) => Some(token.uid),
+            Err(()) => None,
+        },
+        (None, Some(token)) => file_tokens.verify(token, get_current_time()),
+        (None, None) => {
+            return Ok(HttpResponse::Unauthorized()
+                .content_type("application/json; charset=utf-8")
+                .body("invalid or missing auth header"));
+        }
+    };
+    let Some(uid) = uid else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let Some(file) = conn.user_get_file(path.into_inner(), uid).await else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let thumbnail = match query.thumbnail {
+        Some(size) => conn.get_fitting_thumbnail(file.id, size).await,
+        None => None,
+    };
+    let Ok(response) = state
+        .storage_options
+        .serve_file(&req, &file, thumbnail.as_ref())
+        .await
+    else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(response)
+}
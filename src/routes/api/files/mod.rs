@@ -1,5 +1,8 @@
 //! `/api/bayou_v1/files/...`
-//! file methods such as uploading files
+//! file methods such as uploading and downloading files
 
+pub mod delete_file;
+pub mod file_token;
 pub mod file_upload;
-pub(super) mod routes;
\ No newline at end of file
+pub mod get_file;
+pub(super) mod routes;
@@ -1,5 +1,11 @@
-use crate::routes::api::files::file_upload::upload_file;
+use crate::routes::api::files::{
+    delete_file::delete_file, file_token::file_token, file_upload::upload_file, get_file::get_file,
+};
 
 pub fn get_file_routes() -> actix_web::Scope {
-    actix_web::web::scope("/files").service(upload_file)
+    actix_web::web::scope("/files")
+        .service(upload_file)
+        .service(delete_file)
+        .service(file_token)
+        .service(get_file)
 }
@@ -0,0 +1,169 @@
+//! `post /api/bayou_v1/message/react`
+//!
+//! react to a message, expects a [`Reaction`] with a token in the auth header. requires
+//! the add reactions permission, members of the room will receive a
+//! [`SocketMsg::ReactionAdded`]
+//! - ok (200) reaction was added or the user already reacted with the emoji
+//! - unauthorized (401) included token is not valid or not allowed to react to the message
+//! - bad request (400) emoji is not valid
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use tokio::task::spawn_local;
+use uuid::Uuid;
+
+use crate::{
+    db::pg_conn::PgConn,
+    live_server::{server::ChatServerHandle, socket_msg::SocketMsg},
+    routes::api::utilities::auth_header::get_auth_header,
+};
+
+/// the longest unicode emoji sequences are around 10 codepoints
+const MAX_EMOJI_LEN: usize = 32;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Reaction {
+    pub message: Uuid,
+    /// the unicode emoji or the shortcode of the custom emoji
+    pub emoji: String,
+    /// present when reacting with a custom emoji
+    pub emoji_id: Option<Uuid>,
+}
+
+impl Reaction {
+    /// shortcodes may only contain alphanumerics, dashes and underscores. unicode
+    /// emoji must be a single emoji, including sequences such as skin tones, flags
+    /// and those joined with zero width joiners
+    pub fn is_valid(&self) -> bool {
+        if self.emoji.is_empty() || self.emoji.chars().count() > MAX_EMOJI_LEN {
+            return false;
+        }
+        match self.emoji_id {
+            Some(_) => self
+                .emoji
+                .chars()
+                .all(|x| x.is_alphanumeric() || x == '_' || x == '-'),
+            None => emojis::get(&self.emoji).is_some(),
+        }
+    }
+}
+
+pub async fn reaction_notifyer(
+    chat_server: web::Data<ChatServerHandle>,
+    conn: Data<PgConn>,
+    room: Uuid,
+    message: SocketMsg,
+) {
+    let Some(room) = conn.get_room(room).await else {
+        return;
+    };
+    let members = conn.get_room_member_ids(&room).await;
+
+    chat_server
+        .send_room_message(room.id, members, message, None)
+        .await;
+}
+
+#[post("/react")]
+pub async fn add_reaction(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    reaction: web::Json<Reaction>,
+    chat_server: web::Data<ChatServerHandle>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let reaction = reaction.into_inner();
+    if !reaction.is_valid() {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(added) = conn
+        .add_reaction(
+            token.uid,
+            reaction.message,
+            reaction.emoji,
+            reaction.emoji_id,
+        )
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    if let Some(added) = added {
+        spawn_local(reaction_notifyer(
+            chat_server,
+            conn,
+            added.room,
+            SocketMsg::ReactionAdded(added),
+        ));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unicode(emoji: &str) -> Reaction {
+        Reaction {
+            message: Uuid::nil(),
+            emoji: emoji.to_string(),
+            emoji_id: None,
+        }
+    }
+
+    fn custom(shortcode: &str) -> Reaction {
+        Reaction {
+            message: Uuid::nil(),
+            emoji: shortcode.to_string(),
+            emoji_id: Some(Uuid::nil()),
+        }
+    }
+
+    #[test]
+    fn single_emoji_are_valid() {
+        for emoji in ["😀", "❤️", "👍🏽", "🇳🇿", "👨‍👩‍👧", "🏳️‍🌈", "1️⃣"]
+        {
+            assert!(unicode(emoji).is_valid(), "{emoji} should be valid");
+        }
+    }
+
+    #[test]
+    fn anything_but_a_single_emoji_is_invalid() {
+        for emoji in [
+            "", "a", "😀😀", "😀 ", " 😀", "😀a", ":smile:", "\u{200d}", "🏽",
+        ] {
+            assert!(!unicode(emoji).is_valid(), "{emoji:?} should be invalid");
+        }
+    }
+
+    #[test]
+    fn shortcodes_are_alphanumeric() {
+        assert!(custom("blob_cat-2").is_valid());
+        for shortcode in ["", "blob cat", ":blobcat:", "blob\ncat", "<b>"] {
+            assert!(
+                !custom(shortcode).is_valid(),
+                "{shortcode:?} should be invalid"
+            );
+        }
+        assert!(!custom(&"a".repeat(MAX_EMOJI_LEN + 1)).is_valid());
+        assert!(custom(&"a".repeat(MAX_EMOJI_LEN)).is_valid());
+    }
+}
@@ -0,0 +1,78 @@
+//! `post /api/bayou_v1/message/delete`
+//!
+//! delete a message, expects a [`DeleteMessage`] with a token in the auth header. allowed
+//! for the author of the message and users with the manage messages permission in the room,
+//! members of the room will receive a [`SocketMsg::MessageDeleted`]
+//! - ok (200) message was deleted
+//! - unauthorized (401) included token is not valid or not allowed to delete the message
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use tokio::task::spawn_local;
+use uuid::Uuid;
+
+use crate::{
+    db::{pg_conn::PgConn, types::message::DbMessage},
+    live_server::{server::ChatServerHandle, socket_msg::SocketMsg},
+    routes::api::utilities::auth_header::get_auth_header,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeleteMessage {
+    pub id: Uuid,
+}
+
+pub async fn delete_notifyer(
+    chat_server: web::Data<ChatServerHandle>,
+    conn: Data<PgConn>,
+    message: DbMessage,
+) {
+    let Some(room) = conn.get_room(message.info.room).await else {
+        return;
+    };
+    let members = conn.get_room_member_ids(&room).await;
+
+    chat_server
+        .send_room_message(
+            room.id,
+            members,
+            SocketMsg::MessageDeleted {
+                id: message.id,
+                room: room.id,
+            },
+            None,
+        )
+        .await;
+}
+
+#[post("/delete")]
+pub async fn delete_message(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    message: web::Json<DeleteMessage>,
+    chat_server: web::Data<ChatServerHandle>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(message) = conn.delete_message(token.uid, message.id).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    spawn_local(delete_notifyer(chat_server, conn, message));
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
@@ -0,0 +1,102 @@
+//! `post /api/bayou_v1/message/edit`
+//!
+//! edit a message, expects an [`EditMessage`] with a token in the auth header. the
+//! author of a message and members able to manage messages in the room may edit it,
+//! members of the room will receive a [`SocketMsg::MessageEdited`]
+//! - ok (200) should contain the edited [`crate::routes::api::types::api_message::ApiMessage`]
+//!   in the body
+//! - unauthorized (401) included token is not valid or not allowed to edit the message
+//! - bad request (400) message is empty
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use codes_iso_639::part_1::LanguageCode;
+use serde::{Deserialize, Serialize};
+use tokio::task::spawn_local;
+use uuid::Uuid;
+
+use crate::{
+    db::{
+        pg_conn::PgConn,
+        types::message::{DbMessage, TextFormat},
+    },
+    live_server::{server::ChatServerHandle, socket_msg::SocketMsg},
+    routes::api::utilities::auth_header::get_auth_header,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EditMessage {
+    pub id: Uuid,
+    pub content: String,
+    pub format: TextFormat,
+    pub language: Option<LanguageCode>,
+}
+
+pub async fn edit_notifyer(
+    chat_server: web::Data<ChatServerHandle>,
+    conn: Data<PgConn>,
+    message: DbMessage,
+) {
+    let Some(room) = conn.get_room(message.info.room).await else {
+        return;
+    };
+    let Some(message) = conn.get_api_message(message.id, Uuid::nil()).await else {
+        return;
+    };
+    let members = conn.get_room_member_ids(&room).await;
+
+    chat_server
+        .send_room_message(
+            room.id,
+            members,
+            SocketMsg::MessageEdited(Box::new(message)),
+            None,
+        )
+        .await;
+}
+
+#[post("/edit")]
+pub async fn edit_message(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    edit: web::Json<EditMessage>,
+    chat_server: web::Data<ChatServerHandle>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let edit = edit.into_inner();
+    let content = edit.content.trim().to_string();
+    if content.is_empty() {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(edited) = conn
+        .edit_message(token.uid, edit.id, content, edit.format, edit.language)
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let Some(message) = conn.get_api_message(edit.id, token.uid).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    spawn_local(edit_notifyer(chat_server, conn, edited));
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&message).expect("failed to serialize message")))
+}
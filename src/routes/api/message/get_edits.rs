@@ -0,0 +1,40 @@
+//! `get /api/bayou_v1/message/edits/{m_id}`
+//!
+//! get the edit history of a message, expects a token in the auth header
+//! - ok (200) should contain an array of [`crate::db::types::message_edit::MessageEdit`]
+//!   from oldest to newest in the body, each is a version of the message that was replaced
+//! - unauthorized (401) included token is not valid or not allowed to view the message
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use uuid::Uuid;
+
+#[get("/edits/{m_id}")]
+pub async fn get_edits(
+    conn: Data<PgConn>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(edits) = conn.get_message_edits(token.uid, path.into_inner()).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&edits).expect("failed to serialize message edits")))
+}
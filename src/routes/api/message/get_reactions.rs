@@ -0,0 +1,53 @@
+//! `get /api/bayou_v1/message/reactions/{m_id}?emoji=...&emoji_id=...`
+//!
+//! get the users that reacted to a message with an emoji, expects a token in the auth
+//! header. emoji_id should be present for custom emoji
+//! - ok (200) should contain an array of [`crate::routes::api::types::api_user::ApiUser`]
+//!   in order of when they reacted in the body
+//! - unauthorized (401) included token is not valid or not allowed to view the message
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReactionQuery {
+    pub emoji: String,
+    pub emoji_id: Option<Uuid>,
+}
+
+#[get("/reactions/{m_id}")]
+pub async fn get_reactions(
+    conn: Data<PgConn>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    query: web::Query<ReactionQuery>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let query = query.into_inner();
+    let Ok(users) = conn
+        .get_reaction_users(token.uid, path.into_inner(), query.emoji, query.emoji_id)
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&users).expect("failed to serialize users")))
+}
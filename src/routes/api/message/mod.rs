@@ -1,5 +1,13 @@
 //! `/api/bayou_v1/message/...`
-//! message specific methods such as sending messages
+//! message specific methods such as sending, editing and deleting messages
+//! as well as reacting to them
 
+pub mod add_reaction;
+pub mod delete_message;
+pub mod edit_message;
+pub mod get_edits;
+pub mod get_reactions;
+pub mod remove_reaction;
 pub(super) mod routes;
+pub mod search;
 pub mod send_message;
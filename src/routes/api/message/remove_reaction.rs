@@ -0,0 +1,65 @@
+//! `post /api/bayou_v1/message/unreact`
+//!
+//! remove your reaction from a message, expects a [`Reaction`] with a token in the auth
+//! header. members of the room will receive a [`SocketMsg::ReactionRemoved`]
+//! - ok (200) reaction was removed or did not exist
+//! - unauthorized (401) included token is not valid or not allowed to view the message
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use tokio::task::spawn_local;
+
+use crate::{
+    db::pg_conn::PgConn,
+    live_server::{server::ChatServerHandle, socket_msg::SocketMsg},
+    routes::api::utilities::auth_header::get_auth_header,
+};
+
+use super::add_reaction::{reaction_notifyer, Reaction};
+
+#[post("/unreact")]
+pub async fn remove_reaction(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    reaction: web::Json<Reaction>,
+    chat_server: web::Data<ChatServerHandle>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let reaction = reaction.into_inner();
+    let Ok(removed) = conn
+        .remove_reaction(
+            token.uid,
+            reaction.message,
+            reaction.emoji,
+            reaction.emoji_id,
+        )
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    if let Some(removed) = removed {
+        spawn_local(reaction_notifyer(
+            chat_server,
+            conn,
+            removed.room,
+            SocketMsg::ReactionRemoved(removed),
+        ));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
@@ -1,5 +1,17 @@
-use super::send_message::send_message;
+use super::{
+    add_reaction::add_reaction, delete_message::delete_message, edit_message::edit_message,
+    get_edits::get_edits, get_reactions::get_reactions, remove_reaction::remove_reaction,
+    search::search_messages, send_message::send_message,
+};
 
 pub fn get_message_routes() -> actix_web::Scope {
-    actix_web::web::scope("/message").service(send_message)
+    actix_web::web::scope("/message")
+        .service(send_message)
+        .service(edit_message)
+        .service(delete_message)
+        .service(get_edits)
+        .service(add_reaction)
+        .service(remove_reaction)
+        .service(get_reactions)
+        .service(search_messages)
 }
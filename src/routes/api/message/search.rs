@@ -0,0 +1,67 @@
+//! `get /api/bayou_v1/message/search`
+//!
+//! search the messages of the rooms the user can view, expects a token in the auth header.
+//! words are stemmed for the language of each message so searching `running` finds `runs`
+//! in english messages
+//!
+//! query params, see [`crate::db::types::message_search::MessageSearch`]
+//! - `query` required, the search terms
+//! - `community` optional, only search the rooms of this community
+//! - `room` optional, only search this room
+//! - `author` optional, user id, only messages sent by this user
+//! - `since` optional, only messages published at or after this timestamp
+//! - `until` optional, only messages published before this timestamp
+//! - `has_attachment` optional, bool, only messages with or without attachments
+//! - `is_reply` optional, bool, only replies or only messages that aren't replies
+//! - `older` optional, message id, get the results older than this message for the next page
+//!
+//! responses
+//! - ok (200) list of [`crate::routes::api::types::api_message::ApiMessage`] from newest
+//!   to oldest in body
+//! - bad request (400) the query is empty or too long
+//! - unauthorized (401) included token is not valid or can't view the given room or community
+
+use crate::{
+    db::{
+        pg_conn::PgConn,
+        types::message_search::{MessageSearch, MAX_SEARCH_LENGTH},
+    },
+    routes::api::utilities::auth_header::get_auth_header,
+};
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+
+#[get("/search")]
+pub async fn search_messages(
+    conn: Data<PgConn>,
+    info: web::Query<MessageSearch>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let search = info.into_inner();
+    if search.query.trim().is_empty() || search.query.len() > MAX_SEARCH_LENGTH {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(messages) = conn.search_messages(token.uid, search).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&messages).expect("failed to serialize messages")))
+}
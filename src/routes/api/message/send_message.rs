@@ -1,9 +1,14 @@
 //! `post /api/bayou_v1/message/new`
 //!
-//! send a new message, expects [`crate::db::types::message::Messageinfo`] with a token in the auth header
-//! - ok (200) message successfully sent
-//! - unauthorized (401) included token is not valid or not allowed to send to given room, message not sent
-//! - bad request (400) message is empty
+//! send a new message, expects [`crate::db::types::message::Messageinfo`] with a token in the auth header.
+//! mentions in the content are resolved and mentioned members receive a [`SocketMsg::Mentioned`].
+//! setting `thread` to the id of a message in the room sends the message in the thread it starts,
+//! messages in a thread may only reply to messages in the same thread or the message that started it
+//! - ok (200) message successfully sent, should contain the sent
+//!   [`crate::routes::api::types::api_message::ApiMessage`]
+//! - unauthorized (401) included token is not valid or not allowed to send to given room, message not sent.
+//!   also returned if attachments are not files uploaded by the user or the thread can't be sent to
+//! - bad request (400) message has no content or attachments
 
 use actix_web::{
     post,
@@ -26,7 +31,7 @@ use crate::{
         server::{ChatServerHandle, MessageTarget},
         socket_msg::SocketMsg,
     },
-    routes::api::utilities::auth_header::get_auth_header,
+    routes::api::{types::api_mention::ApiMention, utilities::auth_header::get_auth_header},
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -36,8 +41,7 @@ pub struct NewRoom {
     community: Uuid,
 }
 
-/// todo create get room members function in database
-/// to support dms and fine grained control in communities
+/// todo fine grained control in communities
 pub async fn message_notifyer(
     chat_server: web::Data<ChatServerHandle>,
     conn: Data<PgConn>,
@@ -46,18 +50,49 @@ pub async fn message_notifyer(
     let Some(room) = conn.get_room(message.info.room).await else {
         return;
     };
-    let Some(message) = conn.get_api_message(message.id).await else {
+    let Some(message) = conn.get_api_message(message.id, Uuid::nil()).await else {
         return;
     };
-    let members = match room.community {
-        Some(community) => conn.get_comm_members(community).await,
-        None => todo!(),
+    let members = conn.get_room_member_ids(&room).await;
+    // messages in threads are only sent to connections subscribed to the room
+    let unread = message
+        .thread
+        .is_none()
+        .then_some(SocketMsg::UnreadMessage {
+            room: room.id,
+            id: message.id,
+            user: message.user.id,
+        });
+    let mentioned: Vec<Uuid> = match message.mentions.is_empty() {
+        true => Vec::new(),
+        false => {
+            let everyone = message
+                .mentions
+                .iter()
+                .any(|x| matches!(x, ApiMention::Everyone));
+            let users = conn.get_mentioned_user_ids(message.id).await;
+            members
+                .iter()
+                .filter(|x| **x != message.user.id && (everyone || users.contains(x)))
+                .copied()
+                .collect()
+        }
     };
-    let members: Vec<Uuid> = members.into_iter().map(|x| x.id).collect();
+    let mention = (!mentioned.is_empty()).then(|| SocketMsg::Mentioned(Box::new(message.clone())));
 
     chat_server
-        .send_message(SocketMsg::NewMessage(message), MessageTarget::List(members))
+        .send_room_message(
+            room.id,
+            members,
+            SocketMsg::NewMessage(Box::new(message)),
+            unread,
+        )
         .await;
+    if let Some(mention) = mention {
+        chat_server
+            .send_message(mention, MessageTarget::List(mentioned))
+            .await;
+    }
 }
 
 #[post("/new")]
@@ -84,7 +119,7 @@ pub async fn send_message(
     };
     let mut message = message.into_inner();
     message.content = message.content.trim().to_string();
-    if message.content.is_empty() {
+    if message.content.is_empty() && message.attachments.is_empty() {
         return Ok(HttpResponse::BadRequest()
             .content_type("application/json; charset=utf-8")
             .body(""));
@@ -97,8 +132,13 @@ pub async fn send_message(
                 .body(""));
         }
     };
+    let Some(sent) = conn.get_api_message(message.id, user.id).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
     spawn_local(message_notifyer(chat_server, conn, message));
     Ok(HttpResponse::Ok()
         .content_type("application/json; charset=utf-8")
-        .body(serde_json::to_string("").expect("failed to serialize dbcommunity")))
+        .body(serde_json::to_string(&sent).expect("failed to serialize message")))
 }
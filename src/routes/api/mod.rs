@@ -1,7 +1,12 @@
+pub mod admin;
+pub mod chat;
 pub mod community;
+pub mod emoji;
+pub mod files;
 pub mod login;
 pub mod message;
 pub mod regester_device;
+pub mod resolve;
 pub mod room;
 pub(super) mod routes;
 pub mod signup;
@@ -9,4 +14,3 @@ pub mod types;
 pub mod uname_taken;
 pub(super) mod utilities;
 pub mod websocket;
-pub mod files;
\ No newline at end of file
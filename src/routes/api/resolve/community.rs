@@ -0,0 +1,80 @@
+//! `get /api/bayou_v1/resolve/community/{domain}/{id}`
+//!
+//! get a community from another instance by its id on that instance, expects an auth
+//! token in the authorization header and the user to be a member of the community. the
+//! community is fetched again if it is stale and the history of its rooms that isn't
+//! known complete is fetched in the background
+//! - ok (200) should contain a json [`crate::routes::api::types::api_community::ApiCommunity`]
+//! - bad request (400) the domain isn't a host with an optional port
+//! - not found (404) we don't have the community
+//! - unauthorized (401) included token is not valid or the user isn't a member
+
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use tokio::task::spawn_local;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::pg_conn::PgConn,
+    federation::{
+        activity::ObjectRef,
+        client::valid_domain,
+        resolve::{self, backfill_community},
+        signatures::InstanceSigner,
+    },
+    routes::api::{types::api_community::ApiCommunity, utilities::auth_header::get_auth_header},
+};
+
+#[get("/community/{domain}/{id}")]
+pub async fn resolve_community(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    signer: Data<InstanceSigner>,
+    path: web::Path<(String, Uuid)>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let (domain, id) = path.into_inner();
+    if !valid_domain(&domain) {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let external = ObjectRef { id, domain };
+    let Some(community) = conn.get_community_external(&external).await else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    if conn
+        .get_comm_membership(community.id, token.uid)
+        .await
+        .is_none()
+    {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let community = resolve::resolve_community(&conn, &config, &signer, &external)
+        .await
+        .unwrap_or(community);
+    let com_id = community.id;
+    spawn_local(async move { backfill_community(&conn, &config, &signer, com_id).await });
+    let community: ApiCommunity = community.into();
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&community).expect("failed to serialize community")))
+}
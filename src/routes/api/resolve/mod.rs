@@ -0,0 +1,7 @@
+//! `/api/bayou_v1/resolve/...`
+//! looking up users and communities from other instances, they are fetched
+//! from their instance when we don't have them or they are stale
+
+pub mod community;
+pub(super) mod routes;
+pub mod user;
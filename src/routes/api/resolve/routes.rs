@@ -0,0 +1,7 @@
+use super::{community::resolve_community, user::resolve_user};
+
+pub fn get_resolve_routes() -> actix_web::Scope {
+    actix_web::web::scope("/resolve")
+        .service(resolve_user)
+        .service(resolve_community)
+}
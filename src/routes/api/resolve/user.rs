@@ -0,0 +1,59 @@
+//! `get /api/bayou_v1/resolve/user/{handle}`
+//!
+//! get a user by a handle of the form `username@domain`, expects an auth token in the
+//! authorization header. users of other instances are fetched from their instance if
+//! we don't have them or they are stale
+//! - ok (200) should contain a json [`crate::routes::api::types::api_user::ApiUser`]
+//! - bad request (400) the handle is malformed
+//! - not found (404) the user doesn't exist or their instance couldn't be reached
+//! - unauthorized (401) included token is not valid
+
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+
+use crate::{
+    config::Config,
+    db::pg_conn::PgConn,
+    federation::{
+        resolve::{self, parse_handle},
+        signatures::InstanceSigner,
+    },
+    routes::api::{types::api_user::ApiUser, utilities::auth_header::get_auth_header},
+};
+
+#[get("/user/{handle}")]
+pub async fn resolve_user(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    signer: Data<InstanceSigner>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Some((username, domain)) = parse_handle(&path) else {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let Some(user) = resolve::resolve_user(&conn, &config, &signer, username, domain).await else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let user: ApiUser = user.into();
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&user).expect("failed to serialize user")))
+}
@@ -0,0 +1,76 @@
+//! `post /api/bayou_v1/room/ack`
+//!
+//! mark a message and everything before it in its room as read, expects an
+//! [`AckRequest`] with a token in the auth header. read states only move forward.
+//! the user's other connections will receive a [`SocketMsg::ReadStateUpdated`]
+//! - ok (200) [`crate::routes::api::types::api_read_state::ApiReadState`] of the room in the body
+//! - unauthorized (401) included token is not valid or not allowed to view the message
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use tokio::task::spawn_local;
+use uuid::Uuid;
+
+use crate::{
+    db::pg_conn::PgConn,
+    live_server::{
+        server::{ChatServerHandle, MessageTarget},
+        socket_msg::SocketMsg,
+    },
+    routes::api::{types::api_read_state::ApiReadState, utilities::auth_header::get_auth_header},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AckRequest {
+    pub message: Uuid,
+}
+
+/// sync a read state to every connection of the user
+pub async fn read_state_notifyer(
+    chat_server: web::Data<ChatServerHandle>,
+    uid: Uuid,
+    read_state: ApiReadState,
+) {
+    chat_server
+        .send_message(
+            SocketMsg::ReadStateUpdated(read_state),
+            MessageTarget::List(vec![uid]),
+        )
+        .await;
+}
+
+#[post("/ack")]
+pub async fn ack(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    ack: web::Json<AckRequest>,
+    chat_server: web::Data<ChatServerHandle>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(read_state) = conn.ack_message(token.uid, ack.message).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    spawn_local(read_state_notifyer(
+        chat_server,
+        token.uid,
+        read_state.clone(),
+    ));
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&read_state).expect("failed to serialize read state")))
+}
@@ -0,0 +1,52 @@
+//! `post /api/bayou_v1/room/overrides/delete`
+//!
+//! remove the permission override of a role in a room, expects an auth token in the
+//! authorization header and a body with a [`DeleteOverride`]
+//! - ok (200) override was removed
+//! - unauthorized (401) included token is not valid or not allowed to remove the override
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeleteOverride {
+    pub room: Uuid,
+    pub role: Uuid,
+}
+
+#[post("/overrides/delete")]
+pub async fn delete_override(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    room_override: web::Json<DeleteOverride>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    if conn
+        .delete_room_override(token.uid, room_override.room, room_override.role)
+        .await
+        .is_err()
+    {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
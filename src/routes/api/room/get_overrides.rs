@@ -0,0 +1,40 @@
+//! `get /api/bayou_v1/room/overrides/{room_id}`
+//!
+//! get the permission overrides of a room, expects an auth token in the authorization header
+//! - ok (200) should contain an array of [`crate::db::types::comm::room_override::RoomOverride`]
+//!   present in the body
+//! - unauthorized (401) included token is not valid or not able to view the room
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use uuid::Uuid;
+
+#[get("/overrides/{room_id}")]
+pub async fn get_overrides(
+    conn: Data<PgConn>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(overrides) = conn.get_room_overrides(path.into_inner(), token.uid).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&overrides).expect("failed to serialize room overrides")))
+}
@@ -0,0 +1,45 @@
+//! `get /api/bayou_v1/room/permissions/{room_id}`
+//!
+//! get the requesting user's permissions in a room after overrides are applied,
+//! expects an auth token in the authorization header
+//! - ok (200) should contain a json [`crate::db::types::comm::permissions::Permissions`]
+//!   in the body, users that are not able to view the room have no permissions
+//! - unauthorized (401) included token is not valid
+//! - not found (404) room does not exist
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use uuid::Uuid;
+
+#[get("/permissions/{room_id}")]
+pub async fn get_permissions(
+    conn: Data<PgConn>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Some(permissions) = conn
+        .get_room_permissions(path.into_inner(), token.uid)
+        .await
+    else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&permissions).expect("failed to serialize permissions")))
+}
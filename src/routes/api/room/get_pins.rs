@@ -0,0 +1,55 @@
+//! `get /api/bayou_v1/room/pins/{room_id}`
+//!
+//! get the pinned messages of a room, most recently pinned first. expects a token in
+//! the auth header
+//!
+//! query params
+//! - `before` optional, message id of the last pinned message of the previous page
+//!
+//! responses
+//! - ok (200) list of [`crate::routes::api::types::api_message::ApiMessage`] in body
+//! - unauthorized (401) included token is not valid to view given room
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GetPinsQuery {
+    pub before: Option<Uuid>,
+}
+
+#[get("/pins/{room_id}")]
+pub async fn get_pins(
+    conn: Data<PgConn>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    query: web::Query<GetPinsQuery>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(pins) = conn
+        .get_room_pins(path.into_inner(), token.uid, query.before)
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&pins).expect("failed to serialize messages")))
+}
@@ -0,0 +1,56 @@
+//! `get /api/bayou_v1/room/threads/{room_id}`
+//!
+//! get the active threads of a room, the threads replied to most recently first. threads
+//! without replies for a week are left out. expects a token in the auth header
+//!
+//! query params
+//! - `before` optional, message id that started the last thread of the previous page
+//!
+//! responses
+//! - ok (200) list of [`crate::routes::api::types::api_message::ApiMessage`] that started
+//!   each thread in body, their `thread_summary` has the reply count and latest activity
+//! - unauthorized (401) included token is not valid to view given room
+
+use crate::{db::pg_conn::PgConn, routes::api::utilities::auth_header::get_auth_header};
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GetThreadsQuery {
+    pub before: Option<Uuid>,
+}
+
+#[get("/threads/{room_id}")]
+pub async fn get_threads(
+    conn: Data<PgConn>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    query: web::Query<GetThreadsQuery>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(threads) = conn
+        .get_active_threads(path.into_inner(), token.uid, query.before)
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&threads).expect("failed to serialize messages")))
+}
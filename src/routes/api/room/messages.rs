@@ -5,15 +5,20 @@
 //! query params
 //! - `room` required, room id
 //! - `inclusive` optional, get messages including provided `older` or `newer` does nothing
-//! if neither is present
+//!   if neither is present
 //! - `older` optional, message id, gets the messages older than the provided message
 //! - `newer` optional, message id, gets the messages newer than the provided message
-//! only one of `older` or `newer` should be passed, both is intentionally undefined
-//! behavior as it is subject to change or being rejected
+//! - `around` optional, message id, gets the provided message with the messages on either
+//!   side of it, used to jump to a message
+//!
+//! only one of `older`, `newer` or `around` may be passed
 //!
 //! responses
-//! - ok (200) list of [`crate::routes::api::types::api_message::ApiMessage`] in body
-//! - unauthorized (401) included token is not valid to view given room
+//! - ok (200) list of [`crate::routes::api::types::api_message::ApiMessage`] in body, or a
+//!   [`crate::routes::api::types::message_window::MessageWindow`] when `around` is passed
+//! - bad request (400) more than one of `older`, `newer` or `around` was passed
+//! - unauthorized (401) included token is not valid to view given room or the message
+//!   passed as `around` isn't in the room
 
 use crate::{
     db::pg_conn::PgConn,
@@ -33,6 +38,7 @@ pub struct GetMessagesQuery {
     pub inclusive: Option<bool>,
     pub older: Option<Uuid>,
     pub newer: Option<Uuid>,
+    pub around: Option<Uuid>,
 }
 
 fn return_result(messages: &Vec<ApiMessage>) -> Result<HttpResponse> {
@@ -58,6 +64,27 @@ pub async fn get_messages(
             .body(""));
     }
 
+    let relations = [info.older, info.newer, info.around];
+    if relations.iter().flatten().count() > 1 {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+
+    if let Some(around) = info.around {
+        let Ok(window) = conn
+            .get_room_messages_around(info.room, token.uid, around)
+            .await
+        else {
+            return Ok(HttpResponse::Unauthorized()
+                .content_type("application/json; charset=utf-8")
+                .body(""));
+        };
+        return Ok(HttpResponse::Ok()
+            .content_type("application/json; charset=utf-8")
+            .body(serde_json::to_string(&window).expect("failed to serialize message window")));
+    }
+
     if let Some(older) = info.older {
         let Ok(messages) = conn
             .get_room_messages_in_relation(
@@ -1,5 +1,16 @@
 //! `/api/bayou_v1/room/...`
-//! room specific methods such as getting the message history
+//! room specific methods such as getting the message history,
+//! threads, pinning messages, read states and managing permission overrides
 
+pub mod ack;
+pub mod delete_override;
+pub mod get_overrides;
+pub mod get_permissions;
+pub mod get_pins;
+pub mod get_threads;
 pub mod messages;
+pub mod pin_message;
 pub(super) mod routes;
+pub mod set_override;
+pub mod thread_messages;
+pub mod unpin_message;
@@ -0,0 +1,90 @@
+//! `post /api/bayou_v1/room/pins/pin`
+//!
+//! pin a message in its room, expects a [`PinRequest`] with a token in the auth header.
+//! requires the manage messages permission in community rooms, any member of a dm or
+//! group chat can pin. the pin is announced in the room and members of the room will
+//! receive a [`SocketMsg::PinsUpdated`]
+//! - ok (200) message was pinned or was already pinned
+//! - unauthorized (401) included token is not valid or not allowed to pin the message
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::{Deserialize, Serialize};
+use tokio::task::spawn_local;
+use uuid::Uuid;
+
+use crate::{
+    db::pg_conn::PgConn,
+    live_server::{server::ChatServerHandle, socket_msg::SocketMsg},
+    routes::api::{
+        message::send_message::message_notifyer, utilities::auth_header::get_auth_header,
+    },
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PinRequest {
+    pub message: Uuid,
+}
+
+pub async fn pins_notifyer(
+    chat_server: web::Data<ChatServerHandle>,
+    conn: Data<PgConn>,
+    room: Uuid,
+) {
+    let Some(room) = conn.get_room(room).await else {
+        return;
+    };
+    let members = conn.get_room_member_ids(&room).await;
+
+    chat_server
+        .send_room_message(
+            room.id,
+            members,
+            SocketMsg::PinsUpdated { room: room.id },
+            None,
+        )
+        .await;
+}
+
+#[post("/pins/pin")]
+pub async fn pin_message(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    pin: web::Json<PinRequest>,
+    chat_server: web::Data<ChatServerHandle>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Some(user) = conn.get_user_uid(&token.uid).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let Ok(pinned) = conn.pin_message(&user, pin.message).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    if let Some((pin, announcement)) = pinned {
+        spawn_local(message_notifyer(
+            chat_server.clone(),
+            conn.clone(),
+            announcement,
+        ));
+        spawn_local(pins_notifyer(chat_server, conn, pin.room_id));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
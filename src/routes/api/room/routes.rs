@@ -1,5 +1,21 @@
-use super::messages::get_messages;
+use super::{
+    ack::ack, delete_override::delete_override, get_overrides::get_overrides,
+    get_permissions::get_permissions, get_pins::get_pins, get_threads::get_threads,
+    messages::get_messages, pin_message::pin_message, set_override::set_override,
+    thread_messages::get_thread_messages, unpin_message::unpin_message,
+};
 
 pub fn get_room_routes() -> actix_web::Scope {
-    actix_web::web::scope("/room").service(get_messages)
+    actix_web::web::scope("/room")
+        .service(get_messages)
+        .service(get_permissions)
+        .service(get_overrides)
+        .service(set_override)
+        .service(delete_override)
+        .service(pin_message)
+        .service(unpin_message)
+        .service(get_pins)
+        .service(ack)
+        .service(get_threads)
+        .service(get_thread_messages)
 }
@@ -0,0 +1,49 @@
+//! `post /api/bayou_v1/room/overrides/set`
+//!
+//! set the permission override of a role in a room, expects an auth token in the authorization
+//! header and a body with a [`crate::db::types::comm::room_override::RoomOverride`]. requires
+//! the manage rooms and manage roles permissions, the role must be below the user's highest role
+//! and the override may only contain permissions the user has
+//! - ok (200) should contain the json [`crate::db::types::comm::room_override::RoomOverride`]
+//!   in the body
+//! - unauthorized (401) included token is not valid or not allowed to set the override
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+
+use crate::{
+    db::{pg_conn::PgConn, types::comm::room_override::RoomOverride},
+    routes::api::utilities::auth_header::get_auth_header,
+};
+
+#[post("/overrides/set")]
+pub async fn set_override(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    room_override: web::Json<RoomOverride>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(room_override) = conn
+        .set_room_override(token.uid, room_override.into_inner())
+        .await
+    else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&room_override).expect("failed to serialize room override")))
+}
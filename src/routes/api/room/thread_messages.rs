@@ -0,0 +1,111 @@
+//! `get /api/bayou_v1/room/thread/{thread_id}`
+//!
+//! get the messages of a thread from oldest to newest, the thread id is the id of the
+//! message that started it. expects a token in the auth header
+//!
+//! query params
+//! - `inclusive` optional, get messages including provided `older` or `newer` does nothing
+//!   if neither is present
+//! - `older` optional, message id, gets the messages older than the provided message
+//! - `newer` optional, message id, gets the messages newer than the provided message
+//! - `around` optional, message id, gets the provided message with the messages on either
+//!   side of it, used to jump to a message
+//!
+//! only one of `older`, `newer` or `around` may be passed
+//!
+//! responses
+//! - ok (200) list of [`crate::routes::api::types::api_message::ApiMessage`] in body, or a
+//!   [`crate::routes::api::types::message_window::MessageWindow`] when `around` is passed.
+//!   the message that started the thread is not included
+//! - bad request (400) more than one of `older`, `newer` or `around` was passed
+//! - unauthorized (401) included token is not valid to view given thread or the message
+//!   passed as `around` isn't in the thread
+
+use crate::{
+    db::pg_conn::PgConn,
+    routes::api::{types::api_message::ApiMessage, utilities::auth_header::get_auth_header},
+};
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GetThreadQuery {
+    pub inclusive: Option<bool>,
+    pub older: Option<Uuid>,
+    pub newer: Option<Uuid>,
+    pub around: Option<Uuid>,
+}
+
+fn return_result(messages: &Vec<ApiMessage>) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(messages).expect("failed to serialize messages")))
+}
+
+#[get("/thread/{thread_id}")]
+pub async fn get_thread_messages(
+    conn: Data<PgConn>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    info: web::Query<GetThreadQuery>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let thread_id = path.into_inner();
+    let relations = [info.older, info.newer, info.around];
+    if relations.iter().flatten().count() > 1 {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    if let Some(around) = info.around {
+        let Ok(window) = conn
+            .get_thread_messages_around(thread_id, token.uid, around)
+            .await
+        else {
+            return Ok(HttpResponse::Unauthorized()
+                .content_type("application/json; charset=utf-8")
+                .body(""));
+        };
+        return Ok(HttpResponse::Ok()
+            .content_type("application/json; charset=utf-8")
+            .body(serde_json::to_string(&window).expect("failed to serialize message window")));
+    }
+    let relation = match (info.older, info.newer) {
+        (Some(older), _) => Some((older, true)),
+        (None, Some(newer)) => Some((newer, false)),
+        (None, None) => None,
+    };
+    let result = match relation {
+        Some((post, before)) => {
+            conn.get_thread_messages_in_relation(
+                thread_id,
+                token.uid,
+                post,
+                info.inclusive.unwrap_or(false),
+                before,
+            )
+            .await
+        }
+        None => conn.get_thread_messages(thread_id, token.uid).await,
+    };
+    let Ok(messages) = result else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    return_result(&messages)
+}
@@ -0,0 +1,51 @@
+//! `post /api/bayou_v1/room/pins/unpin`
+//!
+//! unpin a message, expects a [`PinRequest`] with a token in the auth header. requires
+//! the same permissions as pinning, members of the room will receive a
+//! [`crate::live_server::socket_msg::SocketMsg::PinsUpdated`]
+//! - ok (200) message was unpinned or was not pinned
+//! - unauthorized (401) included token is not valid or not allowed to unpin the message
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use tokio::task::spawn_local;
+
+use crate::{
+    db::pg_conn::PgConn, live_server::server::ChatServerHandle,
+    routes::api::utilities::auth_header::get_auth_header,
+};
+
+use super::pin_message::{pins_notifyer, PinRequest};
+
+#[post("/pins/unpin")]
+pub async fn unpin_message(
+    req: HttpRequest,
+    conn: Data<PgConn>,
+    pin: web::Json<PinRequest>,
+    chat_server: web::Data<ChatServerHandle>,
+) -> Result<HttpResponse> {
+    let Some(token) = get_auth_header(&req) else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body("invalid or missing auth header"));
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    }
+    let Ok(unpinned) = conn.unpin_message(token.uid, pin.message).await else {
+        return Ok(HttpResponse::Unauthorized()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    if let Some(pin) = unpinned {
+        spawn_local(pins_notifyer(chat_server, conn, pin.room_id));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(""))
+}
@@ -1,8 +1,10 @@
 use crate::routes::api::files::routes::get_file_routes;
 
 use super::{
-    community::routes::get_community_routes, login::login, message::routes::get_message_routes,
-    regester_device::register_device, room::routes::get_room_routes, signup::signup,
+    admin::routes::get_admin_routes, chat::routes::get_chat_routes,
+    community::routes::get_community_routes, emoji::routes::get_emoji_routes, login::login,
+    message::routes::get_message_routes, regester_device::register_device,
+    resolve::routes::get_resolve_routes, room::routes::get_room_routes, signup::signup,
     uname_taken::username_availible, websocket::websocket_handler,
 };
 
@@ -12,9 +14,13 @@ pub fn get_api_routes() -> actix_web::Scope {
         .service(login)
         .service(register_device)
         .service(get_community_routes())
+        .service(get_chat_routes())
         .service(get_message_routes())
         .service(get_room_routes())
         .service(username_availible)
         .service(websocket_handler)
         .service(get_file_routes())
+        .service(get_resolve_routes())
+        .service(get_admin_routes())
+        .service(get_emoji_routes())
 }
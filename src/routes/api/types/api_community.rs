@@ -11,6 +11,10 @@ pub struct ApiCommunity {
     pub description: Option<String>,
     pub owner: Uuid,
     pub created: i64,
+    /// unread messages across the rooms the user can view, only
+    /// present when listing the user's joined communities
+    pub unread_count: Option<i64>,
+    pub mention_count: Option<i64>,
 }
 
 impl From<DbCommunity> for ApiCommunity {
@@ -22,6 +26,8 @@ impl From<DbCommunity> for ApiCommunity {
             description: value.info.description,
             owner: value.owner,
             created: value.created,
+            unread_count: None,
+            mention_count: None,
         }
     }
 }
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::types::{custom_emoji::CustomEmoji, emoji_pack::EmojiPack},
+    federation::{
+        emoji::{EmojiFederation, EmojiFormat},
+        media::Media,
+    },
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiEmoji {
+    /// used to react with the emoji
+    pub id: Uuid,
+    pub shortcode: String,
+    pub icon: Media<EmojiFormat>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiEmojiPack {
+    pub id: Uuid,
+    /// none for packs hosted by static sites
+    pub domain: Option<String>,
+    pub website: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub author_url: Option<String>,
+    pub license: Option<String>,
+    /// none until the pack has emoji
+    pub highlight_emoji: Option<String>,
+    pub last_updated: i64,
+    pub emoji: Vec<ApiEmoji>,
+}
+
+impl ApiEmoji {
+    pub fn from_db(config: &Config, pack: &EmojiPack, emoji: &CustomEmoji) -> Option<Self> {
+        let federated = EmojiFederation::from_db(config, pack, emoji)?;
+        Some(ApiEmoji {
+            id: emoji.id,
+            shortcode: federated.shortcode,
+            icon: federated.icon,
+        })
+    }
+}
+
+impl ApiEmojiPack {
+    pub fn from_db(config: &Config, pack: EmojiPack, emoji: Vec<CustomEmoji>) -> Self {
+        ApiEmojiPack {
+            emoji: emoji
+                .iter()
+                .filter_map(|x| ApiEmoji::from_db(config, &pack, x))
+                .collect(),
+            id: pack.id,
+            domain: pack.domain,
+            website: pack.website,
+            name: pack.name,
+            description: pack.description,
+            author_url: pack.author_url,
+            license: pack.license,
+            highlight_emoji: pack.highlight_emoji,
+            last_updated: pack.last_updated,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::types::{file::DbFile, file_thumbnail::FileThumbnail};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiFile {
+    pub id: Uuid,
+    /// where the file may be downloaded from, requires the auth header or
+    /// a file token, see [`crate::routes::api::files::get_file`]
+    #[serde(default)]
+    pub url: String,
+    pub mime: String,
+    /// size in bytes
+    pub size: i64,
+    pub file_name: Option<String>,
+    /// alt text, for attachments this is the description given when attaching
+    pub description: Option<String>,
+    /// dimensions in pixels for images and videos
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    /// placeholder for images while they load, see <https://blurha.sh>
+    pub blurhash: Option<String>,
+    /// smaller versions of images, ordered from smallest to largest.
+    /// empty if the file is not an image or is already small
+    pub thumbnails: Vec<ApiThumbnail>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiThumbnail {
+    /// the longest edge the thumbnail was scaled to fit within
+    pub size: i64,
+    #[serde(default)]
+    pub url: String,
+    pub mime: String,
+    pub width: i64,
+    pub height: i64,
+}
+
+impl ApiFile {
+    pub fn from_db(file: DbFile, thumbnails: Vec<FileThumbnail>, base_url: &str) -> Self {
+        let thumbnails = thumbnails
+            .into_iter()
+            .map(|x| ApiThumbnail {
+                url: String::new(),
+                size: x.size,
+                mime: x.mime,
+                width: x.width,
+                height: x.height,
+            })
+            .collect();
+        let mut api_file = ApiFile {
+            url: String::new(),
+            id: file.id,
+            mime: file.mime,
+            size: file.size,
+            file_name: file.file_name,
+            description: file.description,
+            width: file.width,
+            height: file.height,
+            blurhash: file.blurhash,
+            thumbnails,
+        };
+        api_file.set_urls(base_url);
+        api_file
+    }
+    /// fill in where the file and its thumbnails can be downloaded from
+    pub fn set_urls(&mut self, base_url: &str) {
+        self.url = file_url(base_url, &self.id);
+        for thumbnail in &mut self.thumbnails {
+            thumbnail.url = thumbnail_url(base_url, &self.id, thumbnail.size);
+        }
+    }
+}
+
+/// where a file is downloaded from, see [`crate::config::Config::base_url`]
+pub fn file_url(base_url: &str, file_id: &Uuid) -> String {
+    format!("{base_url}/api/bayou_v1/files/{file_id}")
+}
+
+pub fn thumbnail_url(base_url: &str, file_id: &Uuid, size: i64) -> String {
+    format!("{}?thumbnail={size}", file_url(base_url, file_id))
+}
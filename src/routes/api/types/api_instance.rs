@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::db::types::instance::Instance;
+
+/// the federation policy for another instance
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiInstance {
+    pub domain: String,
+    pub blocked: bool,
+    pub allowlisted: bool,
+    pub reason: Option<String>,
+}
+
+impl From<Instance> for ApiInstance {
+    fn from(value: Instance) -> Self {
+        ApiInstance {
+            domain: value.domain,
+            blocked: value.blocked,
+            allowlisted: value.allowlisted,
+            reason: value.reason,
+        }
+    }
+}
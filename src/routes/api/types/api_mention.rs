@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::api_user::ApiUser;
+
+/// a mention in a message that was resolved when it was sent
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum ApiMention {
+    User(ApiUser),
+    Role { id: Uuid, name: String },
+    Everyone,
+}
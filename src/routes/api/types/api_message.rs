@@ -2,20 +2,41 @@ use codes_iso_639::part_1::LanguageCode;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{db::types::message::TextFormat, routes::api::types::proxy_user::ApiProxyUser};
+use crate::{
+    db::{
+        markdown::Block,
+        types::{message::TextFormat, system_event::SystemEvent},
+    },
+    routes::api::types::proxy_user::ApiProxyUser,
+};
 
-use super::api_user::ApiUser;
+use super::{
+    api_file::ApiFile, api_mention::ApiMention, api_reaction::ReactionCount, api_user::ApiUser,
+};
+
+/// reply previews are cut off after this many characters
+pub const REPLY_PREVIEW_LENGTH: usize = 200;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReplyPreview {
     pub id: Uuid,
     pub user: ApiUser,
     pub proxy: Option<ApiProxyUser>,
+    /// the plain text of the message, cut off after [`REPLY_PREVIEW_LENGTH`]
+    /// characters when the message is longer
     pub content: String,
     pub format: TextFormat,
     pub language: Option<LanguageCode>,
 }
 
+/// the replies in the thread started by a message
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThreadSummary {
+    pub reply_count: i64,
+    /// when the latest reply was published
+    pub last_activity: i64,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ApiMessage {
     pub id: Uuid,
@@ -39,5 +60,33 @@ pub struct ApiMessage {
     pub preview: Option<ReplyPreview>,
     pub content: String,
     pub format: TextFormat,
+    /// markdown messages rendered by the server, none for plain text messages
+    pub rendered: Option<Vec<Block>>,
+    /// the content without formatting and with spoilers hidden, for
+    /// notifications and anywhere formatting can't be shown
+    pub plain_text: String,
     pub language: Option<LanguageCode>,
+    /// present when the message was generated by the server,
+    /// content will then be a plain text fallback
+    pub system_event: Option<SystemEvent>,
+    /// the message that started the thread this message is in, present
+    /// for messages sent in a thread
+    pub thread: Option<Uuid>,
+    /// present when the message started a thread that has replies
+    pub thread_summary: Option<ThreadSummary>,
+    pub attachments: Vec<ApiFile>,
+    /// in the order they appear in the content, mentions the author was
+    /// not allowed to make or of users outside the room are left out
+    pub mentions: Vec<ApiMention>,
+    /// messages sent over the socket are not personalized, `me` will always be false
+    pub reactions: Vec<ReactionCount>,
+}
+
+impl ApiMessage {
+    /// fill in where the attachments can be downloaded from
+    pub fn set_file_urls(&mut self, base_url: &str) {
+        for attachment in &mut self.attachments {
+            attachment.set_urls(base_url);
+        }
+    }
 }
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// the status a user chooses for themselves
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Status {
+    #[default]
+    Online,
+    Idle,
+    DoNotDisturb,
+    /// appear offline to other users while still connected
+    Invisible,
+}
+
+/// a user's status as seen by other users
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresenceStatus {
+    Online,
+    Idle,
+    DoNotDisturb,
+    Offline,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ApiPresence {
+    pub user: Uuid,
+    pub status: PresenceStatus,
+    /// a short status message set by the user, never present while offline
+    pub custom_status: Option<String>,
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// all reactions to a message with a single emoji
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReactionCount {
+    /// the unicode emoji or the shortcode of a custom emoji
+    pub emoji: String,
+    /// present for custom emoji, may refer to an emoji that has since been deleted
+    pub emoji_id: Option<Uuid>,
+    pub count: i64,
+    /// if the user viewing the message reacted with this emoji
+    pub me: bool,
+}
+
+/// a single user's reaction to a message
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiReaction {
+    pub message: Uuid,
+    pub room: Uuid,
+    pub user: Uuid,
+    pub emoji: String,
+    pub emoji_id: Option<Uuid>,
+}
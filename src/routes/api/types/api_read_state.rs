@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::types::read_state::RoomUnreads;
+
+/// how much of a room the user has read
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiReadState {
+    pub room: Uuid,
+    /// the latest message the user has read, none if they have never read the room
+    pub last_read: Option<Uuid>,
+    /// messages from other users after `last_read`
+    pub unread_count: i64,
+    /// unread messages that mention the user
+    pub mention_count: i64,
+}
+
+impl From<RoomUnreads> for ApiReadState {
+    fn from(value: RoomUnreads) -> Self {
+        ApiReadState {
+            room: value.room_id,
+            last_read: value.last_read,
+            unread_count: value.unread_count,
+            mention_count: value.mention_count,
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::types::{read_state::RoomUnreads, room::Room};
+
+/// a room along with how much of it the requesting user has read
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiRoom {
+    #[serde(flatten)]
+    pub room: Room,
+    pub last_read: Option<Uuid>,
+    pub unread_count: i64,
+    pub mention_count: i64,
+}
+
+impl ApiRoom {
+    pub fn new(room: Room, unreads: RoomUnreads) -> Self {
+        ApiRoom {
+            room,
+            last_read: unreads.last_read,
+            unread_count: unreads.unread_count,
+            mention_count: unreads.mention_count,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::api_community::ApiCommunity;
+
+/// information about a community shown before redeeming an invite
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InvitePreview {
+    pub token: Uuid,
+    pub community: ApiCommunity,
+    pub member_count: i64,
+    pub expiry: i64,
+}
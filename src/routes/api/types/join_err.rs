@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum JoinErr {
+    InvalidToken,
+    ExpiredToken,
+    Banned,
+    /// the instance of a remote community could not be reached or
+    /// sent back an invalid community
+    Unreachable,
+    /// we don't federate with the community's instance or it doesn't federate with us
+    Blocked,
+}
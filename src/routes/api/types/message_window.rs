@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::api_message::ApiMessage;
+
+/// the messages around a message, used to jump to a message
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MessageWindow {
+    /// from oldest to newest, including the message the window is around
+    pub messages: Vec<ApiMessage>,
+    /// the oldest message in the window, present when there are older messages.
+    /// passed as `older` to get the messages before the window
+    pub older: Option<Uuid>,
+    /// the newest message in the window, present when there are newer messages.
+    /// passed as `newer` to get the messages after the window
+    pub newer: Option<Uuid>,
+}
+
+impl MessageWindow {
+    /// build a window of up to `count` messages on each side of a message
+    /// - `before` up to `count + 1` messages before the message, oldest to newest
+    /// - `after` up to `count + 2` messages starting with the message, oldest to newest
+    pub fn new(mut before: Vec<ApiMessage>, mut after: Vec<ApiMessage>, count: usize) -> Self {
+        let has_older = before.len() > count;
+        if has_older {
+            before.drain(..before.len() - count);
+        }
+        let has_newer = after.len() > count + 1;
+        after.truncate(count + 1);
+        before.append(&mut after);
+        MessageWindow {
+            older: before.first().filter(|_| has_older).map(|x| x.id),
+            newer: before.last().filter(|_| has_newer).map(|x| x.id),
+            messages: before,
+        }
+    }
+}
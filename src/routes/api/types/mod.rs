@@ -1,9 +1,20 @@
 pub mod api_community;
+pub mod api_emoji;
+pub mod api_file;
+pub mod api_instance;
+pub mod api_mention;
 pub mod api_message;
+pub mod api_presence;
+pub mod api_reaction;
+pub mod api_read_state;
+pub mod api_room;
 pub mod api_user;
 pub mod auth_err;
 pub mod info_with_token;
+pub mod invite_preview;
+pub mod join_err;
 pub mod login_request;
+pub mod message_window;
 pub mod proxy_user;
 pub mod signup_result;
 pub mod signup_user;
@@ -22,10 +22,7 @@ impl ApiProxyUser {
     }
     pub fn maybe_from_row(row: &tokio_postgres::Row) -> Option<Self> {
         let id: Option<Uuid> = row.get("proxy_id");
-        match id {
-            Some(_) => Some(Self::from_row(row)),
-            None => None,
-        }
+        id.map(|_| Self::from_row(row))
     }
 }
 
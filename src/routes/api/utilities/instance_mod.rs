@@ -0,0 +1,21 @@
+use actix_web::HttpRequest;
+
+use crate::db::pg_conn::PgConn;
+
+use super::auth_header::get_auth_header;
+
+/// if the request has a valid auth token for an admin or moderator of our instance
+pub async fn is_instance_mod(req: &HttpRequest, conn: &PgConn) -> bool {
+    let Some(token) = get_auth_header(req) else {
+        return false;
+    };
+    if conn.validate_auth_token(&token).await.is_err() {
+        return false;
+    }
+    let Some(user) = conn.get_user_uid(&token.uid).await else {
+        return false;
+    };
+    user.local_info
+        .as_ref()
+        .is_some_and(|x| x.is_admin || x.instance_mod)
+}
@@ -1 +1,2 @@
 pub mod auth_header;
+pub mod instance_mod;
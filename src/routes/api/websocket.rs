@@ -2,7 +2,12 @@
 //!
 //! expects an [`crate::db::types::tokens::auth_token::AuthToken`] sent
 //! through the channel upon connection, will send live messages to the
-//! client to be used for rendering the message log
+//! client to be used for rendering the message log. alongside the token
+//! the client may include `subscribe` with a list of rooms and `last_seq`
+//! with the `seq` of the last event it received to have missed events
+//! replayed after reconnecting. once authenticated the
+//! client may send [`crate::live_server::client_msg::ClientMsg`] for typing
+//! indicators and presence
 
 use actix_web::{
     get,
@@ -1,7 +1,11 @@
 use api::routes::get_api_routes;
+use well_known::routes::get_well_known_routes;
 
 pub mod api;
+pub mod well_known;
 
 pub fn get_routes() -> actix_web::Scope {
-    actix_web::web::scope("").service(get_api_routes())
+    actix_web::web::scope("")
+        .service(get_api_routes())
+        .service(get_well_known_routes())
 }
@@ -0,0 +1,62 @@
+//! `get /.well-known/bayou/communities/{id}`
+//!
+//! one of our communities for an instance with members in it, the request must be signed
+//! - ok (200) should contain a json [`crate::federation::activity::FederatedCommunity`]
+//!   with the rooms members from the requesting instance can view
+//! - not found (404) the community doesn't exist
+//! - forbidden (403) the requesting instance has no members in the community
+//! - unauthorized (401) the request isn't signed, a
+//!   [`crate::federation::signatures::SignatureErr`] will be in the body
+//! - forbidden (403) we don't federate with the signing instance, the body
+//!   will contain [`crate::federation::signatures::SignatureErr::Blocked`]
+//!
+//! refused requests have a [`crate::federation::activity::InboxErr`] in the body
+
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::pg_conn::PgConn,
+    federation::{activity::InboxErr, signatures::KeyStore},
+};
+
+#[get("/communities/{id}")]
+pub async fn community(
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    key_store: Data<KeyStore>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let domain = match key_store.verify_request(&req, &[], &conn, &config).await {
+        Ok(x) => x,
+        Err(err) => {
+            return Ok(HttpResponse::build(err.status())
+                .content_type("application/json; charset=utf-8")
+                .body(serde_json::to_string(&err).expect("failed to serialize SignatureErr")));
+        }
+    };
+    let err = match conn
+        .get_federated_community(path.into_inner(), &domain)
+        .await
+    {
+        Ok(community) => {
+            return Ok(HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .body(serde_json::to_string(&community).expect("failed to serialize community")));
+        }
+        Err(err) => err,
+    };
+    let mut response = match err {
+        InboxErr::UnknownObject => HttpResponse::NotFound(),
+        InboxErr::Forbidden => HttpResponse::Forbidden(),
+    };
+    Ok(response
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&err).expect("failed to serialize InboxErr")))
+}
@@ -0,0 +1,35 @@
+//! `get /.well-known/bayou/emoji/{pack id}/{shortcode}`
+//!
+//! one of the emoji of our emoji packs, the request doesn't need to be signed
+//! - ok (200) should contain a json [`crate::federation::emoji::EmojiFederation`]
+//! - not found (404) the emoji doesn't exist or isn't ours
+
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse, Result,
+};
+use uuid::Uuid;
+
+use crate::{config::Config, db::pg_conn::PgConn, federation::emoji::EmojiFederation};
+
+#[get("/emoji/{pack_id}/{shortcode}")]
+pub async fn emoji(
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    path: web::Path<(Uuid, String)>,
+) -> Result<HttpResponse> {
+    let (pack_id, shortcode) = path.into_inner();
+    let emoji = conn
+        .get_local_emoji(&config.instance_domain, pack_id, &shortcode)
+        .await
+        .and_then(|(pack, emoji)| EmojiFederation::from_db(&config, &pack, &emoji));
+    let Some(emoji) = emoji else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&emoji).expect("failed to serialize emoji")))
+}
@@ -0,0 +1,42 @@
+//! `get /.well-known/bayou/emoji/{pack id}/{shortcode}/media`
+//!
+//! the image of one of our emoji, the request doesn't need to be signed
+//! - ok (200) the image with its content type
+//! - not found (404) the emoji doesn't exist or isn't ours
+
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use uuid::Uuid;
+
+use crate::{config::Config, db::pg_conn::PgConn};
+
+#[get("/emoji/{pack_id}/{shortcode}/media")]
+pub async fn emoji_media(
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    req: HttpRequest,
+    path: web::Path<(Uuid, String)>,
+) -> Result<HttpResponse> {
+    let (pack_id, shortcode) = path.into_inner();
+    let emoji = conn
+        .get_local_emoji(&config.instance_domain, pack_id, &shortcode)
+        .await;
+    let file = match emoji.and_then(|(_, emoji)| emoji.file_id) {
+        Some(file_id) => conn.get_file(file_id).await,
+        None => None,
+    };
+    let Some(file) = file else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let Ok(response) = config.storage_options.serve_file(&req, &file, None).await else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(response)
+}
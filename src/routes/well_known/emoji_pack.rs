@@ -0,0 +1,33 @@
+//! `get /.well-known/bayou/emoji/{pack id}`
+//!
+//! one of our emoji packs, packs are public so the request doesn't need to be signed
+//! - ok (200) should contain a json [`crate::federation::emoji::EmojiPackFederation`]
+//! - not found (404) the pack doesn't exist, isn't ours or has no emoji
+
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse, Result,
+};
+use uuid::Uuid;
+
+use crate::{config::Config, db::pg_conn::PgConn};
+
+#[get("/emoji/{pack_id}")]
+pub async fn emoji_pack(
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let Some(pack) = conn
+        .get_federated_emoji_pack(&config, path.into_inner())
+        .await
+    else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&pack).expect("failed to serialize emoji pack")))
+}
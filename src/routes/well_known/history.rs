@@ -0,0 +1,89 @@
+//! `get /.well-known/bayou/rooms/{id}/history`
+//!
+//! a page of the history of one of our rooms for an instance with members able to
+//! view it, the request must be signed. messages in threads are included
+//!
+//! query params
+//! - `limit` optional, the most messages to return, at most 100
+//! - `before_id` and `before_domain` optional, the message to get the messages
+//!   older than, as given in the previous page
+//!
+//! responses
+//! - ok (200) should contain a json [`crate::federation::activity::FederatedHistory`]
+//! - not found (404) the room or the message to get messages before doesn't exist
+//! - forbidden (403) the requesting instance has no members able to view the room
+//! - unauthorized (401) the request isn't signed, a
+//!   [`crate::federation::signatures::SignatureErr`] will be in the body
+//! - forbidden (403) we don't federate with the signing instance, the body
+//!   will contain [`crate::federation::signatures::SignatureErr::Blocked`]
+//!
+//! refused requests have a [`crate::federation::activity::InboxErr`] in the body
+
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::pg_conn::PgConn,
+    federation::{
+        activity::{InboxErr, ObjectRef},
+        signatures::KeyStore,
+    },
+};
+
+const MAX_LIMIT: i64 = 100;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub limit: Option<i64>,
+    pub before_id: Option<Uuid>,
+    pub before_domain: Option<String>,
+}
+
+#[get("/rooms/{id}/history")]
+pub async fn history(
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    key_store: Data<KeyStore>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    query: web::Query<HistoryQuery>,
+) -> Result<HttpResponse> {
+    let domain = match key_store.verify_request(&req, &[], &conn, &config).await {
+        Ok(x) => x,
+        Err(err) => {
+            return Ok(HttpResponse::build(err.status())
+                .content_type("application/json; charset=utf-8")
+                .body(serde_json::to_string(&err).expect("failed to serialize SignatureErr")));
+        }
+    };
+    let query = query.into_inner();
+    let before = match (query.before_id, query.before_domain) {
+        (Some(id), Some(domain)) => Some(ObjectRef { id, domain }),
+        _ => None,
+    };
+    let limit = query.limit.unwrap_or(MAX_LIMIT).clamp(1, MAX_LIMIT);
+    let err = match conn
+        .get_federated_history(path.into_inner(), &domain, before, limit)
+        .await
+    {
+        Ok(history) => {
+            return Ok(HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .body(serde_json::to_string(&history).expect("failed to serialize history")));
+        }
+        Err(err) => err,
+    };
+    let mut response = match err {
+        InboxErr::UnknownObject => HttpResponse::NotFound(),
+        InboxErr::Forbidden => HttpResponse::Forbidden(),
+    };
+    Ok(response
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&err).expect("failed to serialize InboxErr")))
+}
@@ -0,0 +1,118 @@
+//! `post /.well-known/bayou/inbox`
+//!
+//! receive an activity from another instance, expects a
+//! [`crate::federation::activity::Envelope`]. received messages and edits are
+//! sent to local members and, for our own communities, relayed to other
+//! instances.
+//! messages by authors on instances we don't federate with are dropped
+//! - ok (200) the activity was received or had already been received
+//! - not found (404) the activity refers to a room or message we don't have
+//! - forbidden (403) the sending instance may not deliver the activity
+//! - unauthorized (401) the request isn't signed by the origin's instance, a
+//!   [`crate::federation::signatures::SignatureErr`] will be in the body
+//! - forbidden (403) we don't federate with the signing instance, the body
+//!   will contain [`crate::federation::signatures::SignatureErr::Blocked`]
+//! - bad request (400) the body is not a valid envelope
+//!
+//! refused activities have a [`crate::federation::activity::InboxErr`] in the body
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use tokio::task::spawn_local;
+
+use crate::{
+    config::Config,
+    db::pg_conn::PgConn,
+    federation::{
+        activity::{Activity, Envelope, InboxErr},
+        policy::federates_with,
+        signatures::KeyStore,
+    },
+    live_server::server::ChatServerHandle,
+    routes::api::message::{
+        delete_message::delete_notifyer, edit_message::edit_notifyer,
+        send_message::message_notifyer,
+    },
+};
+
+#[post("/inbox")]
+pub async fn inbox(
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    key_store: Data<KeyStore>,
+    req: HttpRequest,
+    body: web::Bytes,
+    chat_server: web::Data<ChatServerHandle>,
+) -> Result<HttpResponse> {
+    let signer = match key_store.verify_request(&req, &body, &conn, &config).await {
+        Ok(x) => x,
+        Err(err) => {
+            return Ok(HttpResponse::build(err.status())
+                .content_type("application/json; charset=utf-8")
+                .body(serde_json::to_string(&err).expect("failed to serialize SignatureErr")));
+        }
+    };
+    let Ok(envelope) = serde_json::from_slice::<Envelope>(&body) else {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let result = match envelope.activity {
+        _ if envelope.origin != signer => Err(InboxErr::Forbidden),
+        // messages relayed by a community's instance from authors on
+        // instances we don't federate with are dropped
+        Activity::Message(message)
+            if message.author.domain != envelope.origin
+                && message.author.domain != config.instance_domain
+                && !federates_with(&conn, &config, &message.author.domain).await =>
+        {
+            Ok(())
+        }
+        Activity::Message(message) => {
+            match conn.receive_message(&envelope.origin, *message).await {
+                Ok(Some(message)) => {
+                    spawn_local(message_notifyer(chat_server, conn, message));
+                    Ok(())
+                }
+                Ok(None) => Ok(()),
+                Err(err) => Err(err),
+            }
+        }
+        Activity::Edit(message) => match conn.receive_edit(&envelope.origin, *message).await {
+            Ok(Some(message)) => {
+                spawn_local(edit_notifyer(chat_server, conn, message));
+                Ok(())
+            }
+            Ok(None) => Ok(()),
+            Err(err) => Err(err),
+        },
+        Activity::Delete { message } => {
+            match conn.receive_delete(&envelope.origin, message).await {
+                Ok(Some(message)) => {
+                    spawn_local(delete_notifyer(chat_server, conn, message));
+                    Ok(())
+                }
+                Ok(None) => Ok(()),
+                Err(err) => Err(err),
+            }
+        }
+    };
+    let err = match result {
+        Ok(()) => {
+            return Ok(HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .body(""));
+        }
+        Err(err) => err,
+    };
+    let mut response = match err {
+        InboxErr::UnknownObject => HttpResponse::NotFound(),
+        InboxErr::Forbidden => HttpResponse::Forbidden(),
+    };
+    Ok(response
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&err).expect("failed to serialize InboxErr")))
+}
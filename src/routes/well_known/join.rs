@@ -0,0 +1,72 @@
+//! `post /.well-known/bayou/join`
+//!
+//! join a community for a user of another instance using an invite, expects a
+//! [`crate::federation::activity::RemoteJoin`]. the join is announced in the
+//! community's system channel
+//! - ok (200) should contain a json [`crate::federation::activity::FederatedCommunity`]
+//!   with the rooms the user can view
+//! - bad request (400) the invite could not be used, a [`crate::routes::api::types::join_err::JoinErr`]
+//!   will be in the body
+//! - unauthorized (401) the request isn't signed by the user's instance, a
+//!   [`crate::federation::signatures::SignatureErr`] will be in the body
+//! - forbidden (403) we don't federate with the signing instance, the body
+//!   will contain [`crate::federation::signatures::SignatureErr::Blocked`]
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+use tokio::task::spawn_local;
+
+use crate::{
+    config::Config,
+    db::pg_conn::PgConn,
+    federation::{
+        activity::RemoteJoin,
+        signatures::{KeyStore, SignatureErr},
+    },
+    live_server::server::ChatServerHandle,
+    routes::api::message::send_message::message_notifyer,
+};
+
+#[post("/join")]
+pub async fn join(
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    key_store: Data<KeyStore>,
+    req: HttpRequest,
+    body: web::Bytes,
+    chat_server: web::Data<ChatServerHandle>,
+) -> Result<HttpResponse> {
+    let signer = key_store.verify_request(&req, &body, &conn, &config).await;
+    let Ok(join) = serde_json::from_slice::<RemoteJoin>(&body) else {
+        return Ok(HttpResponse::BadRequest()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    let verified = match signer {
+        Ok(domain) if domain == join.user.domain => Ok(()),
+        Ok(_) => Err(SignatureErr::Invalid),
+        Err(err) => Err(err),
+    };
+    if let Err(err) = verified {
+        return Ok(HttpResponse::build(err.status())
+            .content_type("application/json; charset=utf-8")
+            .body(serde_json::to_string(&err).expect("failed to serialize SignatureErr")));
+    }
+    let (community, announcement) = match conn.redeem_remote_invite(join.token, &join.user).await {
+        Ok(x) => x,
+        Err(err) => {
+            return Ok(HttpResponse::BadRequest()
+                .content_type("application/json; charset=utf-8")
+                .body(serde_json::to_string(&err).expect("failed to serialize JoinErr")));
+        }
+    };
+    if let Some(announcement) = announcement {
+        spawn_local(message_notifyer(chat_server, conn, announcement));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&community).expect("failed to serialize community")))
+}
@@ -0,0 +1,16 @@
+//! `get /.well-known/bayou/key`
+//!
+//! the public key this instance signs federation requests with, other
+//! instances use it to verify our signatures
+//! - ok (200) should contain a json [`crate::federation::signatures::FederatedKey`]
+
+use actix_web::{get, web::Data, HttpResponse, Result};
+
+use crate::federation::signatures::InstanceSigner;
+
+#[get("/key")]
+pub async fn key(signer: Data<InstanceSigner>) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&signer.public_key).expect("failed to serialize key")))
+}
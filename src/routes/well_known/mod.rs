@@ -0,0 +1,14 @@
+//! `/.well-known/bayou/...`
+//! the endpoints other instances use to federate, see
+//! [`crate::federation::activity`] for the protocol
+
+pub mod community;
+pub mod emoji;
+pub mod emoji_media;
+pub mod emoji_pack;
+pub mod history;
+pub mod inbox;
+pub mod join;
+pub mod key;
+pub(super) mod routes;
+pub mod user;
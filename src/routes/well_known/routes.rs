@@ -0,0 +1,17 @@
+use super::{
+    community::community, emoji::emoji, emoji_media::emoji_media, emoji_pack::emoji_pack,
+    history::history, inbox::inbox, join::join, key::key, user::user,
+};
+
+pub fn get_well_known_routes() -> actix_web::Scope {
+    actix_web::web::scope("/.well-known/bayou")
+        .service(inbox)
+        .service(join)
+        .service(key)
+        .service(user)
+        .service(community)
+        .service(history)
+        .service(emoji_pack)
+        .service(emoji)
+        .service(emoji_media)
+}
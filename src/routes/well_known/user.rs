@@ -0,0 +1,43 @@
+//! `get /.well-known/bayou/users/{username}`
+//!
+//! the profile of one of our users for another instance, the request must be signed
+//! - ok (200) should contain a json [`crate::federation::activity::FederatedUser`]
+//! - not found (404) the user doesn't exist
+//! - unauthorized (401) the request isn't signed, a
+//!   [`crate::federation::signatures::SignatureErr`] will be in the body
+//! - forbidden (403) we don't federate with the signing instance, the body
+//!   will contain [`crate::federation::signatures::SignatureErr::Blocked`]
+
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpRequest, HttpResponse, Result,
+};
+
+use crate::{config::Config, db::pg_conn::PgConn, federation::signatures::KeyStore};
+
+#[get("/users/{username}")]
+pub async fn user(
+    conn: Data<PgConn>,
+    config: Data<Config>,
+    key_store: Data<KeyStore>,
+    req: HttpRequest,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    if let Err(err) = key_store.verify_request(&req, &[], &conn, &config).await {
+        return Ok(HttpResponse::build(err.status())
+            .content_type("application/json; charset=utf-8")
+            .body(serde_json::to_string(&err).expect("failed to serialize SignatureErr")));
+    }
+    let Some(user) = conn
+        .get_federated_user(&path.into_inner(), &config.instance_domain)
+        .await
+    else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("application/json; charset=utf-8")
+            .body(""));
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .body(serde_json::to_string(&user).expect("failed to serialize user")))
+}
@@ -0,0 +1,95 @@
+//! setup shared by the tests that run against a database. they are ignored by
+//! default, run them with `cargo test -- --ignored` and `BAYOU_TEST_DATABASE`
+//! set to the connection string of a database that may be written to, eg
+//! `host=127.0.0.1 user=bayou password=password dbname=bayou_test`
+
+#![allow(dead_code)]
+
+use bayou::{
+    cryptography::keys::SigningAlgorithm,
+    db::{
+        pg_conn::PgConn,
+        types::{comm::community::Communityinfo, comm::community::DbCommunity, user::DbUser},
+    },
+    routes::api::types::signup_user::SignupUser,
+};
+use deadpool_postgres::Config;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+pub const DOMAIN: &str = "bayou.test";
+
+/// connect to the test database, migrations are applied by the first test to connect
+pub async fn test_conn() -> PgConn {
+    static MIGRATED: Mutex<bool> = Mutex::const_new(false);
+    let url = std::env::var("BAYOU_TEST_DATABASE").expect("BAYOU_TEST_DATABASE is not set");
+    let config = Config {
+        url: Some(url),
+        ..Default::default()
+    };
+    let conn = PgConn {
+        db: config
+            .create_pool(None, tokio_postgres::NoTls)
+            .expect("failed to create pool"),
+        base_url: format!("https://{DOMAIN}"),
+    };
+    let mut migrated = MIGRATED.lock().await;
+    if !*migrated {
+        conn.init().await.expect("failed to apply migrations");
+        conn.get_or_init_main_instance(DOMAIN, SigningAlgorithm::default(), false)
+            .await;
+        *migrated = true;
+    }
+    conn
+}
+
+/// sign up a user with a random name
+pub async fn create_user(conn: &PgConn) -> DbUser {
+    let username = Uuid::new_v4().simple().to_string();
+    conn.try_signup_user(
+        SignupUser {
+            username,
+            password: "password".to_string(),
+            email: None,
+            token: None,
+            application_message: None,
+        },
+        DOMAIN,
+        false,
+    )
+    .await
+    .expect("failed to sign up user")
+}
+
+pub async fn create_community(conn: &PgConn, owner: &DbUser) -> DbCommunity {
+    conn.create_community(
+        Communityinfo {
+            name: "community".to_string(),
+            description: None,
+        },
+        owner,
+    )
+    .await
+}
+
+/// the config of the instance under test, it is only used for its
+/// domain and federation settings
+pub fn test_config() -> bayou::config::Config {
+    serde_json::from_value(serde_json::json!({
+        "instance_domain": DOMAIN,
+        "bind_address": "127.0.0.1",
+        "port": 8020,
+        "open_signups": true,
+        "allow_applications": false,
+        "max_standard_upload_size": 50,
+        "max_superuser_upload_size": null,
+        "upload_memory_limit": 25,
+        "pg_user": "",
+        "pg_password": "",
+        "pg_host": "",
+        "pg_port": 5432,
+        "pg_dbname": "",
+        "storage_options": { "type": "Local", "args": { "base_path": "./" } },
+    }))
+    .expect("invalid test config")
+}
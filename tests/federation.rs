@@ -0,0 +1,167 @@
+//! activities delivered by other instances, see `tests/common` for running these
+
+mod common;
+
+use bayou::{
+    db::{
+        curr_time::get_current_time,
+        pg_conn::PgConn,
+        types::{comm::community::DbCommunity, message::TextFormat},
+    },
+    federation::activity::{FederatedEvent, FederatedMessage, FederatedUser, InboxErr, ObjectRef},
+};
+use common::{create_community, create_user, test_conn, DOMAIN};
+use uuid::Uuid;
+
+/// a user from another instance with a random domain
+fn remote_user() -> FederatedUser {
+    FederatedUser {
+        domain: format!("{}.test", Uuid::new_v4().simple()),
+        username: "remote".to_string(),
+        display_name: None,
+        summary: None,
+        created: get_current_time(),
+    }
+}
+
+/// a user from another instance who joined the community through an invite
+async fn remote_member(conn: &PgConn, community: &DbCommunity) -> FederatedUser {
+    let invite = conn
+        .create_invite(community.id, community.owner, i64::MAX, None)
+        .await
+        .expect("owner failed to create invite");
+    let user = remote_user();
+    conn.redeem_remote_invite(invite.id, &user)
+        .await
+        .expect("remote user failed to join");
+    user
+}
+
+fn message(author: &FederatedUser, room: ObjectRef) -> FederatedMessage {
+    FederatedMessage {
+        id: ObjectRef {
+            id: Uuid::now_v7(),
+            domain: author.domain.clone(),
+        },
+        room,
+        author: author.clone(),
+        published: get_current_time(),
+        edited: None,
+        is_reply: false,
+        in_reply_to: None,
+        thread: None,
+        content: "hello".to_string(),
+        format: TextFormat::Plain,
+        language: None,
+        mentions_everyone: false,
+        system_event: None,
+    }
+}
+
+/// the room members are able to send messages in
+async fn system_room(conn: &PgConn, community: &DbCommunity) -> ObjectRef {
+    let room = conn
+        .get_comm_rooms(community.id, community.owner)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|x| x.room.system_channel)
+        .expect("community has no system channel")
+        .room;
+    ObjectRef {
+        id: room.external_id,
+        domain: room.domain,
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn remote_members_can_send_messages() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let remote = remote_member(&conn, &community).await;
+    let room = system_room(&conn, &community).await;
+
+    let received = conn
+        .receive_message(&remote.domain, message(&remote, room.clone()))
+        .await;
+    assert!(matches!(received, Ok(Some(_))));
+    // only the author's instance may deliver their messages
+    let received = conn
+        .receive_message("other.test", message(&remote, room))
+        .await;
+    assert!(matches!(received, Err(InboxErr::Forbidden)));
+}
+
+#[tokio::test]
+#[ignore]
+async fn announcements_in_local_rooms_are_refused() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let remote = remote_member(&conn, &community).await;
+    let room = system_room(&conn, &community).await;
+
+    for _ in 0..2 {
+        let announcement = FederatedMessage {
+            system_event: Some(FederatedEvent::MemberJoined),
+            ..message(&remote, room.clone())
+        };
+        let received = conn.receive_message(&remote.domain, announcement).await;
+        assert!(matches!(received, Err(InboxErr::Forbidden)));
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn remote_edits_are_applied_once() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let remote = remote_member(&conn, &community).await;
+    let room = system_room(&conn, &community).await;
+
+    let original = message(&remote, room);
+    let Ok(Some(stored)) = conn.receive_message(&remote.domain, original.clone()).await else {
+        panic!("message was not stored");
+    };
+    let edit = FederatedMessage {
+        content: "edited".to_string(),
+        edited: Some(original.published + 1),
+        ..original
+    };
+    // only the author's instance may edit their messages
+    let received = conn.receive_edit("other.test", edit.clone()).await;
+    assert!(matches!(received, Err(InboxErr::Forbidden)));
+
+    let Ok(Some(edited)) = conn.receive_edit(&remote.domain, edit.clone()).await else {
+        panic!("edit was not applied");
+    };
+    assert_eq!(edited.info.content, "edited");
+    let history = conn.get_message_edits(owner.id, stored.id).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].content, "hello");
+    // the same edit delivered again is ignored
+    let received = conn.receive_edit(&remote.domain, edit).await;
+    assert!(matches!(received, Ok(None)));
+}
+
+#[tokio::test]
+#[ignore]
+async fn remote_joins_are_announced_by_us() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let invite = conn
+        .create_invite(community.id, community.owner, i64::MAX, None)
+        .await
+        .expect("owner failed to create invite");
+    let user = remote_user();
+    let (_, announcement) = conn
+        .redeem_remote_invite(invite.id, &user)
+        .await
+        .expect("remote user failed to join");
+    let announcement = announcement.expect("join was not announced");
+    assert_eq!(announcement.domain, DOMAIN);
+}
@@ -0,0 +1,173 @@
+//! community invites, see `tests/common` for running these
+
+mod common;
+
+use bayou::{
+    db::{
+        curr_time::get_current_time,
+        types::comm::{permissions::Permissions, role::RoleInfo},
+    },
+    routes::api::types::join_err::JoinErr,
+};
+use common::{create_community, create_user, test_conn};
+use futures_util::future::join_all;
+
+#[tokio::test]
+#[ignore]
+async fn creating_invites_requires_create_invites() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let outsider = create_user(&conn).await;
+    assert!(conn
+        .create_invite(community.id, outsider.id, i64::MAX, None)
+        .await
+        .is_err());
+
+    let member = create_user(&conn).await;
+    let invite = conn
+        .create_invite(community.id, owner.id, i64::MAX, None)
+        .await
+        .unwrap();
+    conn.redeem_invite(invite.id, &member).await.unwrap();
+    // the default role can create invites until it is taken away
+    assert!(conn
+        .create_invite(community.id, member.id, i64::MAX, None)
+        .await
+        .is_ok());
+    let default = conn
+        .get_comm_roles(community.id, owner.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|x| x.is_default)
+        .unwrap();
+    conn.update_role(
+        default.id,
+        owner.id,
+        RoleInfo {
+            permissions: Permissions::VIEW_ROOMS,
+            ..default.info
+        },
+    )
+    .await
+    .unwrap();
+    assert!(conn
+        .create_invite(community.id, member.id, i64::MAX, None)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+#[ignore]
+async fn invites_are_revoked_by_their_creator_or_managers() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let creator = create_user(&conn).await;
+    let other = create_user(&conn).await;
+    let invite = conn
+        .create_invite(community.id, owner.id, i64::MAX, None)
+        .await
+        .unwrap();
+    conn.redeem_invite(invite.id, &creator).await.unwrap();
+    conn.redeem_invite(invite.id, &other).await.unwrap();
+
+    let created = conn
+        .create_invite(community.id, creator.id, i64::MAX, None)
+        .await
+        .unwrap();
+    assert!(conn.revoke_invite(created.id, other.id).await.is_err());
+    assert!(conn.revoke_invite(created.id, creator.id).await.is_ok());
+    assert!(conn.get_invite_preview(created.id).await.is_none());
+
+    let created = conn
+        .create_invite(community.id, creator.id, i64::MAX, None)
+        .await
+        .unwrap();
+    assert!(conn.revoke_invite(created.id, owner.id).await.is_ok());
+}
+
+#[tokio::test]
+#[ignore]
+async fn expired_invites_are_refused() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let invite = conn
+        .create_invite(community.id, owner.id, get_current_time() - 1, None)
+        .await
+        .unwrap();
+    assert!(conn.get_invite_preview(invite.id).await.is_none());
+    let user = create_user(&conn).await;
+    assert!(matches!(
+        conn.redeem_invite(invite.id, &user).await,
+        Err(JoinErr::ExpiredToken)
+    ));
+    assert!(matches!(
+        conn.redeem_invite(invite.id, &user).await,
+        Err(JoinErr::InvalidToken)
+    ));
+}
+
+#[tokio::test]
+#[ignore]
+async fn banned_users_can_not_join() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let user = create_user(&conn).await;
+    conn.ban_member(community.id, owner.id, user.id, None)
+        .await
+        .unwrap();
+    let invite = conn
+        .create_invite(community.id, owner.id, i64::MAX, Some(1))
+        .await
+        .unwrap();
+    assert!(matches!(
+        conn.redeem_invite(invite.id, &user).await,
+        Err(JoinErr::Banned)
+    ));
+    // refused joins don't use up the invite
+    assert!(conn.get_invite_preview(invite.id).await.is_some());
+}
+
+#[tokio::test]
+#[ignore]
+async fn members_rejoining_do_not_use_up_invites() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let user = create_user(&conn).await;
+    let invite = conn
+        .create_invite(community.id, owner.id, i64::MAX, Some(2))
+        .await
+        .unwrap();
+    conn.redeem_invite(invite.id, &user).await.unwrap();
+    conn.redeem_invite(invite.id, &user).await.unwrap();
+    assert!(conn.get_invite_preview(invite.id).await.is_some());
+}
+
+#[tokio::test]
+#[ignore]
+async fn concurrent_joins_do_not_exceed_max_uses() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let invite = conn
+        .create_invite(community.id, owner.id, i64::MAX, Some(3))
+        .await
+        .unwrap();
+    let mut users = Vec::new();
+    for _ in 0..8 {
+        users.push(create_user(&conn).await);
+    }
+    let results = join_all(users.iter().map(|x| conn.redeem_invite(invite.id, x))).await;
+    let joined = results.iter().filter(|x| x.is_ok()).count();
+    assert_eq!(joined, 3);
+    assert!(results
+        .iter()
+        .filter_map(|x| x.as_ref().err())
+        .all(|x| matches!(x, JoinErr::InvalidToken)));
+    assert!(conn.get_invite_preview(invite.id).await.is_none());
+}
@@ -0,0 +1,314 @@
+//! the role hierarchy of communities, see `tests/common` for running these
+
+mod common;
+
+use bayou::db::{
+    pg_conn::PgConn,
+    types::{
+        comm::{
+            community::DbCommunity,
+            permissions::Permissions,
+            role::{Role, RoleInfo},
+            room_override::RoomOverride,
+        },
+        message::{Messageinfo, TextFormat},
+        user::DbUser,
+    },
+};
+use common::{create_community, create_user, test_conn};
+use uuid::Uuid;
+
+fn role_info(permissions: Permissions, position: i64) -> RoleInfo {
+    RoleInfo {
+        name: "role".to_string(),
+        permissions,
+        position,
+    }
+}
+
+fn text(room: Uuid, content: &str) -> Messageinfo {
+    Messageinfo {
+        is_reply: false,
+        in_reply_to: None,
+        proxy_id: None,
+        content: content.to_string(),
+        format: TextFormat::Plain,
+        language: None,
+        room,
+        thread: None,
+        attachments: Vec::new(),
+    }
+}
+
+async fn join(conn: &PgConn, community: &DbCommunity, user: &DbUser) {
+    let invite = conn
+        .create_invite(community.id, community.owner, i64::MAX, None)
+        .await
+        .expect("owner failed to create invite");
+    conn.redeem_invite(invite.id, user)
+        .await
+        .expect("failed to join community");
+}
+
+/// a member holding a new role with the given permissions and position
+async fn member_with_role(
+    conn: &PgConn,
+    community: &DbCommunity,
+    permissions: Permissions,
+    position: i64,
+) -> (DbUser, Role) {
+    let user = create_user(conn).await;
+    join(conn, community, &user).await;
+    let role = conn
+        .create_role(
+            community.id,
+            community.owner,
+            role_info(permissions, position),
+        )
+        .await
+        .expect("owner failed to create role");
+    conn.set_role_assigned(role.id, community.owner, user.id, true)
+        .await
+        .expect("owner failed to assign role");
+    (user, role)
+}
+
+async fn default_role(conn: &PgConn, community: &DbCommunity) -> Role {
+    conn.get_comm_roles(community.id, community.owner)
+        .await
+        .expect("failed to get roles")
+        .into_iter()
+        .find(|x| x.is_default)
+        .expect("community has no default role")
+}
+
+#[tokio::test]
+#[ignore]
+async fn members_only_kick_members_below_them() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let (senior, _) = member_with_role(&conn, &community, Permissions::KICK_MEMBERS, 2).await;
+    let (junior, _) = member_with_role(&conn, &community, Permissions::KICK_MEMBERS, 1).await;
+
+    assert!(conn
+        .kick_member(community.id, junior.id, senior.id)
+        .await
+        .is_err());
+    assert!(conn
+        .kick_member(community.id, senior.id, owner.id)
+        .await
+        .is_err());
+    assert!(conn
+        .kick_member(community.id, senior.id, junior.id)
+        .await
+        .is_ok());
+    assert!(conn
+        .get_comm_membership(community.id, junior.id)
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+#[ignore]
+async fn lifting_a_ban_requires_the_banning_members_standing() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let (senior, _) = member_with_role(&conn, &community, Permissions::BAN_MEMBERS, 2).await;
+    let (junior, _) = member_with_role(&conn, &community, Permissions::BAN_MEMBERS, 1).await;
+    let target = create_user(&conn).await;
+
+    let ban = conn
+        .ban_member(community.id, senior.id, target.id, None)
+        .await
+        .expect("senior failed to ban");
+    assert_eq!(ban.banned_position, 2);
+
+    assert!(conn
+        .unban_member(community.id, junior.id, target.id)
+        .await
+        .is_err());
+    // banning again would replace the ban with one the junior could lift
+    assert!(conn
+        .ban_member(community.id, junior.id, target.id, None)
+        .await
+        .is_err());
+    assert!(conn
+        .unban_member(community.id, senior.id, target.id)
+        .await
+        .is_ok());
+    let bans = conn.get_comm_bans(community.id, owner.id).await.unwrap();
+    assert!(bans.iter().all(|x| x.uid != target.id));
+}
+
+#[tokio::test]
+#[ignore]
+async fn the_owner_lifts_any_ban() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let (moderator, _) = member_with_role(&conn, &community, Permissions::BAN_MEMBERS, 3).await;
+    let target = create_user(&conn).await;
+
+    let ban = conn
+        .ban_member(community.id, owner.id, target.id, None)
+        .await
+        .expect("owner failed to ban");
+    assert_eq!(ban.banned_position, i64::MAX);
+    assert!(conn
+        .unban_member(community.id, moderator.id, target.id)
+        .await
+        .is_err());
+    assert!(conn
+        .unban_member(community.id, owner.id, target.id)
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+#[ignore]
+async fn roles_are_only_managed_from_above() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let (manager, manager_role) =
+        member_with_role(&conn, &community, Permissions::MANAGE_ROLES, 2).await;
+
+    // at or above the manager's own role
+    assert!(conn
+        .create_role(community.id, manager.id, role_info(Permissions::NONE, 2))
+        .await
+        .is_err());
+    assert!(conn
+        .update_role(manager_role.id, manager.id, role_info(Permissions::ALL, 2))
+        .await
+        .is_err());
+    // permissions the manager doesn't have
+    assert!(conn
+        .create_role(
+            community.id,
+            manager.id,
+            role_info(Permissions::BAN_MEMBERS, 1)
+        )
+        .await
+        .is_err());
+    let role = conn
+        .create_role(
+            community.id,
+            manager.id,
+            role_info(Permissions::MANAGE_ROLES, 1),
+        )
+        .await
+        .expect("manager failed to create a role below them");
+    assert!(conn
+        .update_role(role.id, manager.id, role_info(Permissions::NONE, 2))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+#[ignore]
+async fn editing_the_default_role_requires_manage_community() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let default = default_role(&conn, &community).await;
+    let (manager, _) = member_with_role(
+        &conn,
+        &community,
+        Permissions::MANAGE_ROLES | Permissions::KICK_MEMBERS,
+        2,
+    )
+    .await;
+    let (admin, _) = member_with_role(
+        &conn,
+        &community,
+        Permissions::MANAGE_ROLES | Permissions::MANAGE_COMMUNITY,
+        3,
+    )
+    .await;
+
+    let everyone_kicks = role_info(Permissions::DEFAULT | Permissions::KICK_MEMBERS, 0);
+    assert!(conn
+        .update_role(default.id, manager.id, everyone_kicks.clone())
+        .await
+        .is_err());
+    let updated = conn
+        .update_role(default.id, admin.id, role_info(Permissions::VIEW_ROOMS, 5))
+        .await
+        .expect("failed to update default role");
+    assert_eq!(updated.info.permissions, Permissions::VIEW_ROOMS);
+    // the default role stays at the bottom
+    assert_eq!(updated.info.position, 0);
+}
+
+#[tokio::test]
+#[ignore]
+async fn members_of_dms_can_pin() {
+    let conn = test_conn().await;
+    let user = create_user(&conn).await;
+    let other = create_user(&conn).await;
+    let outsider = create_user(&conn).await;
+    let room = conn
+        .open_dm(&user, other.id)
+        .await
+        .expect("failed to open dm");
+    let message = conn
+        .send_message(&user, text(room.id, "hello"))
+        .await
+        .expect("failed to send message");
+
+    assert!(conn.pin_message(&outsider, message.id).await.is_err());
+    assert!(matches!(
+        conn.pin_message(&other, message.id).await,
+        Ok(Some(_))
+    ));
+    assert!(matches!(
+        conn.unpin_message(user.id, message.id).await,
+        Ok(Some(_))
+    ));
+}
+
+#[tokio::test]
+#[ignore]
+async fn only_members_who_can_view_the_room_are_mentioned() {
+    let conn = test_conn().await;
+    let owner = create_user(&conn).await;
+    let community = create_community(&conn, &owner).await;
+    let member = create_user(&conn).await;
+    join(&conn, &community, &member).await;
+    let room = conn
+        .get_comm_rooms(community.id, owner.id)
+        .await
+        .expect("failed to get rooms")
+        .into_iter()
+        .find(|x| x.room.system_channel)
+        .expect("community has no system channel")
+        .room;
+    let content = format!("hi @{}", member.info.username);
+
+    let message = conn
+        .send_message(&owner, text(room.id, &content))
+        .await
+        .expect("failed to send message");
+    assert_eq!(conn.get_mentioned_user_ids(message.id).await, [member.id]);
+
+    let default = default_role(&conn, &community).await;
+    conn.set_room_override(
+        owner.id,
+        RoomOverride {
+            room_id: room.id,
+            role_id: default.id,
+            allow: Permissions::NONE,
+            deny: Permissions::VIEW_ROOMS,
+        },
+    )
+    .await
+    .expect("failed to hide the room");
+    let message = conn
+        .send_message(&owner, text(room.id, &content))
+        .await
+        .expect("failed to send message");
+    assert!(conn.get_mentioned_user_ids(message.id).await.is_empty());
+}
@@ -0,0 +1,190 @@
+//! verifying signed requests from other instances, see `tests/common` for running these
+
+mod common;
+
+use std::time::{Duration, SystemTime};
+
+use actix_web::{test::TestRequest, HttpRequest};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use bayou::{
+    cryptography::{
+        http_signatures::{digest, SignedParts, SIGNED_HEADERS},
+        keys::{PrivateKey, SigningAlgorithm},
+    },
+    db::{curr_time::get_current_time, pg_conn::PgConn, types::instance::InstanceKey},
+    federation::signatures::{KeyStore, SignatureErr, REPLAY_WINDOW},
+};
+use common::{test_config, test_conn, DOMAIN};
+use uuid::Uuid;
+
+const PATH: &str = "/.well-known/bayou/inbox";
+const BODY: &[u8] = b"{\"hello\":\"world\"}";
+
+/// a remote instance with a random domain whose key is already stored,
+/// so verifying its requests doesn't fetch anything
+async fn remote_instance(conn: &PgConn) -> (String, PrivateKey) {
+    let domain = format!("{}.test", Uuid::new_v4().simple());
+    let key = PrivateKey::generate(SigningAlgorithm::Ed25519);
+    conn.set_remote_key(
+        &domain,
+        InstanceKey {
+            algorithm: SigningAlgorithm::Ed25519,
+            public_key: key.public_key().to_pem(),
+            private_key: None,
+            fetched_at: Some(get_current_time()),
+        },
+    )
+    .await;
+    (domain, key)
+}
+
+/// a request from `domain` to `host` signed as if it was sent at `sent`
+fn signed_request(
+    key: &PrivateKey,
+    domain: &str,
+    host: &str,
+    sent: SystemTime,
+    body: &[u8],
+) -> HttpRequest {
+    let date = httpdate::fmt_http_date(sent);
+    let digest = digest(body);
+    let parts = SignedParts {
+        method: "post",
+        path: PATH,
+        host,
+        date: &date,
+        digest: &digest,
+    };
+    let signature = format!(
+        r#"keyId="https://{domain}/.well-known/bayou/key",algorithm="ed25519",headers="{}",signature="{}""#,
+        SIGNED_HEADERS.join(" "),
+        BASE64_STANDARD.encode(key.sign(parts.signing_string().as_bytes()))
+    );
+    TestRequest::post()
+        .uri(PATH)
+        .insert_header(("host", host))
+        .insert_header(("date", date))
+        .insert_header(("digest", digest))
+        .insert_header(("signature", signature))
+        .to_http_request()
+}
+
+#[tokio::test]
+#[ignore]
+async fn signed_requests_are_accepted_once() {
+    let conn = test_conn().await;
+    let config = test_config();
+    let store = KeyStore::default();
+    let (domain, key) = remote_instance(&conn).await;
+
+    let req = signed_request(&key, &domain, DOMAIN, SystemTime::now(), BODY);
+    let verified = store.verify_request(&req, BODY, &conn, &config).await;
+    assert_eq!(verified.ok(), Some(domain.clone()));
+    assert!(matches!(
+        store.verify_request(&req, BODY, &conn, &config).await,
+        Err(SignatureErr::Replayed)
+    ));
+    // ed25519 signatures are deterministic so only other requests are signed differently
+    let req = signed_request(&key, &domain, DOMAIN, SystemTime::now(), b"{}");
+    assert!(store
+        .verify_request(&req, b"{}", &conn, &config)
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+#[ignore]
+async fn dates_must_be_within_the_replay_window() {
+    let conn = test_conn().await;
+    let config = test_config();
+    let store = KeyStore::default();
+    let (domain, key) = remote_instance(&conn).await;
+    let skew = REPLAY_WINDOW + Duration::from_secs(60);
+
+    for sent in [SystemTime::now() - skew, SystemTime::now() + skew] {
+        let req = signed_request(&key, &domain, DOMAIN, sent, BODY);
+        assert!(matches!(
+            store.verify_request(&req, BODY, &conn, &config).await,
+            Err(SignatureErr::Expired)
+        ));
+    }
+    // clocks a little out of sync are tolerated either way
+    let skew = REPLAY_WINDOW - Duration::from_secs(60);
+    for sent in [SystemTime::now() - skew, SystemTime::now() + skew] {
+        let req = signed_request(&key, &domain, DOMAIN, sent, BODY);
+        assert!(store
+            .verify_request(&req, BODY, &conn, &config)
+            .await
+            .is_ok());
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn requests_must_be_signed_for_us() {
+    let conn = test_conn().await;
+    let config = test_config();
+    let store = KeyStore::default();
+    let (domain, key) = remote_instance(&conn).await;
+
+    // a request signed for another instance being replayed to us
+    let req = signed_request(&key, &domain, "other.test", SystemTime::now(), BODY);
+    assert!(matches!(
+        store.verify_request(&req, BODY, &conn, &config).await,
+        Err(SignatureErr::Invalid)
+    ));
+}
+
+#[tokio::test]
+#[ignore]
+async fn changed_or_unsigned_requests_are_refused() {
+    let conn = test_conn().await;
+    let config = test_config();
+    let store = KeyStore::default();
+    let (domain, key) = remote_instance(&conn).await;
+
+    let req = signed_request(&key, &domain, DOMAIN, SystemTime::now(), BODY);
+    assert!(matches!(
+        store.verify_request(&req, b"{}", &conn, &config).await,
+        Err(SignatureErr::Invalid)
+    ));
+    // the stored key was fetched recently so it isn't fetched again
+    let other = PrivateKey::generate(SigningAlgorithm::Ed25519);
+    let req = signed_request(&other, &domain, DOMAIN, SystemTime::now(), BODY);
+    assert!(matches!(
+        store.verify_request(&req, BODY, &conn, &config).await,
+        Err(SignatureErr::Invalid)
+    ));
+    // our own domain can't sign requests to us
+    let req = signed_request(&key, DOMAIN, DOMAIN, SystemTime::now(), BODY);
+    assert!(matches!(
+        store.verify_request(&req, BODY, &conn, &config).await,
+        Err(SignatureErr::Invalid)
+    ));
+    let req = TestRequest::post()
+        .uri(PATH)
+        .insert_header(("host", DOMAIN))
+        .to_http_request();
+    assert!(matches!(
+        store.verify_request(&req, BODY, &conn, &config).await,
+        Err(SignatureErr::Missing)
+    ));
+}
+
+#[tokio::test]
+#[ignore]
+async fn blocked_instances_are_refused() {
+    let conn = test_conn().await;
+    let config = test_config();
+    let store = KeyStore::default();
+    let (domain, key) = remote_instance(&conn).await;
+    conn.block_instance(&domain, None)
+        .await
+        .expect("failed to block instance");
+
+    let req = signed_request(&key, &domain, DOMAIN, SystemTime::now(), BODY);
+    assert!(matches!(
+        store.verify_request(&req, BODY, &conn, &config).await,
+        Err(SignatureErr::Blocked)
+    ));
+}